@@ -0,0 +1,428 @@
+//! RDF dataset canonicalization (RDFC-1.0, also known as URDNA2015).
+//!
+//! The canonicalization gives every blank node a label that only depends on
+//! the structure of the dataset, so two isomorphic datasets have the same
+//! canonical N-Quads serialization.
+
+use crate::RcQuad;
+use crate::literal::value_of;
+use crate::serializer::escape_string;
+
+use sha2::{ Digest, Sha256 };
+
+use sophia::dataset::Dataset;
+use sophia::quad::Quad;
+use sophia::term::RcTerm;
+use sophia::term::Term;
+
+use std::cell::Cell;
+use std::collections::BTreeMap;
+use std::collections::HashMap;
+
+const XSD_STRING: &str = "http://www.w3.org/2001/XMLSchema#string";
+
+/// The maximum number of times the Hash N-Degree Quads algorithm can be run
+/// for one dataset. Some datasets (with a lot of indistinguishable blank
+/// nodes) require an exponential number of runs.
+const MAX_N_DEGREE_RUNS: usize = 100_000;
+
+/// An error raised when a dataset is too complex to be canonicalized
+#[derive(Debug, Clone, PartialEq)]
+pub struct CanonicalizationError {
+    /// The number of blank nodes of the dataset
+    pub blank_nodes: usize
+}
+
+impl std::fmt::Display for CanonicalizationError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "The dataset with {} blank nodes is too complex to be canonicalized", self.blank_nodes)
+    }
+}
+
+impl std::error::Error for CanonicalizationError {}
+
+fn blank_node_label(term: &RcTerm) -> Option<String> {
+    match term {
+        Term::BNode(_) => Some(value_of(term)),
+        _ => None
+    }
+}
+
+fn sha256_hex(data: &str) -> String {
+    format!("{:x}", Sha256::digest(data.as_bytes()))
+}
+
+/// Writes a term in canonical N-Quads, with the blank nodes labeled by
+/// `label`
+fn write_term(term: &RcTerm, label: &dyn Fn(&str) -> String) -> String {
+    match term {
+        Term::Iri(_) => format!("<{}>", value_of(term)),
+        Term::BNode(_) => label(&value_of(term)),
+        Term::Variable(_) => format!("?{}", value_of(term)),
+        Term::Literal(literal) => {
+            let value = escape_string(&value_of(term));
+
+            if let Some(lang) = literal.lang() {
+                return format!("\"{}\"@{}", value, lang.as_ref());
+            }
+
+            match value_of(&literal.dt()).as_str() {
+                XSD_STRING => format!("\"{}\"", value),
+                datatype => format!("\"{}\"^^<{}>", value, datatype)
+            }
+        }
+    }
+}
+
+/// Writes a quad as a canonical N-Quads line
+fn write_quad(quad: &RcQuad, label: &dyn Fn(&str) -> String) -> String {
+    let mut line = String::new();
+
+    for term in [quad.s(), quad.p(), quad.o()].iter().copied().chain(quad.g()) {
+        line.push_str(&write_term(term, label));
+        line.push(' ');
+    }
+
+    line.push_str(".\n");
+    line
+}
+
+/// Rearranges `items` into the next permutation in lexicographic order.
+/// Returns false if `items` was the last permutation.
+fn next_permutation(items: &mut [String]) -> bool {
+    let pivot = match (1..items.len()).rev().find(|&i| items[i - 1] < items[i]) {
+        Some(i) => i - 1,
+        None => return false
+    };
+
+    let successor = (pivot + 1..items.len()).rev().find(|&i| items[i] > items[pivot]).unwrap();
+    items.swap(pivot, successor);
+    items[pivot + 1..].reverse();
+    true
+}
+
+/// Issues identifiers with a prefix and a counter, remembering the order in
+/// which they were issued
+#[derive(Clone)]
+struct IdentifierIssuer {
+    prefix: &'static str,
+    issued: HashMap<String, String>,
+    order: Vec<String>
+}
+
+impl IdentifierIssuer {
+    fn new(prefix: &'static str) -> IdentifierIssuer {
+        IdentifierIssuer { prefix, issued: HashMap::new(), order: vec!() }
+    }
+
+    fn get(&self, existing: &str) -> Option<&String> {
+        self.issued.get(existing)
+    }
+
+    fn issue(&mut self, existing: &str) -> String {
+        if let Some(identifier) = self.issued.get(existing) {
+            return identifier.clone();
+        }
+
+        let identifier = format!("{}{}", self.prefix, self.order.len());
+        self.issued.insert(existing.to_string(), identifier.clone());
+        self.order.push(existing.to_string());
+        identifier
+    }
+}
+
+/// The state of the canonicalization algorithm
+struct Canonicalizer<'q> {
+    quads: &'q [RcQuad],
+    /// The indexes of the quads in which each blank node appears
+    blank_node_quads: HashMap<String, Vec<usize>>,
+    /// The first degree hash of each blank node
+    first_degree_hashes: HashMap<String, String>,
+    canonical_issuer: IdentifierIssuer,
+    remaining_n_degree_runs: Cell<usize>
+}
+
+impl<'q> Canonicalizer<'q> {
+    fn new(quads: &'q [RcQuad]) -> Canonicalizer<'q> {
+        let mut blank_node_quads: HashMap<String, Vec<usize>> = HashMap::new();
+
+        for (i, quad) in quads.iter().enumerate() {
+            for term in [quad.s(), quad.o()].iter().copied().chain(quad.g()) {
+                if let Some(label) = blank_node_label(term) {
+                    let indexes = blank_node_quads.entry(label).or_default();
+                    if indexes.last() != Some(&i) {
+                        indexes.push(i);
+                    }
+                }
+            }
+        }
+
+        Canonicalizer {
+            quads,
+            blank_node_quads,
+            first_degree_hashes: HashMap::new(),
+            canonical_issuer: IdentifierIssuer::new("_:c14n"),
+            remaining_n_degree_runs: Cell::new(MAX_N_DEGREE_RUNS)
+        }
+    }
+
+    /// The Hash First Degree Quads algorithm
+    fn hash_first_degree(&self, reference: &str) -> String {
+        let label = |blank_node: &str| if blank_node == reference { "_:a".to_string() } else { "_:z".to_string() };
+
+        let mut lines: Vec<String> = self.blank_node_quads[reference].iter()
+            .map(|&i| write_quad(&self.quads[i], &label))
+            .collect();
+        lines.sort();
+
+        sha256_hex(&lines.concat())
+    }
+
+    /// The Hash Related Blank Node algorithm
+    fn hash_related(&self, related: &str, quad: &RcQuad, issuer: &IdentifierIssuer, position: &str) -> String {
+        let identifier = self.canonical_issuer.get(related)
+            .or_else(|| issuer.get(related))
+            .unwrap_or_else(|| &self.first_degree_hashes[related]);
+
+        let mut input = position.to_string();
+        if position != "g" {
+            input.push_str(&format!("<{}>", value_of(quad.p())));
+        }
+        input.push_str(identifier);
+
+        sha256_hex(&input)
+    }
+
+    /// The Hash N-Degree Quads algorithm
+    fn hash_n_degree(&self, identifier: &str, mut issuer: IdentifierIssuer) -> Result<(String, IdentifierIssuer), CanonicalizationError> {
+        let remaining_runs = self.remaining_n_degree_runs.get();
+        if remaining_runs == 0 {
+            return Err(CanonicalizationError { blank_nodes: self.blank_node_quads.len() });
+        }
+        self.remaining_n_degree_runs.set(remaining_runs - 1);
+
+        let mut related_by_hash: BTreeMap<String, Vec<String>> = BTreeMap::new();
+
+        for &i in &self.blank_node_quads[identifier] {
+            let quad = &self.quads[i];
+
+            for (position, term) in [("s", Some(quad.s())), ("o", Some(quad.o())), ("g", quad.g())].iter() {
+                if let Some(related) = term.and_then(blank_node_label) {
+                    if related != identifier {
+                        let hash = self.hash_related(&related, quad, &issuer, position);
+                        related_by_hash.entry(hash).or_default().push(related);
+                    }
+                }
+            }
+        }
+
+        let mut data_to_hash = String::new();
+
+        for (related_hash, mut related) in related_by_hash {
+            data_to_hash.push_str(&related_hash);
+
+            let mut chosen_path = String::new();
+            let mut chosen_issuer = None;
+            let is_worse = |path: &str, chosen_path: &str| {
+                !chosen_path.is_empty() && path.len() >= chosen_path.len() && path > chosen_path
+            };
+
+            related.sort();
+
+            loop {
+                let mut issuer_copy = issuer.clone();
+                let mut path = String::new();
+                let mut recursion_list = vec!();
+
+                for blank_node in &related {
+                    match self.canonical_issuer.get(blank_node) {
+                        Some(canonical) => path.push_str(canonical),
+                        None => {
+                            if issuer_copy.get(blank_node).is_none() {
+                                recursion_list.push(blank_node.clone());
+                            }
+                            path.push_str(&issuer_copy.issue(blank_node));
+                        }
+                    }
+
+                    if is_worse(&path, &chosen_path) {
+                        break;
+                    }
+                }
+
+                if !is_worse(&path, &chosen_path) {
+                    for blank_node in recursion_list {
+                        let (hash, result_issuer) = self.hash_n_degree(&blank_node, issuer_copy.clone())?;
+                        path.push_str(&issuer_copy.issue(&blank_node));
+                        path.push_str(&format!("<{}>", hash));
+                        issuer_copy = result_issuer;
+
+                        if is_worse(&path, &chosen_path) {
+                            break;
+                        }
+                    }
+
+                    if chosen_path.is_empty() || path < chosen_path {
+                        chosen_path = path;
+                        chosen_issuer = Some(issuer_copy);
+                    }
+                }
+
+                if !next_permutation(&mut related) {
+                    break;
+                }
+            }
+
+            data_to_hash.push_str(&chosen_path);
+            issuer = chosen_issuer.unwrap();
+        }
+
+        Ok((sha256_hex(&data_to_hash), issuer))
+    }
+
+    /// Issues the canonical identifiers of every blank node
+    fn run(mut self) -> Result<IdentifierIssuer, CanonicalizationError> {
+        let mut blank_nodes: Vec<String> = self.blank_node_quads.keys().cloned().collect();
+        blank_nodes.sort();
+
+        let mut blank_nodes_by_hash: BTreeMap<String, Vec<String>> = BTreeMap::new();
+
+        for blank_node in blank_nodes {
+            let hash = self.hash_first_degree(&blank_node);
+            self.first_degree_hashes.insert(blank_node.clone(), hash.clone());
+            blank_nodes_by_hash.entry(hash).or_default().push(blank_node);
+        }
+
+        let mut shared_hashes = vec!();
+
+        for (_, blank_nodes) in blank_nodes_by_hash {
+            if blank_nodes.len() == 1 {
+                self.canonical_issuer.issue(&blank_nodes[0]);
+            } else {
+                shared_hashes.push(blank_nodes);
+            }
+        }
+
+        for blank_nodes in shared_hashes {
+            let mut results = vec!();
+
+            for blank_node in blank_nodes {
+                if self.canonical_issuer.get(&blank_node).is_some() {
+                    continue;
+                }
+
+                let mut issuer = IdentifierIssuer::new("_:b");
+                issuer.issue(&blank_node);
+                results.push(self.hash_n_degree(&blank_node, issuer)?);
+            }
+
+            results.sort_by(|a, b| a.0.cmp(&b.0));
+
+            for (_, issuer) in results {
+                for existing in &issuer.order {
+                    self.canonical_issuer.issue(existing);
+                }
+            }
+        }
+
+        Ok(self.canonical_issuer)
+    }
+}
+
+/// Returns the canonical N-Quads serialization of the quads
+fn canonical_nquads<D>(dataset: &D) -> Result<String, CanonicalizationError>
+    where D: Dataset {
+    let quads: Vec<RcQuad> = dataset.quads()
+        .map(|quad| RcQuad::new_from_quad(&quad.unwrap()))
+        .collect();
+
+    let canonical_issuer = Canonicalizer::new(&quads).run()?;
+    let label = |blank_node: &str| canonical_issuer.get(blank_node).unwrap().clone();
+
+    let mut lines: Vec<String> = quads.iter().map(|quad| write_quad(quad, &label)).collect();
+    lines.sort();
+    lines.dedup();
+
+    Ok(lines.concat())
+}
+
+/// Canonicalization of Sophia datasets
+pub trait DatasetCanonicalizer: Dataset + Sized {
+    /// Returns the canonical N-Quads serialization of the dataset: blank nodes
+    /// are labeled `c14n0`, `c14n1`, ... and the lines are sorted.
+    fn to_canonical(&self) -> Result<String, CanonicalizationError> {
+        canonical_nquads(self)
+    }
+
+    /// Returns the hexadecimal SHA-256 digest of the canonical serialization
+    fn canonical_hash(&self) -> Result<String, CanonicalizationError> {
+        Ok(sha256_hex(&canonical_nquads(self)?))
+    }
+
+    /// Returns true if `other` is equal to this dataset, up to the labels of
+    /// the blank nodes
+    fn is_isomorphic<D>(&self, other: &D) -> Result<bool, CanonicalizationError>
+        where D: Dataset {
+        Ok(canonical_nquads(self)? == canonical_nquads(other)?)
+    }
+
+    /// Returns true if a blank node appears in the dataset
+    fn has_blank_nodes(&self) -> bool {
+        self.quads().any(|quad| {
+            let quad = RcQuad::new_from_quad(&quad.unwrap());
+            [quad.s(), quad.o()].iter().copied().chain(quad.g())
+                .any(|term| blank_node_label(term).is_some())
+        })
+    }
+}
+
+impl<D> DatasetCanonicalizer for D where D: Dataset {}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::treedataset::TreeDataset;
+    use sophia::dataset::MutableDataset;
+
+    fn iri(value: &str) -> RcTerm {
+        RcTerm::new_iri(value).unwrap()
+    }
+
+    fn bnode(label: &str) -> RcTerm {
+        RcTerm::new_bnode(label).unwrap()
+    }
+
+    /// Builds a dataset from (subject, object) pairs linked by ex:p, where
+    /// strings starting with `_:` are blank nodes
+    fn linked(pairs: &[(&str, &str)]) -> TreeDataset {
+        let term = |value: &str| match value.strip_prefix("_:") {
+            Some(label) => bnode(label),
+            None => iri(value)
+        };
+        let mut dataset = TreeDataset::new();
+
+        for (s, o) in pairs {
+            dataset.insert(&term(s), &iri("http://example.org/p"), &term(o), None as Option<&RcTerm>).unwrap();
+        }
+
+        dataset
+    }
+
+    #[test]
+    fn labels_blank_nodes_canonically() {
+        let dataset = linked(&[("_:x", "http://example.org/o")]);
+        assert_eq!(dataset.to_canonical().unwrap(), "_:c14n0 <http://example.org/p> <http://example.org/o> .\n");
+    }
+
+    #[test]
+    fn isomorphic_datasets_have_the_same_canonical_form() {
+        let cycle = linked(&[("_:a", "_:b"), ("_:b", "_:c"), ("_:c", "_:a")]);
+        let relabeled_cycle = linked(&[("_:z", "_:x"), ("_:x", "_:y"), ("_:y", "_:z")]);
+        let chain = linked(&[("_:a", "_:b"), ("_:b", "_:c"), ("_:c", "_:c")]);
+
+        assert_eq!(cycle.to_canonical(), relabeled_cycle.to_canonical());
+        assert_eq!(cycle.canonical_hash(), relabeled_cycle.canonical_hash());
+        assert!(cycle.is_isomorphic(&relabeled_cycle).unwrap());
+        assert!(!cycle.is_isomorphic(&chain).unwrap());
+    }
+}
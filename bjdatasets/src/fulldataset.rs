@@ -21,6 +21,7 @@ use sophia::dataset::DResult;
 use sophia::dataset::DQuad;
 
 use crate::RcQuad;
+use crate::graphs::NamedGraphs;
 
 #[cfg(test)]
 use sophia::test_dataset_impl;
@@ -421,6 +422,17 @@ impl Data {
         }
     }
 
+    /// Returns every identifier used in the quads at the term position of
+    /// the *position*th 1 index hashmap
+    pub fn distinct_1(&self, position: usize) -> Vec<u32> {
+        self.one_indexes[position]
+            .get_or_init(|| self.build_1(position))
+            .iter()
+            .filter(|(_, values)| !values.is_empty())
+            .map(|(key, _)| *key)
+            .collect()
+    }
+
     /// Decompose the SPGO quad into the wanted 3 indexes quad for the
     /// *position*th hashmap into the form (key, value)
     fn decompose_3(quad: [u32; 4], position: usize) -> ([u32; 3], u32) {
@@ -737,5 +749,33 @@ impl MutableDataset for FullIndexDataset {
     }
 }
 
+impl NamedGraphs for FullIndexDataset {
+    fn graph_names(&self) -> Vec<Option<RcTerm>> {
+        self.data.distinct_1(POS_G)
+            .into_iter()
+            .map(|g| self.term_id_map.get_graph_name(g).unwrap().cloned())
+            .collect()
+    }
+
+    fn drop_graph(&mut self, g: Option<&RcTerm>) -> usize {
+        let g = match self.term_id_map.get_index_for_graph_name(g.map(RefTerm::from).as_ref()) {
+            Some(g) => g,
+            None => return 0
+        };
+
+        let quads = self.data.get_1(POS_G, g).collect::<Vec<_>>();
+
+        for quad in &quads {
+            self.data.remove(*quad);
+
+            for term in quad {
+                self.term_id_map.dec_ref(*term);
+            }
+        }
+
+        quads.len()
+    }
+}
+
 #[cfg(test)]
 sophia::test_dataset_impl!(test_fulldataset, FullIndexDataset);
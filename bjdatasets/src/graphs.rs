@@ -0,0 +1,173 @@
+//! Operations on the named graphs of a dataset.
+//!
+//! The default implementations only rely on `quads_with_g`, which the datasets
+//! of this crate answer with their graph-first indexes. `TreeDataset` and
+//! `FullIndexDataset` also list and drop graphs from their identifiers.
+
+use crate::RcQuad;
+use crate::rdfs::RdfsDataset;
+use crate::vecordataset::VecOrDataset;
+
+use sophia::dataset::inmem::FastDataset;
+use sophia::dataset::inmem::LightDataset;
+use sophia::dataset::Dataset;
+use sophia::dataset::MutableDataset;
+use sophia::quad::Quad;
+use sophia::term::BoxTerm;
+use sophia::term::RcTerm;
+
+use std::collections::HashSet;
+use std::convert::Infallible;
+
+/// Returns the quads of the graph `g` of a dataset
+fn graph_quads<D>(dataset: &D, g: Option<&RcTerm>) -> Vec<RcQuad>
+    where D: Dataset {
+    dataset.quads_with_g(g)
+        .map(|quad| RcQuad::new_from_quad(&quad.unwrap()))
+        .collect()
+}
+
+/// Removes every quad of the graph `g` one by one. Returns the number of
+/// removed quads.
+pub fn drop_graph_by_removal<D>(dataset: &mut D, g: Option<&RcTerm>) -> usize
+    where D: MutableDataset<MutationError = Infallible> {
+    let quads = graph_quads(dataset, g);
+
+    for quad in &quads {
+        dataset.remove(quad.s(), quad.p(), quad.o(), quad.g()).unwrap();
+    }
+
+    quads.len()
+}
+
+/// Inserts a copy of every quad of the graph `from` in the graph `to`.
+/// Returns the number of added quads.
+pub fn copy_graph_by_insertion<D>(dataset: &mut D, from: Option<&RcTerm>, to: Option<&RcTerm>) -> usize
+    where D: MutableDataset<MutationError = Infallible> {
+    if from == to {
+        return 0;
+    }
+
+    let mut number_of_added_quads = 0;
+
+    for quad in graph_quads(dataset, from) {
+        if dataset.insert(quad.s(), quad.p(), quad.o(), to).unwrap() {
+            number_of_added_quads += 1;
+        }
+    }
+
+    number_of_added_quads
+}
+
+/// Removes every quad of the graph `from` and inserts it back in the graph
+/// `to`. Returns the number of moved quads.
+pub fn move_graph_by_reinsertion<D>(dataset: &mut D, from: Option<&RcTerm>, to: Option<&RcTerm>) -> usize
+    where D: MutableDataset<MutationError = Infallible> {
+    let quads = graph_quads(dataset, from);
+
+    if from != to {
+        for quad in &quads {
+            dataset.remove(quad.s(), quad.p(), quad.o(), quad.g()).unwrap();
+            dataset.insert(quad.s(), quad.p(), quad.o(), to).unwrap();
+        }
+    }
+
+    quads.len()
+}
+
+/// Datasets whose graphs can be listed and handled as a whole.
+///
+/// Graph names are represented as `Option<&RcTerm>`, `None` being the default
+/// graph.
+pub trait NamedGraphs: MutableDataset<MutationError = Infallible> + Sized {
+    /// Returns the name of every graph that contains at least one quad
+    fn graph_names(&self) -> Vec<Option<RcTerm>> {
+        let mut seen = HashSet::new();
+        let mut graph_names = vec![];
+
+        for quad in self.quads() {
+            let quad = RcQuad::new_from_quad(&quad.unwrap());
+            let graph_name = quad.g().cloned();
+
+            if seen.insert(graph_name.clone()) {
+                graph_names.push(graph_name);
+            }
+        }
+
+        graph_names
+    }
+
+    /// Removes every quad of the graph `g`. Returns the number of removed
+    /// quads.
+    fn drop_graph(&mut self, g: Option<&RcTerm>) -> usize {
+        drop_graph_by_removal(self, g)
+    }
+
+    /// Adds a copy of every quad of the graph `from` in the graph `to`.
+    /// Returns the number of added quads.
+    fn copy_graph(&mut self, from: Option<&RcTerm>, to: Option<&RcTerm>) -> usize {
+        copy_graph_by_insertion(self, from, to)
+    }
+
+    /// Moves every quad of the graph `from` in the graph `to`. Returns the
+    /// number of moved quads.
+    fn move_graph(&mut self, from: Option<&RcTerm>, to: Option<&RcTerm>) -> usize {
+        move_graph_by_reinsertion(self, from, to)
+    }
+}
+
+impl NamedGraphs for FastDataset {}
+impl NamedGraphs for LightDataset {}
+impl NamedGraphs for Vec<([BoxTerm; 3], Option<BoxTerm>)> {}
+
+// The RDFS rules are applied in each graph separately, so dropping the
+// asserted quads of a graph also drops its inferred quads
+impl NamedGraphs for RdfsDataset {}
+
+impl<D> NamedGraphs for VecOrDataset<D>
+    where D: Dataset + MutableDataset<MutationError = Infallible> + Default {}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::fulldataset::FullIndexDataset;
+    use crate::treedataset::TreeDataset;
+
+    fn iri(value: &str) -> RcTerm {
+        RcTerm::new_iri(value).unwrap()
+    }
+
+    fn manage_graphs<D>(mut dataset: D)
+        where D: NamedGraphs {
+        let s = iri("http://example.org/s");
+        let p = iri("http://example.org/p");
+        let g1 = iri("http://example.org/g1");
+        let g2 = iri("http://example.org/g2");
+
+        dataset.insert(&s, &p, &iri("http://example.org/o1"), Some(&g1)).unwrap();
+        dataset.insert(&s, &p, &iri("http://example.org/o2"), Some(&g1)).unwrap();
+        dataset.insert(&s, &p, &iri("http://example.org/o1"), None as Option<&RcTerm>).unwrap();
+
+        let mut graph_names = dataset.graph_names();
+        graph_names.sort_by_key(|g| g.as_ref().map(|g| g.value().to_string()));
+        assert_eq!(graph_names, vec![None, Some(g1.clone())]);
+
+        assert_eq!(dataset.copy_graph(Some(&g1), None), 1);
+        assert_eq!(dataset.quads_with_g(None as Option<&RcTerm>).count(), 2);
+
+        assert_eq!(dataset.move_graph(Some(&g1), Some(&g2)), 2);
+        assert_eq!(dataset.quads_with_g(Some(&g1)).count(), 0);
+        assert_eq!(dataset.quads_with_g(Some(&g2)).count(), 2);
+
+        assert_eq!(dataset.drop_graph(Some(&g2)), 2);
+        assert_eq!(dataset.graph_names(), vec![None]);
+        assert_eq!(dataset.drop_graph(Some(&g1)), 0);
+    }
+
+    #[test]
+    fn manages_graphs() {
+        manage_graphs(TreeDataset::new());
+        manage_graphs(FullIndexDataset::new());
+        manage_graphs(FastDataset::default());
+    }
+}
@@ -0,0 +1,1642 @@
+//! JSON-LD 1.1 import and export of datasets.
+//!
+//! This module implements the parts of the JSON-LD 1.1 processing algorithms
+//! that are needed to move quads in and out of a dataset:
+//! - the context processing, with term definitions, type coercion, default
+//!   language, `@vocab` and `@base`,
+//! - the expansion of a document,
+//! - the conversion of an expanded document into quads (`jsonld_to_quads`),
+//! - the conversion of a dataset into an expanded document, with
+//!   `@list` detection, and its compaction with a context
+//!   (`dataset_to_jsonld`).
+//!
+//! Remote contexts are never fetched: they are given by a `ContextLoader`
+//! supplied by the caller.
+//!
+//! Not supported: `@nest`, `@included`, `@json` literals, `@direction`,
+//! protected and type-scoped contexts, property-scoped contexts and the
+//! `@graph`, `@id` and `@type` containers.
+
+use crate::RcQuad;
+use crate::literal::value_of;
+
+use serde_json::json;
+use serde_json::Map;
+use serde_json::Value;
+
+use sophia::dataset::Dataset;
+use sophia::quad::Quad;
+use sophia::term::RcTerm;
+use sophia::term::Term;
+
+use std::collections::BTreeMap;
+use std::collections::HashMap;
+
+const RDF_TYPE: &str = "http://www.w3.org/1999/02/22-rdf-syntax-ns#type";
+const RDF_FIRST: &str = "http://www.w3.org/1999/02/22-rdf-syntax-ns#first";
+const RDF_REST: &str = "http://www.w3.org/1999/02/22-rdf-syntax-ns#rest";
+const RDF_NIL: &str = "http://www.w3.org/1999/02/22-rdf-syntax-ns#nil";
+const XSD_STRING: &str = "http://www.w3.org/2001/XMLSchema#string";
+const XSD_BOOLEAN: &str = "http://www.w3.org/2001/XMLSchema#boolean";
+const XSD_INTEGER: &str = "http://www.w3.org/2001/XMLSchema#integer";
+const XSD_DOUBLE: &str = "http://www.w3.org/2001/XMLSchema#double";
+
+const KEYWORDS: [&str; 23] = [
+    "@base", "@container", "@context", "@direction", "@graph", "@id",
+    "@import", "@included", "@index", "@json", "@language", "@list", "@nest",
+    "@none", "@prefix", "@propagate", "@protected", "@reverse", "@set",
+    "@type", "@value", "@version", "@vocab"
+];
+
+/// An error raised while processing a JSON-LD document
+#[derive(Debug, Clone, PartialEq)]
+pub struct JsonLdError {
+    /// The JSON-LD error code, like `invalid term definition`
+    pub code: &'static str,
+    /// A description of the error
+    pub message: String
+}
+
+impl JsonLdError {
+    /// Builds an error with the given JSON-LD error code
+    pub fn new<S: Into<String>>(code: &'static str, message: S) -> JsonLdError {
+        JsonLdError { code, message: message.into() }
+    }
+}
+
+impl std::fmt::Display for JsonLdError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}: {}", self.code, self.message)
+    }
+}
+
+impl std::error::Error for JsonLdError {}
+
+type JsonLdResult<T> = Result<T, JsonLdError>;
+
+/// Returns the JSON-LD document at the given IRI. It is used to load the
+/// remote contexts.
+pub type ContextLoader<'a> = dyn FnMut(&str) -> JsonLdResult<Value> + 'a;
+
+/// A loader that refuses to load any remote context
+pub fn no_context_loader(iri: &str) -> JsonLdResult<Value> {
+    Err(JsonLdError::new("loading remote context failed", format!("No loader to load {}", iri)))
+}
+
+fn is_keyword(value: &str) -> bool {
+    KEYWORDS.contains(&value)
+}
+
+/// Returns the values of an array, or the value itself
+fn as_slice(value: &Value) -> &[Value] {
+    match value {
+        Value::Array(values) => values,
+        Value::Null => &[],
+        other => std::slice::from_ref(other)
+    }
+}
+
+/// Converts a value into an array of values
+fn into_vec(value: Value) -> Vec<Value> {
+    match value {
+        Value::Array(values) => values,
+        Value::Null => vec!(),
+        other => vec!(other)
+    }
+}
+
+/// Adds the values to the array stored in `map` at `key`
+fn add_values(map: &mut Map<String, Value>, key: &str, values: Value) {
+    let entry = map.entry(key.to_string()).or_insert_with(|| Value::Array(vec!()));
+
+    if let Value::Array(array) = entry {
+        array.extend(into_vec(values));
+    }
+}
+
+fn is_list_object(value: &Value) -> bool {
+    value.as_object().is_some_and(|object| object.contains_key("@list"))
+}
+
+
+// ============================================================================
+// IRI resolution
+
+/// Splits an IRI reference into its scheme, authority, path, query and
+/// fragment (RFC 3986, appendix B)
+fn split_reference(reference: &str) -> (Option<&str>, Option<&str>, &str, Option<&str>, Option<&str>) {
+    let (rest, fragment) = match reference.find('#') {
+        Some(position) => (&reference[..position], Some(&reference[position + 1..])),
+        None => (reference, None)
+    };
+
+    let (rest, query) = match rest.find('?') {
+        Some(position) => (&rest[..position], Some(&rest[position + 1..])),
+        None => (rest, None)
+    };
+
+    let scheme_end = rest.find(':').filter(|&position| {
+        let scheme = &rest[..position];
+        !scheme.is_empty()
+            && scheme.starts_with(|c: char| c.is_ascii_alphabetic())
+            && scheme.chars().all(|c| c.is_ascii_alphanumeric() || c == '+' || c == '-' || c == '.')
+    });
+
+    let (scheme, rest) = match scheme_end {
+        Some(position) => (Some(&rest[..position]), &rest[position + 1..]),
+        None => (None, rest)
+    };
+
+    let (authority, path) = if let Some(rest) = rest.strip_prefix("//") {
+        match rest.find('/') {
+            Some(position) => (Some(&rest[..position]), &rest[position..]),
+            None => (Some(rest), "")
+        }
+    } else {
+        (None, rest)
+    };
+
+    (scheme, authority, path, query, fragment)
+}
+
+/// Removes the `.` and `..` segments of a path (RFC 3986, section 5.2.4)
+fn remove_dot_segments(path: &str) -> String {
+    let mut output: Vec<&str> = vec!();
+    let segments: Vec<&str> = path.split('/').collect();
+
+    for (i, segment) in segments.iter().enumerate() {
+        let is_last = i + 1 == segments.len();
+
+        match *segment {
+            "." => if is_last { output.push("") },
+            ".." => {
+                if output.len() > 1 || (output.len() == 1 && !output[0].is_empty()) {
+                    output.pop();
+                }
+                if is_last { output.push("") }
+            },
+            segment => output.push(segment)
+        }
+    }
+
+    let result = output.join("/");
+    if path.starts_with('/') && !result.starts_with('/') {
+        format!("/{}", result)
+    } else {
+        result
+    }
+}
+
+/// Resolves an IRI reference against a base IRI (RFC 3986, section 5.2)
+fn resolve_iri(base: Option<&str>, reference: &str) -> String {
+    let base = match base {
+        Some(base) => base,
+        None => return reference.to_string()
+    };
+
+    let (r_scheme, r_authority, r_path, r_query, r_fragment) = split_reference(reference);
+    let (b_scheme, b_authority, b_path, b_query, _) = split_reference(base);
+
+    let (scheme, authority, path, query) = if r_scheme.is_some() {
+        (r_scheme, r_authority, remove_dot_segments(r_path), r_query)
+    } else if r_authority.is_some() {
+        (b_scheme, r_authority, remove_dot_segments(r_path), r_query)
+    } else if r_path.is_empty() {
+        (b_scheme, b_authority, b_path.to_string(), r_query.or(b_query))
+    } else if r_path.starts_with('/') {
+        (b_scheme, b_authority, remove_dot_segments(r_path), r_query)
+    } else {
+        let merged = if b_authority.is_some() && b_path.is_empty() {
+            format!("/{}", r_path)
+        } else {
+            match b_path.rfind('/') {
+                Some(position) => format!("{}{}", &b_path[..=position], r_path),
+                None => r_path.to_string()
+            }
+        };
+        (b_scheme, b_authority, remove_dot_segments(&merged), r_query)
+    };
+
+    let mut result = String::new();
+    if let Some(scheme) = scheme {
+        result.push_str(scheme);
+        result.push(':');
+    }
+    if let Some(authority) = authority {
+        result.push_str("//");
+        result.push_str(authority);
+    }
+    result.push_str(&path);
+    if let Some(query) = query {
+        result.push('?');
+        result.push_str(query);
+    }
+    if let Some(fragment) = r_fragment {
+        result.push('#');
+        result.push_str(fragment);
+    }
+
+    result
+}
+
+
+// ============================================================================
+// Contexts
+
+/// The definition of a term in a context
+#[derive(Clone, Debug, Default)]
+struct TermDefinition {
+    /// The IRI mapping. `None` if the term is explicitly mapped to null.
+    iri: Option<String>,
+    /// True if the term is a reverse property
+    reverse: bool,
+    /// The type mapping: `@id`, `@vocab`, `@none` or a datatype IRI
+    type_mapping: Option<String>,
+    /// The language mapping. `Some(None)` is an explicit null language.
+    language: Option<Option<String>>,
+    /// The container mapping: `@list`, `@set`, `@language` or `@index`
+    container: Option<String>,
+    /// True if the term can be used as a prefix in compact IRIs
+    prefix: bool
+}
+
+/// An active context
+#[derive(Clone, Debug, Default)]
+pub struct Context {
+    /// The base IRI
+    base: Option<String>,
+    /// The base IRI of the document, restored by a null context
+    original_base: Option<String>,
+    /// The vocabulary mapping
+    vocab: Option<String>,
+    /// The default language
+    language: Option<String>,
+    /// The term definitions
+    terms: HashMap<String, TermDefinition>
+}
+
+impl Context {
+    /// Builds an empty context for a document located at `base`
+    pub fn new(base: Option<&str>) -> Context {
+        Context {
+            base: base.map(String::from),
+            original_base: base.map(String::from),
+            ..Context::default()
+        }
+    }
+
+    /// Returns the context resulting from processing `local` in this context
+    pub fn process(&self, local: &Value, loader: &mut ContextLoader) -> JsonLdResult<Context> {
+        self.process_with_remotes(local, loader, &mut vec!())
+    }
+
+    fn process_with_remotes(&self, local: &Value, loader: &mut ContextLoader, remotes: &mut Vec<String>) -> JsonLdResult<Context> {
+        let mut result = self.clone();
+
+        for context in as_slice(local) {
+            match context {
+                Value::Null => result = Context::new(self.original_base.as_deref()),
+                Value::String(reference) => {
+                    let iri = resolve_iri(result.base.as_deref(), reference);
+
+                    if remotes.contains(&iri) {
+                        return Err(JsonLdError::new("recursive context inclusion", iri));
+                    }
+
+                    let document = loader(&iri)
+                        .map_err(|error| JsonLdError::new("loading remote context failed", error.message))?;
+
+                    // The loader may either give a document with a @context
+                    // entry or the context itself
+                    let remote_context = match document {
+                        Value::Object(mut document) if document.contains_key("@context") =>
+                            document.remove("@context").unwrap(),
+                        document @ Value::Object(_) => document,
+                        _ => return Err(JsonLdError::new("invalid remote context", iri))
+                    };
+
+                    remotes.push(iri);
+                    result = result.process_with_remotes(&remote_context, loader, remotes)?;
+                    remotes.pop();
+                },
+                Value::Object(definitions) => result.process_definitions(definitions)?,
+                _ => return Err(JsonLdError::new("invalid local context", context.to_string()))
+            }
+        }
+
+        Ok(result)
+    }
+
+    /// Processes a context definition, which is a JSON object
+    fn process_definitions(&mut self, local: &Map<String, Value>) -> JsonLdResult<()> {
+        if let Some(version) = local.get("@version") {
+            if version.as_f64() != Some(1.1) {
+                return Err(JsonLdError::new("invalid @version value", version.to_string()));
+            }
+        }
+
+        match local.get("@base") {
+            None => {},
+            Some(Value::Null) => self.base = None,
+            Some(Value::String(base)) => self.base = Some(resolve_iri(self.base.as_deref(), base)),
+            Some(other) => return Err(JsonLdError::new("invalid base IRI", other.to_string()))
+        }
+
+        match local.get("@vocab") {
+            None => {},
+            Some(Value::Null) => self.vocab = None,
+            Some(Value::String(vocab)) => self.vocab = self.expand_iri(vocab, true, true),
+            Some(other) => return Err(JsonLdError::new("invalid vocab mapping", other.to_string()))
+        }
+
+        match local.get("@language") {
+            None => {},
+            Some(Value::Null) => self.language = None,
+            Some(Value::String(language)) => self.language = Some(language.to_lowercase()),
+            Some(other) => return Err(JsonLdError::new("invalid default language", other.to_string()))
+        }
+
+        let mut defined = HashMap::new();
+
+        for term in local.keys() {
+            match term.as_str() {
+                "@base" | "@vocab" | "@language" | "@version" | "@protected" | "@propagate" | "@direction" => {},
+                "@import" => return Err(JsonLdError::new("invalid context entry", "@import is not supported")),
+                term => self.create_term_definition(local, term, &mut defined)?
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Creates the definition of `term` from the local context
+    fn create_term_definition(&mut self, local: &Map<String, Value>, term: &str, defined: &mut HashMap<String, bool>) -> JsonLdResult<()> {
+        match defined.get(term) {
+            Some(true) => return Ok(()),
+            Some(false) => return Err(JsonLdError::new("cyclic IRI mapping", term)),
+            None => {}
+        }
+
+        if term.is_empty() {
+            return Err(JsonLdError::new("invalid term definition", "A term can not be empty"));
+        }
+
+        if is_keyword(term) {
+            return Err(JsonLdError::new("keyword redefinition", term));
+        }
+
+        defined.insert(term.to_string(), false);
+        self.terms.remove(term);
+
+        let mut definition = TermDefinition::default();
+
+        let (value, simple_term) = match &local[term] {
+            Value::Null => {
+                self.terms.insert(term.to_string(), definition);
+                defined.insert(term.to_string(), true);
+                return Ok(());
+            },
+            Value::String(id) => {
+                let mut value = Map::new();
+                value.insert("@id".to_string(), Value::String(id.clone()));
+                (value, true)
+            },
+            Value::Object(value) => (value.clone(), false),
+            other => return Err(JsonLdError::new("invalid term definition", other.to_string()))
+        };
+
+        if let Some(type_mapping) = value.get("@type") {
+            let type_mapping = type_mapping.as_str()
+                .ok_or_else(|| JsonLdError::new("invalid type mapping", type_mapping.to_string()))?;
+            let type_mapping = self.expand_iri_in_definition(type_mapping, false, true, local, defined)?
+                .filter(|iri| iri == "@id" || iri == "@vocab" || iri == "@none" || (!is_keyword(iri) && iri.contains(':')))
+                .ok_or_else(|| JsonLdError::new("invalid type mapping", type_mapping))?;
+            definition.type_mapping = Some(type_mapping);
+        }
+
+        if let Some(reverse) = value.get("@reverse") {
+            if value.contains_key("@id") {
+                return Err(JsonLdError::new("invalid reverse property", term));
+            }
+
+            let reverse = reverse.as_str()
+                .ok_or_else(|| JsonLdError::new("invalid IRI mapping", reverse.to_string()))?;
+            definition.iri = Some(self.expand_iri_in_definition(reverse, false, true, local, defined)?
+                .filter(|iri| iri.contains(':'))
+                .ok_or_else(|| JsonLdError::new("invalid IRI mapping", reverse))?);
+            definition.reverse = true;
+
+            match value.get("@container").map(|container| container.as_str()) {
+                None | Some(Some("@set")) | Some(Some("@index")) => {},
+                _ => return Err(JsonLdError::new("invalid reverse property", term))
+            }
+
+            self.terms.insert(term.to_string(), definition);
+            defined.insert(term.to_string(), true);
+            return Ok(());
+        }
+
+        match value.get("@id") {
+            Some(Value::Null) => definition.iri = None,
+            Some(Value::String(id)) if id != term => {
+                let iri = self.expand_iri_in_definition(id, false, true, local, defined)?;
+
+                match &iri {
+                    Some(iri) if iri == "@context" => return Err(JsonLdError::new("invalid keyword alias", term)),
+                    Some(iri) if !is_keyword(iri) && !iri.contains(':') => return Err(JsonLdError::new("invalid IRI mapping", iri.as_str())),
+                    _ => {}
+                }
+
+                // A simple term whose IRI ends with a general delimiter can
+                // be used as a prefix
+                definition.prefix = simple_term && !term.contains(':')
+                    && iri.as_ref().is_some_and(|iri| iri.ends_with(|c| ":/?#[]@".contains(c)));
+                definition.iri = iri;
+            },
+            None | Some(Value::String(_)) => {
+                match term.find(':').filter(|&position| position > 0) {
+                    Some(position) => {
+                        // A compact IRI or an absolute IRI
+                        let (prefix, suffix) = (&term[..position], &term[position + 1..]);
+
+                        if local.contains_key(prefix) {
+                            self.create_term_definition(local, prefix, defined)?;
+                        }
+
+                        definition.iri = Some(match self.terms.get(prefix).and_then(|prefix| prefix.iri.as_ref()) {
+                            Some(prefix_iri) => format!("{}{}", prefix_iri, suffix),
+                            None => term.to_string()
+                        });
+                    },
+                    None => match &self.vocab {
+                        Some(vocab) => definition.iri = Some(format!("{}{}", vocab, term)),
+                        None => return Err(JsonLdError::new("invalid IRI mapping", term))
+                    }
+                }
+            },
+            Some(other) => return Err(JsonLdError::new("invalid IRI mapping", other.to_string()))
+        }
+
+        if let Some(container) = value.get("@container") {
+            let containers = match container {
+                Value::String(container) => vec!(container.as_str()),
+                Value::Array(containers) => containers.iter()
+                    .map(|container| container.as_str()
+                        .ok_or_else(|| JsonLdError::new("invalid container mapping", container.to_string())))
+                    .collect::<JsonLdResult<Vec<&str>>>()?,
+                other => return Err(JsonLdError::new("invalid container mapping", other.to_string()))
+            };
+
+            if let Some(unsupported) = containers.iter().find(|c| !["@list", "@set", "@index", "@language"].contains(c)) {
+                return Err(JsonLdError::new("invalid container mapping", *unsupported));
+            }
+
+            definition.container = containers.iter()
+                .find(|container| **container != "@set")
+                .or_else(|| containers.first())
+                .map(|container| container.to_string());
+        }
+
+        if let Some(language) = value.get("@language") {
+            if !value.contains_key("@type") {
+                definition.language = Some(match language {
+                    Value::Null => None,
+                    Value::String(language) => Some(language.to_lowercase()),
+                    other => return Err(JsonLdError::new("invalid language mapping", other.to_string()))
+                });
+            }
+        }
+
+        if let Some(prefix) = value.get("@prefix") {
+            definition.prefix = prefix.as_bool()
+                .ok_or_else(|| JsonLdError::new("invalid @prefix value", prefix.to_string()))?;
+        }
+
+        self.terms.insert(term.to_string(), definition);
+        defined.insert(term.to_string(), true);
+        Ok(())
+    }
+
+    /// Expands an IRI while a local context is processed, defining the terms
+    /// it depends on first
+    fn expand_iri_in_definition(&mut self, value: &str, document_relative: bool, vocab: bool,
+        local: &Map<String, Value>, defined: &mut HashMap<String, bool>) -> JsonLdResult<Option<String>> {
+        if is_keyword(value) {
+            return Ok(Some(value.to_string()));
+        }
+
+        if local.contains_key(value) && defined.get(value) != Some(&true) {
+            self.create_term_definition(local, value, defined)?;
+        }
+
+        if let Some(position) = value.find(':') {
+            let prefix = &value[..position];
+            if local.contains_key(prefix) && defined.get(prefix) != Some(&true) {
+                self.create_term_definition(local, prefix, defined)?;
+            }
+        }
+
+        Ok(self.expand_iri(value, document_relative, vocab))
+    }
+
+    /// Expands a term, a compact IRI or a relative IRI. Returns `None` if the
+    /// value is mapped to null.
+    fn expand_iri(&self, value: &str, document_relative: bool, vocab: bool) -> Option<String> {
+        if is_keyword(value) {
+            return Some(value.to_string());
+        }
+
+        if vocab {
+            if let Some(definition) = self.terms.get(value) {
+                return definition.iri.clone();
+            }
+        }
+
+        if let Some(position) = value.find(':') {
+            let (prefix, suffix) = (&value[..position], &value[position + 1..]);
+
+            if prefix == "_" || suffix.starts_with("//") {
+                return Some(value.to_string());
+            }
+
+            if let Some(prefix_iri) = self.terms.get(prefix).and_then(|prefix| prefix.iri.as_ref()) {
+                return Some(format!("{}{}", prefix_iri, suffix));
+            }
+
+            return Some(value.to_string());
+        }
+
+        if vocab {
+            if let Some(vocab) = &self.vocab {
+                return Some(format!("{}{}", vocab, value));
+            }
+        }
+
+        if document_relative {
+            return Some(resolve_iri(self.base.as_deref(), value));
+        }
+
+        Some(value.to_string())
+    }
+}
+
+
+// ============================================================================
+// Expansion
+
+/// Expands a JSON-LD document located at `base`
+pub fn expand(document: &Value, base: Option<&str>, loader: &mut ContextLoader) -> JsonLdResult<Vec<Value>> {
+    let context = Context::new(base);
+
+    let expanded = match expand_element(&context, None, document, loader)? {
+        Value::Object(mut object) if object.len() == 1 && object.contains_key("@graph") =>
+            object.remove("@graph").unwrap(),
+        expanded => expanded
+    };
+
+    Ok(into_vec(expanded))
+}
+
+fn expand_element(context: &Context, active_property: Option<&str>, element: &Value, loader: &mut ContextLoader) -> JsonLdResult<Value> {
+    match element {
+        Value::Null => Ok(Value::Null),
+        Value::Array(items) => {
+            let mut result = vec!();
+
+            for item in items {
+                match expand_element(context, active_property, item, loader)? {
+                    Value::Array(expanded) => result.extend(expanded),
+                    Value::Null => {},
+                    expanded => result.push(expanded)
+                }
+            }
+
+            Ok(Value::Array(result))
+        },
+        Value::Object(object) => expand_object(context, active_property, object, loader),
+        scalar => match active_property {
+            // Free floating values are dropped
+            None | Some("@graph") => Ok(Value::Null),
+            Some(property) => Ok(expand_value(context, property, scalar))
+        }
+    }
+}
+
+fn expand_object(context: &Context, active_property: Option<&str>, object: &Map<String, Value>, loader: &mut ContextLoader) -> JsonLdResult<Value> {
+    let local_context;
+    let context = match object.get("@context") {
+        Some(local) => {
+            local_context = context.process(local, loader)?;
+            &local_context
+        },
+        None => context
+    };
+
+    let mut result = Map::new();
+
+    for (key, value) in object {
+        if key == "@context" {
+            continue;
+        }
+
+        let property = match context.expand_iri(key, false, true) {
+            Some(property) if is_keyword(&property) || property.contains(':') => property,
+            _ => continue
+        };
+
+        if is_keyword(&property) {
+            if active_property == Some("@reverse") {
+                return Err(JsonLdError::new("invalid reverse property map", key.as_str()));
+            }
+
+            let expanded = match property.as_str() {
+                "@id" => match value {
+                    Value::String(id) => Value::String(context.expand_iri(id, true, false).unwrap_or_default()),
+                    other => return Err(JsonLdError::new("invalid @id value", other.to_string()))
+                },
+                "@type" => {
+                    let types = as_slice(value).iter()
+                        .map(|t| match t {
+                            Value::String(t) => Ok(Value::String(context.expand_iri(t, true, true).unwrap_or_else(|| t.clone()))),
+                            other => Err(JsonLdError::new("invalid type value", other.to_string()))
+                        })
+                        .collect::<JsonLdResult<Vec<Value>>>()?;
+                    Value::Array(types)
+                },
+                "@graph" => Value::Array(into_vec(expand_element(context, Some("@graph"), value, loader)?)),
+                "@value" => match value {
+                    Value::Array(_) | Value::Object(_) => return Err(JsonLdError::new("invalid value object value", value.to_string())),
+                    scalar => scalar.clone()
+                },
+                "@language" => match value {
+                    Value::String(language) => Value::String(language.to_lowercase()),
+                    other => return Err(JsonLdError::new("invalid language-tagged string", other.to_string()))
+                },
+                "@index" => match value {
+                    Value::String(_) => value.clone(),
+                    other => return Err(JsonLdError::new("invalid @index value", other.to_string()))
+                },
+                "@list" => match active_property {
+                    None | Some("@graph") => continue,
+                    _ => Value::Array(into_vec(expand_element(context, active_property, value, loader)?))
+                },
+                "@set" => expand_element(context, active_property, value, loader)?,
+                "@reverse" => {
+                    let reverse_map = match expand_element(context, Some("@reverse"), value, loader)? {
+                        Value::Object(reverse_map) => reverse_map,
+                        _ => return Err(JsonLdError::new("invalid @reverse value", value.to_string()))
+                    };
+
+                    for (reverse_property, items) in reverse_map {
+                        if reverse_property == "@reverse" {
+                            if let Value::Object(forward) = items {
+                                for (forward_property, forward_items) in forward {
+                                    add_values(&mut result, &forward_property, forward_items);
+                                }
+                            }
+                        } else {
+                            let reverse = result.entry("@reverse".to_string())
+                                .or_insert_with(|| Value::Object(Map::new()));
+                            add_values(reverse.as_object_mut().unwrap(), &reverse_property, items);
+                        }
+                    }
+
+                    continue;
+                },
+                // Unsupported keywords are ignored
+                _ => continue
+            };
+
+            result.insert(property, expanded);
+            continue;
+        }
+
+        let definition = context.terms.get(key.as_str());
+        let container = definition.and_then(|definition| definition.container.as_deref());
+
+        let expanded = match (container, value) {
+            (Some("@language"), Value::Object(languages)) => {
+                let mut values = vec!();
+
+                for (language, strings) in languages {
+                    for string in as_slice(strings) {
+                        match string {
+                            Value::String(_) => {
+                                let mut language_string = Map::new();
+                                language_string.insert("@value".to_string(), string.clone());
+                                if language != "@none" {
+                                    language_string.insert("@language".to_string(), Value::String(language.to_lowercase()));
+                                }
+                                values.push(Value::Object(language_string));
+                            },
+                            Value::Null => {},
+                            other => return Err(JsonLdError::new("invalid language map value", other.to_string()))
+                        }
+                    }
+                }
+
+                Value::Array(values)
+            },
+            (Some("@index"), Value::Object(indexes)) => {
+                let mut values = vec!();
+
+                for (index, items) in indexes {
+                    for item in into_vec(expand_element(context, Some(key.as_str()), items, loader)?) {
+                        match item {
+                            Value::Object(mut item) => {
+                                if index != "@none" {
+                                    item.entry("@index".to_string()).or_insert_with(|| Value::String(index.clone()));
+                                }
+                                values.push(Value::Object(item));
+                            },
+                            other => values.push(other)
+                        }
+                    }
+                }
+
+                Value::Array(values)
+            },
+            _ => expand_element(context, Some(key.as_str()), value, loader)?
+        };
+
+        if expanded.is_null() {
+            continue;
+        }
+
+        let expanded = if container == Some("@list") && !is_list_object(&expanded) {
+            json!({ "@list": into_vec(expanded) })
+        } else {
+            expanded
+        };
+
+        if definition.is_some_and(|definition| definition.reverse) {
+            let reverse = result.entry("@reverse".to_string())
+                .or_insert_with(|| Value::Object(Map::new()));
+
+            for item in into_vec(expanded) {
+                if is_list_object(&item) || item.get("@value").is_some() {
+                    return Err(JsonLdError::new("invalid reverse property value", item.to_string()));
+                }
+
+                add_values(reverse.as_object_mut().unwrap(), &property, item);
+            }
+        } else {
+            add_values(&mut result, &property, expanded);
+        }
+    }
+
+    if let Some(value) = result.get("@value") {
+        if result.keys().any(|key| !["@value", "@language", "@type", "@index"].contains(&key.as_str()))
+            || (result.contains_key("@language") && result.contains_key("@type")) {
+            return Err(JsonLdError::new("invalid value object", Value::Object(result).to_string()));
+        }
+
+        if value.is_null() {
+            return Ok(Value::Null);
+        }
+
+        if result.contains_key("@language") && !value.is_string() {
+            return Err(JsonLdError::new("invalid language-tagged value", value.to_string()));
+        }
+
+        if let Some(datatype) = result.remove("@type") {
+            match datatype.as_array().map(Vec::as_slice) {
+                Some([Value::String(datatype)]) if datatype.contains(':') && !datatype.starts_with("_:") => {
+                    result.insert("@type".to_string(), Value::String(datatype.clone()));
+                },
+                _ => return Err(JsonLdError::new("invalid typed value", datatype.to_string()))
+            }
+        }
+    } else if result.contains_key("@set") || result.contains_key("@list") {
+        if result.keys().any(|key| key != "@set" && key != "@list" && key != "@index") {
+            return Err(JsonLdError::new("invalid set or list object", Value::Object(result).to_string()));
+        }
+
+        if let Some(set) = result.remove("@set") {
+            return Ok(set);
+        }
+    }
+
+    if result.len() == 1 && result.contains_key("@language") {
+        return Ok(Value::Null);
+    }
+
+    let is_free_floating = result.is_empty() || result.contains_key("@value") || result.contains_key("@list")
+        || (result.len() == 1 && result.contains_key("@id"));
+
+    if (active_property.is_none() || active_property == Some("@graph")) && is_free_floating {
+        return Ok(Value::Null);
+    }
+
+    Ok(Value::Object(result))
+}
+
+/// Expands a scalar value of the given property
+fn expand_value(context: &Context, property: &str, value: &Value) -> Value {
+    let definition = context.terms.get(property);
+    let type_mapping = definition.and_then(|definition| definition.type_mapping.as_deref());
+
+    if let Value::String(string) = value {
+        match type_mapping {
+            Some("@id") => return json!({ "@id": context.expand_iri(string, true, false) }),
+            Some("@vocab") => return json!({ "@id": context.expand_iri(string, true, true) }),
+            _ => {}
+        }
+    }
+
+    let mut result = Map::new();
+    result.insert("@value".to_string(), value.clone());
+
+    match type_mapping {
+        Some(datatype) if datatype != "@id" && datatype != "@vocab" && datatype != "@none" => {
+            result.insert("@type".to_string(), Value::String(datatype.to_string()));
+        },
+        _ => if value.is_string() {
+            let language = match definition.and_then(|definition| definition.language.clone()) {
+                Some(language) => language,
+                None => context.language.clone()
+            };
+
+            if let Some(language) = language {
+                result.insert("@language".to_string(), Value::String(language));
+            }
+        }
+    }
+
+    Value::Object(result)
+}
+
+
+// ============================================================================
+// Conversion to quads
+
+fn iri(value: &str) -> RcTerm {
+    RcTerm::new_iri(value).unwrap()
+}
+
+/// Returns the canonical lexical form of a `xsd:double`
+fn canonical_double(value: f64) -> String {
+    let formatted = format!("{:E}", value);
+
+    match formatted.find('E') {
+        Some(position) if !formatted[..position].contains('.') =>
+            format!("{}.0{}", &formatted[..position], &formatted[position..]),
+        _ => formatted
+    }
+}
+
+fn literal(value: &str, datatype: &str) -> Option<RcTerm> {
+    RcTerm::new_literal_dt(value, RcTerm::new_iri(datatype).ok()?).ok()
+}
+
+/// Converts the value of a value object into a literal
+fn literal_of(value: &Value, datatype: Option<&str>, language: Option<&str>) -> Option<RcTerm> {
+    match value {
+        Value::String(value) => match (language, datatype) {
+            (Some(language), _) => RcTerm::new_literal_lang(value.as_str(), language).ok(),
+            (None, Some(datatype)) => literal(value, datatype),
+            (None, None) => literal(value, XSD_STRING)
+        },
+        Value::Bool(value) => literal(if *value { "true" } else { "false" }, datatype.unwrap_or(XSD_BOOLEAN)),
+        Value::Number(number) => {
+            let float = number.as_f64()?;
+            let is_integer = number.is_i64() || number.is_u64() || (float.fract() == 0.0 && float.abs() < 1e21);
+
+            if is_integer && datatype != Some(XSD_DOUBLE) {
+                let lexical = if number.is_i64() || number.is_u64() { number.to_string() } else { format!("{:.0}", float) };
+                literal(&lexical, datatype.unwrap_or(XSD_INTEGER))
+            } else {
+                literal(&canonical_double(float), datatype.unwrap_or(XSD_DOUBLE))
+            }
+        },
+        _ => None
+    }
+}
+
+/// Gives new labels to the blank nodes of a document
+#[derive(Default)]
+struct BlankNodeIssuer {
+    labels: HashMap<String, String>,
+    counter: usize
+}
+
+impl BlankNodeIssuer {
+    fn fresh(&mut self) -> RcTerm {
+        let label = format!("b{}", self.counter);
+        self.counter += 1;
+        RcTerm::new_bnode(label).unwrap()
+    }
+
+    fn label(&mut self, id: &str) -> RcTerm {
+        if let Some(term) = self.labels.get(id) {
+            return RcTerm::new_bnode(term.as_str()).unwrap();
+        }
+
+        let term = self.fresh();
+        self.labels.insert(id.to_string(), value_of(&term));
+        term
+    }
+}
+
+/// Converts an expanded document into quads
+#[derive(Default)]
+struct QuadWriter {
+    issuer: BlankNodeIssuer,
+    quads: Vec<RcQuad>
+}
+
+impl QuadWriter {
+    fn push(&mut self, s: &RcTerm, p: &RcTerm, o: &RcTerm, g: Option<&RcTerm>) {
+        self.quads.push(RcQuad::new(s, p, o, g));
+    }
+
+    /// Returns the term identified by a node identifier. Relative IRIs are
+    /// not valid terms.
+    fn term_of_id(&mut self, id: &str) -> Option<RcTerm> {
+        if id.starts_with("_:") {
+            Some(self.issuer.label(id))
+        } else if id.contains(':') {
+            RcTerm::new_iri(id).ok()
+        } else {
+            None
+        }
+    }
+
+    /// Writes the quads of a node object and returns its subject
+    fn write_node(&mut self, node: &Map<String, Value>, graph: Option<&RcTerm>) -> Option<RcTerm> {
+        let subject = match node.get("@id").and_then(Value::as_str) {
+            Some(id) => self.term_of_id(id)?,
+            None => self.issuer.fresh()
+        };
+
+        for (property, values) in node {
+            match property.as_str() {
+                "@type" => for datatype in as_slice(values) {
+                    if let Some(datatype) = datatype.as_str().and_then(|datatype| self.term_of_id(datatype)) {
+                        self.push(&subject, &iri(RDF_TYPE), &datatype, graph);
+                    }
+                },
+                "@reverse" => for (reverse_property, items) in values.as_object().into_iter().flatten() {
+                    let predicate = match predicate_of(reverse_property) {
+                        Some(predicate) => predicate,
+                        None => continue
+                    };
+
+                    for item in as_slice(items).iter().filter_map(Value::as_object) {
+                        if let Some(reverse_subject) = self.write_node(item, graph) {
+                            self.push(&reverse_subject, &predicate, &subject, graph);
+                        }
+                    }
+                },
+                "@graph" => for item in as_slice(values).iter().filter_map(Value::as_object) {
+                    if !item.contains_key("@value") && !item.contains_key("@list") {
+                        self.write_node(item, Some(&subject));
+                    }
+                },
+                keyword if is_keyword(keyword) => {},
+                property => {
+                    let predicate = match predicate_of(property) {
+                        Some(predicate) => predicate,
+                        None => continue
+                    };
+
+                    for item in as_slice(values) {
+                        if let Some(object) = self.write_object(item, graph) {
+                            self.push(&subject, &predicate, &object, graph);
+                        }
+                    }
+                }
+            }
+        }
+
+        Some(subject)
+    }
+
+    /// Writes the quads needed to describe an object and returns the term
+    /// that represents it
+    fn write_object(&mut self, item: &Value, graph: Option<&RcTerm>) -> Option<RcTerm> {
+        let item = item.as_object()?;
+
+        if let Some(value) = item.get("@value") {
+            let datatype = item.get("@type").and_then(Value::as_str);
+            let language = item.get("@language").and_then(Value::as_str);
+            literal_of(value, datatype, language)
+        } else if let Some(list) = item.get("@list") {
+            self.write_list(as_slice(list), graph)
+        } else {
+            self.write_node(item, graph)
+        }
+    }
+
+    /// Writes a list with `rdf:first` and `rdf:rest` and returns its head
+    fn write_list(&mut self, items: &[Value], graph: Option<&RcTerm>) -> Option<RcTerm> {
+        let objects: Vec<RcTerm> = items.iter()
+            .filter_map(|item| self.write_object(item, graph))
+            .collect();
+
+        let nil = iri(RDF_NIL);
+        let nodes: Vec<RcTerm> = objects.iter().map(|_| self.issuer.fresh()).collect();
+
+        for (i, object) in objects.iter().enumerate() {
+            self.push(&nodes[i], &iri(RDF_FIRST), object, graph);
+            self.push(&nodes[i], &iri(RDF_REST), nodes.get(i + 1).unwrap_or(&nil), graph);
+        }
+
+        Some(nodes.first().cloned().unwrap_or(nil))
+    }
+}
+
+/// Returns the predicate for a property. Blank node properties are not valid
+/// predicates.
+fn predicate_of(property: &str) -> Option<RcTerm> {
+    if property.starts_with("_:") {
+        None
+    } else {
+        RcTerm::new_iri(property).ok()
+    }
+}
+
+/// Converts a JSON-LD document located at `base` into quads
+pub fn jsonld_to_quads(document: &Value, base: Option<&str>, loader: &mut ContextLoader) -> JsonLdResult<Vec<RcQuad>> {
+    let expanded = expand(document, base, loader)?;
+    let mut writer = QuadWriter::default();
+
+    for node in expanded.iter().filter_map(Value::as_object) {
+        writer.write_node(node, None);
+    }
+
+    Ok(writer.quads)
+}
+
+
+// ============================================================================
+// Conversion from a dataset
+
+/// Returns the node identifier of an IRI or a blank node
+fn id_of(term: &RcTerm) -> Option<String> {
+    match term {
+        Term::Iri(_) => Some(value_of(term)),
+        Term::BNode(_) => Some(format!("_:{}", value_of(term))),
+        _ => None
+    }
+}
+
+/// Returns the expanded object that represents a term
+fn object_of(term: &RcTerm) -> Option<Value> {
+    match term {
+        Term::Literal(literal) => {
+            let value = value_of(term);
+
+            Some(match literal.lang() {
+                Some(language) => json!({ "@value": value, "@language": language.as_ref() }),
+                None => {
+                    let datatype = value_of(&literal.dt());
+                    if datatype == XSD_STRING {
+                        json!({ "@value": value })
+                    } else {
+                        json!({ "@value": value, "@type": datatype })
+                    }
+                }
+            })
+        },
+        Term::Variable(_) => None,
+        _ => Some(json!({ "@id": id_of(term)? }))
+    }
+}
+
+fn new_node(id: &str) -> Map<String, Value> {
+    let mut node = Map::new();
+    node.insert("@id".to_string(), Value::String(id.to_string()));
+    node
+}
+
+/// The nodes of a graph, by identifier
+type NodeMap = BTreeMap<String, Map<String, Value>>;
+
+/// Reads the well formed list whose head is `head`. Its nodes are added to
+/// `list_nodes`.
+fn read_list(nodes: &NodeMap, usages: &HashMap<String, usize>, head: &str, list_nodes: &mut Vec<String>) -> Option<Vec<Value>> {
+    let mut items = vec!();
+    let mut chain = vec!();
+    let mut id = head.to_string();
+
+    while id != RDF_NIL {
+        if !id.starts_with("_:") || usages.get(&id) != Some(&1) || chain.contains(&id) {
+            return None;
+        }
+
+        let node = nodes.get(&id)?;
+        if node.len() != 3 {
+            return None;
+        }
+
+        let first = match node.get(RDF_FIRST)?.as_array()?.as_slice() {
+            [first] => first.clone(),
+            _ => return None
+        };
+
+        let rest = match node.get(RDF_REST)?.as_array()?.as_slice() {
+            [rest] => rest.get("@id")?.as_str()?.to_string(),
+            _ => return None
+        };
+
+        items.push(first);
+        chain.push(id);
+        id = rest;
+    }
+
+    let items = items.into_iter()
+        .map(|item| list_or_value(nodes, usages, item, &mut chain))
+        .collect();
+
+    list_nodes.extend(chain);
+    Some(items)
+}
+
+/// Returns the list that `value` refers to if it is the head of a well formed
+/// list, or `value`
+fn list_or_value(nodes: &NodeMap, usages: &HashMap<String, usize>, value: Value, list_nodes: &mut Vec<String>) -> Value {
+    let head = match value.as_object() {
+        Some(reference) if reference.len() == 1 => reference.get("@id").and_then(Value::as_str).map(String::from),
+        _ => None
+    };
+
+    match head {
+        Some(head) if head == RDF_NIL => json!({ "@list": [] }),
+        Some(head) => match read_list(nodes, usages, &head, list_nodes) {
+            Some(items) => json!({ "@list": items }),
+            None => value
+        },
+        None => value
+    }
+}
+
+/// Replaces the references to the heads of well formed lists with `@list`
+/// objects
+fn convert_lists(nodes: &mut NodeMap, usages: &HashMap<String, usize>) {
+    let mut list_nodes = vec!();
+    let mut converted_nodes = vec!();
+
+    for (id, node) in nodes.iter() {
+        let mut node = node.clone();
+
+        for (property, values) in node.iter_mut() {
+            if is_keyword(property) || property == RDF_FIRST || property == RDF_REST {
+                continue;
+            }
+
+            if let Value::Array(values) = values {
+                for value in values.iter_mut() {
+                    *value = list_or_value(nodes, usages, value.take(), &mut list_nodes);
+                }
+            }
+        }
+
+        converted_nodes.push((id.clone(), node));
+    }
+
+    for (id, node) in converted_nodes {
+        nodes.insert(id, node);
+    }
+
+    for id in list_nodes {
+        nodes.remove(&id);
+    }
+}
+
+/// Converts the quads of a dataset into an expanded JSON-LD document
+pub fn dataset_to_expanded_jsonld<D>(dataset: &D) -> Vec<Value>
+    where D: Dataset {
+    let mut graphs: BTreeMap<String, NodeMap> = BTreeMap::new();
+    let mut usages: HashMap<String, HashMap<String, usize>> = HashMap::new();
+
+    for quad in dataset.quads() {
+        let quad = RcQuad::new_from_quad(&quad.unwrap());
+
+        let (subject, object) = match (id_of(quad.s()), object_of(quad.o())) {
+            (Some(subject), Some(object)) => (subject, object),
+            _ => continue
+        };
+
+        let graph_name = match quad.g() {
+            None => "@default".to_string(),
+            Some(graph) => match id_of(graph) {
+                Some(graph) => graph,
+                None => continue
+            }
+        };
+
+        if graph_name != "@default" {
+            graphs.entry("@default".to_string()).or_default()
+                .entry(graph_name.clone()).or_insert_with(|| new_node(&graph_name));
+        }
+
+        let node = graphs.entry(graph_name.clone()).or_default()
+            .entry(subject.clone()).or_insert_with(|| new_node(&subject));
+
+        let predicate = value_of(quad.p());
+
+        let (key, value) = match object.get("@id") {
+            Some(id) if predicate == RDF_TYPE => ("@type".to_string(), id.clone()),
+            _ => (predicate, object.clone())
+        };
+
+        let values = node.entry(key).or_insert_with(|| Value::Array(vec!()));
+        if let Value::Array(values) = values {
+            if !values.contains(&value) {
+                values.push(value);
+
+                if let Term::BNode(_) = quad.o() {
+                    *usages.entry(graph_name).or_default()
+                        .entry(id_of(quad.o()).unwrap()).or_insert(0) += 1;
+                }
+            }
+        }
+    }
+
+    let no_usage = HashMap::new();
+    for (graph_name, nodes) in graphs.iter_mut() {
+        convert_lists(nodes, usages.get(graph_name).unwrap_or(&no_usage));
+    }
+
+    let default_graph = graphs.remove("@default").unwrap_or_default();
+
+    default_graph.into_iter()
+        .map(|(id, mut node)| {
+            if let Some(graph) = graphs.remove(&id) {
+                let graph = graph.into_values().map(Value::Object).collect();
+                node.insert("@graph".to_string(), Value::Array(graph));
+            }
+
+            Value::Object(node)
+        })
+        .collect()
+}
+
+
+// ============================================================================
+// Compaction
+
+/// Compacts an expanded document with an active context
+struct Compactor<'c> {
+    context: &'c Context
+}
+
+impl<'c> Compactor<'c> {
+    /// Compacts an IRI. If `vocab` is true, terms and the vocabulary mapping
+    /// can be used.
+    fn compact_iri(&self, iri: &str, vocab: bool) -> String {
+        if is_keyword(iri) {
+            return iri.to_string();
+        }
+
+        if vocab {
+            let simple_term = self.context.terms.iter()
+                .filter(|(_, definition)| definition.iri.as_deref() == Some(iri) && !definition.reverse
+                    && definition.type_mapping.is_none() && definition.language.is_none()
+                    && definition.container.is_none())
+                .map(|(term, _)| term)
+                .min_by(|a, b| a.len().cmp(&b.len()).then_with(|| a.cmp(b)));
+
+            if let Some(term) = simple_term {
+                return term.clone();
+            }
+
+            if let Some(vocab) = &self.context.vocab {
+                if iri.starts_with(vocab.as_str()) && iri.len() > vocab.len() {
+                    let suffix = &iri[vocab.len()..];
+                    if !self.context.terms.contains_key(suffix) && !suffix.contains(':') {
+                        return suffix.to_string();
+                    }
+                }
+            }
+        }
+
+        self.context.terms.iter()
+            .filter(|(term, definition)| definition.prefix && !term.contains(':'))
+            .filter_map(|(term, definition)| {
+                let prefix_iri = definition.iri.as_deref()?;
+                if iri.len() > prefix_iri.len() && iri.starts_with(prefix_iri) {
+                    Some(format!("{}:{}", term, &iri[prefix_iri.len()..]))
+                } else {
+                    None
+                }
+            })
+            .filter(|candidate| match self.context.terms.get(candidate) {
+                None => true,
+                Some(definition) => definition.iri.as_deref() == Some(iri)
+            })
+            .min_by(|a, b| a.len().cmp(&b.len()).then_with(|| a.cmp(b)))
+            .unwrap_or_else(|| iri.to_string())
+    }
+
+    /// Ranks how well a term fits a value of its property. `None` means that
+    /// the term can not be used for this value.
+    fn term_rank(&self, definition: &TermDefinition, value: &Value) -> Option<u8> {
+        let object = value.as_object()?;
+        let is_list = object.contains_key("@list");
+
+        match definition.container.as_deref() {
+            Some("@list") => return if is_list { Some(3) } else { None },
+            Some("@language") | Some("@index") => return None,
+            _ => {}
+        }
+
+        let type_mapping = definition.type_mapping.as_deref();
+
+        if is_list {
+            return if type_mapping.is_none() && definition.language.is_none() { Some(1) } else { None };
+        }
+
+        if object.contains_key("@value") {
+            let value_type = object.get("@type").and_then(Value::as_str);
+            let value_language = object.get("@language").and_then(Value::as_str).map(str::to_lowercase);
+
+            match (type_mapping, &definition.language) {
+                (Some("@id"), _) | (Some("@vocab"), _) | (Some("@none"), _) => None,
+                (Some(datatype), _) => if value_type == Some(datatype) { Some(2) } else { None },
+                (None, Some(language)) => if value_type.is_none() && &value_language == language { Some(2) } else { None },
+                (None, None) => Some(1)
+            }
+        } else {
+            match (type_mapping, &definition.language) {
+                (Some("@id"), _) | (Some("@vocab"), _) => Some(if object.len() == 1 { 2 } else { 1 }),
+                (None, None) => Some(1),
+                _ => None
+            }
+        }
+    }
+
+    /// Returns the term to use for a value of `property`
+    fn select_term(&self, property: &str, value: &Value) -> String {
+        self.context.terms.iter()
+            .filter(|(_, definition)| !definition.reverse && definition.iri.as_deref() == Some(property))
+            .filter_map(|(term, definition)| self.term_rank(definition, value).map(|rank| (rank, term)))
+            .max_by(|(rank_a, a), (rank_b, b)| rank_a.cmp(rank_b)
+                .then_with(|| b.len().cmp(&a.len()))
+                .then_with(|| b.cmp(a)))
+            .map(|(_, term)| term.clone())
+            .unwrap_or_else(|| self.compact_iri(property, true))
+    }
+
+    /// Compacts a value of a property represented by `term`
+    fn compact_value(&self, term: &str, value: &Value) -> Value {
+        let definition = self.context.terms.get(term);
+        let object = match value.as_object() {
+            Some(object) => object,
+            None => return value.clone()
+        };
+
+        let type_mapping = definition.and_then(|definition| definition.type_mapping.as_deref());
+
+        if let Some(list) = object.get("@list") {
+            let items: Vec<Value> = as_slice(list).iter().map(|item| self.compact_value(term, item)).collect();
+
+            return if definition.and_then(|definition| definition.container.as_deref()) == Some("@list") {
+                Value::Array(items)
+            } else {
+                json!({ "@list": items })
+            };
+        }
+
+        if let Some(literal_value) = object.get("@value") {
+            let value_type = object.get("@type").and_then(Value::as_str);
+            let value_language = object.get("@language").and_then(Value::as_str).map(str::to_lowercase);
+            let language = match definition.and_then(|definition| definition.language.clone()) {
+                Some(language) => language,
+                None => self.context.language.clone()
+            };
+
+            if !object.contains_key("@index") {
+                if value_type.is_some() && value_type == type_mapping {
+                    return literal_value.clone();
+                }
+
+                if value_type.is_none() && type_mapping.is_none() && value_language == language {
+                    return literal_value.clone();
+                }
+            }
+
+            let mut result = Map::new();
+            result.insert("@value".to_string(), literal_value.clone());
+            if let Some(value_type) = value_type {
+                result.insert("@type".to_string(), Value::String(self.compact_iri(value_type, true)));
+            }
+            if let Some(value_language) = value_language {
+                result.insert("@language".to_string(), Value::String(value_language));
+            }
+            if let Some(index) = object.get("@index") {
+                result.insert("@index".to_string(), index.clone());
+            }
+            return Value::Object(result);
+        }
+
+        if object.len() == 1 {
+            if let Some(id) = object.get("@id").and_then(Value::as_str) {
+                return match type_mapping {
+                    Some("@id") => Value::String(self.compact_iri(id, false)),
+                    Some("@vocab") => Value::String(self.compact_iri(id, true)),
+                    _ => json!({ "@id": self.compact_iri(id, false) })
+                };
+            }
+        }
+
+        self.compact_node(object)
+    }
+
+    /// Compacts a node object
+    fn compact_node(&self, node: &Map<String, Value>) -> Value {
+        let mut result = Map::new();
+
+        for (property, values) in node {
+            match property.as_str() {
+                "@id" => {
+                    let id = values.as_str().unwrap_or_default();
+                    result.insert("@id".to_string(), Value::String(self.compact_iri(id, false)));
+                },
+                "@type" => {
+                    let mut types: Vec<Value> = as_slice(values).iter()
+                        .filter_map(Value::as_str)
+                        .map(|datatype| Value::String(self.compact_iri(datatype, true)))
+                        .collect();
+
+                    let types = if types.len() == 1 { types.remove(0) } else { Value::Array(types) };
+                    result.insert("@type".to_string(), types);
+                },
+                "@graph" => {
+                    let graph = as_slice(values).iter()
+                        .filter_map(Value::as_object)
+                        .map(|node| self.compact_node(node))
+                        .collect();
+                    result.insert("@graph".to_string(), Value::Array(graph));
+                },
+                keyword if is_keyword(keyword) => {
+                    result.insert(keyword.to_string(), values.clone());
+                },
+                property => {
+                    let mut by_term: BTreeMap<String, Vec<Value>> = BTreeMap::new();
+
+                    for value in as_slice(values) {
+                        let mut term = self.select_term(property, value);
+
+                        // A term with a list container can only hold one list
+                        let is_list_term = self.context.terms.get(&term)
+                            .and_then(|definition| definition.container.as_deref()) == Some("@list");
+                        if is_list_term && by_term.contains_key(&term) {
+                            term = self.compact_iri(property, true);
+                        }
+
+                        let compacted = self.compact_value(&term, value);
+                        by_term.entry(term).or_default().push(compacted);
+                    }
+
+                    for (term, mut compacted) in by_term {
+                        let container = self.context.terms.get(&term)
+                            .and_then(|definition| definition.container.as_deref());
+
+                        let value = match container {
+                            Some("@list") => compacted.remove(0),
+                            Some("@set") => Value::Array(compacted),
+                            _ if compacted.len() == 1 => compacted.remove(0),
+                            _ => Value::Array(compacted)
+                        };
+
+                        result.insert(term, value);
+                    }
+                }
+            }
+        }
+
+        Value::Object(result)
+    }
+}
+
+/// Compacts an expanded document with the given context. The context is
+/// written in the `@context` entry of the returned document.
+pub fn compact(expanded: &[Value], context: &Value, loader: &mut ContextLoader) -> JsonLdResult<Value> {
+    let active_context = Context::new(None).process(context, loader)?;
+    let compactor = Compactor { context: &active_context };
+
+    let mut nodes: Vec<Value> = expanded.iter()
+        .filter_map(Value::as_object)
+        .map(|node| compactor.compact_node(node))
+        .collect();
+
+    let mut result = Map::new();
+
+    let has_context = match context {
+        Value::Null => false,
+        Value::Object(context) => !context.is_empty(),
+        Value::Array(contexts) => !contexts.is_empty(),
+        _ => true
+    };
+
+    if has_context {
+        result.insert("@context".to_string(), context.clone());
+    }
+
+    if nodes.len() == 1 {
+        if let Value::Object(node) = nodes.remove(0) {
+            result.extend(node);
+        }
+    } else {
+        result.insert("@graph".to_string(), Value::Array(nodes));
+    }
+
+    Ok(Value::Object(result))
+}
+
+/// Converts the quads of a dataset into a JSON-LD document compacted with the
+/// given context
+pub fn dataset_to_jsonld<D>(dataset: &D, context: &Value, loader: &mut ContextLoader) -> JsonLdResult<Value>
+    where D: Dataset {
+    compact(&dataset_to_expanded_jsonld(dataset), context, loader)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::treedataset::TreeDataset;
+    use sophia::dataset::MutableDataset;
+
+    fn to_dataset(document: &Value) -> TreeDataset {
+        let mut dataset = TreeDataset::new();
+        for quad in jsonld_to_quads(document, Some("http://example.org/doc"), &mut no_context_loader).unwrap() {
+            dataset.insert(quad.s(), quad.p(), quad.o(), quad.g()).unwrap();
+        }
+        dataset
+    }
+
+    #[test]
+    fn resolves_relative_iris() {
+        assert_eq!(resolve_iri(Some("http://a/b/c/d;p?q"), "../g"), "http://a/b/g");
+        assert_eq!(resolve_iri(Some("http://a/b/c/d;p?q"), "#s"), "http://a/b/c/d;p?q#s");
+        assert_eq!(resolve_iri(Some("http://a/b/c/d;p?q"), "/./g"), "http://a/g");
+        assert_eq!(resolve_iri(Some("http://a/b/c/d;p?q"), "g:h"), "g:h");
+    }
+
+    #[test]
+    fn converts_to_quads_with_a_context() {
+        let document = json!({
+            "@context": {
+                "@vocab": "http://example.org/",
+                "knows": { "@type": "@id" },
+                "tags": { "@container": "@list" },
+                "name": { "@language": "en" }
+            },
+            "@id": "alice",
+            "@type": "Person",
+            "name": "Alice",
+            "age": 42,
+            "knows": "bob",
+            "tags": ["a", "b"]
+        });
+
+        let dataset = to_dataset(&document);
+        let alice = iri("http://example.org/alice");
+
+        assert_eq!(dataset.quads().count(), 9);
+        assert!(dataset.contains(&alice, &iri(RDF_TYPE), &iri("http://example.org/Person"), None as Option<&RcTerm>).unwrap());
+        assert!(dataset.contains(&alice, &iri("http://example.org/knows"), &iri("http://example.org/bob"), None as Option<&RcTerm>).unwrap());
+        assert!(dataset.contains(&alice, &iri("http://example.org/name"), &RcTerm::new_literal_lang("Alice", "en").unwrap(), None as Option<&RcTerm>).unwrap());
+        assert!(dataset.contains(&alice, &iri("http://example.org/age"), &literal("42", XSD_INTEGER).unwrap(), None as Option<&RcTerm>).unwrap());
+    }
+
+    #[test]
+    fn round_trips_through_compaction() {
+        let context = json!({
+            "ex": "http://example.org/",
+            "knows": { "@id": "http://example.org/knows", "@type": "@id" },
+            "tags": { "@id": "http://example.org/tags", "@container": "@list" }
+        });
+
+        let document = json!({
+            "@context": context.clone(),
+            "@id": "ex:alice",
+            "@type": "ex:Person",
+            "knows": "ex:bob",
+            "tags": ["a", "b"]
+        });
+
+        let dataset = to_dataset(&document);
+        let compacted = dataset_to_jsonld(&dataset, &context, &mut no_context_loader).unwrap();
+
+        assert_eq!(compacted, document);
+    }
+
+    #[test]
+    fn loads_remote_contexts_with_the_loader() {
+        let document = json!({ "@context": "http://example.org/context", "name": "Bob" });
+
+        let mut loader = |iri: &str| {
+            assert_eq!(iri, "http://example.org/context");
+            Ok(json!({ "@context": { "name": "http://xmlns.com/foaf/0.1/name" } }))
+        };
+
+        let quads = jsonld_to_quads(&json!([document.clone()]), None, &mut loader).unwrap();
+        assert_eq!(quads.len(), 1);
+
+        let error = jsonld_to_quads(&document, None, &mut no_context_loader).unwrap_err();
+        assert_eq!(error.code, "loading remote context failed");
+    }
+}
@@ -1,7 +1,20 @@
 pub mod fulldataset;
 pub mod vecordataset;
 pub mod treedataset;
+pub mod serializer;
+pub mod jsonld;
+pub mod canonical;
+pub mod graphs;
+pub mod skolem;
+pub mod termdictionary;
+pub mod patch;
+pub mod rdfs;
+pub mod shacl;
+pub mod shex;
+pub mod literalindex;
+pub mod textindex;
 
+mod literal;
 mod rcquad;
 
 pub use rcquad::RcQuad;
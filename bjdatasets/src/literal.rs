@@ -0,0 +1,46 @@
+//! Helpers to read the value and the lexical form of terms.
+
+use sophia::term::RcTerm;
+
+const XSD_BOOLEAN: &str = "http://www.w3.org/2001/XMLSchema#boolean";
+const XSD_DECIMAL: &str = "http://www.w3.org/2001/XMLSchema#decimal";
+const XSD_DOUBLE: &str = "http://www.w3.org/2001/XMLSchema#double";
+const XSD_FLOAT: &str = "http://www.w3.org/2001/XMLSchema#float";
+const XSD_INTEGER: &str = "http://www.w3.org/2001/XMLSchema#integer";
+
+/// Returns the value of a term
+pub(crate) fn value_of(term: &RcTerm) -> String {
+    let value = term.value();
+    let value: &str = value.as_ref();
+    value.to_string()
+}
+
+/// Returns true if `lexical` is a valid lexical form of `datatype`. Only the
+/// common numeric and boolean datatypes are checked.
+pub(crate) fn is_well_formed(datatype: &str, lexical: &str) -> bool {
+    let unsigned = lexical.strip_prefix(|c| c == '+' || c == '-').unwrap_or(lexical);
+
+    match datatype {
+        XSD_INTEGER => !unsigned.is_empty() && unsigned.chars().all(|c| c.is_ascii_digit()),
+        XSD_DECIMAL => {
+            let mut parts = unsigned.splitn(2, '.');
+            let integer_part = parts.next().unwrap_or("");
+            let fraction_part = parts.next().unwrap_or("");
+
+            (integer_part.len() + fraction_part.len()) > 0
+                && integer_part.chars().chain(fraction_part.chars()).all(|c| c.is_ascii_digit())
+        },
+        XSD_BOOLEAN => matches_any(lexical, &["true", "false", "1", "0"]),
+        XSD_DOUBLE | XSD_FLOAT => {
+            matches_any(lexical, &["INF", "+INF", "-INF", "NaN"])
+                || (unsigned.starts_with(|c: char| c.is_ascii_digit() || c == '.')
+                    && lexical.chars().all(|c| c.is_ascii_digit() || "+-.eE".contains(c))
+                    && lexical.parse::<f64>().is_ok())
+        },
+        _ => true
+    }
+}
+
+fn matches_any(value: &str, candidates: &[&str]) -> bool {
+    candidates.iter().any(|candidate| *candidate == value)
+}
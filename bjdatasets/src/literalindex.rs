@@ -0,0 +1,374 @@
+//! A secondary index of the numeric and temporal literals of a term
+//! dictionary.
+//!
+//! A term dictionary sees literals as opaque strings, so finding the quads
+//! whose object is greater than a value means reading every quad. The
+//! `LiteralIndex` reads the literals of the `xsd:integer`, `xsd:decimal`,
+//! `xsd:double` and `xsd:float` datatypes as numbers, and the literals of the
+//! `xsd:date` and `xsd:dateTime` datatypes as instants, and keeps their
+//! identifiers sorted by value.
+//!
+//! Numbers and instants are never compared with each other. A date is the
+//! instant at which it begins, and the dates and times without a timezone are
+//! read as UTC. Literals that are not well-formed are not indexed.
+
+use crate::literal::{ is_well_formed, value_of };
+
+use sophia::term::RcTerm;
+use sophia::term::Term;
+
+use std::cmp::Ordering;
+use std::collections::BTreeSet;
+use std::ops::Bound;
+
+const XSD_DATE: &str = "http://www.w3.org/2001/XMLSchema#date";
+const XSD_DATE_TIME: &str = "http://www.w3.org/2001/XMLSchema#dateTime";
+const XSD_DECIMAL: &str = "http://www.w3.org/2001/XMLSchema#decimal";
+const XSD_DOUBLE: &str = "http://www.w3.org/2001/XMLSchema#double";
+const XSD_FLOAT: &str = "http://www.w3.org/2001/XMLSchema#float";
+const XSD_INTEGER: &str = "http://www.w3.org/2001/XMLSchema#integer";
+
+/// The sortable value of a numeric or temporal literal
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum LiteralKey {
+    /// The value of a numeric literal, which is never NaN
+    Number(f64),
+    /// The seconds since 1970-01-01T00:00:00Z and the nanoseconds of a date or
+    /// of a date and time
+    Instant(i64, u32)
+}
+
+impl LiteralKey {
+    /// Returns the key of `term`, if it is a well-formed numeric or temporal
+    /// literal
+    pub fn of(term: &RcTerm) -> Option<LiteralKey> {
+        let datatype = match term {
+            Term::Literal(literal) if literal.lang().is_none() => value_of(&literal.dt()),
+            _ => return None
+        };
+
+        let lexical = value_of(term);
+
+        match datatype.as_str() {
+            XSD_INTEGER | XSD_DECIMAL | XSD_DOUBLE | XSD_FLOAT => {
+                if !is_well_formed(&datatype, &lexical) {
+                    return None;
+                }
+
+                let value: f64 = lexical.parse().ok()?;
+                if value.is_nan() { None } else { Some(LiteralKey::Number(value)) }
+            },
+            XSD_DATE => {
+                let (days, timezone) = parse_date(&lexical)?;
+                instant(days, 0, 0, timezone)
+            },
+            XSD_DATE_TIME => {
+                let (days, rest) = parse_date(&lexical)?;
+                let (seconds, nanoseconds, timezone) = parse_time(rest.strip_prefix('T')?)?;
+                instant(days, seconds, nanoseconds, timezone)
+            },
+            _ => None
+        }
+    }
+
+    /// Returns true if both keys are numbers or both keys are instants
+    fn same_kind(&self, other: &LiteralKey) -> bool {
+        std::mem::discriminant(self) == std::mem::discriminant(other)
+    }
+
+    /// Returns the lowest key of the kind of this key
+    fn lowest(&self) -> LiteralKey {
+        match self {
+            LiteralKey::Number(_) => LiteralKey::Number(f64::NEG_INFINITY),
+            LiteralKey::Instant(..) => LiteralKey::Instant(i64::MIN, 0)
+        }
+    }
+
+    /// Returns the highest key of the kind of this key
+    fn highest(&self) -> LiteralKey {
+        match self {
+            LiteralKey::Number(_) => LiteralKey::Number(f64::INFINITY),
+            LiteralKey::Instant(..) => LiteralKey::Instant(i64::MAX, u32::MAX)
+        }
+    }
+}
+
+impl Eq for LiteralKey {}
+
+impl Ord for LiteralKey {
+    /// Sorts the numbers before the instants
+    fn cmp(&self, other: &Self) -> Ordering {
+        match (self, other) {
+            (LiteralKey::Number(a), LiteralKey::Number(b)) => a.partial_cmp(b).unwrap_or(Ordering::Equal),
+            (LiteralKey::Number(_), LiteralKey::Instant(..)) => Ordering::Less,
+            (LiteralKey::Instant(..), LiteralKey::Number(_)) => Ordering::Greater,
+            (LiteralKey::Instant(a, b), LiteralKey::Instant(c, d)) => (a, b).cmp(&(c, d))
+        }
+    }
+}
+
+impl PartialOrd for LiteralKey {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+/// The identifiers of the numeric and temporal literals, sorted by value
+#[derive(Default)]
+pub struct LiteralIndex {
+    keys: BTreeSet<(LiteralKey, u32)>
+}
+
+impl LiteralIndex {
+    /// Builds an empty index
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Indexes the identifier `index` of `term`. Does nothing if `term` is not
+    /// a numeric or temporal literal.
+    pub fn insert(&mut self, index: u32, term: &RcTerm) {
+        if let Some(key) = LiteralKey::of(term) {
+            self.keys.insert((key, index));
+        }
+    }
+
+    /// Removes the identifier `index` of `term` from the index
+    pub fn remove(&mut self, index: u32, term: &RcTerm) {
+        if let Some(key) = LiteralKey::of(term) {
+            self.keys.remove(&(key, index));
+        }
+    }
+
+    /// Returns the identifiers of the literals between `min` and `max`, in
+    /// increasing order of value.
+    ///
+    /// An unbounded side only reaches the literals of the kind of the other
+    /// bound, and the range is empty if its bounds are of different kinds.
+    /// If both sides are unbounded, every indexed literal is returned.
+    pub fn range(&self, min: Bound<LiteralKey>, max: Bound<LiteralKey>) -> Vec<u32> {
+        match pair_bounds(min, max) {
+            Some(bounds) => self.keys.range(bounds).map(|(_, index)| *index).collect(),
+            None => vec![]
+        }
+    }
+}
+
+fn bound_key(bound: &Bound<LiteralKey>) -> Option<LiteralKey> {
+    match bound {
+        Bound::Included(key) | Bound::Excluded(key) => Some(*key),
+        Bound::Unbounded => None
+    }
+}
+
+/// The bounds of a range of (key, identifier) pairs
+type PairBounds = (Bound<(LiteralKey, u32)>, Bound<(LiteralKey, u32)>);
+
+/// Converts the bounds on the keys into bounds on the (key, identifier)
+/// pairs. Returns `None` if the range is empty.
+fn pair_bounds(min: Bound<LiteralKey>, max: Bound<LiteralKey>) -> Option<PairBounds> {
+    let kind = match (bound_key(&min), bound_key(&max)) {
+        (Some(min_key), Some(max_key)) => {
+            if !min_key.same_kind(&max_key) || min_key > max_key {
+                return None;
+            }
+
+            if min_key == max_key {
+                if let (Bound::Excluded(_), _) | (_, Bound::Excluded(_)) = (&min, &max) {
+                    return None;
+                }
+            }
+
+            min_key
+        },
+        (Some(key), None) | (None, Some(key)) => key,
+        (None, None) => return Some((Bound::Unbounded, Bound::Unbounded))
+    };
+
+    // The identifier 0 is never a term, so it sorts before every indexed pair
+    // of the same key, and u32::MAX after them
+    let lower = match min {
+        Bound::Included(key) => Bound::Included((key, 0)),
+        Bound::Excluded(key) => Bound::Excluded((key, u32::MAX)),
+        Bound::Unbounded => Bound::Included((kind.lowest(), 0))
+    };
+
+    let upper = match max {
+        Bound::Included(key) => Bound::Included((key, u32::MAX)),
+        Bound::Excluded(key) => Bound::Excluded((key, 0)),
+        Bound::Unbounded => Bound::Included((kind.highest(), u32::MAX))
+    };
+
+    Some((lower, upper))
+}
+
+/// Returns the instant of a day, a time of this day and a timezone offset in
+/// minutes
+fn instant(days: i64, seconds: i64, nanoseconds: u32, timezone: &str) -> Option<LiteralKey> {
+    let offset = parse_timezone(timezone)?;
+    Some(LiteralKey::Instant(days * 86_400 + seconds - offset * 60, nanoseconds))
+}
+
+/// Reads the `-?YYYY-MM-DD` date at the start of `text`. Returns the number of
+/// days since 1970-01-01 and the rest of the text.
+fn parse_date(text: &str) -> Option<(i64, &str)> {
+    let (sign, unsigned) = match text.strip_prefix('-') {
+        Some(unsigned) => (-1, unsigned),
+        None => (1, text)
+    };
+
+    let year_length = unsigned.find('-')?;
+    let year = sign * parse_digits(&unsigned[..year_length], 4, 9)?;
+    let rest = &unsigned[year_length + 1..];
+
+    let month = parse_digits(rest.get(..2)?, 2, 2)?;
+    let day = parse_digits(rest.get(3..5)?, 2, 2)?;
+
+    if !rest[2..].starts_with('-') || !(1..=12).contains(&month) || day < 1 || day > days_in_month(year, month) {
+        return None;
+    }
+
+    Some((days_from_civil(year, month, day), &rest[5..]))
+}
+
+/// Reads the `hh:mm:ss(.s+)?` time at the start of `text`. Returns the
+/// seconds since midnight, the nanoseconds and the rest of the text.
+fn parse_time(text: &str) -> Option<(i64, u32, &str)> {
+    let hours = parse_digits(text.get(..2)?, 2, 2)?;
+    let minutes = parse_digits(text.get(3..5)?, 2, 2)?;
+    let seconds = parse_digits(text.get(6..8)?, 2, 2)?;
+
+    if &text[2..3] != ":" || &text[5..6] != ":" || minutes > 59 || seconds > 59 {
+        return None;
+    }
+
+    let mut rest = &text[8..];
+    let mut nanoseconds = 0;
+
+    if let Some(fraction) = rest.strip_prefix('.') {
+        let length = fraction.find(|c: char| !c.is_ascii_digit()).unwrap_or(fraction.len());
+
+        if length == 0 {
+            return None;
+        }
+
+        let digits: String = fraction[..length].chars().chain("000000000".chars()).take(9).collect();
+        nanoseconds = digits.parse().ok()?;
+        rest = &fraction[length..];
+    }
+
+    // 24:00:00 is the end of the day, which is the start of the next one
+    if hours > 24 || (hours == 24 && (minutes, seconds, nanoseconds) != (0, 0, 0)) {
+        return None;
+    }
+
+    Some((hours * 3600 + minutes * 60 + seconds, nanoseconds, rest))
+}
+
+/// Reads a timezone, which is empty, `Z` or `(+|-)hh:mm`. Returns its offset
+/// in minutes.
+fn parse_timezone(text: &str) -> Option<i64> {
+    let sign = match text.chars().next() {
+        None => return Some(0),
+        Some('Z') if text.len() == 1 => return Some(0),
+        Some('+') => 1,
+        Some('-') => -1,
+        _ => return None
+    };
+
+    if text.len() != 6 || text.as_bytes()[3] != b':' {
+        return None;
+    }
+
+    let hours = parse_digits(text.get(1..3)?, 2, 2)?;
+    let minutes = parse_digits(text.get(4..6)?, 2, 2)?;
+
+    if hours > 14 || minutes > 59 {
+        return None;
+    }
+
+    Some(sign * (hours * 60 + minutes))
+}
+
+/// Reads an unsigned number of `min_length` to `max_length` digits
+fn parse_digits(text: &str, min_length: usize, max_length: usize) -> Option<i64> {
+    if text.len() < min_length || text.len() > max_length || !text.chars().all(|c| c.is_ascii_digit()) {
+        return None;
+    }
+
+    text.parse().ok()
+}
+
+fn is_leap_year(year: i64) -> bool {
+    (year % 4 == 0 && year % 100 != 0) || year % 400 == 0
+}
+
+fn days_in_month(year: i64, month: i64) -> i64 {
+    match month {
+        2 if is_leap_year(year) => 29,
+        2 => 28,
+        4 | 6 | 9 | 11 => 30,
+        _ => 31
+    }
+}
+
+/// Returns the number of days between 1970-01-01 and a date of the
+/// proleptic Gregorian calendar
+fn days_from_civil(year: i64, month: i64, day: i64) -> i64 {
+    // The years start in March, so the leap day is the last day of the year
+    let year = if month <= 2 { year - 1 } else { year };
+    let era = if year >= 0 { year } else { year - 399 } / 400;
+    let year_of_era = year - era * 400;
+    let day_of_year = (153 * ((month + 9) % 12) + 2) / 5 + day - 1;
+    let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+
+    era * 146_097 + day_of_era - 719_468
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn literal(lexical: &str, datatype: &str) -> RcTerm {
+        RcTerm::new_literal_dt(lexical, RcTerm::new_iri(datatype).unwrap()).unwrap()
+    }
+
+    #[test]
+    fn reads_numbers_and_instants() {
+        assert_eq!(LiteralKey::of(&literal("-12", XSD_INTEGER)), Some(LiteralKey::Number(-12.0)));
+        assert_eq!(LiteralKey::of(&literal("1.5e3", XSD_DOUBLE)), Some(LiteralKey::Number(1500.0)));
+        assert_eq!(LiteralKey::of(&literal("1.5e3", XSD_INTEGER)), None);
+        assert_eq!(LiteralKey::of(&literal("NaN", XSD_DOUBLE)), None);
+
+        assert_eq!(LiteralKey::of(&literal("1970-01-02", XSD_DATE)), Some(LiteralKey::Instant(86_400, 0)));
+        assert_eq!(LiteralKey::of(&literal("2000-03-01T00:00:00Z", XSD_DATE_TIME)), Some(LiteralKey::Instant(951_868_800, 0)));
+        assert_eq!(
+            LiteralKey::of(&literal("2000-03-01T02:30:00.25+02:30", XSD_DATE_TIME)),
+            Some(LiteralKey::Instant(951_868_800, 250_000_000))
+        );
+        assert_eq!(LiteralKey::of(&literal("2001-02-29", XSD_DATE)), None);
+        assert_eq!(LiteralKey::of(&literal("2000-03-01T25:00:00", XSD_DATE_TIME)), None);
+        assert_eq!(LiteralKey::of(&literal("2000-01-01T00:00:00+0é00", XSD_DATE_TIME)), None);
+        assert_eq!(LiteralKey::of(&literal("2000-01-01T00:00:00+é:00", XSD_DATE_TIME)), None);
+    }
+
+    #[test]
+    fn returns_ranges_of_one_kind() {
+        let mut index = LiteralIndex::new();
+        index.insert(1, &literal("5", XSD_INTEGER));
+        index.insert(2, &literal("30.5", XSD_DECIMAL));
+        index.insert(3, &literal("31", XSD_INTEGER));
+        index.insert(4, &literal("2020-01-01", XSD_DATE));
+        index.insert(5, &literal("text", XSD_INTEGER));
+
+        let thirty = LiteralKey::Number(30.0);
+        assert_eq!(index.range(Bound::Excluded(thirty), Bound::Unbounded), vec![2, 3]);
+        assert_eq!(index.range(Bound::Unbounded, Bound::Included(thirty)), vec![1]);
+        assert_eq!(index.range(Bound::Included(LiteralKey::Number(31.0)), Bound::Excluded(LiteralKey::Number(31.0))), Vec::<u32>::new());
+        assert_eq!(index.range(Bound::Included(thirty), Bound::Included(LiteralKey::Instant(0, 0))), Vec::<u32>::new());
+        assert_eq!(index.range(Bound::Unbounded, Bound::Unbounded), vec![1, 2, 3, 4]);
+
+        index.remove(3, &literal("31", XSD_INTEGER));
+        assert_eq!(index.range(Bound::Excluded(thirty), Bound::Unbounded), vec![2]);
+    }
+}
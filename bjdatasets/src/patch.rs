@@ -0,0 +1,303 @@
+//! RDF Patch documents: the differences between two datasets.
+//!
+//! A patch is a sequence of `A` (add) and `D` (delete) rows, each followed by
+//! the terms of a quad written as in N-Quads. The produced patches contain
+//! one transaction:
+//!
+//! ```text
+//! TX .
+//! D <http://example.org/s> <http://example.org/p> "old" .
+//! A <http://example.org/s> <http://example.org/p> "new" <http://example.org/g> .
+//! TC .
+//! ```
+//!
+//! When reading a patch, the rows of an aborted transaction (`TA`) are
+//! ignored, and the headers (`H`) and prefix rows (`PA`, `PD`) are skipped.
+//! Prefixed names are not supported.
+
+use crate::RcQuad;
+use crate::fulldataset::FullIndexDataset;
+use crate::rdfs::RdfsDataset;
+use crate::serializer::write_term;
+use crate::treedataset::TreeDataset;
+use crate::vecordataset::VecOrDataset;
+
+use sophia::dataset::inmem::FastDataset;
+use sophia::dataset::inmem::LightDataset;
+use sophia::dataset::Dataset;
+use sophia::dataset::MutableDataset;
+use sophia::quad::stream::QuadSource;
+use sophia::quad::Quad;
+use sophia::term::BoxTerm;
+
+use std::convert::Infallible;
+
+/// A row of a patch
+pub enum PatchOperation {
+    /// The quad is added to the dataset
+    Add(RcQuad),
+    /// The quad is removed from the dataset
+    Delete(RcQuad)
+}
+
+/// An error in the text of a patch
+#[derive(Debug, Clone, PartialEq)]
+pub struct PatchError {
+    /// The line of the error, starting at 1
+    pub line: usize,
+    /// The description of the error
+    pub message: String
+}
+
+impl std::fmt::Display for PatchError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Invalid patch at line {}: {}", self.line, self.message)
+    }
+}
+
+impl std::error::Error for PatchError {}
+
+/// A sequence of additions and deletions of quads
+#[derive(Default)]
+pub struct Patch {
+    /// The operations, in the order they are applied
+    pub operations: Vec<PatchOperation>
+}
+
+impl Patch {
+    /// Builds the patch that deletes the quads of `from` that are not in `to`
+    /// and adds the quads of `to` that are not in `from`
+    pub fn between<D1, D2>(from: &D1, to: &D2) -> Patch
+        where D1: Dataset, D2: Dataset {
+        Patch::from_changes(quads_not_in(from, to), quads_not_in(to, from))
+    }
+
+    /// Builds the patch that deletes the `removed` quads, then adds the
+    /// `added` ones
+    pub fn from_changes(removed: Vec<RcQuad>, added: Vec<RcQuad>) -> Patch {
+        Patch {
+            operations: removed.into_iter().map(PatchOperation::Delete)
+                .chain(added.into_iter().map(PatchOperation::Add))
+                .collect()
+        }
+    }
+
+    /// Returns true if the patch changes nothing
+    pub fn is_empty(&self) -> bool {
+        self.operations.is_empty()
+    }
+
+    /// Reads the text of a patch. The text is entirely read before
+    /// returning, so a patch is either fully read or rejected.
+    pub fn parse(text: &str) -> Result<Patch, PatchError> {
+        let mut operations = vec![];
+        let mut transaction: Option<Vec<PatchOperation>> = None;
+
+        for (index, line) in text.lines().enumerate() {
+            let line_number = index + 1;
+            let error = |message: &str| PatchError { line: line_number, message: message.to_string() };
+
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+
+            let (code, rest) = match line.find(char::is_whitespace) {
+                Some(position) => (&line[..position], line[position..].trim_start()),
+                None => (line, "")
+            };
+
+            match code {
+                "H" | "PA" | "PD" => {},
+                "TX" if transaction.is_some() => return Err(error("Nested transaction")),
+                "TX" => transaction = Some(vec![]),
+                "TC" => match transaction.take() {
+                    Some(committed) => operations.extend(committed),
+                    None => return Err(error("TC outside of a transaction"))
+                },
+                "TA" => if transaction.take().is_none() {
+                    return Err(error("TA outside of a transaction"));
+                },
+                "A" | "D" => {
+                    let quad = parse_quad(rest).map_err(|message| error(&message))?;
+                    let operation = if code == "A" { PatchOperation::Add(quad) } else { PatchOperation::Delete(quad) };
+
+                    match transaction.as_mut() {
+                        Some(pending) => pending.push(operation),
+                        None => operations.push(operation)
+                    }
+                },
+                _ => return Err(error(&format!("Unknown row {}", code)))
+            }
+        }
+
+        if transaction.is_some() {
+            return Err(PatchError { line: text.lines().count(), message: "Unterminated transaction".to_string() });
+        }
+
+        Ok(Patch { operations })
+    }
+
+    /// Applies the operations to `dataset`. Returns the number of quads that
+    /// were actually added or removed.
+    pub fn apply<D>(&self, dataset: &mut D) -> usize
+        where D: MutableDataset {
+        let mut number_of_changes = 0;
+
+        for operation in &self.operations {
+            let changed = match operation {
+                PatchOperation::Add(quad) => dataset.insert(quad.s(), quad.p(), quad.o(), quad.g()).unwrap(),
+                PatchOperation::Delete(quad) => dataset.remove(quad.s(), quad.p(), quad.o(), quad.g()).unwrap()
+            };
+
+            if changed {
+                number_of_changes += 1;
+            }
+        }
+
+        number_of_changes
+    }
+}
+
+impl std::fmt::Display for Patch {
+    /// Writes the patch as one transaction
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        writeln!(f, "TX .")?;
+
+        for operation in &self.operations {
+            let (code, quad) = match operation {
+                PatchOperation::Add(quad) => ("A", quad),
+                PatchOperation::Delete(quad) => ("D", quad)
+            };
+
+            write!(f, "{}", code)?;
+
+            for term in [quad.s(), quad.p(), quad.o()].iter().copied().chain(quad.g()) {
+                write!(f, " {}", write_term(term, None))?;
+            }
+
+            writeln!(f, " .")?;
+        }
+
+        writeln!(f, "TC .")
+    }
+}
+
+/// Reads the terms of an `A` or `D` row, which are written as an N-Quads line
+fn parse_quad(text: &str) -> Result<RcQuad, String> {
+    let mut quads = vec![];
+
+    sophia::parser::nq::parse_str(text)
+        .for_each_quad(|quad| quads.push(RcQuad::new_from_quad(&quad)))
+        .map_err(|error| error.to_string())?;
+
+    match quads.len() {
+        1 => Ok(quads.pop().unwrap()),
+        _ => Err("A row must contain exactly one quad".to_string())
+    }
+}
+
+/// Returns the quads of `dataset` that are not in `other`
+fn quads_not_in<D1, D2>(dataset: &D1, other: &D2) -> Vec<RcQuad>
+    where D1: Dataset, D2: Dataset {
+    let mut quads = vec![];
+
+    for quad in dataset.quads() {
+        let quad = RcQuad::new_from_quad(&quad.unwrap());
+
+        if !other.contains(quad.s(), quad.p(), quad.o(), quad.g()).unwrap() {
+            quads.push(quad);
+        }
+    }
+
+    quads
+}
+
+/// Datasets that can compute the patch that turns them into another dataset
+/// of the same type
+pub trait Diff: Dataset + Sized {
+    /// Returns the patch that turns `self` into `other`
+    fn diff(&self, other: &Self) -> Patch {
+        Patch::between(self, other)
+    }
+}
+
+impl Diff for TreeDataset {
+    /// Compares the identifiers of the quads, so every term is only looked up
+    /// once in the dictionary of the other dataset
+    fn diff(&self, other: &Self) -> Patch {
+        Patch::from_changes(self.quads_not_in(other), other.quads_not_in(self))
+    }
+}
+
+impl Diff for FullIndexDataset {}
+impl Diff for FastDataset {}
+impl Diff for LightDataset {}
+impl Diff for Vec<([BoxTerm; 3], Option<BoxTerm>)> {}
+impl Diff for RdfsDataset {}
+
+impl<D> Diff for VecOrDataset<D>
+    where D: Dataset + MutableDataset<MutationError = Infallible> + Default {}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use sophia::term::RcTerm;
+
+    fn iri(value: &str) -> RcTerm {
+        RcTerm::new_iri(value).unwrap()
+    }
+
+    fn fill<D>(dataset: &mut D, objects: &[&str])
+        where D: MutableDataset {
+        for object in objects {
+            dataset.insert(&iri("http://example.org/s"), &iri("http://example.org/p"), &iri(object), None as Option<&RcTerm>).unwrap();
+        }
+    }
+
+    fn roundtrip<D>()
+        where D: Diff + MutableDataset + Default {
+        let mut from = D::default();
+        let mut to = D::default();
+        fill(&mut from, &["http://example.org/a", "http://example.org/b"]);
+        fill(&mut to, &["http://example.org/b", "http://example.org/c"]);
+
+        let text = from.diff(&to).to_string();
+        assert!(text.starts_with("TX .\n"));
+        assert!(text.contains("D <http://example.org/s> <http://example.org/p> <http://example.org/a> .\n"));
+        assert!(text.contains("A <http://example.org/s> <http://example.org/p> <http://example.org/c> .\n"));
+
+        assert_eq!(Patch::parse(&text).unwrap().apply(&mut from), 2);
+        assert!(from.diff(&to).is_empty());
+    }
+
+    #[test]
+    fn diffs_and_patches() {
+        roundtrip::<TreeDataset>();
+        roundtrip::<FullIndexDataset>();
+        roundtrip::<FastDataset>();
+    }
+
+    #[test]
+    fn ignores_aborted_transactions() {
+        let patch = Patch::parse(
+            "H id <urn:uuid:0> .\n\
+            TX .\n\
+            A <http://example.org/s> <http://example.org/p> \"kept\" .\n\
+            TC .\n\
+            TX .\n\
+            A <http://example.org/s> <http://example.org/p> \"aborted\" .\n\
+            TA .\n"
+        ).unwrap();
+
+        assert_eq!(patch.operations.len(), 1);
+    }
+
+    #[test]
+    fn rejects_invalid_patches() {
+        assert_eq!(Patch::parse("TX .\nA <http://example.org/s> .\nTC .\n").unwrap_err().line, 2);
+        assert_eq!(Patch::parse("TX .\n").unwrap_err().line, 1);
+        assert_eq!(Patch::parse("TC .\n").unwrap_err().line, 1);
+        assert_eq!(Patch::parse("X <http://example.org/s> .\n").unwrap_err().line, 1);
+    }
+}
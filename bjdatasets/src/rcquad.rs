@@ -5,6 +5,7 @@ use std::rc::Rc;
 
 /// `RcQuad` is a sophia::Quad implementation that owns its term in the form of
 /// `RcTerms`
+#[derive(Clone, PartialEq, Eq, Hash)]
 pub struct RcQuad {
     /// Subject of the quad
     pub _subject: RcTerm,
@@ -0,0 +1,572 @@
+//! A dataset that keeps the RDFS entailments of its quads.
+//!
+//! `RdfsDataset` stores the asserted quads and their closure under the RDFS
+//! rules rdfs2 (domain), rdfs3 (range), rdfs5 (transitivity of
+//! `subPropertyOf`), rdfs7 (`subPropertyOf`), rdfs9 (`subClassOf`) and
+//! rdfs11 (transitivity of `subClassOf`). The rules are applied in each graph
+//! separately: the premises and the conclusion of a rule are in the same
+//! graph.
+//!
+//! The closure is maintained incrementally. An insertion derives the new
+//! quads from the inserted one. A removal uses the delete-rederive (DRed)
+//! approach: every quad that may depend on the removed one is deleted, then
+//! the deleted quads that can still be derived from the remaining ones are
+//! inserted back.
+//!
+//! Only asserted quads can be removed: removing an inferred quad does
+//! nothing.
+
+use crate::RcQuad;
+use crate::treedataset::TreeDataset;
+
+use sophia::dataset::DQuadSource;
+use sophia::dataset::Dataset;
+use sophia::dataset::MDResult;
+use sophia::dataset::MutableDataset;
+use sophia::quad::streaming_mode::ByValue;
+use sophia::quad::Quad;
+use sophia::term::matcher::AnyOrExactly;
+use sophia::term::RcTerm;
+use sophia::term::Term;
+use sophia::term::TermData;
+
+use std::collections::HashSet;
+use std::convert::Infallible;
+
+const RDF_TYPE: &str = "http://www.w3.org/1999/02/22-rdf-syntax-ns#type";
+const RDFS_DOMAIN: &str = "http://www.w3.org/2000/01/rdf-schema#domain";
+const RDFS_RANGE: &str = "http://www.w3.org/2000/01/rdf-schema#range";
+const RDFS_SUB_CLASS_OF: &str = "http://www.w3.org/2000/01/rdf-schema#subClassOf";
+const RDFS_SUB_PROPERTY_OF: &str = "http://www.w3.org/2000/01/rdf-schema#subPropertyOf";
+
+/// The terms used by the RDFS rules
+struct Vocabulary {
+    rdf_type: RcTerm,
+    domain: RcTerm,
+    range: RcTerm,
+    sub_class_of: RcTerm,
+    sub_property_of: RcTerm
+}
+
+impl Default for Vocabulary {
+    fn default() -> Self {
+        let iri = |value: &str| RcTerm::new_iri(value).unwrap();
+
+        Self {
+            rdf_type: iri(RDF_TYPE),
+            domain: iri(RDFS_DOMAIN),
+            range: iri(RDFS_RANGE),
+            sub_class_of: iri(RDFS_SUB_CLASS_OF),
+            sub_property_of: iri(RDFS_SUB_PROPERTY_OF)
+        }
+    }
+}
+
+/// Adds the quad to `conclusions` if it is a valid RDF quad: rules like rdfs7
+/// could otherwise put a literal in the predicate position.
+fn conclude(conclusions: &mut Vec<RcQuad>, s: &RcTerm, p: &RcTerm, o: &RcTerm, g: Option<&RcTerm>) {
+    let valid_subject = match s {
+        Term::Iri(_) | Term::BNode(_) => true,
+        _ => false
+    };
+
+    let valid_predicate = match p {
+        Term::Iri(_) => true,
+        _ => false
+    };
+
+    if valid_subject && valid_predicate {
+        conclusions.push(RcQuad::new(s, p, o, g));
+    }
+}
+
+/// A dataset that contains its asserted quads and the quads they entail
+/// under the RDFS rules
+#[derive(Default)]
+pub struct RdfsDataset {
+    /// The quads that were inserted
+    asserted: TreeDataset,
+    /// The asserted quads and the inferred ones
+    closure: TreeDataset,
+    /// The terms of the rules
+    vocabulary: Vocabulary
+}
+
+impl RdfsDataset {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns the dataset of the asserted quads
+    pub fn asserted(&self) -> &TreeDataset {
+        &self.asserted
+    }
+
+    /// Returns true if the quad was inserted
+    pub fn is_asserted(&self, s: &RcTerm, p: &RcTerm, o: &RcTerm, g: Option<&RcTerm>) -> bool {
+        self.asserted.contains(s, p, o, g).unwrap()
+    }
+
+    /// Returns true if the quad is only in the dataset because it is entailed
+    /// by other quads
+    pub fn is_inferred(&self, s: &RcTerm, p: &RcTerm, o: &RcTerm, g: Option<&RcTerm>) -> bool {
+        self.closure.contains(s, p, o, g).unwrap() && !self.is_asserted(s, p, o, g)
+    }
+
+    /// Returns a dataset with the given quads of this dataset, without
+    /// running the rules. Each quad stays asserted or inferred, and the quads
+    /// that are not in this dataset are ignored.
+    pub fn subset<I>(&self, quads: I) -> RdfsDataset
+        where I: IntoIterator<Item = RcQuad> {
+        let mut subset = RdfsDataset::new();
+
+        for quad in quads {
+            if !self.closure.contains(quad.s(), quad.p(), quad.o(), quad.g()).unwrap() {
+                continue;
+            }
+
+            subset.closure.insert(quad.s(), quad.p(), quad.o(), quad.g()).unwrap();
+
+            if self.is_asserted(quad.s(), quad.p(), quad.o(), quad.g()) {
+                subset.asserted.insert(quad.s(), quad.p(), quad.o(), quad.g()).unwrap();
+            }
+        }
+
+        subset
+    }
+
+    /// Adds the quad as an inferred quad, without running the rules. Returns
+    /// false if the quad was already in the dataset.
+    pub fn insert_inferred(&mut self, s: &RcTerm, p: &RcTerm, o: &RcTerm, g: Option<&RcTerm>) -> bool {
+        self.closure.insert(s, p, o, g).unwrap()
+    }
+
+    /// Returns the dataset of the asserted quads, to modify their terms
+    /// without running the rules
+    pub(crate) fn asserted_mut(&mut self) -> &mut TreeDataset {
+        &mut self.asserted
+    }
+
+    /// Returns the dataset of every quad, to modify their terms without
+    /// running the rules
+    pub(crate) fn closure_mut(&mut self) -> &mut TreeDataset {
+        &mut self.closure
+    }
+
+    /// Returns the quads of the closure that match the pattern in the graph
+    /// `g`
+    fn matching(&self, s: Option<&RcTerm>, p: Option<&RcTerm>, o: Option<&RcTerm>, g: Option<&RcTerm>) -> Vec<RcQuad> {
+        let matcher = |term: Option<&RcTerm>| match term {
+            Some(term) => AnyOrExactly::Exactly(term.clone()),
+            None => AnyOrExactly::Any
+        };
+
+        self.closure.quads_matching(&matcher(s), &matcher(p), &matcher(o), &AnyOrExactly::Exactly(g.cloned()))
+            .map(|quad| RcQuad::new_from_quad(&quad.unwrap()))
+            .collect()
+    }
+
+    /// Returns the quads that a rule concludes from `quad` and from other
+    /// quads of the closure
+    fn consequences(&self, quad: &RcQuad) -> Vec<RcQuad> {
+        let v = &self.vocabulary;
+        let (s, p, o, g) = (quad.s(), quad.p(), quad.o(), quad.g());
+        let mut conclusions = vec![];
+
+        // The quad as the instance premise of rdfs2, rdfs3 and rdfs7
+        for schema in self.matching(Some(p), Some(&v.domain), None, g) {
+            conclude(&mut conclusions, s, &v.rdf_type, schema.o(), g);
+        }
+
+        for schema in self.matching(Some(p), Some(&v.range), None, g) {
+            conclude(&mut conclusions, o, &v.rdf_type, schema.o(), g);
+        }
+
+        for schema in self.matching(Some(p), Some(&v.sub_property_of), None, g) {
+            conclude(&mut conclusions, s, schema.o(), o, g);
+        }
+
+        // The quad as the schema premise
+        if *p == v.domain {
+            for instance in self.matching(None, Some(s), None, g) {
+                conclude(&mut conclusions, instance.s(), &v.rdf_type, o, g);
+            }
+        }
+
+        if *p == v.range {
+            for instance in self.matching(None, Some(s), None, g) {
+                conclude(&mut conclusions, instance.o(), &v.rdf_type, o, g);
+            }
+        }
+
+        if *p == v.sub_property_of {
+            for instance in self.matching(None, Some(s), None, g) {
+                conclude(&mut conclusions, instance.s(), o, instance.o(), g);
+            }
+
+            for next in self.matching(Some(o), Some(&v.sub_property_of), None, g) {
+                conclude(&mut conclusions, s, &v.sub_property_of, next.o(), g);
+            }
+
+            for previous in self.matching(None, Some(&v.sub_property_of), Some(s), g) {
+                conclude(&mut conclusions, previous.s(), &v.sub_property_of, o, g);
+            }
+        }
+
+        if *p == v.rdf_type {
+            for schema in self.matching(Some(o), Some(&v.sub_class_of), None, g) {
+                conclude(&mut conclusions, s, &v.rdf_type, schema.o(), g);
+            }
+        }
+
+        if *p == v.sub_class_of {
+            for instance in self.matching(None, Some(&v.rdf_type), Some(s), g) {
+                conclude(&mut conclusions, instance.s(), &v.rdf_type, o, g);
+            }
+
+            for next in self.matching(Some(o), Some(&v.sub_class_of), None, g) {
+                conclude(&mut conclusions, s, &v.sub_class_of, next.o(), g);
+            }
+
+            for previous in self.matching(None, Some(&v.sub_class_of), Some(s), g) {
+                conclude(&mut conclusions, previous.s(), &v.sub_class_of, o, g);
+            }
+        }
+
+        conclusions
+    }
+
+    /// Returns true if a rule concludes `quad` from quads of the closure
+    fn is_derivable(&self, quad: &RcQuad) -> bool {
+        let v = &self.vocabulary;
+        let (s, p, o, g) = (quad.s(), quad.p(), quad.o(), quad.g());
+        let contains = |s: &RcTerm, p: &RcTerm, o: &RcTerm| self.closure.contains(s, p, o, g).unwrap();
+
+        // rdfs7
+        if self.matching(None, Some(&v.sub_property_of), Some(p), g).iter()
+            .any(|schema| contains(s, schema.s(), o)) {
+            return true;
+        }
+
+        if *p == v.rdf_type {
+            // rdfs2
+            if self.matching(None, Some(&v.domain), Some(o), g).iter()
+                .any(|schema| !self.matching(Some(s), Some(schema.s()), None, g).is_empty()) {
+                return true;
+            }
+
+            // rdfs3
+            if self.matching(None, Some(&v.range), Some(o), g).iter()
+                .any(|schema| !self.matching(None, Some(schema.s()), Some(s), g).is_empty()) {
+                return true;
+            }
+
+            // rdfs9
+            if self.matching(None, Some(&v.sub_class_of), Some(o), g).iter()
+                .any(|schema| contains(s, &v.rdf_type, schema.s())) {
+                return true;
+            }
+        }
+
+        // rdfs5 and rdfs11
+        for transitive in &[&v.sub_property_of, &v.sub_class_of] {
+            if p == *transitive && self.matching(Some(s), Some(p), None, g).iter()
+                .any(|first| contains(first.o(), p, o)) {
+                return true;
+            }
+        }
+
+        false
+    }
+
+    /// Adds to the closure the quads entailed by the `pending` quads, which
+    /// are already in it
+    fn saturate(&mut self, mut pending: Vec<RcQuad>) {
+        while let Some(quad) = pending.pop() {
+            for conclusion in self.consequences(&quad) {
+                let (s, p, o, g) = (conclusion.s(), conclusion.p(), conclusion.o(), conclusion.g());
+
+                if self.closure.insert(s, p, o, g).unwrap() {
+                    pending.push(conclusion);
+                }
+            }
+        }
+    }
+
+    /// Removes `quad` from the closure with the delete-rederive approach
+    fn delete_rederive(&mut self, quad: RcQuad) {
+        // Overdelete: every inferred quad that may depend on the removed one
+        let mut overdeleted = HashSet::new();
+        let mut pending = vec![quad];
+
+        while let Some(quad) = pending.pop() {
+            for conclusion in self.consequences(&quad) {
+                let (s, p, o, g) = (conclusion.s(), conclusion.p(), conclusion.o(), conclusion.g());
+
+                if !self.is_asserted(s, p, o, g) && overdeleted.insert(conclusion.clone()) {
+                    pending.push(conclusion);
+                }
+            }
+
+            overdeleted.insert(quad);
+        }
+
+        for quad in &overdeleted {
+            self.closure.remove(quad.s(), quad.p(), quad.o(), quad.g()).unwrap();
+        }
+
+        // Rederive: the quads that the remaining quads still entail
+        for quad in overdeleted {
+            let (s, p, o, g) = (quad.s(), quad.p(), quad.o(), quad.g());
+
+            if !self.closure.contains(s, p, o, g).unwrap() && self.is_derivable(&quad) {
+                self.closure.insert(s, p, o, g).unwrap();
+                self.saturate(vec![quad]);
+            }
+        }
+    }
+}
+
+impl Clone for RdfsDataset {
+    /// Copies the asserted quads and the closure without running the rules
+    /// again
+    fn clone(&self) -> Self {
+        self.subset(self.closure.quads().map(|quad| RcQuad::new_from_quad(&quad.unwrap())))
+    }
+}
+
+impl Dataset for RdfsDataset {
+    type Quad = ByValue<RcQuad>;
+    type Error = Infallible;
+
+    fn quads(&self) -> DQuadSource<Self> {
+        self.closure.quads()
+    }
+
+    fn quads_with_s<'s, TS>(&'s self, s: &'s Term<TS>) -> DQuadSource<'s, Self>
+    where TS: TermData {
+        self.closure.quads_with_s(s)
+    }
+
+    fn quads_with_p<'s, TP>(&'s self, p: &'s Term<TP>) -> DQuadSource<'s, Self>
+    where TP: TermData {
+        self.closure.quads_with_p(p)
+    }
+
+    fn quads_with_o<'s, TO>(&'s self, o: &'s Term<TO>) -> DQuadSource<'s, Self>
+    where TO: TermData {
+        self.closure.quads_with_o(o)
+    }
+
+    fn quads_with_g<'s, TG>(&'s self, g: Option<&'s Term<TG>>) -> DQuadSource<'s, Self>
+    where TG: TermData {
+        self.closure.quads_with_g(g)
+    }
+
+    fn quads_with_sp<'s, TS, TP>(&'s self, s: &'s Term<TS>, p: &'s Term<TP>) -> DQuadSource<'s, Self>
+    where TS: TermData, TP: TermData {
+        self.closure.quads_with_sp(s, p)
+    }
+
+    fn quads_with_so<'s, TS, TO>(&'s self, s: &'s Term<TS>, o: &'s Term<TO>) -> DQuadSource<'s, Self>
+    where TS: TermData, TO: TermData {
+        self.closure.quads_with_so(s, o)
+    }
+
+    fn quads_with_sg<'s, TS, TG>(&'s self, s: &'s Term<TS>, g: Option<&'s Term<TG>>) -> DQuadSource<'s, Self>
+    where TS: TermData, TG: TermData {
+        self.closure.quads_with_sg(s, g)
+    }
+
+    fn quads_with_po<'s, TP, TO>(&'s self, p: &'s Term<TP>, o: &'s Term<TO>) -> DQuadSource<'s, Self>
+    where TP: TermData, TO: TermData {
+        self.closure.quads_with_po(p, o)
+    }
+
+    fn quads_with_pg<'s, TP, TG>(&'s self, p: &'s Term<TP>, g: Option<&'s Term<TG>>) -> DQuadSource<'s, Self>
+    where TP: TermData, TG: TermData {
+        self.closure.quads_with_pg(p, g)
+    }
+
+    fn quads_with_og<'s, TO, TG>(&'s self, o: &'s Term<TO>, g: Option<&'s Term<TG>>) -> DQuadSource<'s, Self>
+    where TO: TermData, TG: TermData {
+        self.closure.quads_with_og(o, g)
+    }
+
+    fn quads_with_spo<'s, TS, TP, TO>(&'s self, s: &'s Term<TS>, p: &'s Term<TP>, o: &'s Term<TO>) -> DQuadSource<'s, Self>
+    where TS: TermData, TP: TermData, TO: TermData {
+        self.closure.quads_with_spo(s, p, o)
+    }
+
+    fn quads_with_spg<'s, TS, TP, TG>(&'s self, s: &'s Term<TS>, p: &'s Term<TP>, g: Option<&'s Term<TG>>) -> DQuadSource<'s, Self>
+    where TS: TermData, TP: TermData, TG: TermData {
+        self.closure.quads_with_spg(s, p, g)
+    }
+
+    fn quads_with_sog<'s, TS, TO, TG>(&'s self, s: &'s Term<TS>, o: &'s Term<TO>, g: Option<&'s Term<TG>>) -> DQuadSource<'s, Self>
+    where TS: TermData, TO: TermData, TG: TermData {
+        self.closure.quads_with_sog(s, o, g)
+    }
+
+    fn quads_with_pog<'s, TP, TO, TG>(&'s self, p: &'s Term<TP>, o: &'s Term<TO>, g: Option<&'s Term<TG>>) -> DQuadSource<'s, Self>
+    where TP: TermData, TO: TermData, TG: TermData {
+        self.closure.quads_with_pog(p, o, g)
+    }
+
+    fn quads_with_spog<'s, TS, TP, TO, TG>(&'s self, s: &'s Term<TS>, p: &'s Term<TP>, o: &'s Term<TO>, g: Option<&'s Term<TG>>) -> DQuadSource<'s, Self>
+    where TS: TermData, TP: TermData, TO: TermData, TG: TermData {
+        self.closure.quads_with_spog(s, p, o, g)
+    }
+}
+
+impl MutableDataset for RdfsDataset {
+    type MutationError = Infallible;
+
+    /// Asserts the quad. Returns false if it was already asserted, even if it
+    /// was inferred.
+    fn insert<T, U, V, W>(
+        &mut self,
+        s: &Term<T>,
+        p: &Term<U>,
+        o: &Term<V>,
+        g: Option<&Term<W>>,
+    ) -> MDResult<Self, bool>
+    where
+        T: TermData,
+        U: TermData,
+        V: TermData,
+        W: TermData,
+    {
+        if !self.asserted.insert(s, p, o, g).unwrap() {
+            return Ok(false);
+        }
+
+        if self.closure.insert(s, p, o, g).unwrap() {
+            let g: Option<RcTerm> = g.map(|g| g.into());
+            let quad = RcQuad::new(&s.into(), &p.into(), &o.into(), g.as_ref());
+            self.saturate(vec![quad]);
+        }
+
+        Ok(true)
+    }
+
+    /// Retracts an asserted quad and the inferences that no longer hold.
+    /// Returns false if the quad was not asserted.
+    fn remove<T, U, V, W>(
+        &mut self,
+        s: &Term<T>,
+        p: &Term<U>,
+        o: &Term<V>,
+        g: Option<&Term<W>>,
+    ) -> MDResult<Self, bool>
+    where
+        T: TermData,
+        U: TermData,
+        V: TermData,
+        W: TermData,
+    {
+        if !self.asserted.remove(s, p, o, g).unwrap() {
+            return Ok(false);
+        }
+
+        let g: Option<RcTerm> = g.map(|g| g.into());
+        self.delete_rederive(RcQuad::new(&s.into(), &p.into(), &o.into(), g.as_ref()));
+        Ok(true)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn iri(value: &str) -> RcTerm {
+        RcTerm::new_iri(format!("http://example.org/{}", value)).unwrap()
+    }
+
+    fn term(value: &str) -> RcTerm {
+        RcTerm::new_iri(value).unwrap()
+    }
+
+    fn add(dataset: &mut RdfsDataset, s: &RcTerm, p: &RcTerm, o: &RcTerm) {
+        dataset.insert(s, p, o, None as Option<&RcTerm>).unwrap();
+    }
+
+    fn has(dataset: &RdfsDataset, s: &RcTerm, p: &RcTerm, o: &RcTerm) -> bool {
+        dataset.contains(s, p, o, None as Option<&RcTerm>).unwrap()
+    }
+
+    #[test]
+    fn infers_types_and_hierarchies() {
+        let rdf_type = term(RDF_TYPE);
+        let mut dataset = RdfsDataset::new();
+
+        add(&mut dataset, &iri("Cat"), &term(RDFS_SUB_CLASS_OF), &iri("Mammal"));
+        add(&mut dataset, &iri("Mammal"), &term(RDFS_SUB_CLASS_OF), &iri("Animal"));
+        add(&mut dataset, &iri("hasPet"), &term(RDFS_RANGE), &iri("Cat"));
+        add(&mut dataset, &iri("hasCat"), &term(RDFS_SUB_PROPERTY_OF), &iri("hasPet"));
+        add(&mut dataset, &iri("alice"), &iri("hasCat"), &iri("tom"));
+
+        assert!(has(&dataset, &iri("Cat"), &term(RDFS_SUB_CLASS_OF), &iri("Animal")));
+        assert!(has(&dataset, &iri("alice"), &iri("hasPet"), &iri("tom")));
+        assert!(has(&dataset, &iri("tom"), &rdf_type, &iri("Cat")));
+        assert!(has(&dataset, &iri("tom"), &rdf_type, &iri("Animal")));
+        assert!(dataset.is_inferred(&iri("tom"), &rdf_type, &iri("Animal"), None));
+        assert!(dataset.is_asserted(&iri("alice"), &iri("hasCat"), &iri("tom"), None));
+
+        assert!(!dataset.remove(&iri("tom"), &rdf_type, &iri("Animal"), None as Option<&RcTerm>).unwrap());
+        assert!(has(&dataset, &iri("tom"), &rdf_type, &iri("Animal")));
+    }
+
+    #[test]
+    fn removes_the_inferences_that_no_longer_hold() {
+        let rdf_type = term(RDF_TYPE);
+        let mut dataset = RdfsDataset::new();
+
+        add(&mut dataset, &iri("Cat"), &term(RDFS_SUB_CLASS_OF), &iri("Animal"));
+        add(&mut dataset, &iri("Dog"), &term(RDFS_SUB_CLASS_OF), &iri("Animal"));
+        add(&mut dataset, &iri("tom"), &rdf_type, &iri("Cat"));
+        add(&mut dataset, &iri("tom"), &rdf_type, &iri("Dog"));
+
+        dataset.remove(&iri("tom"), &rdf_type, &iri("Cat"), None as Option<&RcTerm>).unwrap();
+        assert!(has(&dataset, &iri("tom"), &rdf_type, &iri("Animal")));
+
+        dataset.remove(&iri("tom"), &rdf_type, &iri("Dog"), None as Option<&RcTerm>).unwrap();
+        assert!(!has(&dataset, &iri("tom"), &rdf_type, &iri("Animal")));
+        assert_eq!(dataset.quads().count(), 2);
+    }
+
+    #[test]
+    fn keeps_asserted_quads_that_are_also_inferred() {
+        let rdf_type = term(RDF_TYPE);
+        let mut dataset = RdfsDataset::new();
+
+        add(&mut dataset, &iri("Cat"), &term(RDFS_SUB_CLASS_OF), &iri("Animal"));
+        add(&mut dataset, &iri("tom"), &rdf_type, &iri("Cat"));
+        add(&mut dataset, &iri("tom"), &rdf_type, &iri("Animal"));
+
+        assert!(dataset.remove(&iri("tom"), &rdf_type, &iri("Animal"), None as Option<&RcTerm>).unwrap());
+        assert!(dataset.is_inferred(&iri("tom"), &rdf_type, &iri("Animal"), None));
+
+        add(&mut dataset, &iri("tom"), &rdf_type, &iri("Animal"));
+        dataset.remove(&iri("Cat"), &term(RDFS_SUB_CLASS_OF), &iri("Animal"), None as Option<&RcTerm>).unwrap();
+        assert!(has(&dataset, &iri("tom"), &rdf_type, &iri("Animal")));
+    }
+
+    #[test]
+    fn keeps_inferred_quads_in_subsets() {
+        let rdf_type = term(RDF_TYPE);
+        let mut dataset = RdfsDataset::new();
+
+        add(&mut dataset, &iri("Cat"), &term(RDFS_SUB_CLASS_OF), &iri("Animal"));
+        add(&mut dataset, &iri("tom"), &rdf_type, &iri("Cat"));
+
+        let subset = dataset.subset(dataset.quads_with_s(&iri("tom")).map(|quad| RcQuad::new_from_quad(&quad.unwrap())));
+        assert_eq!(subset.quads().count(), 2);
+        assert!(subset.is_asserted(&iri("tom"), &rdf_type, &iri("Cat"), None));
+        assert!(subset.is_inferred(&iri("tom"), &rdf_type, &iri("Animal"), None));
+
+        let copy = dataset.clone();
+        assert_eq!(copy.quads().count(), 3);
+        assert!(copy.is_inferred(&iri("tom"), &rdf_type, &iri("Animal"), None));
+    }
+}
@@ -0,0 +1,467 @@
+//! Readable serializations of datasets in Turtle, TriG and N-Triples.
+//!
+//! The Turtle and TriG serializers group the triples by subject and by
+//! predicate, and abbreviate the IRIs with a `PrefixMap`. Unless told
+//! otherwise, the prefix map discovers the namespaces used by the dataset and
+//! gives them a prefix.
+//!
+//! Turtle and N-Triples can not represent named graphs, so only the triples
+//! of the default graph are written in these formats.
+
+use crate::RcQuad;
+use crate::literal::value_of;
+
+use sophia::dataset::Dataset;
+use sophia::quad::Quad;
+use sophia::term::RcTerm;
+use sophia::term::Term;
+
+use std::collections::BTreeMap;
+use std::fmt::Write;
+
+const RDF_TYPE: &str = "http://www.w3.org/1999/02/22-rdf-syntax-ns#type";
+const XSD_STRING: &str = "http://www.w3.org/2001/XMLSchema#string";
+const XSD_INTEGER: &str = "http://www.w3.org/2001/XMLSchema#integer";
+const XSD_BOOLEAN: &str = "http://www.w3.org/2001/XMLSchema#boolean";
+
+/// Prefixes that are used for well known namespaces when they are
+/// discovered
+const WELL_KNOWN_PREFIXES: [(&str, &str); 10] = [
+    ("rdf"   , "http://www.w3.org/1999/02/22-rdf-syntax-ns#"),
+    ("rdfs"  , "http://www.w3.org/2000/01/rdf-schema#"),
+    ("xsd"   , "http://www.w3.org/2001/XMLSchema#"),
+    ("owl"   , "http://www.w3.org/2002/07/owl#"),
+    ("sh"    , "http://www.w3.org/ns/shacl#"),
+    ("skos"  , "http://www.w3.org/2004/02/skos/core#"),
+    ("foaf"  , "http://xmlns.com/foaf/0.1/"),
+    ("dc"    , "http://purl.org/dc/elements/1.1/"),
+    ("dcterms", "http://purl.org/dc/terms/"),
+    ("schema", "http://schema.org/"),
+];
+
+/// A mapping from prefixes to namespaces used to abbreviate IRIs
+#[derive(Clone, Debug, Default)]
+pub struct PrefixMap {
+    /// The declared (prefix, namespace) pairs, in declaration order
+    prefixes: Vec<(String, String)>
+}
+
+impl PrefixMap {
+    /// Builds an empty prefix map
+    pub fn new() -> PrefixMap {
+        PrefixMap::default()
+    }
+
+    /// Declares `prefix` as an abbreviation for `namespace`.
+    ///
+    /// Returns an error message if the prefix can not be written in Turtle or
+    /// if it is already declared for another namespace.
+    pub fn add(&mut self, prefix: &str, namespace: &str) -> Result<(), String> {
+        if !is_valid_prefix(prefix) {
+            return Err(format!("{} is not a valid prefix", prefix));
+        }
+
+        match self.namespace_of(prefix) {
+            Some(declared) if declared == namespace => Ok(()),
+            Some(declared) => Err(format!("The prefix {} is already declared for {}", prefix, declared)),
+            None => {
+                self.prefixes.push((prefix.to_string(), namespace.to_string()));
+                Ok(())
+            }
+        }
+    }
+
+    /// Returns the namespace abbreviated by `prefix`
+    pub fn namespace_of(&self, prefix: &str) -> Option<&str> {
+        self.prefixes.iter()
+            .find(|(declared, _)| declared == prefix)
+            .map(|(_, namespace)| namespace.as_str())
+    }
+
+    /// Returns the declared (prefix, namespace) pairs
+    pub fn prefixes(&self) -> &[(String, String)] {
+        &self.prefixes
+    }
+
+    /// Gives a prefix to every namespace of the dataset that does not have
+    /// one yet and that is worth it: well known namespaces and namespaces
+    /// used at least twice.
+    pub fn discover<D>(&mut self, dataset: &D)
+        where D: Dataset {
+        let mut usages: Vec<(String, usize)> = vec!();
+
+        for quad in dataset.quads() {
+            let quad = RcQuad::new_from_quad(&quad.unwrap());
+
+            for term in [quad.s(), quad.p(), quad.o()].iter().copied().chain(quad.g()) {
+                let iri = match term {
+                    Term::Iri(_) => value_of(term),
+                    Term::Literal(literal) if literal.lang().is_none() => value_of(&literal.dt()),
+                    _ => continue
+                };
+
+                if let Some((namespace, _)) = split_iri(&iri) {
+                    match usages.iter_mut().find(|(known, _)| known == namespace) {
+                        Some((_, count)) => *count += 1,
+                        None => usages.push((namespace.to_string(), 1))
+                    }
+                }
+            }
+        }
+
+        usages.sort();
+
+        for (namespace, count) in usages {
+            if self.abbreviates(&namespace) {
+                continue;
+            }
+
+            let well_known = WELL_KNOWN_PREFIXES.iter()
+                .find(|(_, known_namespace)| *known_namespace == namespace)
+                .map(|(prefix, _)| prefix.to_string());
+
+            let prefix = match well_known {
+                Some(prefix) if self.namespace_of(&prefix).is_none() => prefix,
+                _ if count < 2 => continue,
+                _ => self.fresh_prefix(&namespace)
+            };
+
+            self.prefixes.push((prefix, namespace));
+        }
+    }
+
+    /// Returns true if an IRI of `namespace` would be abbreviated
+    fn abbreviates(&self, namespace: &str) -> bool {
+        self.prefixes.iter().any(|(_, declared)| declared == namespace)
+    }
+
+    /// Builds an unused prefix for `namespace`, from its last segment if
+    /// possible
+    fn fresh_prefix(&self, namespace: &str) -> String {
+        let last_segment = namespace
+            .trim_end_matches(|c| c == '/' || c == '#')
+            .rsplit(|c| c == '/' || c == '#' || c == ':')
+            .next()
+            .unwrap_or("")
+            .to_lowercase();
+
+        if is_valid_prefix(&last_segment) && self.namespace_of(&last_segment).is_none() {
+            return last_segment;
+        }
+
+        (1..).map(|i| format!("ns{}", i))
+            .find(|prefix| self.namespace_of(prefix).is_none())
+            .unwrap()
+    }
+
+    /// Returns the prefixed name of `iri`, if one of the declared namespaces
+    /// can abbreviate it. The longest namespace is preferred.
+    pub fn abbreviate(&self, iri: &str) -> Option<String> {
+        self.prefixes.iter()
+            .filter(|(_, namespace)| iri.starts_with(namespace.as_str()))
+            .filter(|(_, namespace)| is_valid_local_name(&iri[namespace.len()..]))
+            .max_by_key(|(_, namespace)| namespace.len())
+            .map(|(prefix, namespace)| format!("{}:{}", prefix, &iri[namespace.len()..]))
+    }
+}
+
+/// Splits an IRI into a namespace, ending with its last `#` or `/`, and a
+/// local name, if the local name can be written in a prefixed name
+fn split_iri(iri: &str) -> Option<(&str, &str)> {
+    let position = iri.rfind(|c| c == '#' || c == '/')?;
+    let (namespace, local_name) = iri.split_at(position + 1);
+
+    if is_valid_local_name(local_name) {
+        Some((namespace, local_name))
+    } else {
+        None
+    }
+}
+
+/// Returns true if `prefix` can be used as a prefix in Turtle.
+///
+/// This is a restriction of the Turtle grammar to ASCII characters.
+fn is_valid_prefix(prefix: &str) -> bool {
+    let mut chars = prefix.chars();
+
+    match chars.next() {
+        None => true,
+        Some(first) => first.is_ascii_alphabetic()
+            && chars.all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-')
+    }
+}
+
+/// Returns true if `local_name` can be written after a prefix without
+/// escaping.
+///
+/// This is a restriction of the Turtle grammar to ASCII characters, without
+/// dots.
+fn is_valid_local_name(local_name: &str) -> bool {
+    let mut chars = local_name.chars();
+
+    match chars.next() {
+        None => true,
+        Some(first) => (first.is_ascii_alphanumeric() || first == '_')
+            && chars.all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-')
+    }
+}
+
+/// Escapes the characters of a string literal that can not be written as is
+/// between double quotes.
+///
+/// The control characters are escaped as required by the canonical form of
+/// N-Quads of RDFC-1.0, so the canonicalization uses this function too.
+pub(crate) fn escape_string(value: &str) -> String {
+    let mut escaped = String::with_capacity(value.len());
+
+    for c in value.chars() {
+        match c {
+            '\\' => escaped.push_str("\\\\"),
+            '"' => escaped.push_str("\\\""),
+            '\n' => escaped.push_str("\\n"),
+            '\r' => escaped.push_str("\\r"),
+            '\t' => escaped.push_str("\\t"),
+            '\u{8}' => escaped.push_str("\\b"),
+            '\u{c}' => escaped.push_str("\\f"),
+            c if c <= '\u{1f}' || c == '\u{7f}' => escaped.push_str(&format!("\\u{:04X}", c as u32)),
+            c => escaped.push(c)
+        }
+    }
+
+    escaped
+}
+
+/// Writes an IRI, abbreviated if a prefix map is given and can abbreviate it
+fn write_iri(iri: &str, prefixes: Option<&PrefixMap>) -> String {
+    match prefixes.and_then(|prefixes| prefixes.abbreviate(iri)) {
+        Some(prefixed_name) => prefixed_name,
+        None => format!("<{}>", iri)
+    }
+}
+
+/// Writes a term in Turtle if a prefix map is given, or in N-Triples
+pub(crate) fn write_term(term: &RcTerm, prefixes: Option<&PrefixMap>) -> String {
+    match term {
+        Term::Iri(_) => write_iri(&value_of(term), prefixes),
+        Term::BNode(_) => format!("_:{}", value_of(term)),
+        Term::Variable(_) => format!("?{}", value_of(term)),
+        Term::Literal(literal) => {
+            let value = value_of(term);
+
+            if let Some(lang) = literal.lang() {
+                return format!("\"{}\"@{}", escape_string(&value), lang.as_ref());
+            }
+
+            let datatype = value_of(&literal.dt());
+
+            match datatype.as_str() {
+                XSD_STRING => format!("\"{}\"", escape_string(&value)),
+                XSD_INTEGER if prefixes.is_some() && is_integer(&value) => value,
+                XSD_BOOLEAN if prefixes.is_some() && (value == "true" || value == "false") => value,
+                _ => format!("\"{}\"^^{}", escape_string(&value), write_iri(&datatype, prefixes))
+            }
+        }
+    }
+}
+
+/// Returns true if `value` can be written as a Turtle integer
+fn is_integer(value: &str) -> bool {
+    let digits = value.strip_prefix(|c| c == '+' || c == '-').unwrap_or(value);
+    !digits.is_empty() && digits.chars().all(|c| c.is_ascii_digit())
+}
+
+/// The triples of a graph grouped by subject and by predicate, sorted by their
+/// N-Triples representation
+type GroupedTriples = BTreeMap<String, (RcTerm, BTreeMap<String, (RcTerm, BTreeMap<String, RcTerm>)>)>;
+
+/// Groups the quads of the dataset by graph, subject and predicate. The
+/// default graph is the graph with the `None` key.
+fn group_quads<D>(dataset: &D) -> BTreeMap<Option<String>, (Option<RcTerm>, GroupedTriples)>
+    where D: Dataset {
+    let mut graphs = BTreeMap::new();
+
+    for quad in dataset.quads() {
+        let quad = RcQuad::new_from_quad(&quad.unwrap());
+
+        let (_, subjects) = graphs
+            .entry(quad.g().map(|g| write_term(g, None)))
+            .or_insert_with(|| (quad.g().cloned(), BTreeMap::new()));
+
+        let (_, predicates) = subjects
+            .entry(write_term(quad.s(), None))
+            .or_insert_with(|| (quad.s().clone(), BTreeMap::new()));
+
+        // rdf:type is written first
+        let predicate_key = match quad.p() {
+            Term::Iri(_) if value_of(quad.p()) == RDF_TYPE => String::new(),
+            p => write_term(p, None)
+        };
+
+        let (_, objects) = predicates
+            .entry(predicate_key)
+            .or_insert_with(|| (quad.p().clone(), BTreeMap::new()));
+
+        objects.insert(write_term(quad.o(), None), quad.o().clone());
+    }
+
+    graphs
+}
+
+/// Writes the triples of a graph with subject grouping and predicate lists
+fn write_grouped_triples(output: &mut String, triples: &GroupedTriples, prefixes: &PrefixMap, indentation: &str) {
+    for (subject, predicates) in triples.values() {
+        write!(output, "{}{}", indentation, write_term(subject, Some(prefixes))).unwrap();
+
+        for (i, (predicate, objects)) in predicates.values().enumerate() {
+            if i != 0 {
+                write!(output, " ;\n{}    ", indentation).unwrap();
+            } else {
+                output.push(' ');
+            }
+
+            match predicate {
+                Term::Iri(_) if value_of(predicate) == RDF_TYPE => output.push('a'),
+                _ => output.push_str(&write_term(predicate, Some(prefixes)))
+            }
+
+            let objects: Vec<String> = objects.values()
+                .map(|object| write_term(object, Some(prefixes)))
+                .collect();
+            write!(output, " {}", objects.join(", ")).unwrap();
+        }
+
+        output.push_str(" .\n");
+    }
+}
+
+/// Writes the prefix declarations
+fn write_prefixes(output: &mut String, prefixes: &PrefixMap) {
+    for (prefix, namespace) in prefixes.prefixes() {
+        writeln!(output, "@prefix {}: <{}> .", prefix, namespace).unwrap();
+    }
+
+    if !prefixes.prefixes().is_empty() {
+        output.push('\n');
+    }
+}
+
+/// Readable serializations of a dataset
+pub trait DatasetSerializer: Dataset + Sized {
+    /// Writes the triples of the default graph in Turtle.
+    ///
+    /// `prefixes` is completed with the discovered namespaces.
+    fn to_turtle(&self, prefixes: &PrefixMap) -> String {
+        let mut prefixes = prefixes.clone();
+        prefixes.discover(self);
+
+        let mut output = String::new();
+        write_prefixes(&mut output, &prefixes);
+
+        if let Some((_, triples)) = group_quads(self).get(&None) {
+            write_grouped_triples(&mut output, triples, &prefixes, "");
+        }
+
+        output
+    }
+
+    /// Writes the quads in TriG.
+    ///
+    /// `prefixes` is completed with the discovered namespaces.
+    fn to_trig(&self, prefixes: &PrefixMap) -> String {
+        let mut prefixes = prefixes.clone();
+        prefixes.discover(self);
+
+        let mut output = String::new();
+        write_prefixes(&mut output, &prefixes);
+
+        for (graph, triples) in group_quads(self).values() {
+            match graph {
+                None => write_grouped_triples(&mut output, triples, &prefixes, ""),
+                Some(graph) => {
+                    writeln!(output, "{} {{", write_term(graph, Some(&prefixes))).unwrap();
+                    write_grouped_triples(&mut output, triples, &prefixes, "    ");
+                    output.push_str("}\n");
+                }
+            }
+        }
+
+        output
+    }
+
+    /// Writes the triples of the default graph in N-Triples
+    fn to_ntriples(&self) -> String {
+        let mut output = String::new();
+
+        for quad in self.quads() {
+            let quad = RcQuad::new_from_quad(&quad.unwrap());
+
+            if quad.g().is_none() {
+                writeln!(output, "{} {} {} .",
+                    write_term(quad.s(), None),
+                    write_term(quad.p(), None),
+                    write_term(quad.o(), None)
+                ).unwrap();
+            }
+        }
+
+        output
+    }
+}
+
+impl<D> DatasetSerializer for D where D: Dataset {}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::treedataset::TreeDataset;
+    use sophia::dataset::MutableDataset;
+
+    fn iri(value: &str) -> RcTerm {
+        RcTerm::new_iri(value).unwrap()
+    }
+
+    #[test]
+    fn turtle_groups_subjects_and_predicates() {
+        let mut dataset = TreeDataset::new();
+        let alice = iri("http://example.org/alice");
+        let knows = iri("http://example.org/knows");
+        let rdf_type = iri(RDF_TYPE);
+
+        dataset.insert(&alice, &rdf_type, &iri("http://example.org/Person"), None as Option<&RcTerm>).unwrap();
+        dataset.insert(&alice, &knows, &iri("http://example.org/bob"), None as Option<&RcTerm>).unwrap();
+        dataset.insert(&alice, &knows, &iri("http://example.org/carol"), None as Option<&RcTerm>).unwrap();
+
+        assert_eq!(
+            dataset.to_turtle(&PrefixMap::new()),
+            "@prefix ns1: <http://example.org/> .\n\
+             @prefix rdf: <http://www.w3.org/1999/02/22-rdf-syntax-ns#> .\n\n\
+             ns1:alice a ns1:Person ;\n    \
+             ns1:knows ns1:bob, ns1:carol .\n"
+        );
+    }
+
+    #[test]
+    fn given_prefixes_are_used_and_named_graphs_are_written_in_trig() {
+        let mut dataset = TreeDataset::new();
+        let mut prefixes = PrefixMap::new();
+        prefixes.add("ex", "http://example.org/").unwrap();
+
+        let s = iri("http://example.org/s");
+        let g = iri("http://example.org/g");
+        let label = RcTerm::new_literal_lang("chat", "fr").unwrap();
+        dataset.insert(&s, &s, &label, Some(&g)).unwrap();
+
+        assert_eq!(dataset.to_turtle(&prefixes), "@prefix ex: <http://example.org/> .\n\n");
+        assert_eq!(
+            dataset.to_trig(&prefixes),
+            "@prefix ex: <http://example.org/> .\n\nex:g {\n    ex:s ex:s \"chat\"@fr .\n}\n"
+        );
+        assert_eq!(dataset.to_ntriples(), "");
+    }
+
+    #[test]
+    fn escapes_the_control_characters() {
+        assert_eq!(escape_string("a\"b\\c\nd\te"), "a\\\"b\\\\c\\nd\\te");
+        assert_eq!(escape_string("\u{8}\u{c}\u{0}\u{b}\u{7f}é"), "\\b\\f\\u0000\\u000B\\u007Fé");
+    }
+}
@@ -0,0 +1,840 @@
+//! Validation of a dataset against SHACL Core shapes.
+//!
+//! `validate` reads the shapes of a shapes dataset and checks the focus nodes
+//! of a data dataset against them. Both datasets are read as a single graph:
+//! the graph names of their quads are ignored.
+//!
+//! The supported features are:
+//! - the targets `sh:targetNode`, `sh:targetClass`, `sh:targetSubjectsOf`,
+//! `sh:targetObjectsOf` and the implicit class targets
+//! - the property paths: predicate, sequence, alternative, inverse,
+//! zero-or-more, one-or-more and zero-or-one paths
+//! - the constraints `sh:minCount`, `sh:maxCount`, `sh:datatype`, `sh:class`,
+//! `sh:pattern` (with `sh:flags`), `sh:in`, `sh:node` and `sh:property`
+//! - `sh:deactivated`, `sh:severity` and `sh:message`
+//!
+//! The patterns are compiled with the `regex` crate, which does not support
+//! back-references and look-arounds. Recursive shapes are considered
+//! conforming when a focus node is checked again against a shape it is
+//! already being checked against.
+
+use crate::RcQuad;
+use crate::literal::{ is_well_formed, value_of };
+use crate::serializer::write_term;
+
+use regex::Regex;
+use sophia::dataset::Dataset;
+use sophia::dataset::MutableDataset;
+use sophia::quad::Quad;
+use sophia::term::RcTerm;
+use sophia::term::Term;
+
+use std::collections::HashMap;
+use std::collections::HashSet;
+use std::iter::once;
+
+const SH: &str = "http://www.w3.org/ns/shacl#";
+const RDF: &str = "http://www.w3.org/1999/02/22-rdf-syntax-ns#";
+const RDFS: &str = "http://www.w3.org/2000/01/rdf-schema#";
+const XSD: &str = "http://www.w3.org/2001/XMLSchema#";
+
+const RDF_LANG_STRING: &str = "http://www.w3.org/1999/02/22-rdf-syntax-ns#langString";
+
+fn iri(namespace: &str, name: &str) -> RcTerm {
+    RcTerm::new_iri(format!("{}{}", namespace, name)).unwrap()
+}
+
+/// The terms of the shapes and of the validation report
+struct Vocabulary {
+    rdf_type: RcTerm,
+    rdf_first: RcTerm,
+    rdf_rest: RcTerm,
+    rdf_nil: RcTerm,
+    rdfs_class: RcTerm,
+    rdfs_sub_class_of: RcTerm,
+    xsd_boolean: RcTerm,
+    node_shape: RcTerm,
+    property_shape: RcTerm,
+    target_node: RcTerm,
+    target_class: RcTerm,
+    target_subjects_of: RcTerm,
+    target_objects_of: RcTerm,
+    path: RcTerm,
+    inverse_path: RcTerm,
+    alternative_path: RcTerm,
+    zero_or_more_path: RcTerm,
+    one_or_more_path: RcTerm,
+    zero_or_one_path: RcTerm,
+    property: RcTerm,
+    node: RcTerm,
+    min_count: RcTerm,
+    max_count: RcTerm,
+    datatype: RcTerm,
+    class: RcTerm,
+    pattern: RcTerm,
+    flags: RcTerm,
+    in_list: RcTerm,
+    deactivated: RcTerm,
+    severity: RcTerm,
+    message: RcTerm,
+    violation: RcTerm,
+    validation_report: RcTerm,
+    validation_result: RcTerm,
+    conforms: RcTerm,
+    result: RcTerm,
+    focus_node: RcTerm,
+    result_path: RcTerm,
+    value: RcTerm,
+    source_shape: RcTerm,
+    source_constraint_component: RcTerm,
+    result_severity: RcTerm,
+    result_message: RcTerm
+}
+
+impl Default for Vocabulary {
+    fn default() -> Self {
+        Self {
+            rdf_type: iri(RDF, "type"),
+            rdf_first: iri(RDF, "first"),
+            rdf_rest: iri(RDF, "rest"),
+            rdf_nil: iri(RDF, "nil"),
+            rdfs_class: iri(RDFS, "Class"),
+            rdfs_sub_class_of: iri(RDFS, "subClassOf"),
+            xsd_boolean: iri(XSD, "boolean"),
+            node_shape: iri(SH, "NodeShape"),
+            property_shape: iri(SH, "PropertyShape"),
+            target_node: iri(SH, "targetNode"),
+            target_class: iri(SH, "targetClass"),
+            target_subjects_of: iri(SH, "targetSubjectsOf"),
+            target_objects_of: iri(SH, "targetObjectsOf"),
+            path: iri(SH, "path"),
+            inverse_path: iri(SH, "inversePath"),
+            alternative_path: iri(SH, "alternativePath"),
+            zero_or_more_path: iri(SH, "zeroOrMorePath"),
+            one_or_more_path: iri(SH, "oneOrMorePath"),
+            zero_or_one_path: iri(SH, "zeroOrOnePath"),
+            property: iri(SH, "property"),
+            node: iri(SH, "node"),
+            min_count: iri(SH, "minCount"),
+            max_count: iri(SH, "maxCount"),
+            datatype: iri(SH, "datatype"),
+            class: iri(SH, "class"),
+            pattern: iri(SH, "pattern"),
+            flags: iri(SH, "flags"),
+            in_list: iri(SH, "in"),
+            deactivated: iri(SH, "deactivated"),
+            severity: iri(SH, "severity"),
+            message: iri(SH, "message"),
+            violation: iri(SH, "Violation"),
+            validation_report: iri(SH, "ValidationReport"),
+            validation_result: iri(SH, "ValidationResult"),
+            conforms: iri(SH, "conforms"),
+            result: iri(SH, "result"),
+            focus_node: iri(SH, "focusNode"),
+            result_path: iri(SH, "resultPath"),
+            value: iri(SH, "value"),
+            source_shape: iri(SH, "sourceShape"),
+            source_constraint_component: iri(SH, "sourceConstraintComponent"),
+            result_severity: iri(SH, "resultSeverity"),
+            result_message: iri(SH, "resultMessage")
+        }
+    }
+}
+
+/// An error in the shapes dataset
+#[derive(Debug, Clone, PartialEq)]
+pub struct ShaclError {
+    /// The description of the error
+    pub message: String
+}
+
+impl ShaclError {
+    fn new(message: String) -> Self {
+        Self { message }
+    }
+}
+
+impl std::fmt::Display for ShaclError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Invalid shapes graph: {}", self.message)
+    }
+}
+
+impl std::error::Error for ShaclError {}
+
+/// A failed constraint
+#[derive(Debug, Clone, PartialEq)]
+pub struct ValidationResult {
+    /// The node that was checked against the shape
+    pub focus_node: RcTerm,
+    /// The path of the property shape, if the shape has one
+    pub result_path: Option<RcTerm>,
+    /// The value node that failed the constraint, if the constraint checks
+    /// the value nodes one by one
+    pub value: Option<RcTerm>,
+    /// The shape of the constraint
+    pub source_shape: RcTerm,
+    /// The IRI of the constraint component, like
+    /// `sh:MinCountConstraintComponent`
+    pub source_constraint_component: RcTerm,
+    /// The severity of the shape, `sh:Violation` by default
+    pub severity: RcTerm,
+    /// The messages of the shape
+    pub messages: Vec<RcTerm>
+}
+
+/// The outcome of a validation
+pub struct ValidationReport {
+    /// The failed constraints
+    pub results: Vec<ValidationResult>,
+    /// The triples that describe the complex paths of the results, copied from
+    /// the shapes dataset
+    path_quads: Vec<RcQuad>
+}
+
+impl ValidationReport {
+    /// Returns true if no constraint failed
+    pub fn conforms(&self) -> bool {
+        self.results.is_empty()
+    }
+
+    /// Writes the report as a `sh:ValidationReport` in the default graph of
+    /// `dataset`
+    pub fn write_to<D>(&self, dataset: &mut D)
+        where D: MutableDataset {
+        let v = Vocabulary::default();
+        let mut add = |s: &RcTerm, p: &RcTerm, o: &RcTerm| {
+            dataset.insert(s, p, o, None as Option<&RcTerm>).unwrap();
+        };
+
+        let report = RcTerm::new_bnode("report").unwrap();
+        let conforms = RcTerm::new_literal_dt(self.conforms().to_string(), v.xsd_boolean.clone()).unwrap();
+
+        add(&report, &v.rdf_type, &v.validation_report);
+        add(&report, &v.conforms, &conforms);
+
+        for (index, result) in self.results.iter().enumerate() {
+            let node = RcTerm::new_bnode(format!("result{}", index + 1)).unwrap();
+
+            add(&report, &v.result, &node);
+            add(&node, &v.rdf_type, &v.validation_result);
+            add(&node, &v.focus_node, &result.focus_node);
+            add(&node, &v.source_shape, &result.source_shape);
+            add(&node, &v.source_constraint_component, &result.source_constraint_component);
+            add(&node, &v.result_severity, &result.severity);
+
+            if let Some(path) = &result.result_path {
+                add(&node, &v.result_path, path);
+            }
+
+            if let Some(value) = &result.value {
+                add(&node, &v.value, value);
+            }
+
+            for message in &result.messages {
+                add(&node, &v.result_message, message);
+            }
+        }
+
+        for quad in &self.path_quads {
+            add(quad.s(), quad.p(), quad.o());
+        }
+    }
+}
+
+/// Validates `data` against the shapes of `shapes`
+pub fn validate<S, D>(shapes: &S, data: &D) -> Result<ValidationReport, ShaclError>
+    where S: Dataset, D: Dataset {
+    let mut validator = Validator {
+        shapes: Graph { dataset: shapes },
+        data: Graph { dataset: data },
+        vocabulary: Vocabulary::default(),
+        patterns: HashMap::new(),
+        stack: vec![],
+        result_paths: vec![]
+    };
+
+    let mut results = vec![];
+
+    for shape in validator.shapes_with_targets() {
+        for focus_node in validator.focus_nodes(&shape) {
+            results.extend(validator.validate_shape(&shape, &focus_node)?);
+        }
+    }
+
+    Ok(ValidationReport { results, path_quads: validator.path_quads() })
+}
+
+/// Returns the terms without their duplicates, in their first order
+fn distinct<I>(terms: I) -> Vec<RcTerm>
+    where I: Iterator<Item = RcTerm> {
+    let mut seen = HashSet::new();
+    terms.filter(|term| seen.insert(term.clone())).collect()
+}
+
+/// Read access to the triples of a dataset, whatever their graph
+struct Graph<'a, D> {
+    dataset: &'a D
+}
+
+impl<'a, D> Graph<'a, D>
+    where D: Dataset {
+    fn objects(&self, s: &RcTerm, p: &RcTerm) -> Vec<RcTerm> {
+        distinct(self.dataset.quads_with_sp(s, p).map(|quad| quad.unwrap().o().into()))
+    }
+
+    fn object(&self, s: &RcTerm, p: &RcTerm) -> Option<RcTerm> {
+        self.dataset.quads_with_sp(s, p).next().map(|quad| quad.unwrap().o().into())
+    }
+
+    fn subjects(&self, p: &RcTerm, o: &RcTerm) -> Vec<RcTerm> {
+        distinct(self.dataset.quads_with_po(p, o).map(|quad| quad.unwrap().s().into()))
+    }
+
+    fn subjects_of(&self, p: &RcTerm) -> Vec<RcTerm> {
+        distinct(self.dataset.quads_with_p(p).map(|quad| quad.unwrap().s().into()))
+    }
+
+    fn objects_of(&self, p: &RcTerm) -> Vec<RcTerm> {
+        distinct(self.dataset.quads_with_p(p).map(|quad| quad.unwrap().o().into()))
+    }
+
+    fn has(&self, s: &RcTerm, p: &RcTerm, o: &RcTerm) -> bool {
+        self.dataset.quads_with_spo(s, p, o).next().is_some()
+    }
+
+    /// Returns the members of the RDF list that starts at `head`
+    fn list(&self, head: &RcTerm, v: &Vocabulary) -> Result<Vec<RcTerm>, ShaclError> {
+        let mut members = vec![];
+        let mut visited = HashSet::new();
+        let mut node = head.clone();
+
+        while node != v.rdf_nil {
+            let invalid_list = || ShaclError::new(format!("{} is not a valid list", write_term(head, None)));
+
+            if !visited.insert(node.clone()) {
+                return Err(invalid_list());
+            }
+
+            members.push(self.object(&node, &v.rdf_first).ok_or_else(invalid_list)?);
+            node = self.object(&node, &v.rdf_rest).ok_or_else(invalid_list)?;
+        }
+
+        Ok(members)
+    }
+}
+
+/// A SHACL property path
+enum Path {
+    Predicate(RcTerm),
+    Inverse(Box<Path>),
+    Sequence(Vec<Path>),
+    Alternative(Vec<Path>),
+    ZeroOrMore(Box<Path>),
+    OneOrMore(Box<Path>),
+    ZeroOrOne(Box<Path>)
+}
+
+struct Validator<'a, S, D> {
+    shapes: Graph<'a, S>,
+    data: Graph<'a, D>,
+    vocabulary: Vocabulary,
+    /// The compiled regular expressions, by pattern and flags
+    patterns: HashMap<(String, String), Regex>,
+    /// The shapes that are being checked and their focus node
+    stack: Vec<(RcTerm, RcTerm)>,
+    /// The complex paths used in the results
+    result_paths: Vec<RcTerm>
+}
+
+impl<'a, S, D> Validator<'a, S, D>
+    where S: Dataset, D: Dataset {
+    /// Returns the shapes that have at least one target
+    fn shapes_with_targets(&self) -> Vec<RcTerm> {
+        let v = &self.vocabulary;
+
+        let explicit = [&v.target_node, &v.target_class, &v.target_subjects_of, &v.target_objects_of]
+            .iter()
+            .flat_map(|target| self.shapes.subjects_of(target))
+            .collect::<Vec<_>>();
+
+        let implicit = self.shapes.subjects(&v.rdf_type, &v.rdfs_class)
+            .into_iter()
+            .filter(|class| {
+                self.shapes.has(class, &v.rdf_type, &v.node_shape)
+                    || self.shapes.has(class, &v.rdf_type, &v.property_shape)
+            });
+
+        distinct(explicit.into_iter().chain(implicit))
+    }
+
+    /// Returns the focus nodes of the targets of `shape`
+    fn focus_nodes(&self, shape: &RcTerm) -> Vec<RcTerm> {
+        let v = &self.vocabulary;
+        let mut nodes = self.shapes.objects(shape, &v.target_node);
+
+        let mut classes = self.shapes.objects(shape, &v.target_class);
+        if self.shapes.has(shape, &v.rdf_type, &v.rdfs_class) {
+            classes.push(shape.clone());
+        }
+
+        for class in classes {
+            nodes.extend(self.instances(&class));
+        }
+
+        for predicate in self.shapes.objects(shape, &v.target_subjects_of) {
+            nodes.extend(self.data.subjects_of(&predicate));
+        }
+
+        for predicate in self.shapes.objects(shape, &v.target_objects_of) {
+            nodes.extend(self.data.objects_of(&predicate));
+        }
+
+        distinct(nodes.into_iter())
+    }
+
+    /// Returns the classes that are `class` or one of its transitive
+    /// subclasses
+    fn subclasses(&self, class: &RcTerm) -> Vec<RcTerm> {
+        let mut classes = vec![class.clone()];
+        let mut seen = HashSet::new();
+        seen.insert(class.clone());
+
+        let mut index = 0;
+        while index < classes.len() {
+            for subclass in self.data.subjects(&self.vocabulary.rdfs_sub_class_of, &classes[index]) {
+                if seen.insert(subclass.clone()) {
+                    classes.push(subclass);
+                }
+            }
+
+            index += 1;
+        }
+
+        classes
+    }
+
+    /// Returns the SHACL instances of `class` in the data
+    fn instances(&self, class: &RcTerm) -> Vec<RcTerm> {
+        distinct(
+            self.subclasses(class)
+                .iter()
+                .flat_map(|subclass| self.data.subjects(&self.vocabulary.rdf_type, subclass))
+        )
+    }
+
+    /// Returns true if `node` has a type that is `class` or one of its
+    /// subclasses
+    fn is_instance(&self, node: &RcTerm, class: &RcTerm) -> bool {
+        let mut seen = HashSet::new();
+        let mut pending = self.data.objects(node, &self.vocabulary.rdf_type);
+
+        while let Some(current) = pending.pop() {
+            if current == *class {
+                return true;
+            }
+
+            if seen.insert(current.clone()) {
+                pending.extend(self.data.objects(&current, &self.vocabulary.rdfs_sub_class_of));
+            }
+        }
+
+        false
+    }
+
+    /// Reads the path described by `node` in the shapes
+    fn parse_path(&self, node: &RcTerm) -> Result<Path, ShaclError> {
+        let v = &self.vocabulary;
+
+        if let Term::Iri(_) = node {
+            return Ok(Path::Predicate(node.clone()));
+        }
+
+        let parse_all = |list: &RcTerm| -> Result<Vec<Path>, ShaclError> {
+            self.shapes.list(list, v)?.iter().map(|member| self.parse_path(member)).collect()
+        };
+
+        if self.shapes.object(node, &v.rdf_first).is_some() {
+            return Ok(Path::Sequence(parse_all(node)?));
+        }
+
+        if let Some(list) = self.shapes.object(node, &v.alternative_path) {
+            return Ok(Path::Alternative(parse_all(&list)?));
+        }
+
+        let unary_paths: [(&RcTerm, fn(Box<Path>) -> Path); 4] = [
+            (&v.inverse_path, Path::Inverse),
+            (&v.zero_or_more_path, Path::ZeroOrMore),
+            (&v.one_or_more_path, Path::OneOrMore),
+            (&v.zero_or_one_path, Path::ZeroOrOne)
+        ];
+
+        for (predicate, build) in unary_paths.iter() {
+            if let Some(inner) = self.shapes.object(node, predicate) {
+                return Ok(build(Box::new(self.parse_path(&inner)?)));
+            }
+        }
+
+        Err(ShaclError::new(format!("{} is not a valid path", write_term(node, None))))
+    }
+
+    /// Returns the nodes reached from `node` by following `path`, or by
+    /// following it backwards if `inverse` is true
+    fn path_values(&self, path: &Path, node: &RcTerm, inverse: bool) -> Vec<RcTerm> {
+        match path {
+            Path::Predicate(predicate) if inverse => self.data.subjects(predicate, node),
+            Path::Predicate(predicate) => self.data.objects(node, predicate),
+            Path::Inverse(inner) => self.path_values(inner, node, !inverse),
+            Path::Sequence(steps) => {
+                let mut nodes = vec![node.clone()];
+                let steps: Vec<&Path> = if inverse { steps.iter().rev().collect() } else { steps.iter().collect() };
+
+                for step in steps {
+                    nodes = distinct(nodes.iter().flat_map(|current| self.path_values(step, current, inverse)));
+                }
+
+                nodes
+            },
+            Path::Alternative(alternatives) => distinct(
+                alternatives.iter().flat_map(|alternative| self.path_values(alternative, node, inverse))
+            ),
+            Path::ZeroOrMore(inner) => self.path_closure(inner, node, inverse, true),
+            Path::OneOrMore(inner) => self.path_closure(inner, node, inverse, false),
+            Path::ZeroOrOne(inner) => distinct(once(node.clone()).chain(self.path_values(inner, node, inverse)))
+        }
+    }
+
+    /// Returns the nodes reached from `node` by following `path` one or more
+    /// times, or zero or more times if `reflexive` is true
+    fn path_closure(&self, path: &Path, node: &RcTerm, inverse: bool, reflexive: bool) -> Vec<RcTerm> {
+        let mut reached = vec![];
+        let mut seen = HashSet::new();
+
+        if reflexive {
+            seen.insert(node.clone());
+            reached.push(node.clone());
+        }
+
+        let mut pending = vec![node.clone()];
+
+        while let Some(current) = pending.pop() {
+            for next in self.path_values(path, &current, inverse) {
+                if seen.insert(next.clone()) {
+                    reached.push(next.clone());
+                    pending.push(next);
+                }
+            }
+        }
+
+        reached
+    }
+
+    /// Returns the regular expression of `sh:pattern` and `sh:flags`
+    fn regex(&mut self, pattern: &str, flags: &str) -> Result<Regex, ShaclError> {
+        let key = (pattern.to_string(), flags.to_string());
+
+        if let Some(regex) = self.patterns.get(&key) {
+            return Ok(regex.clone());
+        }
+
+        if let Some(flag) = flags.chars().find(|flag| !"imsx".contains(*flag)) {
+            return Err(ShaclError::new(format!("Unsupported regular expression flag {}", flag)));
+        }
+
+        let source = if flags.is_empty() { pattern.to_string() } else { format!("(?{}){}", flags, pattern) };
+        let regex = Regex::new(&source).map_err(|error| ShaclError::new(error.to_string()))?;
+
+        self.patterns.insert(key, regex.clone());
+        Ok(regex)
+    }
+
+    /// Returns the non negative integer of `sh:minCount` or `sh:maxCount`
+    fn count(term: &RcTerm) -> Result<usize, ShaclError> {
+        let count = match term {
+            Term::Literal(_) => value_of(term).parse().ok(),
+            _ => None
+        };
+
+        count.ok_or_else(|| ShaclError::new(format!("{} is not a non negative integer", write_term(term, None))))
+    }
+
+    /// Returns true if `value` is a well-formed literal of `datatype`
+    fn has_datatype(value: &RcTerm, datatype: &RcTerm) -> bool {
+        let actual = match value {
+            Term::Literal(literal) if literal.lang().is_some() => RDF_LANG_STRING.to_string(),
+            Term::Literal(literal) => value_of(&literal.dt()),
+            _ => return false
+        };
+
+        actual == value_of(datatype) && is_well_formed(&actual, &value_of(value))
+    }
+
+    /// Checks `focus_node` against `shape` and returns the failed constraints
+    fn validate_shape(&mut self, shape: &RcTerm, focus_node: &RcTerm) -> Result<Vec<ValidationResult>, ShaclError> {
+        let deactivated = self.shapes.objects(shape, &self.vocabulary.deactivated)
+            .iter()
+            .any(|flag| value_of(flag) == "true");
+
+        let key = (shape.clone(), focus_node.clone());
+        if deactivated || self.stack.contains(&key) {
+            return Ok(vec![]);
+        }
+
+        self.stack.push(key);
+        let results = self.check_constraints(shape, focus_node);
+        self.stack.pop();
+
+        results
+    }
+
+    fn check_constraints(&mut self, shape: &RcTerm, focus_node: &RcTerm) -> Result<Vec<ValidationResult>, ShaclError> {
+        let path = self.shapes.object(shape, &self.vocabulary.path);
+
+        let values = match &path {
+            Some(path) => self.path_values(&self.parse_path(path)?, focus_node, false),
+            None => vec![focus_node.clone()]
+        };
+
+        // The name of the constraint component and the value node of every
+        // failed constraint
+        let mut failures: Vec<(&str, Option<RcTerm>)> = vec![];
+
+        if path.is_some() {
+            for min_count in self.shapes.objects(shape, &self.vocabulary.min_count) {
+                if values.len() < Self::count(&min_count)? {
+                    failures.push(("MinCount", None));
+                }
+            }
+
+            for max_count in self.shapes.objects(shape, &self.vocabulary.max_count) {
+                if values.len() > Self::count(&max_count)? {
+                    failures.push(("MaxCount", None));
+                }
+            }
+        }
+
+        for datatype in self.shapes.objects(shape, &self.vocabulary.datatype) {
+            for value in values.iter().filter(|value| !Self::has_datatype(value, &datatype)) {
+                failures.push(("Datatype", Some(value.clone())));
+            }
+        }
+
+        for class in self.shapes.objects(shape, &self.vocabulary.class) {
+            for value in values.iter().filter(|value| !self.is_instance(value, &class)) {
+                failures.push(("Class", Some(value.clone())));
+            }
+        }
+
+        for pattern in self.shapes.objects(shape, &self.vocabulary.pattern) {
+            let flags = self.shapes.object(shape, &self.vocabulary.flags)
+                .map(|flags| value_of(&flags))
+                .unwrap_or_default();
+            let regex = self.regex(&value_of(&pattern), &flags)?;
+
+            for value in &values {
+                let matches = match value {
+                    Term::BNode(_) => false,
+                    _ => regex.is_match(&value_of(value))
+                };
+
+                if !matches {
+                    failures.push(("Pattern", Some(value.clone())));
+                }
+            }
+        }
+
+        for list in self.shapes.objects(shape, &self.vocabulary.in_list) {
+            let allowed = self.shapes.list(&list, &self.vocabulary)?;
+
+            for value in values.iter().filter(|value| !allowed.contains(value)) {
+                failures.push(("In", Some(value.clone())));
+            }
+        }
+
+        for node_shape in self.shapes.objects(shape, &self.vocabulary.node) {
+            for value in &values {
+                if !self.validate_shape(&node_shape, value)?.is_empty() {
+                    failures.push(("Node", Some(value.clone())));
+                }
+            }
+        }
+
+        if let Some(path) = &path {
+            let is_complex_path = match path {
+                Term::BNode(_) => true,
+                _ => false
+            };
+
+            if is_complex_path && !failures.is_empty() && !self.result_paths.contains(path) {
+                self.result_paths.push(path.clone());
+            }
+        }
+
+        let severity = self.shapes.object(shape, &self.vocabulary.severity)
+            .unwrap_or_else(|| self.vocabulary.violation.clone());
+        let messages = self.shapes.objects(shape, &self.vocabulary.message);
+
+        let mut results: Vec<ValidationResult> = failures.into_iter()
+            .map(|(component, value)| ValidationResult {
+                focus_node: focus_node.clone(),
+                result_path: path.clone(),
+                value,
+                source_shape: shape.clone(),
+                source_constraint_component: iri(SH, &format!("{}ConstraintComponent", component)),
+                severity: severity.clone(),
+                messages: messages.clone()
+            })
+            .collect();
+
+        for property_shape in self.shapes.objects(shape, &self.vocabulary.property) {
+            for value in &values {
+                results.extend(self.validate_shape(&property_shape, value)?);
+            }
+        }
+
+        Ok(results)
+    }
+
+    /// Returns the triples that describe the complex paths of the results
+    fn path_quads(&self) -> Vec<RcQuad> {
+        let mut quads = vec![];
+        let mut seen: HashSet<RcTerm> = self.result_paths.iter().cloned().collect();
+        let mut pending = self.result_paths.clone();
+
+        while let Some(node) = pending.pop() {
+            for quad in self.shapes.dataset.quads_with_s(&node) {
+                let mut quad = RcQuad::new_from_quad(&quad.unwrap());
+                quad._graph = None;
+
+                if let Term::BNode(_) = quad.o() {
+                    if seen.insert(quad.o().clone()) {
+                        pending.push(quad.o().clone());
+                    }
+                }
+
+                quads.push(quad);
+            }
+        }
+
+        quads
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::treedataset::TreeDataset;
+    use sophia::quad::stream::QuadSource;
+
+    const PREFIXES: &str = "http://example.org/";
+
+    fn dataset(nquads: &str) -> TreeDataset {
+        let nquads = nquads
+            .replace("ex:", PREFIXES)
+            .replace("sh:", SH)
+            .replace("rdf:", RDF)
+            .replace("rdfs:", RDFS)
+            .replace("xsd:", XSD);
+
+        let mut dataset = TreeDataset::new();
+        sophia::parser::nq::parse_str(&nquads).in_dataset(&mut dataset).unwrap();
+        dataset
+    }
+
+    fn components(report: &ValidationReport) -> Vec<String> {
+        let mut components: Vec<String> = report.results.iter()
+            .map(|result| value_of(&result.source_constraint_component).replace(SH, ""))
+            .collect();
+        components.sort();
+        components
+    }
+
+    #[test]
+    fn checks_cardinality_and_datatype() {
+        let shapes = dataset(r#"
+            <ex:PersonShape> <rdf:type> <sh:NodeShape> .
+            <ex:PersonShape> <sh:targetClass> <ex:Person> .
+            <ex:PersonShape> <sh:property> _:age .
+            _:age <sh:path> <ex:age> .
+            _:age <sh:minCount> "1"^^<xsd:integer> .
+            _:age <sh:maxCount> "1"^^<xsd:integer> .
+            _:age <sh:datatype> <xsd:integer> .
+        "#);
+
+        let data = dataset(r#"
+            <ex:Student> <rdfs:subClassOf> <ex:Person> .
+            <ex:alice> <rdf:type> <ex:Person> .
+            <ex:alice> <ex:age> "32"^^<xsd:integer> .
+            <ex:bob> <rdf:type> <ex:Student> .
+            <ex:bob> <ex:age> "old" .
+            <ex:bob> <ex:age> "12"^^<xsd:integer> .
+            <ex:carol> <rdf:type> <ex:Person> .
+        "#);
+
+        let report = validate(&shapes, &data).unwrap();
+        assert!(!report.conforms());
+        assert_eq!(components(&report), vec!["DatatypeConstraintComponent", "MaxCountConstraintComponent", "MinCountConstraintComponent"]);
+
+        let mut report_dataset = TreeDataset::new();
+        report.write_to(&mut report_dataset);
+        let conforms = RcTerm::new_literal_dt("false", iri(XSD, "boolean")).unwrap();
+        assert_eq!(report_dataset.quads_with_po(&iri(SH, "conforms"), &conforms).count(), 1);
+        assert_eq!(report_dataset.quads_with_p(&iri(SH, "result")).count(), 3);
+    }
+
+    #[test]
+    fn checks_values_along_paths() {
+        let shapes = dataset(r#"
+            <ex:BookShape> <sh:targetSubjectsOf> <ex:isbn> .
+            <ex:BookShape> <sh:property> _:isbn .
+            _:isbn <sh:path> <ex:isbn> .
+            _:isbn <sh:pattern> "^[0-9-]+$" .
+            <ex:BookShape> <sh:property> _:author .
+            _:author <sh:path> _:inverse .
+            _:inverse <sh:inversePath> <ex:wrote> .
+            _:author <sh:class> <ex:Person> .
+            _:author <sh:node> <ex:NamedShape> .
+            <ex:NamedShape> <sh:property> _:name .
+            _:name <sh:path> <ex:name> .
+            _:name <sh:minCount> "1"^^<xsd:integer> .
+            <ex:BookShape> <sh:property> _:format .
+            _:format <sh:path> <ex:format> .
+            _:format <sh:in> _:l1 .
+            _:l1 <rdf:first> "paper" .
+            _:l1 <rdf:rest> _:l2 .
+            _:l2 <rdf:first> "ebook" .
+            _:l2 <rdf:rest> <rdf:nil> .
+        "#);
+
+        let data = dataset(r#"
+            <ex:book1> <ex:isbn> "978-3-16" .
+            <ex:book1> <ex:format> "paper" .
+            <ex:alice> <ex:wrote> <ex:book1> .
+            <ex:alice> <rdf:type> <ex:Person> .
+            <ex:alice> <ex:name> "Alice" .
+            <ex:book2> <ex:isbn> "unknown" .
+            <ex:book2> <ex:format> "scroll" .
+            <ex:robot> <ex:wrote> <ex:book2> .
+        "#);
+
+        let report = validate(&shapes, &data).unwrap();
+        assert_eq!(components(&report), vec!["ClassConstraintComponent", "InConstraintComponent", "NodeConstraintComponent", "PatternConstraintComponent"]);
+        assert!(report.results.iter().all(|result| result.focus_node == iri(PREFIXES, "book2")));
+
+        let mut report_dataset = TreeDataset::new();
+        report.write_to(&mut report_dataset);
+        assert_eq!(report_dataset.quads_with_p(&iri(SH, "inversePath")).count(), 1);
+    }
+
+    #[test]
+    fn rejects_invalid_shapes() {
+        let shapes = dataset(r#"
+            <ex:Shape> <sh:targetNode> <ex:alice> .
+            <ex:Shape> <sh:property> _:p .
+            _:p <sh:path> <ex:name> .
+            _:p <sh:minCount> "many" .
+        "#);
+
+        assert!(validate(&shapes, &TreeDataset::new()).is_err());
+    }
+}
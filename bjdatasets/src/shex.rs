@@ -0,0 +1,1647 @@
+//! Validation of a dataset against a ShEx 2 schema.
+//!
+//! A schema is read from its compact syntax (ShExC) with `Schema::parse`, and
+//! the nodes to check are given by a shape map read with `ShapeMap::parse`.
+//! `Schema::validate` checks every node of the shape map against its shape
+//! and returns one `ShexResult` per node. The graph names of the data are
+//! ignored.
+//!
+//! The supported features are:
+//! - the `PREFIX` and `BASE` directives and the `start` shape
+//! - the shape expressions `AND`, `OR`, `NOT`, `.` and the shape references
+//! - the node constraints: node kinds, datatypes, value sets (with IRI,
+//!   literal and language stems), string facets and numeric facets
+//! - the shapes with `EXTRA` and `CLOSED`, and their triple expressions:
+//!   each-of (`;`), one-of (`|`), inverse triple constraints (`^`) and
+//!   cardinalities
+//! - the fixed shape map entries `node@shape`, and the query entries
+//!   `{FOCUS predicate _}@shape` and `{_ predicate FOCUS}@shape`
+//!
+//! Imports, includes (`&label`), annotations, semantic actions and value set
+//! exclusions are not supported. Recursive shapes are considered satisfied
+//! when a node is checked again against a shape it is already being checked
+//! against.
+
+use crate::literal::{ is_well_formed, value_of };
+use crate::serializer::write_term;
+
+use regex::Regex;
+use sophia::dataset::Dataset;
+use sophia::quad::Quad;
+use sophia::term::RcTerm;
+use sophia::term::Term;
+
+use std::collections::HashMap;
+use std::collections::HashSet;
+
+const RDF_TYPE: &str = "http://www.w3.org/1999/02/22-rdf-syntax-ns#type";
+const RDF_LANG_STRING: &str = "http://www.w3.org/1999/02/22-rdf-syntax-ns#langString";
+const XSD: &str = "http://www.w3.org/2001/XMLSchema#";
+const XSD_BOOLEAN: &str = "http://www.w3.org/2001/XMLSchema#boolean";
+const XSD_DECIMAL: &str = "http://www.w3.org/2001/XMLSchema#decimal";
+const XSD_DOUBLE: &str = "http://www.w3.org/2001/XMLSchema#double";
+const XSD_INTEGER: &str = "http://www.w3.org/2001/XMLSchema#integer";
+const XSD_STRING: &str = "http://www.w3.org/2001/XMLSchema#string";
+
+/// The local names of the numeric XML Schema datatypes
+const NUMERIC_DATATYPES: [&str; 16] = [
+    "integer", "decimal", "double", "float",
+    "int", "long", "short", "byte",
+    "nonNegativeInteger", "nonPositiveInteger", "negativeInteger", "positiveInteger",
+    "unsignedInt", "unsignedLong", "unsignedShort", "unsignedByte"
+];
+
+/// An error in the text of a schema or of a shape map
+#[derive(Debug, Clone, PartialEq)]
+pub struct ShexError {
+    /// The read document, `schema` or `shape map`
+    pub document: &'static str,
+    /// The line of the error, starting at 1
+    pub line: usize,
+    /// The description of the error
+    pub message: String
+}
+
+impl std::fmt::Display for ShexError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Invalid ShEx {} at line {}: {}", self.document, self.line, self.message)
+    }
+}
+
+impl std::error::Error for ShexError {}
+
+/// The outcome of the validation of a node against a shape
+#[derive(Debug, Clone, PartialEq)]
+pub struct ShexResult {
+    /// The validated node
+    pub node: RcTerm,
+    /// The label of the shape, or `START` for the start shape
+    pub shape: String,
+    /// True if the node satisfies the shape
+    pub conforms: bool,
+    /// Why the node does not satisfy the shape
+    pub reason: Option<String>
+}
+
+/// A ShEx schema
+pub struct Schema {
+    /// The shape expression of `start`, if any
+    start: Option<ShapeExpr>,
+    /// The shape expressions by label. The labels are IRIs, or blank node
+    /// labels starting with `_:`.
+    shapes: HashMap<String, ShapeExpr>,
+    /// The prefixes of the schema, also used to read the shape maps
+    prefixes: HashMap<String, String>,
+    /// The base IRI of the schema, also used to read the shape maps
+    base: Option<String>
+}
+
+impl Schema {
+    /// Reads a schema written in ShExC
+    pub fn parse(text: &str) -> Result<Schema, ShexError> {
+        Parser::new("schema", text, HashMap::new(), None).schema()
+    }
+
+    /// Checks the nodes of `shape_map` against their shapes
+    pub fn validate<D>(&self, data: &D, shape_map: &ShapeMap) -> Vec<ShexResult>
+        where D: Dataset {
+        let mut validation = Validation {
+            schema: self,
+            data,
+            assumptions: HashSet::new(),
+            typing: HashMap::new()
+        };
+
+        let mut results = vec![];
+
+        for (selector, label) in &shape_map.entries {
+            let nodes = match selector {
+                NodeSelector::Node(node) => vec![node.clone()],
+                NodeSelector::SubjectsOf(predicate) => {
+                    distinct(data.quads_with_p(predicate).map(|quad| quad.unwrap().s().into()))
+                },
+                NodeSelector::ObjectsOf(predicate) => {
+                    distinct(data.quads_with_p(predicate).map(|quad| quad.unwrap().o().into()))
+                }
+            };
+
+            for node in nodes {
+                let outcome = match (label, &self.start) {
+                    (Some(label), _) => validation.check(&node, label),
+                    (None, Some(start)) => validation.satisfies(&node, start),
+                    (None, None) => Err("the schema has no start shape".to_string())
+                };
+
+                results.push(ShexResult {
+                    node,
+                    shape: label.clone().unwrap_or_else(|| "START".to_string()),
+                    conforms: outcome.is_ok(),
+                    reason: outcome.err()
+                });
+            }
+        }
+
+        results
+    }
+}
+
+/// The nodes of a shape map entry
+enum NodeSelector {
+    /// A given node
+    Node(RcTerm),
+    /// The subjects of the predicate, written `{FOCUS predicate _}`
+    SubjectsOf(RcTerm),
+    /// The objects of the predicate, written `{_ predicate FOCUS}`
+    ObjectsOf(RcTerm)
+}
+
+/// The nodes to validate, and the shapes to validate them against
+pub struct ShapeMap {
+    /// The nodes and the labels of their shape. `None` is the start shape.
+    entries: Vec<(NodeSelector, Option<String>)>
+}
+
+impl ShapeMap {
+    /// Reads a comma separated list of `node@shape` entries. The prefixes and
+    /// the base IRI of `schema` can be used, and the shapes must be defined
+    /// by `schema`.
+    pub fn parse(text: &str, schema: &Schema) -> Result<ShapeMap, ShexError> {
+        let mut parser = Parser::new("shape map", text, schema.prefixes.clone(), schema.base.clone());
+        let mut entries = vec![];
+
+        while !parser.at_end() {
+            if !entries.is_empty() {
+                parser.expect(",")?;
+            }
+
+            let selector = parser.node_selector()?;
+            parser.expect("@")?;
+
+            let label = if parser.eat_keyword("START") {
+                if schema.start.is_none() {
+                    return Err(parser.error("The schema has no start shape"));
+                }
+
+                None
+            } else {
+                let label = parser.label()?.ok_or_else(|| parser.error("Expected a shape label"))?;
+
+                if !schema.shapes.contains_key(&label) {
+                    return Err(parser.error(&format!("Undefined shape {}", label)));
+                }
+
+                Some(label)
+            };
+
+            entries.push((selector, label));
+        }
+
+        Ok(ShapeMap { entries })
+    }
+}
+
+/// Validates the nodes of `shape_map` in `data` against the shapes of
+/// `schema`, both written in their compact syntax
+pub fn validate<D>(data: &D, schema: &str, shape_map: &str) -> Result<Vec<ShexResult>, ShexError>
+    where D: Dataset {
+    let schema = Schema::parse(schema)?;
+    let shape_map = ShapeMap::parse(shape_map, &schema)?;
+    Ok(schema.validate(data, &shape_map))
+}
+
+/// Returns the terms without their duplicates, in their first order
+fn distinct<I>(terms: I) -> Vec<RcTerm>
+    where I: Iterator<Item = RcTerm> {
+    let mut seen = HashSet::new();
+    terms.filter(|term| seen.insert(term.clone())).collect()
+}
+
+/// A shape expression
+enum ShapeExpr {
+    /// Satisfied if one of the expressions is satisfied
+    Or(Vec<ShapeExpr>),
+    /// Satisfied if all the expressions are satisfied
+    And(Vec<ShapeExpr>),
+    /// Satisfied if the expression is not satisfied
+    Not(Box<ShapeExpr>),
+    /// The shape expression of a label
+    Reference(String),
+    NodeConstraint(NodeConstraint),
+    Shape(Shape),
+    /// `.`, satisfied by every node
+    Any
+}
+
+/// The kinds of nodes of a node constraint
+enum NodeKind {
+    Iri,
+    BNode,
+    Literal,
+    NonLiteral
+}
+
+/// A constraint on a node itself
+#[derive(Default)]
+struct NodeConstraint {
+    kind: Option<NodeKind>,
+    /// The IRI of the datatype of the literals
+    datatype: Option<String>,
+    values: Option<Vec<ValueSetValue>>,
+    facets: Vec<Facet>
+}
+
+/// A member of a value set
+enum ValueSetValue {
+    Value(RcTerm),
+    /// The IRIs that start with the stem, written `<stem>~`
+    IriStem(String),
+    /// The literals whose lexical form starts with the stem, written
+    /// `"stem"~`
+    LiteralStem(String),
+    /// The literals of a language, written `@en`
+    Language(String),
+    /// The literals of a language or of its sub-languages, written `@en~`.
+    /// An empty stem matches every language-tagged literal.
+    LanguageStem(String)
+}
+
+/// A string or numeric facet
+enum Facet {
+    Length(usize),
+    MinLength(usize),
+    MaxLength(usize),
+    Pattern(Regex),
+    MinInclusive(f64),
+    MinExclusive(f64),
+    MaxInclusive(f64),
+    MaxExclusive(f64),
+    TotalDigits(usize),
+    FractionDigits(usize)
+}
+
+/// A shape, whose triple expression constrains the triples of the node
+struct Shape {
+    /// Only the predicates of the triple constraints can be used on the node
+    closed: bool,
+    /// The predicates whose triples may not match the triple constraints
+    extra: Vec<RcTerm>,
+    expression: Option<TripleExpr>,
+    /// The triple constraints of the expression, which refers to them by
+    /// their index
+    constraints: Vec<TripleConstraint>
+}
+
+/// A triple expression and its cardinality
+struct TripleExpr {
+    kind: TripleExprKind,
+    min: usize,
+    /// The maximum number of repetitions, `None` if unbounded
+    max: Option<usize>
+}
+
+enum TripleExprKind {
+    /// Matched by the triples that match every expression
+    EachOf(Vec<TripleExpr>),
+    /// Matched by the triples that match one of the expressions
+    OneOf(Vec<TripleExpr>),
+    /// The index of a triple constraint of the shape, matched by one triple
+    Constraint(usize)
+}
+
+/// A constraint on the triples of a predicate
+struct TripleConstraint {
+    predicate: RcTerm,
+    /// The node is the object of the triples instead of their subject
+    inverse: bool,
+    /// The expression satisfied by the other end of the triples
+    value: ShapeExpr
+}
+
+impl TripleExpr {
+    /// Returns the sum of the finite bounds of the cardinalities, which caps
+    /// the number of repetitions that match no triple
+    fn bounds(&self) -> usize {
+        let own = self.min + self.max.unwrap_or(0);
+
+        own + match &self.kind {
+            TripleExprKind::EachOf(operands) | TripleExprKind::OneOf(operands) => {
+                operands.iter().map(TripleExpr::bounds).sum()
+            },
+            TripleExprKind::Constraint(_) => 0
+        }
+    }
+
+    /// Returns, for every `k` up to `cap`, whether the triples counted by
+    /// `counts` for the constraints of the expression can be split into `k`
+    /// groups that each match the expression
+    fn repetitions(&self, counts: &[usize], cap: usize) -> Vec<bool> {
+        let body = self.body_repetitions(counts, cap);
+
+        (0..=cap)
+            .map(|k| {
+                let low = k.saturating_mul(self.min);
+                let high = self.max.map_or(cap, |max| k.saturating_mul(max).min(cap));
+                (low..=high).any(|j| body[j])
+            })
+            .collect()
+    }
+
+    /// Same as `repetitions`, ignoring the cardinality of the expression
+    fn body_repetitions(&self, counts: &[usize], cap: usize) -> Vec<bool> {
+        match &self.kind {
+            TripleExprKind::Constraint(index) => (0..=cap).map(|j| counts[*index] == j).collect(),
+            TripleExprKind::EachOf(operands) => {
+                let operands: Vec<Vec<bool>> = operands.iter()
+                    .map(|operand| operand.repetitions(counts, cap))
+                    .collect();
+
+                (0..=cap).map(|j| operands.iter().all(|operand| operand[j])).collect()
+            },
+            TripleExprKind::OneOf(alternatives) => {
+                // Every repetition picks one alternative, so the numbers of
+                // repetitions of the alternatives add up
+                let mut reachable = vec![false; cap + 1];
+                reachable[0] = true;
+
+                for alternative in alternatives {
+                    let repetitions = alternative.repetitions(counts, cap);
+                    let mut next = vec![false; cap + 1];
+
+                    for sum in (0..=cap).filter(|sum| reachable[*sum]) {
+                        for j in (0..=cap - sum).filter(|j| repetitions[*j]) {
+                            next[sum + j] = true;
+                        }
+                    }
+
+                    reachable = next;
+                }
+
+                reachable
+            }
+        }
+    }
+}
+
+/// Returns true if the triples that have several candidate constraints can
+/// be assigned to one of them so that the counts of the constraints match
+/// `expression`. A `None` candidate leaves the triple unassigned.
+///
+/// The triples are grouped by set of candidates, and only the number of
+/// triples of a group that go to each candidate is enumerated, as the
+/// triples of a group are interchangeable.
+fn assign(expression: &TripleExpr, counts: &mut [usize], groups: &[(Vec<Option<usize>>, usize)], cap: usize) -> bool {
+    match groups.split_first() {
+        None => expression.repetitions(counts, cap)[1],
+        Some(((candidates, size), rest)) => distribute(expression, counts, candidates, *size, rest, cap)
+    }
+}
+
+/// Returns true if `remaining` triples can be shared between `candidates` so
+/// that the triples of the other `groups` can then be assigned
+fn distribute(expression: &TripleExpr, counts: &mut [usize], candidates: &[Option<usize>], remaining: usize,
+    groups: &[(Vec<Option<usize>>, usize)], cap: usize) -> bool {
+    match candidates.split_first() {
+        None => remaining == 0 && assign(expression, counts, groups, cap),
+        Some((candidate, others)) => (0..=remaining).any(|taken| {
+            if let Some(index) = candidate {
+                counts[*index] += taken;
+            }
+
+            let found = distribute(expression, counts, others, remaining - taken, groups, cap);
+
+            if let Some(index) = candidate {
+                counts[*index] -= taken;
+            }
+
+            found
+        })
+    }
+}
+
+impl NodeKind {
+    fn contains(&self, node: &RcTerm) -> bool {
+        match (self, node) {
+            (NodeKind::Iri, Term::Iri(_)) => true,
+            (NodeKind::BNode, Term::BNode(_)) => true,
+            (NodeKind::Literal, Term::Literal(_)) => true,
+            (NodeKind::NonLiteral, Term::Literal(_)) => false,
+            (NodeKind::NonLiteral, _) => true,
+            _ => false
+        }
+    }
+
+    fn name(&self) -> &'static str {
+        match self {
+            NodeKind::Iri => "an IRI",
+            NodeKind::BNode => "a blank node",
+            NodeKind::Literal => "a literal",
+            NodeKind::NonLiteral => "a non-literal"
+        }
+    }
+}
+
+impl NodeConstraint {
+    fn is_empty(&self) -> bool {
+        self.kind.is_none() && self.datatype.is_none() && self.values.is_none() && self.facets.is_empty()
+    }
+
+    fn check(&self, node: &RcTerm) -> Result<(), String> {
+        if let Some(kind) = &self.kind {
+            if !kind.contains(node) {
+                return Err(format!("{} is not {}", write_term(node, None), kind.name()));
+            }
+        }
+
+        if let Some(datatype) = &self.datatype {
+            if !has_datatype(node, datatype) {
+                return Err(format!("{} is not a valid literal of datatype <{}>", write_term(node, None), datatype));
+            }
+        }
+
+        if let Some(values) = &self.values {
+            if !values.iter().any(|value| value.contains(node)) {
+                return Err(format!("{} is not in the value set", write_term(node, None)));
+            }
+        }
+
+        match self.facets.iter().find(|facet| !facet.check(node)) {
+            Some(facet) => Err(format!("{} does not satisfy {}", write_term(node, None), facet)),
+            None => Ok(())
+        }
+    }
+}
+
+/// Returns true if `node` is a well-formed literal of `datatype`
+fn has_datatype(node: &RcTerm, datatype: &str) -> bool {
+    let actual = match node {
+        Term::Literal(literal) if literal.lang().is_some() => RDF_LANG_STRING.to_string(),
+        Term::Literal(literal) => value_of(&literal.dt()),
+        _ => return false
+    };
+
+    actual == datatype && is_well_formed(&actual, &value_of(node))
+}
+
+/// Returns the local name of the XML Schema datatype of a numeric literal
+fn numeric_datatype(node: &RcTerm) -> Option<String> {
+    match node {
+        Term::Literal(literal) if literal.lang().is_none() => {
+            let datatype = value_of(&literal.dt());
+            let name = datatype.strip_prefix(XSD)?;
+            NUMERIC_DATATYPES.iter().find(|numeric| **numeric == name).map(|name| name.to_string())
+        },
+        _ => None
+    }
+}
+
+impl ValueSetValue {
+    fn contains(&self, node: &RcTerm) -> bool {
+        match (self, node) {
+            (ValueSetValue::Value(value), _) => value == node,
+            (ValueSetValue::IriStem(stem), Term::Iri(_)) => value_of(node).starts_with(stem.as_str()),
+            (ValueSetValue::LiteralStem(stem), Term::Literal(_)) => value_of(node).starts_with(stem.as_str()),
+            (ValueSetValue::Language(tag), Term::Literal(literal)) => match literal.lang() {
+                Some(language) => {
+                    let language: &str = language.as_ref();
+                    language.eq_ignore_ascii_case(tag)
+                },
+                None => false
+            },
+            (ValueSetValue::LanguageStem(stem), Term::Literal(literal)) => match literal.lang() {
+                Some(language) => {
+                    let language: &str = language.as_ref();
+                    let language = language.to_ascii_lowercase();
+                    let stem = stem.to_ascii_lowercase();
+                    stem.is_empty() || language == stem || language.starts_with(&format!("{}-", stem))
+                },
+                None => false
+            },
+            _ => false
+        }
+    }
+}
+
+impl Facet {
+    fn check(&self, node: &RcTerm) -> bool {
+        match self {
+            Facet::Length(length) => lexical_form(node).map_or(false, |lexical| lexical.chars().count() == *length),
+            Facet::MinLength(length) => lexical_form(node).map_or(false, |lexical| lexical.chars().count() >= *length),
+            Facet::MaxLength(length) => lexical_form(node).map_or(false, |lexical| lexical.chars().count() <= *length),
+            Facet::Pattern(regex) => lexical_form(node).map_or(false, |lexical| regex.is_match(&lexical)),
+            Facet::MinInclusive(bound) => numeric_value(node).map_or(false, |value| value >= *bound),
+            Facet::MinExclusive(bound) => numeric_value(node).map_or(false, |value| value > *bound),
+            Facet::MaxInclusive(bound) => numeric_value(node).map_or(false, |value| value <= *bound),
+            Facet::MaxExclusive(bound) => numeric_value(node).map_or(false, |value| value < *bound),
+            Facet::TotalDigits(digits) => decimal_digits(node).map_or(false, |(total, _)| total <= *digits),
+            Facet::FractionDigits(digits) => decimal_digits(node).map_or(false, |(_, fraction)| fraction <= *digits)
+        }
+    }
+}
+
+/// Returns the string checked by the string facets: the IRI of an IRI or the
+/// lexical form of a literal
+fn lexical_form(node: &RcTerm) -> Option<String> {
+    match node {
+        Term::Iri(_) | Term::Literal(_) => Some(value_of(node)),
+        _ => None
+    }
+}
+
+fn numeric_value(node: &RcTerm) -> Option<f64> {
+    numeric_datatype(node).and_then(|_| value_of(node).parse().ok())
+}
+
+/// Returns the number of significant digits and of fraction digits of a
+/// literal whose datatype is derived from `xsd:decimal`
+fn decimal_digits(node: &RcTerm) -> Option<(usize, usize)> {
+    let datatype = numeric_datatype(node)?;
+
+    if datatype == "double" || datatype == "float" {
+        return None;
+    }
+
+    let lexical = value_of(node);
+    let unsigned = lexical.trim_start_matches(['+', '-']);
+    let mut parts = unsigned.splitn(2, '.');
+    let integer_digits = parts.next().unwrap_or("").trim_start_matches('0').len();
+    let fraction_digits = parts.next().unwrap_or("").trim_end_matches('0').len();
+
+    Some((integer_digits + fraction_digits, fraction_digits))
+}
+
+impl std::fmt::Display for Facet {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Facet::Length(length) => write!(f, "LENGTH {}", length),
+            Facet::MinLength(length) => write!(f, "MINLENGTH {}", length),
+            Facet::MaxLength(length) => write!(f, "MAXLENGTH {}", length),
+            Facet::Pattern(regex) => write!(f, "/{}/", regex.as_str()),
+            Facet::MinInclusive(bound) => write!(f, "MININCLUSIVE {}", bound),
+            Facet::MinExclusive(bound) => write!(f, "MINEXCLUSIVE {}", bound),
+            Facet::MaxInclusive(bound) => write!(f, "MAXINCLUSIVE {}", bound),
+            Facet::MaxExclusive(bound) => write!(f, "MAXEXCLUSIVE {}", bound),
+            Facet::TotalDigits(digits) => write!(f, "TOTALDIGITS {}", digits),
+            Facet::FractionDigits(digits) => write!(f, "FRACTIONDIGITS {}", digits)
+        }
+    }
+}
+
+/// The state of the validation of the nodes of a shape map
+struct Validation<'a, D> {
+    schema: &'a Schema,
+    data: &'a D,
+    /// The node and shape pairs that are being checked, which are assumed to
+    /// be satisfied
+    assumptions: HashSet<(RcTerm, String)>,
+    /// The outcomes of the checks that did not depend on an assumption
+    typing: HashMap<(RcTerm, String), Result<(), String>>
+}
+
+impl<'a, D> Validation<'a, D>
+    where D: Dataset {
+    /// Checks `node` against the shape expression of `label`
+    fn check(&mut self, node: &RcTerm, label: &str) -> Result<(), String> {
+        let key = (node.clone(), label.to_string());
+
+        if let Some(outcome) = self.typing.get(&key) {
+            return outcome.clone();
+        }
+
+        if self.assumptions.contains(&key) {
+            return Ok(());
+        }
+
+        let schema = self.schema;
+        let expression = match schema.shapes.get(label) {
+            Some(expression) => expression,
+            None => return Err(format!("undefined shape {}", label))
+        };
+
+        self.assumptions.insert(key.clone());
+        let outcome = self.satisfies(node, expression);
+        self.assumptions.remove(&key);
+
+        if self.assumptions.is_empty() {
+            self.typing.insert(key, outcome.clone());
+        }
+
+        outcome
+    }
+
+    fn satisfies(&mut self, node: &RcTerm, expression: &'a ShapeExpr) -> Result<(), String> {
+        match expression {
+            ShapeExpr::Any => Ok(()),
+            ShapeExpr::Or(operands) => {
+                let mut reasons = vec![];
+
+                for operand in operands {
+                    match self.satisfies(node, operand) {
+                        Ok(()) => return Ok(()),
+                        Err(reason) => reasons.push(reason)
+                    }
+                }
+
+                Err(format!("no alternative is satisfied ({})", reasons.join("; ")))
+            },
+            ShapeExpr::And(operands) => operands.iter().try_for_each(|operand| self.satisfies(node, operand)),
+            ShapeExpr::Not(operand) => match self.satisfies(node, operand) {
+                Ok(()) => Err(format!("{} satisfies a negated shape expression", write_term(node, None))),
+                Err(_) => Ok(())
+            },
+            ShapeExpr::Reference(label) => self.check(node, label)
+                .map_err(|reason| format!("{} does not match {}: {}", write_term(node, None), label, reason)),
+            ShapeExpr::NodeConstraint(constraint) => constraint.check(node),
+            ShapeExpr::Shape(shape) => self.match_shape(node, shape)
+        }
+    }
+
+    /// Checks the triples of `node` against the triple expression of `shape`
+    fn match_shape(&mut self, node: &RcTerm, shape: &'a Shape) -> Result<(), String> {
+        let constraints = &shape.constraints;
+
+        // The predicates, the other ends and the directions of the triples
+        let mut neighbourhood: Vec<(RcTerm, RcTerm, bool)> = vec![];
+        let mut seen = HashSet::new();
+
+        for quad in self.data.quads_with_s(node) {
+            let quad = quad.unwrap();
+            neighbourhood.push((quad.p().into(), quad.o().into(), false));
+        }
+
+        let inverse_predicates = distinct(constraints.iter()
+            .filter(|constraint| constraint.inverse)
+            .map(|constraint| constraint.predicate.clone()));
+
+        for predicate in &inverse_predicates {
+            for quad in self.data.quads_with_po(predicate, node) {
+                neighbourhood.push((predicate.clone(), quad.unwrap().s().into(), true));
+            }
+        }
+
+        neighbourhood.retain(|triple| seen.insert(triple.clone()));
+
+        let mut counts = vec![0; constraints.len()];
+        // The sets of candidate constraints and their number of triples
+        let mut groups: Vec<(Vec<Option<usize>>, usize)> = vec![];
+
+        for (predicate, value, inverse) in &neighbourhood {
+            let candidates: Vec<usize> = (0..constraints.len())
+                .filter(|index| constraints[*index].predicate == *predicate && constraints[*index].inverse == *inverse)
+                .collect();
+
+            if candidates.is_empty() {
+                if shape.closed && !inverse {
+                    return Err(format!("{} has the property {} that the closed shape does not allow",
+                        write_term(node, None), write_term(predicate, None)));
+                }
+
+                continue;
+            }
+
+            let mut matching = vec![];
+            let mut reasons = vec![];
+
+            for index in candidates {
+                match self.satisfies(value, &constraints[index].value) {
+                    Ok(()) => matching.push(Some(index)),
+                    Err(reason) => reasons.push(reason)
+                }
+            }
+
+            if !inverse && shape.extra.contains(predicate) {
+                matching.push(None);
+            }
+
+            if matching.is_empty() {
+                return Err(format!("{} {} of {} does not match: {}",
+                    if *inverse { "the subject" } else { "the value" },
+                    write_term(value, None), write_term(predicate, None), reasons.join("; ")));
+            }
+
+            if matching.len() > 1 {
+                match groups.iter_mut().find(|(candidates, _)| *candidates == matching) {
+                    Some((_, size)) => *size += 1,
+                    None => groups.push((matching, 1))
+                }
+            } else if let Some(index) = matching[0] {
+                counts[index] += 1;
+            }
+        }
+
+        let expression = match &shape.expression {
+            Some(expression) => expression,
+            None => return Ok(())
+        };
+
+        let cap = neighbourhood.len() + expression.bounds() + 1;
+
+        if assign(expression, &mut counts, &groups, cap) {
+            Ok(())
+        } else {
+            Err(format!("the triples of {} do not match the cardinalities of the shape", write_term(node, None)))
+        }
+    }
+}
+
+/// Returns true if `c` can be used in a prefix or a local name
+fn is_name_char(c: char) -> bool {
+    c.is_alphanumeric() || c == '_' || c == '-'
+}
+
+/// A reader of the compact syntax of schemas and shape maps
+struct Parser<'a> {
+    document: &'static str,
+    text: &'a str,
+    /// The byte offset of the next character to read
+    position: usize,
+    prefixes: HashMap<String, String>,
+    base: Option<String>,
+    /// The referenced shape labels and the lines of the references
+    references: Vec<(String, usize)>
+}
+
+impl<'a> Parser<'a> {
+    fn new(document: &'static str, text: &'a str, prefixes: HashMap<String, String>, base: Option<String>) -> Self {
+        Self { document, text, position: 0, prefixes, base, references: vec![] }
+    }
+
+    fn rest(&self) -> &'a str {
+        &self.text[self.position..]
+    }
+
+    fn line(&self) -> usize {
+        self.text[..self.position].matches('\n').count() + 1
+    }
+
+    fn error(&self, message: &str) -> ShexError {
+        ShexError { document: self.document, line: self.line(), message: message.to_string() }
+    }
+
+    /// Skips the white spaces and the comments
+    fn skip_space(&mut self) {
+        loop {
+            let rest = self.rest();
+            let trimmed = rest.trim_start();
+            self.position += rest.len() - trimmed.len();
+
+            if !trimmed.starts_with('#') {
+                break;
+            }
+
+            self.position += trimmed.find('\n').unwrap_or(trimmed.len());
+        }
+    }
+
+    fn at_end(&mut self) -> bool {
+        self.skip_space();
+        self.rest().is_empty()
+    }
+
+    fn peek(&mut self) -> Option<char> {
+        self.skip_space();
+        self.rest().chars().next()
+    }
+
+    /// Reads `token` if it is next
+    fn eat(&mut self, token: &str) -> bool {
+        self.skip_space();
+
+        if self.rest().starts_with(token) {
+            self.position += token.len();
+            true
+        } else {
+            false
+        }
+    }
+
+    fn expect(&mut self, token: &str) -> Result<(), ShexError> {
+        if self.eat(token) {
+            Ok(())
+        } else {
+            Err(self.error(&format!("Expected {}", token)))
+        }
+    }
+
+    /// Reads `keyword`, whatever its case, if it is next
+    fn eat_keyword(&mut self, keyword: &str) -> bool {
+        self.skip_space();
+        let rest = self.rest();
+
+        let found = match rest.get(..keyword.len()) {
+            Some(start) => start.eq_ignore_ascii_case(keyword)
+                && !rest[keyword.len()..].starts_with(|c: char| is_name_char(c) || c == ':'),
+            None => false
+        };
+
+        if found {
+            self.position += keyword.len();
+        }
+
+        found
+    }
+
+    fn at_keyword(&mut self, keyword: &str) -> bool {
+        let position = self.position;
+        let found = self.eat_keyword(keyword);
+        self.position = position;
+        found
+    }
+
+    /// Returns true if the next `{` starts a repetition count like `{2,3}`
+    /// rather than a shape
+    fn at_repeat_count(&mut self) -> bool {
+        self.skip_space();
+        let rest = self.rest();
+        rest.starts_with('{') && rest[1..].trim_start().starts_with(|c: char| c.is_ascii_digit())
+    }
+
+    fn schema(mut self) -> Result<Schema, ShexError> {
+        let mut start = None;
+        let mut shapes = HashMap::new();
+
+        while !self.at_end() {
+            if self.eat_keyword("PREFIX") {
+                self.skip_space();
+                let rest = self.rest();
+                let length = rest.find(|c: char| !(is_name_char(c) || c == '.')).unwrap_or(rest.len());
+
+                if !rest[length..].starts_with(':') {
+                    return Err(self.error("Expected a prefix"));
+                }
+
+                let prefix = rest[..length].to_string();
+                self.position += length + 1;
+                let namespace = self.iri_reference()?;
+                self.prefixes.insert(prefix, namespace);
+            } else if self.eat_keyword("BASE") {
+                self.base = Some(self.iri_reference()?);
+            } else if self.eat_keyword("IMPORT") {
+                return Err(self.error("Imports are not supported"));
+            } else if self.eat_keyword("start") {
+                self.expect("=")?;
+                start = Some(self.shape_expression()?);
+            } else {
+                let line = self.line();
+                let label = self.label()?.ok_or_else(|| self.error("Expected a shape label"))?;
+
+                if self.eat_keyword("EXTERNAL") {
+                    return Err(self.error("External shapes are not supported"));
+                }
+
+                let expression = self.shape_expression()?;
+
+                if shapes.insert(label.clone(), expression).is_some() {
+                    return Err(ShexError { document: self.document, line, message: format!("Shape {} is defined twice", label) });
+                }
+            }
+        }
+
+        if let Some((label, line)) = self.references.iter().find(|(label, _)| !shapes.contains_key(label)) {
+            return Err(ShexError { document: self.document, line: *line, message: format!("Undefined shape {}", label) });
+        }
+
+        Ok(Schema { start, shapes, prefixes: self.prefixes, base: self.base })
+    }
+
+    /// Reads an IRI written between `<` and `>`
+    fn iri_reference(&mut self) -> Result<String, ShexError> {
+        self.skip_space();
+        let rest = self.rest();
+
+        if !rest.starts_with('<') {
+            return Err(self.error("Expected an IRI"));
+        }
+
+        let end = rest.find('>').ok_or_else(|| self.error("Unterminated IRI"))?;
+        self.position += end + 1;
+        Ok(self.resolve(&rest[1..end]))
+    }
+
+    /// Resolves a relative IRI against the base IRI, by appending it to the
+    /// base up to its last `/`, or to the base without its fragment for a
+    /// fragment
+    fn resolve(&self, reference: &str) -> String {
+        let has_scheme = reference.find(':')
+            .map_or(false, |colon| !reference[..colon].contains(['/', '?', '#']));
+
+        match &self.base {
+            Some(base) if !has_scheme => {
+                if reference.starts_with('#') || reference.is_empty() {
+                    format!("{}{}", base.split('#').next().unwrap_or(""), reference)
+                } else {
+                    format!("{}{}", &base[..base.rfind('/').map_or(0, |slash| slash + 1)], reference)
+                }
+            },
+            _ => reference.to_string()
+        }
+    }
+
+    /// Reads an IRI or a prefixed name, if there is one
+    fn iri(&mut self) -> Result<Option<String>, ShexError> {
+        self.skip_space();
+        let rest = self.rest();
+
+        if rest.starts_with('<') {
+            return self.iri_reference().map(Some);
+        }
+
+        if rest.starts_with("_:") || rest.starts_with(|c: char| c.is_ascii_digit() || c == '-' || c == '.') {
+            return Ok(None);
+        }
+
+        let prefix_length = rest.find(|c: char| !(is_name_char(c) || c == '.')).unwrap_or(rest.len());
+
+        if !rest[prefix_length..].starts_with(':') {
+            return Ok(None);
+        }
+
+        let prefix = &rest[..prefix_length];
+        let local = &rest[prefix_length + 1..];
+        let mut local_length = local.find(|c: char| !(is_name_char(c) || c == '.' || c == ':' || c == '%'))
+            .unwrap_or(local.len());
+
+        while local[..local_length].ends_with('.') {
+            local_length -= 1;
+        }
+
+        let namespace = self.prefixes.get(prefix)
+            .ok_or_else(|| self.error(&format!("Undefined prefix {}:", prefix)))?
+            .clone();
+
+        self.position += prefix_length + 1 + local_length;
+        Ok(Some(namespace + &local[..local_length]))
+    }
+
+    /// Reads a shape label, which is an IRI, a prefixed name or a blank node
+    /// label, if there is one
+    fn label(&mut self) -> Result<Option<String>, ShexError> {
+        self.skip_space();
+        let rest = self.rest();
+
+        if let Some(name) = rest.strip_prefix("_:") {
+            let length = 2 + name.find(|c: char| !is_name_char(c)).unwrap_or(name.len());
+            self.position += length;
+            return Ok(Some(rest[..length].to_string()));
+        }
+
+        self.iri()
+    }
+
+    fn iri_term(&self, iri: String) -> Result<RcTerm, ShexError> {
+        RcTerm::new_iri(iri).map_err(|error| self.error(&error.to_string()))
+    }
+
+    /// Reads a predicate, which is an IRI or `a`, if there is one
+    fn predicate(&mut self) -> Result<Option<RcTerm>, ShexError> {
+        if self.eat_keyword("a") {
+            return self.iri_term(RDF_TYPE.to_string()).map(Some);
+        }
+
+        match self.iri()? {
+            Some(iri) => self.iri_term(iri).map(Some),
+            None => Ok(None)
+        }
+    }
+
+    fn shape_expression(&mut self) -> Result<ShapeExpr, ShexError> {
+        let mut operands = vec![self.shape_and()?];
+
+        while self.eat_keyword("OR") {
+            operands.push(self.shape_and()?);
+        }
+
+        Ok(if operands.len() == 1 { operands.pop().unwrap() } else { ShapeExpr::Or(operands) })
+    }
+
+    fn shape_and(&mut self) -> Result<ShapeExpr, ShexError> {
+        let mut operands = vec![self.shape_not()?];
+
+        while self.eat_keyword("AND") {
+            operands.push(self.shape_not()?);
+        }
+
+        Ok(if operands.len() == 1 { operands.pop().unwrap() } else { ShapeExpr::And(operands) })
+    }
+
+    fn shape_not(&mut self) -> Result<ShapeExpr, ShexError> {
+        if self.eat_keyword("NOT") {
+            Ok(ShapeExpr::Not(Box::new(self.shape_atom()?)))
+        } else {
+            self.shape_atom()
+        }
+    }
+
+    fn shape_atom(&mut self) -> Result<ShapeExpr, ShexError> {
+        if self.eat("(") {
+            let expression = self.shape_expression()?;
+            self.expect(")")?;
+            return Ok(expression);
+        }
+
+        if self.at_shape_or_reference() {
+            return self.shape_or_reference();
+        }
+
+        if self.eat(".") {
+            return Ok(ShapeExpr::Any);
+        }
+
+        let constraint = ShapeExpr::NodeConstraint(self.node_constraint()?);
+
+        if self.at_shape_or_reference() {
+            Ok(ShapeExpr::And(vec![constraint, self.shape_or_reference()?]))
+        } else {
+            Ok(constraint)
+        }
+    }
+
+    fn at_shape_or_reference(&mut self) -> bool {
+        match self.peek() {
+            Some('@') => true,
+            Some('{') => !self.at_repeat_count(),
+            _ => self.at_keyword("CLOSED") || self.at_keyword("EXTRA")
+        }
+    }
+
+    fn shape_or_reference(&mut self) -> Result<ShapeExpr, ShexError> {
+        if self.eat("@") {
+            let line = self.line();
+            let label = self.label()?.ok_or_else(|| self.error("Expected a shape label"))?;
+            self.references.push((label.clone(), line));
+            return Ok(ShapeExpr::Reference(label));
+        }
+
+        let mut shape = Shape { closed: false, extra: vec![], expression: None, constraints: vec![] };
+
+        loop {
+            if self.eat_keyword("CLOSED") {
+                shape.closed = true;
+            } else if self.eat_keyword("EXTRA") {
+                while let Some(predicate) = self.predicate()? {
+                    shape.extra.push(predicate);
+                }
+            } else {
+                break;
+            }
+        }
+
+        self.expect("{")?;
+
+        if !self.eat("}") {
+            shape.expression = Some(self.one_of(&mut shape.constraints)?);
+            self.expect("}")?;
+        }
+
+        self.reject_annotations()?;
+        Ok(ShapeExpr::Shape(shape))
+    }
+
+    fn reject_annotations(&mut self) -> Result<(), ShexError> {
+        if self.eat("//") {
+            return Err(self.error("Annotations are not supported"));
+        }
+
+        if self.eat("%") {
+            return Err(self.error("Semantic actions are not supported"));
+        }
+
+        Ok(())
+    }
+
+    fn one_of(&mut self, constraints: &mut Vec<TripleConstraint>) -> Result<TripleExpr, ShexError> {
+        let mut alternatives = vec![self.each_of(constraints)?];
+
+        while self.eat("|") {
+            alternatives.push(self.each_of(constraints)?);
+        }
+
+        Ok(if alternatives.len() == 1 {
+            alternatives.pop().unwrap()
+        } else {
+            TripleExpr { kind: TripleExprKind::OneOf(alternatives), min: 1, max: Some(1) }
+        })
+    }
+
+    fn each_of(&mut self, constraints: &mut Vec<TripleConstraint>) -> Result<TripleExpr, ShexError> {
+        let mut operands = vec![self.unary_triple_expression(constraints)?];
+
+        while self.eat(";") {
+            match self.peek() {
+                Some('}') | Some(')') | Some('|') | None => break,
+                _ => operands.push(self.unary_triple_expression(constraints)?)
+            }
+        }
+
+        Ok(if operands.len() == 1 {
+            operands.pop().unwrap()
+        } else {
+            TripleExpr { kind: TripleExprKind::EachOf(operands), min: 1, max: Some(1) }
+        })
+    }
+
+    fn unary_triple_expression(&mut self, constraints: &mut Vec<TripleConstraint>) -> Result<TripleExpr, ShexError> {
+        // The labels of triple expressions are only useful to includes
+        if self.eat("$") {
+            self.label()?.ok_or_else(|| self.error("Expected a triple expression label"))?;
+        }
+
+        if self.eat("&") {
+            return Err(self.error("Includes are not supported"));
+        }
+
+        if self.eat("(") {
+            let expression = self.one_of(constraints)?;
+            self.expect(")")?;
+            let (min, max) = self.cardinality()?;
+            self.reject_annotations()?;
+
+            return Ok(TripleExpr { kind: TripleExprKind::EachOf(vec![expression]), min, max });
+        }
+
+        let inverse = self.eat("^");
+        let predicate = self.predicate()?.ok_or_else(|| self.error("Expected a triple constraint"))?;
+        let value = self.shape_expression()?;
+        let (min, max) = self.cardinality()?;
+        self.reject_annotations()?;
+
+        constraints.push(TripleConstraint { predicate, inverse, value });
+        Ok(TripleExpr { kind: TripleExprKind::Constraint(constraints.len() - 1), min, max })
+    }
+
+    /// Reads a cardinality, which is one if there is none
+    fn cardinality(&mut self) -> Result<(usize, Option<usize>), ShexError> {
+        if self.eat("*") {
+            return Ok((0, None));
+        }
+
+        if self.eat("+") {
+            return Ok((1, None));
+        }
+
+        if self.eat("?") {
+            return Ok((0, Some(1)));
+        }
+
+        if !self.at_repeat_count() {
+            return Ok((1, Some(1)));
+        }
+
+        self.expect("{")?;
+        let min = self.integer()?;
+
+        let max = if !self.eat(",") {
+            Some(min)
+        } else if self.eat("*") || self.peek() == Some('}') {
+            None
+        } else {
+            Some(self.integer()?)
+        };
+
+        self.expect("}")?;
+
+        match max {
+            Some(max) if max < min => Err(self.error("The maximum of a cardinality is lower than its minimum")),
+            _ => Ok((min, max))
+        }
+    }
+
+    fn integer(&mut self) -> Result<usize, ShexError> {
+        self.skip_space();
+        let rest = self.rest();
+        let length = rest.find(|c: char| !c.is_ascii_digit()).unwrap_or(rest.len());
+        let value = rest[..length].parse().map_err(|_| self.error("Expected a non negative integer"))?;
+        self.position += length;
+        Ok(value)
+    }
+
+    /// Reads a number, if there is one. Returns its lexical form and its
+    /// datatype.
+    fn numeric(&mut self) -> Option<(String, &'static str)> {
+        self.skip_space();
+        let bytes = self.rest().as_bytes();
+        let digits = |from: usize| from + bytes[from..].iter().take_while(|byte| byte.is_ascii_digit()).count();
+
+        let sign = match bytes.first() {
+            Some(b'+') | Some(b'-') => 1,
+            _ => 0
+        };
+
+        let mut end = digits(sign);
+        let mut datatype = XSD_INTEGER;
+
+        if bytes.get(end) == Some(&b'.') && bytes.get(end + 1).map_or(false, u8::is_ascii_digit) {
+            end = digits(end + 1);
+            datatype = XSD_DECIMAL;
+        }
+
+        if end == sign {
+            return None;
+        }
+
+        if let Some(b'e') | Some(b'E') = bytes.get(end) {
+            let exponent = match bytes.get(end + 1) {
+                Some(b'+') | Some(b'-') => end + 2,
+                _ => end + 1
+            };
+
+            if bytes.get(exponent).map_or(false, u8::is_ascii_digit) {
+                end = digits(exponent);
+                datatype = XSD_DOUBLE;
+            }
+        }
+
+        let lexical = self.rest()[..end].to_string();
+        self.position += end;
+        Some((lexical, datatype))
+    }
+
+    /// Reads a quoted string, if there is one
+    fn string(&mut self) -> Result<Option<String>, ShexError> {
+        self.skip_space();
+        let rest = self.rest();
+
+        let quote = match rest.chars().next() {
+            Some(quote) if quote == '"' || quote == '\'' => quote,
+            _ => return Ok(None)
+        };
+
+        let mut value = String::new();
+        let mut chars = rest.char_indices().skip(1);
+
+        while let Some((index, c)) = chars.next() {
+            match c {
+                _ if c == quote => {
+                    self.position += index + 1;
+                    return Ok(Some(value));
+                },
+                '\n' => break,
+                '\\' => {
+                    let escaped = match chars.next() {
+                        Some((_, 'n')) => Some('\n'),
+                        Some((_, 't')) => Some('\t'),
+                        Some((_, 'r')) => Some('\r'),
+                        Some((_, 'b')) => Some('\u{8}'),
+                        Some((_, 'f')) => Some('\u{c}'),
+                        Some((_, 'u')) => unicode_escape(&mut chars, 4),
+                        Some((_, 'U')) => unicode_escape(&mut chars, 8),
+                        Some((_, c)) if c == '"' || c == '\'' || c == '\\' => Some(c),
+                        _ => None
+                    };
+
+                    value.push(escaped.ok_or_else(|| self.error("Invalid escape sequence"))?);
+                },
+                _ => value.push(c)
+            }
+        }
+
+        Err(self.error("Unterminated string"))
+    }
+
+    /// Reads a literal, if there is one
+    fn literal(&mut self) -> Result<Option<RcTerm>, ShexError> {
+        if let Some(value) = self.string()? {
+            if let Some(tag) = self.language_tag() {
+                return RcTerm::new_literal_lang(value, tag).map(Some).map_err(|error| self.error(&error.to_string()));
+            }
+
+            let datatype = if self.eat("^^") {
+                self.iri()?.ok_or_else(|| self.error("Expected a datatype"))?
+            } else {
+                XSD_STRING.to_string()
+            };
+
+            let datatype = self.iri_term(datatype)?;
+            return RcTerm::new_literal_dt(value, datatype).map(Some).map_err(|error| self.error(&error.to_string()));
+        }
+
+        let (lexical, datatype) = if self.eat_keyword("true") {
+            ("true".to_string(), XSD_BOOLEAN)
+        } else if self.eat_keyword("false") {
+            ("false".to_string(), XSD_BOOLEAN)
+        } else {
+            match self.numeric() {
+                Some(number) => number,
+                None => return Ok(None)
+            }
+        };
+
+        let datatype = self.iri_term(datatype.to_string())?;
+        RcTerm::new_literal_dt(lexical, datatype).map(Some).map_err(|error| self.error(&error.to_string()))
+    }
+
+    /// Reads the `@tag` that directly follows a string, if there is one. A
+    /// `@` followed by a prefixed name is the shape of a shape map entry.
+    fn language_tag(&mut self) -> Option<String> {
+        let rest = self.rest();
+
+        if !rest.starts_with('@') || !rest[1..].starts_with(|c: char| c.is_ascii_alphabetic()) {
+            return None;
+        }
+
+        let length = rest[1..].find(|c: char| !(c.is_ascii_alphanumeric() || c == '-')).unwrap_or(rest.len() - 1);
+
+        if rest[1 + length..].starts_with(':') {
+            return None;
+        }
+
+        self.position += 1 + length;
+        Some(rest[1..1 + length].to_string())
+    }
+
+    fn node_constraint(&mut self) -> Result<NodeConstraint, ShexError> {
+        let mut constraint = NodeConstraint::default();
+
+        if self.eat_keyword("LITERAL") {
+            constraint.kind = Some(NodeKind::Literal);
+        } else if self.eat_keyword("IRI") {
+            constraint.kind = Some(NodeKind::Iri);
+        } else if self.eat_keyword("BNODE") {
+            constraint.kind = Some(NodeKind::BNode);
+        } else if self.eat_keyword("NONLITERAL") {
+            constraint.kind = Some(NodeKind::NonLiteral);
+        } else if self.eat("[") {
+            constraint.values = Some(self.value_set()?);
+        } else if let Some(datatype) = self.iri()? {
+            constraint.datatype = Some(datatype);
+        }
+
+        while let Some(facet) = self.facet()? {
+            constraint.facets.push(facet);
+        }
+
+        if constraint.is_empty() {
+            return Err(self.error("Expected a shape expression"));
+        }
+
+        Ok(constraint)
+    }
+
+    /// Reads the values of a value set, after its `[`
+    fn value_set(&mut self) -> Result<Vec<ValueSetValue>, ShexError> {
+        let mut values = vec![];
+
+        while !self.eat("]") {
+            if self.eat("@") {
+                let rest = self.rest();
+                let length = rest.find(|c: char| !(c.is_ascii_alphanumeric() || c == '-')).unwrap_or(rest.len());
+                let tag = rest[..length].to_string();
+                self.position += length;
+
+                values.push(if self.eat("~") { ValueSetValue::LanguageStem(tag) } else { ValueSetValue::Language(tag) });
+            } else if self.peek() == Some('.') || self.peek() == Some('-') {
+                return Err(self.error("Value set exclusions are not supported"));
+            } else if let Some(iri) = self.iri()? {
+                values.push(if self.eat("~") { ValueSetValue::IriStem(iri) } else { ValueSetValue::Value(self.iri_term(iri)?) });
+            } else if let Some(literal) = self.literal()? {
+                values.push(if self.eat("~") { ValueSetValue::LiteralStem(value_of(&literal)) } else { ValueSetValue::Value(literal) });
+            } else {
+                return Err(self.error("Expected a value or ]"));
+            }
+        }
+
+        Ok(values)
+    }
+
+    /// Reads a string or numeric facet, if there is one
+    fn facet(&mut self) -> Result<Option<Facet>, ShexError> {
+        let facet = if self.eat_keyword("LENGTH") {
+            Facet::Length(self.integer()?)
+        } else if self.eat_keyword("MINLENGTH") {
+            Facet::MinLength(self.integer()?)
+        } else if self.eat_keyword("MAXLENGTH") {
+            Facet::MaxLength(self.integer()?)
+        } else if self.eat_keyword("TOTALDIGITS") {
+            Facet::TotalDigits(self.integer()?)
+        } else if self.eat_keyword("FRACTIONDIGITS") {
+            Facet::FractionDigits(self.integer()?)
+        } else if self.eat_keyword("MININCLUSIVE") {
+            Facet::MinInclusive(self.bound()?)
+        } else if self.eat_keyword("MINEXCLUSIVE") {
+            Facet::MinExclusive(self.bound()?)
+        } else if self.eat_keyword("MAXINCLUSIVE") {
+            Facet::MaxInclusive(self.bound()?)
+        } else if self.eat_keyword("MAXEXCLUSIVE") {
+            Facet::MaxExclusive(self.bound()?)
+        } else if self.peek() == Some('/') && !self.rest().starts_with("//") {
+            Facet::Pattern(self.pattern()?)
+        } else {
+            return Ok(None);
+        };
+
+        Ok(Some(facet))
+    }
+
+    fn bound(&mut self) -> Result<f64, ShexError> {
+        self.numeric()
+            .and_then(|(lexical, _)| lexical.parse().ok())
+            .ok_or_else(|| self.error("Expected a number"))
+    }
+
+    /// Reads a `/pattern/flags` regular expression
+    fn pattern(&mut self) -> Result<Regex, ShexError> {
+        self.expect("/")?;
+        let rest = self.rest();
+        let mut pattern = String::new();
+        let mut chars = rest.char_indices();
+
+        let end = loop {
+            match chars.next() {
+                Some((index, '/')) => break index,
+                Some((_, '\\')) => match chars.next() {
+                    Some((_, '/')) => pattern.push('/'),
+                    Some((_, c)) => {
+                        pattern.push('\\');
+                        pattern.push(c);
+                    },
+                    None => return Err(self.error("Unterminated regular expression"))
+                },
+                Some((_, '\n')) | None => return Err(self.error("Unterminated regular expression")),
+                Some((_, c)) => pattern.push(c)
+            }
+        };
+
+        let flags_length = rest[end + 1..].find(|c: char| !c.is_ascii_alphabetic()).unwrap_or(rest.len() - end - 1);
+        let flags = &rest[end + 1..end + 1 + flags_length];
+        self.position += end + 1 + flags_length;
+
+        if let Some(flag) = flags.chars().find(|flag| !"imsx".contains(*flag)) {
+            return Err(self.error(&format!("Unsupported regular expression flag {}", flag)));
+        }
+
+        let source = if flags.is_empty() { pattern } else { format!("(?{}){}", flags, pattern) };
+        Regex::new(&source).map_err(|error| self.error(&error.to_string()))
+    }
+
+    /// Reads the node selector of a shape map entry
+    fn node_selector(&mut self) -> Result<NodeSelector, ShexError> {
+        if self.eat("{") {
+            let selector = if self.eat_keyword("FOCUS") {
+                let predicate = self.predicate()?.ok_or_else(|| self.error("Expected a predicate"))?;
+                self.expect("_")?;
+                NodeSelector::SubjectsOf(predicate)
+            } else {
+                self.expect("_")?;
+                let predicate = self.predicate()?.ok_or_else(|| self.error("Expected a predicate"))?;
+
+                if !self.eat_keyword("FOCUS") {
+                    return Err(self.error("Expected FOCUS"));
+                }
+
+                NodeSelector::ObjectsOf(predicate)
+            };
+
+            self.expect("}")?;
+            return Ok(selector);
+        }
+
+        if let Some(label) = self.label()? {
+            let node = if let Some(bnode) = label.strip_prefix("_:") {
+                RcTerm::new_bnode(bnode).map_err(|error| self.error(&error.to_string()))?
+            } else {
+                self.iri_term(label)?
+            };
+
+            return Ok(NodeSelector::Node(node));
+        }
+
+        match self.literal()? {
+            Some(literal) => Ok(NodeSelector::Node(literal)),
+            None => Err(self.error("Expected a node"))
+        }
+    }
+}
+
+/// Reads the `length` hexadecimal digits of a `\u` or `\U` escape sequence
+fn unicode_escape<I>(chars: &mut I, length: usize) -> Option<char>
+    where I: Iterator<Item = (usize, char)> {
+    let digits: String = chars.take(length).map(|(_, c)| c).collect();
+
+    if digits.len() != length {
+        return None;
+    }
+
+    u32::from_str_radix(&digits, 16).ok().and_then(std::char::from_u32)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::treedataset::TreeDataset;
+    use sophia::quad::stream::QuadSource;
+
+    const EX: &str = "http://example.org/";
+
+    fn dataset(nquads: &str) -> TreeDataset {
+        let mut dataset = TreeDataset::new();
+        sophia::parser::nq::parse_str(&nquads.replace("ex:", EX)).in_dataset(&mut dataset).unwrap();
+        dataset
+    }
+
+    /// Returns the local names of the nodes that conform, sorted
+    fn conforming(results: &[ShexResult]) -> Vec<String> {
+        let mut nodes: Vec<String> = results.iter()
+            .filter(|result| result.conforms)
+            .map(|result| value_of(&result.node).replace(EX, ""))
+            .collect();
+        nodes.sort();
+        nodes
+    }
+
+    const SCHEMA: &str = r#"
+        PREFIX ex: <http://example.org/>
+        PREFIX xsd: <http://www.w3.org/2001/XMLSchema#>
+
+        ex:Person {
+          ex:name xsd:string MINLENGTH 2 ;
+          ex:age xsd:integer MININCLUSIVE 0 ? ;
+          ( ex:email IRI | ex:phone /^[0-9 ]+$/ ) + ;
+          ex:knows @ex:Person *
+        }
+    "#;
+
+    #[test]
+    fn checks_triple_expressions() {
+        let data = dataset(r#"
+            <ex:alice> <ex:name> "Alice" .
+            <ex:alice> <ex:age> "32"^^<http://www.w3.org/2001/XMLSchema#integer> .
+            <ex:alice> <ex:email> <mailto:alice@example.org> .
+            <ex:alice> <ex:phone> "555 1234" .
+            <ex:alice> <ex:knows> <ex:bob> .
+            <ex:bob> <ex:name> "Bob" .
+            <ex:bob> <ex:phone> "555 4321" .
+            <ex:bob> <ex:knows> <ex:alice> .
+            <ex:carol> <ex:name> "C" .
+            <ex:carol> <ex:phone> "555" .
+            <ex:dave> <ex:name> "Dave" .
+            <ex:dave> <ex:name> "David" .
+            <ex:dave> <ex:email> <mailto:dave@example.org> .
+            <ex:erin> <ex:name> "Erin" .
+            <ex:erin> <ex:phone> "555" .
+            <ex:erin> <ex:knows> <ex:carol> .
+        "#);
+
+        let results = validate(&data, SCHEMA, "ex:alice@ex:Person, ex:bob@ex:Person, ex:carol@ex:Person, ex:dave@ex:Person, ex:erin@ex:Person").unwrap();
+        assert_eq!(results.len(), 5);
+        assert_eq!(conforming(&results), vec!["alice", "bob"]);
+        assert!(results.iter().all(|result| result.conforms != result.reason.is_some()));
+    }
+
+    #[test]
+    fn checks_closed_shapes_and_query_maps() {
+        let schema = r#"
+            PREFIX ex: <http://example.org/>
+            start = @ex:Issue
+            ex:Issue CLOSED EXTRA a {
+              a [ex:Bug ex:Feature] ;
+              ^ex:reported @ex:User {1,2}
+            }
+            ex:User IRI AND { ex:mbox [<mailto:>~] }
+        "#;
+
+        let data = dataset(r#"
+            <ex:issue1> <http://www.w3.org/1999/02/22-rdf-syntax-ns#type> <ex:Bug> .
+            <ex:issue1> <http://www.w3.org/1999/02/22-rdf-syntax-ns#type> <ex:Item> .
+            <ex:alice> <ex:reported> <ex:issue1> .
+            <ex:alice> <ex:mbox> <mailto:alice@example.org> .
+            <ex:issue2> <http://www.w3.org/1999/02/22-rdf-syntax-ns#type> <ex:Feature> .
+            <ex:issue2> <ex:priority> "high" .
+            <ex:alice> <ex:reported> <ex:issue2> .
+            <ex:issue3> <http://www.w3.org/1999/02/22-rdf-syntax-ns#type> <ex:Bug> .
+            _:anonymous <ex:reported> <ex:issue3> .
+            _:anonymous <ex:mbox> <mailto:anonymous@example.org> .
+        "#);
+
+        let results = validate(&data, schema, "{_ ex:reported FOCUS}@START").unwrap();
+        assert_eq!(results.len(), 3);
+        assert!(results.iter().all(|result| result.shape == "START"));
+        assert_eq!(conforming(&results), vec!["issue1"]);
+    }
+
+    #[test]
+    fn assigns_the_triples_of_overlapping_constraints() {
+        let schema = r#"
+            PREFIX ex: <http://example.org/>
+            PREFIX xsd: <http://www.w3.org/2001/XMLSchema#>
+            ex:S { ex:p xsd:string {2} ; ex:p . }
+        "#;
+
+        let mut nquads = String::from("<ex:a> <ex:p> \"1\" .\n<ex:a> <ex:p> \"2\" .\n<ex:a> <ex:p> \"3\" .\n");
+        for value in 0..40 {
+            nquads.push_str(&format!("<ex:b> <ex:p> \"{}\" .\n", value));
+        }
+
+        let results = validate(&dataset(&nquads), schema, "ex:a@ex:S, ex:b@ex:S").unwrap();
+        assert_eq!(conforming(&results), vec!["a"]);
+    }
+
+    #[test]
+    fn rejects_invalid_schemas() {
+        assert_eq!(Schema::parse("PREFIX ex: <http://example.org/>\n\nex:S { ex:p @ex:T }").err().unwrap().line, 3);
+        assert_eq!(Schema::parse("<http://example.org/S> {\n  ex:p .\n}").err().unwrap().line, 2);
+        assert!(Schema::parse("<http://example.org/S> { <http://example.org/p> . {3,1} }").is_err());
+
+        let schema = Schema::parse("<http://example.org/S> {}").unwrap();
+        let error = ShapeMap::parse("<http://example.org/n>@<http://example.org/T>", &schema).err().unwrap();
+        assert_eq!(error.document, "shape map");
+    }
+}
@@ -0,0 +1,252 @@
+//! Skolemization: replacement of the blank nodes of a dataset with IRIs.
+//!
+//! The skolem IRI of the blank node `_:b0` for the base IRI
+//! `http://example.org/` is `http://example.org/.well-known/genid/b0`, as
+//! advised by RDF 1.1 Concepts. De-skolemization turns these IRIs back into
+//! blank nodes.
+
+use crate::RcQuad;
+use crate::literal::value_of;
+use crate::fulldataset::FullIndexDataset;
+use crate::rdfs::RdfsDataset;
+use crate::treedataset::TreeDataset;
+use crate::vecordataset::VecOrDataset;
+
+use sophia::dataset::inmem::FastDataset;
+use sophia::dataset::inmem::LightDataset;
+use sophia::dataset::Dataset;
+use sophia::dataset::MutableDataset;
+use sophia::quad::Quad;
+use sophia::term::BoxTerm;
+use sophia::term::RcTerm;
+use sophia::term::Term;
+use sophia::term::TermError;
+
+use std::collections::HashMap;
+use std::convert::Infallible;
+
+/// The path under which the skolem IRIs are minted
+pub const GENID_PATH: &str = "/.well-known/genid/";
+
+/// Returns the prefix of the skolem IRIs built from `base_iri`
+pub fn genid_prefix(base_iri: &str) -> String {
+    format!("{}{}", base_iri.trim_end_matches('/'), GENID_PATH)
+}
+
+/// Returns the skolem IRI of `term` if it is a blank node
+fn skolem_iri(term: &RcTerm, prefix: &str) -> Result<Option<RcTerm>, TermError> {
+    match term {
+        Term::BNode(_) => RcTerm::new_iri(format!("{}{}", prefix, value_of(term))).map(Some),
+        _ => Ok(None)
+    }
+}
+
+/// Returns the blank node of `term` if it is a skolem IRI
+fn deskolemized_bnode(term: &RcTerm, prefix: &str) -> Result<Option<RcTerm>, TermError> {
+    match term {
+        Term::Iri(_) => match value_of(term).strip_prefix(prefix) {
+            Some(label) => RcTerm::new_bnode(label.to_string()).map(Some),
+            None => Ok(None)
+        },
+        _ => Ok(None)
+    }
+}
+
+/// Datasets whose blank nodes can be replaced with skolem IRIs, and back.
+///
+/// The renaming is computed before the dataset is modified, so a `TermError`
+/// (for example an invalid base IRI) leaves the dataset unchanged.
+pub trait Skolemize {
+    /// Renames every term for which `rename` returns a new term. Returns the
+    /// number of renamed terms.
+    fn rename_terms<F>(&mut self, rename: F) -> Result<usize, TermError>
+        where F: FnMut(&RcTerm) -> Result<Option<RcTerm>, TermError>;
+
+    /// Replaces every blank node with an IRI under
+    /// `{base_iri}/.well-known/genid/`. Returns the number of replaced blank
+    /// nodes.
+    fn skolemize(&mut self, base_iri: &str) -> Result<usize, TermError> {
+        let prefix = genid_prefix(base_iri);
+        self.rename_terms(|term| skolem_iri(term, &prefix))
+    }
+
+    /// Replaces every IRI under `{base_iri}/.well-known/genid/` with a blank
+    /// node. Returns the number of replaced IRIs.
+    fn deskolemize(&mut self, base_iri: &str) -> Result<usize, TermError> {
+        let prefix = genid_prefix(base_iri);
+        self.rename_terms(|term| deskolemized_bnode(term, &prefix))
+    }
+}
+
+/// The new term of every term of a dataset, `None` if it is not renamed
+pub type Renaming = HashMap<RcTerm, Option<RcTerm>>;
+
+/// Calls `rename` once on every term of a dataset. Returns the renaming and
+/// the quads that contain a renamed term.
+pub fn find_renamed_quads<D, F>(dataset: &D, mut rename: F) -> Result<(Renaming, Vec<RcQuad>), TermError>
+    where D: Dataset,
+        F: FnMut(&RcTerm) -> Result<Option<RcTerm>, TermError> {
+    let mut renaming = Renaming::new();
+    let mut renamed_quads = vec![];
+
+    for quad in dataset.quads() {
+        let quad = RcQuad::new_from_quad(&quad.unwrap());
+        let mut is_renamed = false;
+
+        for term in [quad.s(), quad.p(), quad.o()].iter().copied().chain(quad.g()) {
+            if !renaming.contains_key(term) {
+                let new_term = rename(term)?;
+                renaming.insert(term.clone(), new_term);
+            }
+
+            is_renamed |= renaming[term].is_some();
+        }
+
+        if is_renamed {
+            renamed_quads.push(quad);
+        }
+    }
+
+    Ok((renaming, renamed_quads))
+}
+
+/// Returns `quad` with the new terms of `renaming`
+pub fn renamed_quad(renaming: &Renaming, quad: &RcQuad) -> RcQuad {
+    let new_term = |term: &RcTerm| renaming[term].clone().unwrap_or_else(|| term.clone());
+
+    RcQuad::new(
+        &new_term(quad.s()),
+        &new_term(quad.p()),
+        &new_term(quad.o()),
+        quad.g().map(new_term).as_ref()
+    )
+}
+
+/// Renames the terms of a dataset by removing every quad that contains a
+/// renamed term and inserting it back with the new terms
+pub fn rename_by_reinsertion<D, F>(dataset: &mut D, rename: F) -> Result<usize, TermError>
+    where D: MutableDataset<MutationError = Infallible>,
+        F: FnMut(&RcTerm) -> Result<Option<RcTerm>, TermError> {
+    let (renaming, renamed_quads) = find_renamed_quads(dataset, rename)?;
+
+    for quad in renamed_quads {
+        let new_quad = renamed_quad(&renaming, &quad);
+        dataset.remove(quad.s(), quad.p(), quad.o(), quad.g()).unwrap();
+        dataset.insert(new_quad.s(), new_quad.p(), new_quad.o(), new_quad.g()).unwrap();
+    }
+
+    Ok(renaming.values().filter(|new_term| new_term.is_some()).count())
+}
+
+impl Skolemize for TreeDataset {
+    fn rename_terms<F>(&mut self, rename: F) -> Result<usize, TermError>
+        where F: FnMut(&RcTerm) -> Result<Option<RcTerm>, TermError> {
+        self.rename_terms_in_place(rename)
+    }
+}
+
+impl Skolemize for RdfsDataset {
+    /// Renames the terms of the closure, then the same terms in the asserted
+    /// quads, so the inferred quads are not asserted by the renaming
+    fn rename_terms<F>(&mut self, mut rename: F) -> Result<usize, TermError>
+        where F: FnMut(&RcTerm) -> Result<Option<RcTerm>, TermError> {
+        let mut renaming = HashMap::new();
+
+        let number_of_renamed_terms = self.closure_mut().rename_terms_in_place(|term| {
+            let new_term = rename(term)?;
+
+            if let Some(new_term) = &new_term {
+                renaming.insert(term.clone(), new_term.clone());
+            }
+
+            Ok(new_term)
+        })?;
+
+        self.asserted_mut()
+            .rename_terms_in_place(|term| Ok::<_, TermError>(renaming.get(term).cloned()))?;
+
+        Ok(number_of_renamed_terms)
+    }
+}
+
+macro_rules! impl_skolemize_by_reinsertion {
+    ($($dataset: ty),*) => {
+        $(
+            impl Skolemize for $dataset {
+                fn rename_terms<F>(&mut self, rename: F) -> Result<usize, TermError>
+                    where F: FnMut(&RcTerm) -> Result<Option<RcTerm>, TermError> {
+                    rename_by_reinsertion(self, rename)
+                }
+            }
+        )*
+    };
+}
+
+impl_skolemize_by_reinsertion!(
+    FullIndexDataset,
+    FastDataset,
+    LightDataset,
+    Vec<([BoxTerm; 3], Option<BoxTerm>)>
+);
+
+impl<D> Skolemize for VecOrDataset<D>
+    where D: Dataset + MutableDataset<MutationError = Infallible> + Default {
+    fn rename_terms<F>(&mut self, rename: F) -> Result<usize, TermError>
+        where F: FnMut(&RcTerm) -> Result<Option<RcTerm>, TermError> {
+        rename_by_reinsertion(self, rename)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn iri(value: &str) -> RcTerm {
+        RcTerm::new_iri(value).unwrap()
+    }
+
+    fn bnode(label: &str) -> RcTerm {
+        RcTerm::new_bnode(label).unwrap()
+    }
+
+    fn roundtrip<D>(mut dataset: D)
+        where D: Skolemize + MutableDataset {
+        let p = iri("http://example.org/p");
+        let skolem = iri("http://example.org/.well-known/genid/b0");
+
+        dataset.insert(&bnode("b0"), &p, &iri("http://example.org/o"), None as Option<&RcTerm>).unwrap();
+        dataset.insert(&iri("http://example.org/s"), &p, &bnode("b0"), Some(&bnode("b0"))).unwrap();
+
+        assert_eq!(dataset.skolemize("http://example.org/").unwrap(), 1);
+        assert!(dataset.contains(&skolem, &p, &iri("http://example.org/o"), None as Option<&RcTerm>).unwrap());
+        assert!(dataset.contains(&iri("http://example.org/s"), &p, &skolem, Some(&skolem)).unwrap());
+        assert_eq!(dataset.quads().count(), 2);
+
+        assert_eq!(dataset.deskolemize("http://example.org").unwrap(), 1);
+        assert!(dataset.contains(&bnode("b0"), &p, &iri("http://example.org/o"), None as Option<&RcTerm>).unwrap());
+        assert!(dataset.contains(&iri("http://example.org/s"), &p, &bnode("b0"), Some(&bnode("b0"))).unwrap());
+        assert_eq!(dataset.quads().count(), 2);
+    }
+
+    #[test]
+    fn skolemizes_and_deskolemizes() {
+        roundtrip(TreeDataset::new());
+        roundtrip(FullIndexDataset::new());
+        roundtrip(FastDataset::default());
+    }
+
+    #[test]
+    fn merges_a_blank_node_with_its_existing_skolem_iri() {
+        let p = iri("http://example.org/p");
+        let skolem = iri("http://example.org/.well-known/genid/b0");
+        let mut dataset = TreeDataset::new();
+
+        dataset.insert(&bnode("b0"), &p, &iri("http://example.org/o"), None as Option<&RcTerm>).unwrap();
+        dataset.insert(&skolem, &p, &iri("http://example.org/o"), None as Option<&RcTerm>).unwrap();
+        dataset.insert(&skolem, &p, &bnode("b0"), None as Option<&RcTerm>).unwrap();
+
+        assert_eq!(dataset.skolemize("http://example.org/").unwrap(), 1);
+        assert_eq!(dataset.quads().count(), 2);
+        assert!(dataset.contains(&skolem, &p, &skolem, None as Option<&RcTerm>).unwrap());
+    }
+}
@@ -0,0 +1,429 @@
+//! A term dictionary that matches `RcTerm`s with `u32` identifiers.
+//!
+//! It behaves like the `TermIndexMapU` of Sophia, but the term of an
+//! identifier can be replaced in place. Datasets that store their quads as
+//! identifiers can then rename a term without touching their indexes.
+//!
+//! The dictionary can also group identifiers into classes of equivalent
+//! terms, so a dataset can store its quads under the representative of each
+//! class. The classes are a union-find whose members always point directly
+//! to their representative: when two classes are merged, the members of the
+//! smaller one are moved to the bigger one. Classes can not be split, only
+//! dissolved so the dataset merges their members again, and the members of a
+//! class keep a reference on their identifier so they are never released
+//! while they are in it.
+//!
+//! A `LiteralIndex` keeps the identifiers of the numeric and temporal
+//! literals sorted by value, so they can be searched by range, and a
+//! `TextIndex` keeps the words of the string literals, so they can be
+//! searched by text. Like the optional trees of an `IndexingForest4`, they
+//! are built by the first search and maintained afterwards.
+
+use crate::literalindex::LiteralIndex;
+use crate::literalindex::LiteralKey;
+use crate::textindex::TextIndex;
+
+use once_cell::unsync::OnceCell;
+use sophia::term::factory::RcTermFactory;
+use sophia::term::index_map::TermIndexMap;
+use sophia::term::RcTerm;
+use sophia::term::RefTerm;
+use sophia::term::Term;
+use sophia::term::TermData;
+
+use std::borrow::Borrow;
+use std::collections::HashMap;
+use std::hash::{ Hash, Hasher };
+use std::ops::Bound;
+
+/// A term that the identifiers can be looked up with, whatever its data
+trait LookupKey {
+    /// Returns the term with borrowed data
+    fn term(&self) -> RefTerm;
+}
+
+impl<T: TermData> LookupKey for Term<T> {
+    fn term(&self) -> RefTerm {
+        self.into()
+    }
+}
+
+impl Hash for dyn LookupKey + '_ {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.term().hash(state);
+    }
+}
+
+impl PartialEq for dyn LookupKey + '_ {
+    fn eq(&self, other: &Self) -> bool {
+        self.term() == other.term()
+    }
+}
+
+impl Eq for dyn LookupKey + '_ {}
+
+/// A term stored as a key of the dictionary. It is hashed and compared as a
+/// `LookupKey`, so it can be found from a `RefTerm` without building an
+/// `RcTerm`.
+struct TermKey(RcTerm);
+
+impl LookupKey for TermKey {
+    fn term(&self) -> RefTerm {
+        self.0.term()
+    }
+}
+
+impl<'a> Borrow<dyn LookupKey + 'a> for TermKey {
+    fn borrow(&self) -> &(dyn LookupKey + 'a) {
+        self
+    }
+}
+
+impl Hash for TermKey {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        (self as &dyn LookupKey).hash(state);
+    }
+}
+
+impl PartialEq for TermKey {
+    fn eq(&self, other: &Self) -> bool {
+        self.term() == other.term()
+    }
+}
+
+impl Eq for TermKey {}
+
+/// A `TermIndexMap` with `u32` identifiers whose terms can be replaced.
+///
+/// The identifier 0 is the `NULL_INDEX`, used for the default graph.
+pub struct TermDictionary {
+    /// The term and the number of references of every identifier
+    i2t: Vec<Option<(RcTerm, u32)>>,
+    /// The identifiers that are no longer used
+    free_ids: Vec<u32>,
+    /// The identifier of every term
+    t2i: HashMap<TermKey, u32>,
+    /// The representative of every identifier that is in a class but is not
+    /// its representative
+    representatives: HashMap<u32, u32>,
+    /// The members of every class with more than one identifier, by
+    /// representative
+    classes: HashMap<u32, Vec<u32>>,
+    /// The identifiers of the numeric and temporal literals, once built
+    literal_index: OnceCell<LiteralIndex>,
+    /// The identifiers of the string literals by word, once built
+    text_index: OnceCell<TextIndex>
+}
+
+impl Default for TermDictionary {
+    fn default() -> Self {
+        Self {
+            i2t: vec![None],
+            free_ids: vec![],
+            t2i: HashMap::new(),
+            representatives: HashMap::new(),
+            classes: HashMap::new(),
+            literal_index: OnceCell::new(),
+            text_index: OnceCell::new()
+        }
+    }
+}
+
+impl TermDictionary {
+    /// Builds an empty dictionary
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Replaces the term of the identifier `index` with `term`.
+    ///
+    /// Returns false, and changes nothing, if `index` is not used or if
+    /// `term` already has another identifier.
+    pub fn replace_term(&mut self, index: u32, term: RcTerm) -> bool {
+        if let Some(&other) = self.t2i.get(&term as &dyn LookupKey) {
+            return other == index;
+        }
+
+        let entry = match self.i2t.get_mut(index as usize) {
+            Some(Some(entry)) => entry,
+            _ => return false
+        };
+
+        if let Some(literal_index) = self.literal_index.get_mut() {
+            literal_index.remove(index, &entry.0);
+            literal_index.insert(index, &term);
+        }
+
+        if let Some(text_index) = self.text_index.get_mut() {
+            text_index.remove(index, &entry.0);
+            text_index.insert(index, &term);
+        }
+
+        self.t2i.remove(&entry.0 as &dyn LookupKey);
+        self.t2i.insert(TermKey(term.clone()), index);
+        entry.0 = term;
+        true
+    }
+
+    /// Builds the `LiteralIndex` of the terms of the dictionary now instead
+    /// of at the first range search. Does nothing if it is already built.
+    pub fn enable_literal_index(&mut self) {
+        self.literal_index();
+    }
+
+    /// Returns the identifiers of the numeric or temporal literals between
+    /// `min` and `max`, in increasing order of value.
+    ///
+    /// The first search builds the literal index by reading every term.
+    pub fn literals_in_range(&self, min: Bound<LiteralKey>, max: Bound<LiteralKey>) -> Vec<u32> {
+        self.literal_index().range(min, max)
+    }
+
+    /// Returns the `LiteralIndex`, built from the current terms if needed
+    fn literal_index(&self) -> &LiteralIndex {
+        self.literal_index.get_or_init(|| self.build_literal_index())
+    }
+
+    /// Builds a `LiteralIndex` of the current terms
+    fn build_literal_index(&self) -> LiteralIndex {
+        let mut literal_index = LiteralIndex::new();
+
+        for (index, term) in self.iter() {
+            literal_index.insert(index, term);
+        }
+
+        literal_index
+    }
+
+    /// Builds the `TextIndex` of the terms of the dictionary now instead of
+    /// at the first text search. Does nothing if it is already built.
+    pub fn enable_text_index(&mut self) {
+        self.text_index();
+    }
+
+    /// Returns the identifiers of the string literals whose words start with
+    /// the words of `text`, best matches first (see `TextIndex::search`).
+    ///
+    /// The first search builds the text index by reading every term.
+    pub fn search_literals(&self, text: &str, lang: Option<&str>) -> Vec<u32> {
+        self.text_index().search(text, lang)
+    }
+
+    /// Returns the `TextIndex`, built from the current terms if needed
+    fn text_index(&self) -> &TextIndex {
+        self.text_index.get_or_init(|| self.build_text_index())
+    }
+
+    /// Builds a `TextIndex` of the current terms
+    fn build_text_index(&self) -> TextIndex {
+        let mut text_index = TextIndex::new();
+
+        for (index, term) in self.iter() {
+            text_index.insert(index, term);
+        }
+
+        text_index
+    }
+
+    /// Returns the representative of the class of `index`, which is `index`
+    /// itself if it is not equivalent to another identifier
+    pub fn representative(&self, index: u32) -> u32 {
+        self.representatives.get(&index).copied().unwrap_or(index)
+    }
+
+    /// Returns every identifier of the class of `index`
+    pub fn equivalents(&self, index: u32) -> Vec<u32> {
+        match self.classes.get(&self.representative(index)) {
+            Some(members) => members.clone(),
+            None => vec![index]
+        }
+    }
+
+    /// Returns the representative of the class of the term `t`, if the term
+    /// is in the dictionary
+    pub fn get_canonical_index(&self, t: &RefTerm) -> Option<u32> {
+        self.get_index(t).map(|index| self.representative(index))
+    }
+
+    /// Moves a reference on `index` to the representative of its class, and
+    /// returns the representative
+    pub fn canonicalize(&mut self, index: u32) -> u32 {
+        let representative = self.representative(index);
+
+        if representative != index {
+            self.inc_ref(representative);
+            self.dec_ref(index);
+        }
+
+        representative
+    }
+
+    /// Merges the classes of `a` and `b`.
+    ///
+    /// Returns the former representative of the absorbed class and the
+    /// representative of the merged class, or `None` if `a` and `b` were
+    /// already equivalent.
+    pub fn merge(&mut self, a: u32, b: u32) -> Option<(u32, u32)> {
+        let a = self.representative(a);
+        let b = self.representative(b);
+
+        if a == b {
+            return None;
+        }
+
+        let class_size = |classes: &HashMap<u32, Vec<u32>>, index| classes.get(&index).map_or(1, Vec::len);
+        let (absorbed, representative) = if class_size(&self.classes, a) >= class_size(&self.classes, b) {
+            (b, a)
+        } else {
+            (a, b)
+        };
+
+        if !self.classes.contains_key(&representative) {
+            self.inc_ref(representative);
+            self.classes.insert(representative, vec![representative]);
+        }
+
+        let absorbed_members = match self.classes.remove(&absorbed) {
+            Some(members) => members,
+            None => {
+                self.inc_ref(absorbed);
+                vec![absorbed]
+            }
+        };
+
+        for member in &absorbed_members {
+            self.representatives.insert(*member, representative);
+        }
+
+        self.classes.get_mut(&representative).unwrap().extend(absorbed_members);
+        Some((absorbed, representative))
+    }
+
+    /// Dissolves the class of `index`, so each of its members is only
+    /// equivalent to itself, and returns the members.
+    ///
+    /// The references that the class held on its members are released, so the
+    /// caller must hold its own references on the members it still uses.
+    pub fn dissolve(&mut self, index: u32) -> Vec<u32> {
+        let members = match self.classes.remove(&self.representative(index)) {
+            Some(members) => members,
+            None => return vec![index]
+        };
+
+        for member in &members {
+            self.representatives.remove(member);
+        }
+
+        for member in &members {
+            self.dec_ref(*member);
+        }
+
+        members
+    }
+
+    /// Returns an iterator on every used identifier and its term
+    pub fn iter(&self) -> impl Iterator<Item = (u32, &RcTerm)> {
+        self.i2t.iter()
+            .enumerate()
+            .filter_map(|(index, entry)| entry.as_ref().map(|(term, _)| (index as u32, term)))
+    }
+}
+
+impl TermIndexMap for TermDictionary {
+    type Index = u32;
+    type Factory = RcTermFactory;
+    const NULL_INDEX: u32 = 0;
+
+    fn get_index(&self, t: &RefTerm) -> Option<u32> {
+        self.t2i.get(t as &dyn LookupKey).copied()
+    }
+
+    fn make_index(&mut self, t: &RefTerm) -> u32 {
+        if let Some(&index) = self.t2i.get(t as &dyn LookupKey) {
+            self.inc_ref(index);
+            return index;
+        }
+
+        // The term is only copied when it is new
+        let term = RcTerm::from(t);
+
+        let index = match self.free_ids.pop() {
+            Some(index) => {
+                self.i2t[index as usize] = Some((term.clone(), 1));
+                index
+            },
+            None => {
+                self.i2t.push(Some((term.clone(), 1)));
+                (self.i2t.len() - 1) as u32
+            }
+        };
+
+        if let Some(literal_index) = self.literal_index.get_mut() {
+            literal_index.insert(index, &term);
+        }
+
+        if let Some(text_index) = self.text_index.get_mut() {
+            text_index.insert(index, &term);
+        }
+
+        self.t2i.insert(TermKey(term), index);
+        index
+    }
+
+    fn get_term(&self, i: u32) -> Option<&RcTerm> {
+        match self.i2t.get(i as usize) {
+            Some(Some((term, _))) => Some(term),
+            _ => None
+        }
+    }
+
+    fn inc_ref(&mut self, i: u32) {
+        if let Some(Some((_, count))) = self.i2t.get_mut(i as usize) {
+            *count += 1;
+        }
+    }
+
+    fn dec_ref(&mut self, i: u32) {
+        if i == Self::NULL_INDEX {
+            return;
+        }
+
+        let released = match self.i2t.get_mut(i as usize) {
+            Some(Some((_, count))) => {
+                *count -= 1;
+                *count == 0
+            },
+            _ => false
+        };
+
+        if released {
+            let (term, _) = self.i2t[i as usize].take().unwrap();
+            self.t2i.remove(&term as &dyn LookupKey);
+            self.free_ids.push(i);
+
+            if let Some(literal_index) = self.literal_index.get_mut() {
+                literal_index.remove(i, &term);
+            }
+
+            if let Some(text_index) = self.text_index.get_mut() {
+                text_index.remove(i, &term);
+            }
+        }
+    }
+
+    fn shrink_to_fit(&mut self) {
+        while let Some(None) = self.i2t.last() {
+            if self.i2t.len() == 1 {
+                break;
+            }
+
+            self.i2t.pop();
+        }
+
+        let len = self.i2t.len() as u32;
+        self.free_ids.retain(|&index| index < len);
+        self.i2t.shrink_to_fit();
+        self.free_ids.shrink_to_fit();
+        self.t2i.shrink_to_fit();
+    }
+}
@@ -0,0 +1,201 @@
+//! An inverted index of the words of the string literals of a term
+//! dictionary.
+//!
+//! The `TextIndex` splits the value of every `xsd:string` and language-tagged
+//! literal into words, which are the runs of alphanumeric characters, and
+//! lowercases them. The lowercasing follows the language of the literal:
+//! Turkish and Azerbaijani distinguish the dotted and the dotless i.
+//!
+//! A search splits its text the same way and returns the literals that have,
+//! for every word of the text, a word that starts with it. So `"par"` finds
+//! `"Paris"` and `"Parc des Princes"`, and `"new yo"` finds `"New York"`.
+//! A search in a language range only returns the literals whose language tag
+//! is in this range.
+
+use crate::literal::value_of;
+
+use sophia::term::RcTerm;
+use sophia::term::Term;
+
+use std::collections::BTreeMap;
+use std::collections::BTreeSet;
+use std::collections::HashMap;
+
+const XSD_STRING: &str = "http://www.w3.org/2001/XMLSchema#string";
+
+/// Returns the lowercased words of `text`, written in the language `lang`
+pub fn tokenize(text: &str, lang: Option<&str>) -> Vec<String> {
+    let is_turkic = lang.map_or(false, |lang| language_matches(lang, "tr") || language_matches(lang, "az"));
+
+    text.split(|c: char| !c.is_alphanumeric())
+        .filter(|word| !word.is_empty())
+        .map(|word| {
+            if is_turkic {
+                word.replace('I', "ı").replace('İ', "i").to_lowercase()
+            } else {
+                // The default lowercasing of İ adds a combining dot
+                word.replace('İ', "i").to_lowercase()
+            }
+        })
+        .collect()
+}
+
+/// Returns true if the language tag `tag` is in the language range `range`,
+/// which is either the tag itself or one of its prefixes, like `en` for
+/// `en-GB`. The comparison ignores case.
+pub fn language_matches(tag: &str, range: &str) -> bool {
+    let tag = tag.to_ascii_lowercase();
+    let range = range.to_ascii_lowercase();
+
+    tag == range || (tag.starts_with(&range) && tag[range.len()..].starts_with('-'))
+}
+
+/// Returns the value and the language of `term`, if it is a string literal
+fn text_of(term: &RcTerm) -> Option<(String, Option<String>)> {
+    match term {
+        Term::Literal(literal) => match literal.lang() {
+            Some(lang) => Some((value_of(term), Some(lang.to_string()))),
+            None if value_of(&literal.dt()) == XSD_STRING => Some((value_of(term), None)),
+            None => None
+        },
+        _ => None
+    }
+}
+
+/// The identifiers of the string literals, by word
+#[derive(Default)]
+pub struct TextIndex {
+    postings: BTreeMap<String, BTreeSet<u32>>,
+    /// The language tag of every indexed language-tagged literal
+    languages: HashMap<u32, String>
+}
+
+impl TextIndex {
+    /// Builds an empty index
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Indexes the words of `term` under its identifier `index`. Does nothing
+    /// if `term` is not a string literal.
+    pub fn insert(&mut self, index: u32, term: &RcTerm) {
+        if let Some((value, lang)) = text_of(term) {
+            for word in tokenize(&value, lang.as_deref()) {
+                self.postings.entry(word).or_default().insert(index);
+            }
+
+            if let Some(lang) = lang {
+                self.languages.insert(index, lang);
+            }
+        }
+    }
+
+    /// Removes the words of `term` from the index
+    pub fn remove(&mut self, index: u32, term: &RcTerm) {
+        if let Some((value, lang)) = text_of(term) {
+            for word in tokenize(&value, lang.as_deref()) {
+                if let Some(indexes) = self.postings.get_mut(&word) {
+                    indexes.remove(&index);
+
+                    if indexes.is_empty() {
+                        self.postings.remove(&word);
+                    }
+                }
+            }
+
+            self.languages.remove(&index);
+        }
+    }
+
+    /// Returns the identifiers of the literals that have a word starting with
+    /// every word of `text`. If `lang` is given, only the literals whose
+    /// language tag is in this range are returned, and `text` is lowercased
+    /// with its rules.
+    ///
+    /// The literals that contain the most words of `text` in full come first.
+    /// Then the literals are sorted by identifier. A text without words
+    /// matches nothing.
+    pub fn search(&self, text: &str, lang: Option<&str>) -> Vec<u32> {
+        let mut words = tokenize(text, lang);
+        words.sort();
+        words.dedup();
+
+        if words.is_empty() {
+            return vec![];
+        }
+
+        // The number of words of the text that each candidate has in full
+        let mut candidates: Option<HashMap<u32, usize>> = None;
+
+        for word in &words {
+            let exact = self.postings.get(word);
+            let mut matches: HashMap<u32, usize> = HashMap::new();
+
+            for (_, indexes) in self.postings.range(word.clone()..).take_while(|(key, _)| key.starts_with(word.as_str())) {
+                for index in indexes.iter().filter(|index| self.is_in_language(**index, lang)) {
+                    let is_exact = exact.map_or(false, |exact| exact.contains(index));
+                    matches.insert(*index, is_exact as usize);
+                }
+            }
+
+            candidates = Some(match candidates {
+                None => matches,
+                Some(candidates) => candidates.into_iter()
+                    .filter_map(|(index, score)| matches.get(&index).map(|exact| (index, score + exact)))
+                    .collect()
+            });
+        }
+
+        let mut results: Vec<(u32, usize)> = candidates.unwrap_or_default().into_iter().collect();
+        results.sort_by(|(a, a_score), (b, b_score)| b_score.cmp(a_score).then(a.cmp(b)));
+        results.into_iter().map(|(index, _)| index).collect()
+    }
+
+    /// Returns true if no language range is given, or if the literal `index`
+    /// has a language tag in the range `lang`
+    fn is_in_language(&self, index: u32, lang: Option<&str>) -> bool {
+        match lang {
+            None => true,
+            Some(range) => self.languages.get(&index).map_or(false, |tag| language_matches(tag, range))
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn string(value: &str) -> RcTerm {
+        RcTerm::new_literal_dt(value, RcTerm::new_iri(XSD_STRING).unwrap()).unwrap()
+    }
+
+    #[test]
+    fn lowercases_words_by_language() {
+        assert_eq!(tokenize("Hello, World-Wide web!", None), vec!["hello", "world", "wide", "web"]);
+        assert_eq!(tokenize("ILIK İstanbul", Some("tr-TR")), vec!["ılık", "istanbul"]);
+        assert_eq!(tokenize("ILIK İstanbul", Some("en")), vec!["ilik", "istanbul"]);
+        assert!(language_matches("en-GB", "EN"));
+        assert!(!language_matches("eng", "en"));
+    }
+
+    #[test]
+    fn searches_words_by_prefix() {
+        let mut index = TextIndex::new();
+        index.insert(1, &string("Parc des Princes"));
+        index.insert(2, &string("Paris"));
+        index.insert(3, &RcTerm::new_literal_lang("New York", "en").unwrap());
+        index.insert(4, &string("Par"));
+        index.insert(5, &RcTerm::new_iri("http://example.org/Paris").unwrap());
+
+        assert_eq!(index.search("par", None), vec![4, 1, 2]);
+        assert_eq!(index.search("NEW yo", None), vec![3]);
+        assert_eq!(index.search("new paris", None), Vec::<u32>::new());
+        assert_eq!(index.search(" ,", None), Vec::<u32>::new());
+        assert_eq!(index.search("york", Some("en")), vec![3]);
+        assert_eq!(index.search("york", Some("fr")), Vec::<u32>::new());
+        assert_eq!(index.search("paris", Some("en")), Vec::<u32>::new());
+
+        index.remove(4, &string("Par"));
+        assert_eq!(index.search("par", None), vec![1, 2]);
+    }
+}
@@ -1,25 +1,32 @@
 use crate::RcQuad;
+use crate::graphs::NamedGraphs;
+use crate::literalindex::LiteralKey;
+use crate::termdictionary::TermDictionary;
 
 use identifier_forest::IndexingForest4;
 use identifier_forest::IndexingForest4Filter;
+use identifier_forest::TermRole;
+use identifier_forest::owl_rl::OWL_SAME_AS;
 use sophia::dataset::MutableDataset;
 use sophia::dataset::DQuad;
 use sophia::dataset::DQuadSource;
 use sophia::dataset::Dataset;
 use sophia::dataset::DResult;
 use sophia::dataset::MDResult;
-use sophia::graph::inmem::TermIndexMapU;
+use sophia::quad::Quad;
 use sophia::quad::streaming_mode::ByValue;
 use sophia::quad::streaming_mode::StreamedQuad;
-use sophia::term::factory::RcTermFactory;
 use sophia::term::index_map::TermIndexMap;
 use sophia::term::RcTerm;
 use sophia::term::RefTerm;
 use sophia::term::Term;
 use sophia::term::TermData;
 
+use std::collections::HashMap;
+use std::collections::HashSet;
 use std::convert::Infallible;
 use std::iter::empty;
+use std::ops::Bound;
 
 #[cfg(test)]
 use sophia::test_dataset_impl;
@@ -29,8 +36,14 @@ use sophia::test_dataset_impl;
 pub struct TreeDataset {
     /// Underlying trees that manipulates identifiers
     forest: IndexingForest4,
-    /// A `TermIndexMapU` that matches RcTerms with u32 identifiers
-    term_id_map: TermIndexMapU<u32, RcTermFactory>
+    /// A `TermDictionary` that matches RcTerms with u32 identifiers
+    term_id_map: TermDictionary,
+    /// The `owl:sameAs` term if the quads are stored under the representative
+    /// of their terms
+    same_as: Option<RcTerm>,
+    /// The asserted `owl:sameAs` quads, with their original terms, when the
+    /// quads are stored under the representative of their terms
+    same_as_links: IndexingForest4
 }
 
 impl TreeDataset {
@@ -41,18 +54,128 @@ impl TreeDataset {
     pub fn new_anti(s: bool, p: bool, o: bool, g: bool) -> Self {
         Self {
             forest: IndexingForest4::new_anti(s, p, o, g),
-            term_id_map: TermIndexMapU::<u32, RcTermFactory>::default()
+            term_id_map: TermDictionary::default(),
+            same_as: None,
+            same_as_links: IndexingForest4::default()
         }
     }
 
+    /// Builds a dataset in which the terms linked by `owl:sameAs` are
+    /// equivalent.
+    ///
+    /// The subject, predicate and object of the quads are stored under the
+    /// representative of their class of equivalent terms, so querying any
+    /// member of a class answers for the whole class, and the returned quads
+    /// use the representatives. `equivalent_terms` and `expand_quad` give
+    /// back the original terms. Graph names are not canonicalized.
+    ///
+    /// The `owl:sameAs` quads themselves are kept with their original terms,
+    /// so they are returned and removed as they were inserted. A query on a
+    /// member of a class still matches the `owl:sameAs` quads of the other
+    /// members.
+    ///
+    /// Removing an `owl:sameAs` quad rebuilds the class of its terms from the
+    /// remaining ones, so they become distinct again if nothing else links
+    /// them. The quads stored under the class stay with the class of its
+    /// former representative, as the terms they were inserted with are lost.
+    pub fn new_with_same_as_canonicalization() -> Self {
+        Self {
+            same_as: Some(RcTerm::new_iri(OWL_SAME_AS).unwrap()),
+            ..Self::default()
+        }
+    }
+
+    /// Builds a dataset that keeps its numeric and temporal literals sorted
+    /// by value from the start, instead of sorting them at the first
+    /// `quads_with_object_in_range` call.
+    pub fn new_with_literal_index() -> Self {
+        let mut dataset = Self::default();
+        dataset.enable_literal_index();
+        dataset
+    }
+
+    /// Builds a dataset that keeps the words of its string literals from the
+    /// start, instead of reading them at the first `search_literals` call.
+    pub fn new_with_text_index() -> Self {
+        let mut dataset = Self::default();
+        dataset.enable_text_index();
+        dataset
+    }
+
     /// Returns an iterator on Sophia Quads that matches the given pattern of indexes.
     /// 
     /// indexes is in the format on four term indexes, in the order Subject,
     /// Prdicate, Object, Graph. None means every term must be matched, a given
     /// value that only the given term must be matched.
     fn quads_with_opt_spog<'s>(&'s self, indexes: [Option<u32>; 4]) -> DQuadSource<'s, Self> {
-        let quads = self.forest.filter(indexes);
-        InflatedQuadsIterator::new_box(quads, &self.term_id_map)
+        InflatedQuadsIterator::new_box(self.matching_identifier_quads(indexes), &self.term_id_map)
+    }
+
+    /// Returns the identifier quads that match a pattern of representatives:
+    /// the stored quads, then the `owl:sameAs` quads whose terms are in the
+    /// classes of the pattern.
+    fn matching_identifier_quads(&self, indexes: [Option<u32>; 4]) -> std::iter::Chain<IndexingForest4Filter, std::vec::IntoIter<[u32; 4]>> {
+        self.forest.filter(indexes).chain(self.matching_same_as_links(indexes))
+    }
+
+    /// Returns the asserted `owl:sameAs` quads whose subject, predicate and
+    /// object are equivalent to the ones of the pattern
+    fn matching_same_as_links(&self, indexes: [Option<u32>; 4]) -> Vec<[u32; 4]> {
+        if self.same_as.is_none() {
+            return vec![];
+        }
+
+        let mut patterns = vec![[None, None, None, indexes[3]]];
+
+        for position in 0..3 {
+            if let Some(index) = indexes[position] {
+                let equivalents = self.term_id_map.equivalents(index);
+
+                patterns = patterns.into_iter()
+                    .flat_map(|pattern| equivalents.iter().map(move |equivalent| {
+                        let mut pattern = pattern;
+                        pattern[position] = Some(*equivalent);
+                        pattern
+                    }))
+                    .collect();
+            }
+        }
+
+        patterns.into_iter()
+            .flat_map(|pattern| self.same_as_links.filter(pattern))
+            .collect()
+    }
+
+    /// Removes the asserted `owl:sameAs` quad with these terms, whose
+    /// predicate is the identifier `p`. Returns true if it was in the dataset.
+    fn remove_same_as_link<T, V, W>(&mut self, s: &Term<T>, p: u32, o: &Term<V>, g: Option<&Term<W>>) -> bool
+    where T: TermData, V: TermData, W: TermData {
+        let s = self.term_id_map.get_index(&s.into());
+        let o = self.term_id_map.get_index(&o.into());
+        let g = self.term_id_map.get_index_for_graph_name(g.map(RefTerm::from).as_ref());
+
+        let (s, link) = match (s, o, g) {
+            (Some(s), Some(o), Some(g)) => (s, [s, p, o, g]),
+            _ => return false
+        };
+
+        if !self.same_as_links.delete(link) {
+            return false;
+        }
+
+        self.rebuild_class(s);
+
+        for index in &link {
+            self.term_id_map.dec_ref(*index);
+        }
+
+        true
+    }
+
+    /// Returns true if the term of identifier `index` is the `owl:sameAs` of
+    /// the canonicalization
+    fn is_same_as(&self, index: u32) -> bool {
+        self.same_as.is_some() && self.term_id_map.get_term(index) == self.same_as.as_ref()
     }
 }
 
@@ -67,7 +190,7 @@ impl Dataset for TreeDataset {
     // One term
     fn quads_with_s<'s, TS>(&'s self, s: &'s Term<TS>) -> DQuadSource<'s, Self>
     where TS: TermData {
-        let s = self.term_id_map.get_index(&s.into());
+        let s = self.term_id_map.get_canonical_index(&s.into());
         if s.is_none() {
             return Box::new(empty());
         } else {
@@ -77,7 +200,7 @@ impl Dataset for TreeDataset {
 
     fn quads_with_p<'s, TP>(&'s self, p: &'s Term<TP>) -> DQuadSource<'s, Self>
     where TP: TermData {
-        let p = self.term_id_map.get_index(&p.into());
+        let p = self.term_id_map.get_canonical_index(&p.into());
         if p.is_none() {
             return Box::new(empty());
         } else {
@@ -87,7 +210,7 @@ impl Dataset for TreeDataset {
 
     fn quads_with_o<'s, TO>(&'s self, o: &'s Term<TO>) -> DQuadSource<'s, Self>
     where TO: TermData {
-        let o = self.term_id_map.get_index(&o.into());
+        let o = self.term_id_map.get_canonical_index(&o.into());
         if o.is_none() {
             return Box::new(empty());
         } else {
@@ -109,12 +232,12 @@ impl Dataset for TreeDataset {
     // Two terms
     fn quads_with_sp<'s, TS, TP>(&'s self, s: &'s Term<TS>, p: &'s Term<TP>) -> DQuadSource<'s, Self>
     where TS: TermData, TP: TermData {
-        let s = self.term_id_map.get_index(&s.into());
+        let s = self.term_id_map.get_canonical_index(&s.into());
         if s.is_none() {
             return Box::new(empty());
         }
 
-        let p = self.term_id_map.get_index(&p.into());
+        let p = self.term_id_map.get_canonical_index(&p.into());
         if p.is_none() {
             return Box::new(empty());
         }
@@ -124,12 +247,12 @@ impl Dataset for TreeDataset {
 
     fn quads_with_so<'s, TS, TO>(&'s self, s: &'s Term<TS>, o: &'s Term<TO>) -> DQuadSource<'s, Self>
     where TS: TermData, TO: TermData {
-        let s = self.term_id_map.get_index(&s.into());
+        let s = self.term_id_map.get_canonical_index(&s.into());
         if s.is_none() {
             return Box::new(empty());
         }
 
-        let o = self.term_id_map.get_index(&o.into());
+        let o = self.term_id_map.get_canonical_index(&o.into());
         if o.is_none() {
             return Box::new(empty());
         }
@@ -139,7 +262,7 @@ impl Dataset for TreeDataset {
 
     fn quads_with_sg<'s, TS, TG>(&'s self, s: &'s Term<TS>, g: Option<&'s Term<TG>>) -> DQuadSource<'s, Self>
     where TS: TermData, TG: TermData {
-        let s = self.term_id_map.get_index(&s.into());
+        let s = self.term_id_map.get_canonical_index(&s.into());
         if s.is_none() {
             return Box::new(empty());
         }
@@ -154,12 +277,12 @@ impl Dataset for TreeDataset {
 
     fn quads_with_po<'s, TP, TO>(&'s self, p: &'s Term<TP>, o: &'s Term<TO>) -> DQuadSource<'s, Self>
     where TP: TermData, TO: TermData {
-        let p = self.term_id_map.get_index(&p.into());
+        let p = self.term_id_map.get_canonical_index(&p.into());
         if p.is_none() {
             return Box::new(empty());
         }
 
-        let o = self.term_id_map.get_index(&o.into());
+        let o = self.term_id_map.get_canonical_index(&o.into());
         if o.is_none() {
             return Box::new(empty());
         }
@@ -169,7 +292,7 @@ impl Dataset for TreeDataset {
 
     fn quads_with_pg<'s, TP, TG>(&'s self, p: &'s Term<TP>, g: Option<&'s Term<TG>>) -> DQuadSource<'s, Self>
     where TP: TermData, TG: TermData {
-        let p = self.term_id_map.get_index(&p.into());
+        let p = self.term_id_map.get_canonical_index(&p.into());
         if p.is_none() {
             return Box::new(empty());
         }
@@ -184,7 +307,7 @@ impl Dataset for TreeDataset {
 
     fn quads_with_og<'s, TO, TG>(&'s self, o: &'s Term<TO>, g: Option<&'s Term<TG>>) -> DQuadSource<'s, Self>
     where TO: TermData, TG: TermData {
-        let o = self.term_id_map.get_index(&o.into());
+        let o = self.term_id_map.get_canonical_index(&o.into());
         if o.is_none() {
             return Box::new(empty());
         }
@@ -200,17 +323,17 @@ impl Dataset for TreeDataset {
     // Three terms
     fn quads_with_spo<'s, TS, TP, TO>(&'s self, s: &'s Term<TS>, p: &'s Term<TP>, o: &'s Term<TO>) -> DQuadSource<'s, Self>
     where TS: TermData, TP: TermData, TO: TermData {
-        let s = self.term_id_map.get_index(&s.into());
+        let s = self.term_id_map.get_canonical_index(&s.into());
         if s.is_none() {
             return Box::new(empty());
         }
 
-        let p = self.term_id_map.get_index(&p.into());
+        let p = self.term_id_map.get_canonical_index(&p.into());
         if p.is_none() {
             return Box::new(empty());
         }
 
-        let o = self.term_id_map.get_index(&o.into());
+        let o = self.term_id_map.get_canonical_index(&o.into());
         if o.is_none() {
             return Box::new(empty());
         }
@@ -220,12 +343,12 @@ impl Dataset for TreeDataset {
 
     fn quads_with_spg<'s, TS, TP, TG>(&'s self, s: &'s Term<TS>, p: &'s Term<TP>, g: Option<&'s Term<TG>>) -> DQuadSource<'s, Self>
     where TS: TermData, TP: TermData, TG: TermData {
-        let s = self.term_id_map.get_index(&s.into());
+        let s = self.term_id_map.get_canonical_index(&s.into());
         if s.is_none() {
             return Box::new(empty());
         }
 
-        let p = self.term_id_map.get_index(&p.into());
+        let p = self.term_id_map.get_canonical_index(&p.into());
         if p.is_none() {
             return Box::new(empty());
         }
@@ -240,12 +363,12 @@ impl Dataset for TreeDataset {
 
     fn quads_with_sog<'s, TS, TO, TG>(&'s self, s: &'s Term<TS>, o: &'s Term<TO>, g: Option<&'s Term<TG>>) -> DQuadSource<'s, Self>
     where TS: TermData, TO: TermData, TG: TermData {
-        let s = self.term_id_map.get_index(&s.into());
+        let s = self.term_id_map.get_canonical_index(&s.into());
         if s.is_none() {
             return Box::new(empty());
         }
 
-        let o = self.term_id_map.get_index(&o.into());
+        let o = self.term_id_map.get_canonical_index(&o.into());
         if o.is_none() {
             return Box::new(empty());
         }
@@ -260,12 +383,12 @@ impl Dataset for TreeDataset {
 
     fn quads_with_pog<'s, TP, TO, TG>(&'s self, p: &'s Term<TP>, o: &'s Term<TO>, g: Option<&'s Term<TG>>) -> DQuadSource<'s, Self>
     where TP: TermData, TO: TermData, TG: TermData {
-        let p = self.term_id_map.get_index(&p.into());
+        let p = self.term_id_map.get_canonical_index(&p.into());
         if p.is_none() {
             return Box::new(empty());
         }
         
-        let o = self.term_id_map.get_index(&o.into());
+        let o = self.term_id_map.get_canonical_index(&o.into());
         if o.is_none() {
             return Box::new(empty());
         }
@@ -283,9 +406,9 @@ impl Dataset for TreeDataset {
     fn quads_with_spog<'s, T1, T2, T3, T4>(&'s self, t1: &'s Term<T1>, t2: &'s Term<T2>, t3: &'s Term<T3>, t4: Option<&'s Term<T4>>) -> DQuadSource<'s, Self>
     where T1: TermData, T2: TermData, T3: TermData, T4: TermData
     {
-        let t1 = self.term_id_map.get_index(&t1.into());
-        let t2 = self.term_id_map.get_index(&t2.into());
-        let t3 = self.term_id_map.get_index(&t3.into());
+        let t1 = self.term_id_map.get_canonical_index(&t1.into());
+        let t2 = self.term_id_map.get_canonical_index(&t2.into());
+        let t3 = self.term_id_map.get_canonical_index(&t3.into());
         let t4 = self.term_id_map.get_index_for_graph_name(t4.map(RefTerm::from).as_ref());
         match (t1, t2, t3, t4) {
             (Some(_), Some(_), Some(_), Some(_)) => {
@@ -298,20 +421,21 @@ impl Dataset for TreeDataset {
 
 /// An adapter that transforms an iterator on identifier quads into an iterator
 /// of Sophia Quads
-pub struct InflatedQuadsIterator<'a> {
-    base_iterator: IndexingForest4Filter<'a>,
-    term_id_map: &'a TermIndexMapU<u32, RcTermFactory>,
+pub struct InflatedQuadsIterator<'a, I = IndexingForest4Filter<'a>> {
+    base_iterator: I,
+    term_id_map: &'a TermDictionary,
     last_tuple: Option<[(u32, &'a RcTerm); 3]>,
     last_graph: Option<(u32, &'a RcTerm)>
 }
 
-impl<'a> InflatedQuadsIterator<'a> {
+impl<'a, I> InflatedQuadsIterator<'a, I>
+    where I: Iterator<Item = [u32; 4]> {
     /// Builds a Box of InflatedQuadsIterator from an iterator on identifier quad
     /// and a `TermIndexMap` to match the `DQuadSource` interface.
     pub fn new_box(
-        base_iterator: IndexingForest4Filter<'a>,
-        term_id_map: &'a TermIndexMapU<u32, RcTermFactory>
-    ) -> Box<InflatedQuadsIterator<'a>> {
+        base_iterator: I,
+        term_id_map: &'a TermDictionary
+    ) -> Box<InflatedQuadsIterator<'a, I>> {
         Box::new(InflatedQuadsIterator {
             base_iterator: base_iterator,
             term_id_map: term_id_map,
@@ -321,7 +445,8 @@ impl<'a> InflatedQuadsIterator<'a> {
     }
 }
 
-impl<'a> Iterator for InflatedQuadsIterator<'a> {
+impl<'a, I> Iterator for InflatedQuadsIterator<'a, I>
+    where I: Iterator<Item = [u32; 4]> {
     type Item = DResult<TreeDataset, DQuad<'a, TreeDataset>>;
 
     fn next(&mut self) -> Option<Self::Item> {
@@ -342,7 +467,7 @@ impl<'a> Iterator for InflatedQuadsIterator<'a> {
             self.last_tuple = Some([(spog[0], s), (spog[1], p), (spog[2], o)]);
 
             let g = match (spog[3], self.last_graph) {
-                (x, _) if x == TermIndexMapU::<u32, RcTermFactory>::NULL_INDEX => None,
+                (x, _) if x == TermDictionary::NULL_INDEX => None,
                 (x, Some((y, value))) if x == y => Some(value),
                 (_, _) => {
                     let g = self.term_id_map.get_graph_name(spog[3]).unwrap();
@@ -378,6 +503,23 @@ impl MutableDataset for TreeDataset {
         let gi = self
             .term_id_map
             .make_index_for_graph_name(g.map(RefTerm::from).as_ref());
+
+        if self.is_same_as(pi) {
+            self.merge_equivalent_terms(si, oi);
+
+            let modified = self.same_as_links.insert([si, pi, oi, gi]);
+            if !modified {
+                for index in &[si, pi, oi, gi] {
+                    self.term_id_map.dec_ref(*index);
+                }
+            }
+
+            return Ok(modified);
+        }
+
+        let si = self.term_id_map.canonicalize(si);
+        let pi = self.term_id_map.canonicalize(pi);
+        let oi = self.term_id_map.canonicalize(oi);
         let modified = self.forest.insert([si, pi, oi, gi]);
         if !modified {
             self.term_id_map.dec_ref(si);
@@ -402,9 +544,17 @@ impl MutableDataset for TreeDataset {
         V: TermData,
         W: TermData,
     {
-        let si = self.term_id_map.get_index(&s.into());
-        let pi = self.term_id_map.get_index(&p.into());
-        let oi = self.term_id_map.get_index(&o.into());
+        if self.same_as.is_some() {
+            let pi = self.term_id_map.get_index(&p.into());
+
+            if let Some(pi) = pi.filter(|pi| self.is_same_as(*pi)) {
+                return Ok(self.remove_same_as_link(s, pi, o, g));
+            }
+        }
+
+        let si = self.term_id_map.get_canonical_index(&s.into());
+        let pi = self.term_id_map.get_canonical_index(&p.into());
+        let oi = self.term_id_map.get_canonical_index(&o.into());
         let gi = self
             .term_id_map
             .get_index_for_graph_name(g.map(RefTerm::from).as_ref());
@@ -434,8 +584,506 @@ impl TreeDataset {
     pub fn ensure_has_index_for(&mut self, s: bool, p: bool, o: bool, g: bool) {
         self.forest.ensure_has_index_for(s, p, o, g);
     }
+
+    /// Builds the index of the numeric and temporal literals that
+    /// `quads_with_object_in_range` uses, if it is not already built.
+    pub fn enable_literal_index(&mut self) {
+        self.term_id_map.enable_literal_index();
+    }
+
+    /// Returns the quads whose object is a numeric or temporal literal between
+    /// `min` and `max`, and whose predicate is `p` and graph is `g` when they
+    /// are given.
+    ///
+    /// The quads are sorted by increasing value of their object. Numbers and
+    /// dates are never compared, so a range whose bounds are of different
+    /// kinds is empty. The first call builds the literal index, which is then
+    /// maintained as the dataset changes.
+    pub fn quads_with_object_in_range(
+        &self,
+        p: Option<&RcTerm>,
+        g: Option<Option<&RcTerm>>,
+        min: Bound<LiteralKey>,
+        max: Bound<LiteralKey>
+    ) -> Vec<RcQuad> {
+        let p = match p {
+            Some(p) => match self.term_id_map.get_canonical_index(&p.into()) {
+                Some(p) => Some(p),
+                None => return vec![]
+            },
+            None => None
+        };
+
+        let g = match g {
+            Some(g) => match self.term_id_map.get_index_for_graph_name(g.map(RefTerm::from).as_ref()) {
+                Some(g) => Some(g),
+                None => return vec![]
+            },
+            None => None
+        };
+
+        let mut visited_objects = HashSet::new();
+        let mut quads = vec![];
+
+        for o in self.term_id_map.literals_in_range(min, max) {
+            let o = self.term_id_map.representative(o);
+
+            if !visited_objects.insert(o) {
+                continue;
+            }
+
+            for spog in self.forest.filter([None, p, Some(o), g]) {
+                quads.push(RcQuad::new(
+                    self.term_id_map.get_term(spog[0]).unwrap(),
+                    self.term_id_map.get_term(spog[1]).unwrap(),
+                    self.term_id_map.get_term(spog[2]).unwrap(),
+                    self.term_id_map.get_graph_name(spog[3]).unwrap()
+                ));
+            }
+        }
+
+        quads
+    }
+
+    /// Builds the index of the words of the string literals that
+    /// `search_literals` uses, if it is not already built.
+    pub fn enable_text_index(&mut self) {
+        self.term_id_map.enable_text_index();
+    }
+
+    /// Returns the quads whose object is a string literal with a word
+    /// starting with every word of `text`, and whose predicate is `p` when it
+    /// is given.
+    ///
+    /// If `lang` is given, only the literals of this language range are
+    /// matched, and `text` is lowercased with the rules of this language. The
+    /// quads of the best matching literals come first, and at most `limit`
+    /// quads are returned. The first call builds the text index, which is
+    /// then maintained as the dataset changes.
+    pub fn search_literals(&self, text: &str, p: Option<&RcTerm>, lang: Option<&str>, limit: Option<usize>) -> Vec<RcQuad> {
+        let p = match p {
+            Some(p) => match self.term_id_map.get_canonical_index(&p.into()) {
+                Some(p) => Some(p),
+                None => return vec![]
+            },
+            None => None
+        };
+
+        let limit = limit.unwrap_or(usize::MAX);
+        let mut visited_objects = HashSet::new();
+        let mut quads = vec![];
+
+        for o in self.term_id_map.search_literals(text, lang) {
+            let o = self.term_id_map.representative(o);
+
+            if !visited_objects.insert(o) {
+                continue;
+            }
+
+            for spog in self.forest.filter([None, p, Some(o), None]) {
+                if quads.len() == limit {
+                    return quads;
+                }
+
+                quads.push(RcQuad::new(
+                    self.term_id_map.get_term(spog[0]).unwrap(),
+                    self.term_id_map.get_term(spog[1]).unwrap(),
+                    self.term_id_map.get_term(spog[2]).unwrap(),
+                    self.term_id_map.get_graph_name(spog[3]).unwrap()
+                ));
+            }
+        }
+
+        quads
+    }
+
+    /// Renames every term for which `rename` returns a new term. Returns the
+    /// number of renamed terms.
+    ///
+    /// The terms are replaced in the term dictionary, so the quads keep their
+    /// identifiers. The quads of a term are only reinserted if its new term is
+    /// already used in the dataset.
+    pub fn rename_terms_in_place<F, E>(&mut self, mut rename: F) -> Result<usize, E>
+        where F: FnMut(&RcTerm) -> Result<Option<RcTerm>, E> {
+        let mut renaming = vec![];
+
+        for (index, term) in self.term_id_map.iter() {
+            if let Some(new_term) = rename(term)? {
+                renaming.push((index, new_term));
+            }
+        }
+
+        let number_of_renamed_terms = renaming.len();
+
+        for (index, new_term) in renaming {
+            if !self.term_id_map.replace_term(index, new_term.clone()) {
+                self.merge_term(index, &new_term);
+            }
+        }
+
+        Ok(number_of_renamed_terms)
+    }
+
+    /// Returns the quads of this dataset that are not in `other`.
+    ///
+    /// The quads are compared by identifiers: every term of this dataset is
+    /// looked up once in the dictionary of `other`.
+    pub fn quads_not_in(&self, other: &TreeDataset) -> Vec<RcQuad> {
+        let mut translation: HashMap<u32, Option<u32>> = HashMap::new();
+        translation.insert(TermDictionary::NULL_INDEX, Some(TermDictionary::NULL_INDEX));
+
+        let mut quads = vec![];
+
+        let identifier_quads = self.forest.filter([None, None, None, None])
+            .chain(self.same_as_links.filter([None, None, None, None]));
+
+        for spog in identifier_quads {
+            let mut other_spog = [None; 4];
+
+            for (position, index) in spog.iter().enumerate() {
+                other_spog[position] = *translation.entry(*index).or_insert_with(|| {
+                    let term = self.term_id_map.get_term(*index).unwrap();
+                    other.term_id_map.get_canonical_index(&term.into())
+                });
+            }
+
+            let is_in_other = other_spog.iter().all(Option::is_some)
+                && other.matching_identifier_quads(other_spog).next().is_some();
+
+            if !is_in_other {
+                quads.push(RcQuad::new(
+                    self.term_id_map.get_term(spog[0]).unwrap(),
+                    self.term_id_map.get_term(spog[1]).unwrap(),
+                    self.term_id_map.get_term(spog[2]).unwrap(),
+                    self.term_id_map.get_graph_name(spog[3]).unwrap()
+                ));
+            }
+        }
+
+        quads
+    }
+
+    /// Returns the terms that are equivalent to `term`, including itself
+    pub fn equivalent_terms(&self, term: &RcTerm) -> Vec<RcTerm> {
+        match self.term_id_map.get_index(&term.into()) {
+            Some(index) => self.term_id_map.equivalents(index)
+                .into_iter()
+                .map(|equivalent| self.term_id_map.get_term(equivalent).unwrap().clone())
+                .collect(),
+            None => vec![term.clone()]
+        }
+    }
+
+    /// Returns every quad that `quad` stands for, by replacing its subject,
+    /// predicate and object with each of their equivalent terms
+    pub fn expand_quad(&self, quad: &RcQuad) -> Vec<RcQuad> {
+        let subjects = self.equivalent_terms(quad.s());
+        let predicates = self.equivalent_terms(quad.p());
+        let objects = self.equivalent_terms(quad.o());
+
+        let mut quads = vec![];
+
+        for s in &subjects {
+            for p in &predicates {
+                for o in &objects {
+                    quads.push(RcQuad::new(s, p, o, quad.g()));
+                }
+            }
+        }
+
+        quads
+    }
+
+    /// Makes the terms of identifiers `a` and `b` equivalent. The quads of the
+    /// representative of the absorbed class are moved to the representative
+    /// of the merged class.
+    fn merge_equivalent_terms(&mut self, a: u32, b: u32) {
+        if let Some((absorbed, representative)) = self.term_id_map.merge(a, b) {
+            self.move_quads(absorbed, representative);
+        }
+    }
+
+    /// Rebuilds the class of `index` from the `owl:sameAs` quads that are
+    /// still asserted between its members, after one of them was removed.
+    ///
+    /// The quads of the former representative are moved to the representative
+    /// of its new class. The terms that the quads were inserted with are not
+    /// known, so they do not go back to the other classes.
+    fn rebuild_class(&mut self, index: u32) {
+        let former_representative = self.term_id_map.representative(index);
+        let members = self.term_id_map.dissolve(index);
+
+        let same_as_links = &self.same_as_links;
+        let links = members.iter()
+            .flat_map(|member| same_as_links.filter([Some(*member), None, None, None]))
+            .collect::<Vec<_>>();
+
+        for link in links {
+            self.term_id_map.merge(link[0], link[2]);
+        }
+
+        let representative = self.term_id_map.representative(former_representative);
+        self.move_quads(former_representative, representative);
+    }
+
+    /// Replaces the identifier `from` with `to` in the subject, predicate and
+    /// object of the stored quads
+    fn move_quads(&mut self, from: u32, to: u32) {
+        if from == to {
+            return;
+        }
+
+        for position in 0..3 {
+            let mut pattern = [None; 4];
+            pattern[position] = Some(from);
+
+            let quads = self.forest.filter(pattern).collect::<Vec<_>>();
+
+            for quad in quads {
+                self.forest.delete(quad);
+
+                let mut moved = quad;
+                for index in moved.iter_mut().take(3) {
+                    if *index == from {
+                        *index = to;
+                    }
+                }
+
+                // The references of the new quad are taken before the ones of
+                // the old quad are released, so no shared term is released
+                if self.forest.insert(moved) {
+                    for index in &moved {
+                        self.term_id_map.inc_ref(*index);
+                    }
+                }
+
+                for index in &quad {
+                    self.term_id_map.dec_ref(*index);
+                }
+            }
+        }
+    }
+
+    /// Replaces the term of identifier `index` with `new_term`, which is
+    /// already used in the dataset, by reinserting its quads
+    fn merge_term(&mut self, index: u32, new_term: &RcTerm) {
+        let old_term = self.term_id_map.get_term(index).unwrap().clone();
+        let mut quads = vec![];
+
+        for position in 0..4 {
+            let mut pattern = [None; 4];
+            pattern[position] = Some(index);
+
+            quads.extend(
+                self.quads_with_opt_spog(pattern).map(|quad| RcQuad::new_from_quad(&quad.unwrap()))
+            );
+        }
+
+        let renamed = |term: &RcTerm| if *term == old_term { new_term.clone() } else { term.clone() };
+
+        for quad in quads {
+            self.remove(quad.s(), quad.p(), quad.o(), quad.g()).unwrap();
+            self.insert(
+                &renamed(quad.s()),
+                &renamed(quad.p()),
+                &renamed(quad.o()),
+                quad.g().map(renamed).as_ref()
+            ).unwrap();
+        }
+    }
+}
+
+impl NamedGraphs for TreeDataset {
+    fn graph_names(&self) -> Vec<Option<RcTerm>> {
+        let mut graphs = self.forest.distinct_identifiers(TermRole::Graph);
+
+        if self.same_as.is_some() {
+            graphs.extend(self.same_as_links.distinct_identifiers(TermRole::Graph));
+            graphs.sort_unstable();
+            graphs.dedup();
+        }
+
+        graphs.into_iter()
+            .map(|g| self.term_id_map.get_graph_name(g).unwrap().cloned())
+            .collect()
+    }
+
+    fn drop_graph(&mut self, g: Option<&RcTerm>) -> usize {
+        let g = match self.term_id_map.get_index_for_graph_name(g.map(RefTerm::from).as_ref()) {
+            Some(g) => g,
+            None => return 0
+        };
+
+        let quads = self.forest.filter([None, None, None, Some(g)]).collect::<Vec<_>>();
+        let links = self.same_as_links.filter([None, None, None, Some(g)]).collect::<Vec<_>>();
+
+        for quad in &quads {
+            self.forest.delete(*quad);
+        }
+
+        for link in &links {
+            self.same_as_links.delete(*link);
+        }
+
+        for link in &links {
+            self.rebuild_class(link[0]);
+        }
+
+        for term in quads.iter().chain(&links).flatten() {
+            self.term_id_map.dec_ref(*term);
+        }
+
+        quads.len() + links.len()
+    }
 }
 
 
 #[cfg(test)]
 sophia::test_dataset_impl!(test_treedataset, TreeDataset);
+
+#[cfg(test)]
+sophia::test_dataset_impl!(test_treedataset_same_as, TreeDataset, true, TreeDataset::new_with_same_as_canonicalization);
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn iri(value: &str) -> RcTerm {
+        RcTerm::new_iri(format!("http://example.org/{}", value)).unwrap()
+    }
+
+    fn same_as() -> RcTerm {
+        RcTerm::new_iri(OWL_SAME_AS).unwrap()
+    }
+
+    fn add(dataset: &mut TreeDataset, s: &RcTerm, p: &RcTerm, o: &RcTerm) {
+        dataset.insert(s, p, o, None as Option<&RcTerm>).unwrap();
+    }
+
+    #[test]
+    fn queries_answer_for_the_whole_class() {
+        let mut dataset = TreeDataset::new_with_same_as_canonicalization();
+        add(&mut dataset, &iri("paris"), &iri("population"), &iri("big"));
+        add(&mut dataset, &iri("lutece"), &iri("founded"), &iri("old"));
+        add(&mut dataset, &iri("paris"), &same_as(), &iri("lutece"));
+
+        assert_eq!(dataset.quads_with_s(&iri("paris")).count(), 3);
+        assert_eq!(dataset.quads_with_s(&iri("lutece")).count(), 3);
+        assert!(dataset.contains(&iri("lutece"), &iri("population"), &iri("big"), None as Option<&RcTerm>).unwrap());
+
+        let equivalents = dataset.equivalent_terms(&iri("paris"));
+        assert_eq!(equivalents.len(), 2);
+        assert!(equivalents.contains(&iri("lutece")));
+
+        let quad = dataset.quads_with_p(&iri("founded")).next().unwrap().unwrap();
+        let expanded = dataset.expand_quad(&RcQuad::new_from_quad(&quad));
+        assert_eq!(expanded.len(), 2);
+    }
+
+    #[test]
+    fn merges_duplicate_quads() {
+        let mut dataset = TreeDataset::new_with_same_as_canonicalization();
+        add(&mut dataset, &iri("a"), &iri("p"), &iri("o"));
+        add(&mut dataset, &iri("b"), &iri("p"), &iri("o"));
+        add(&mut dataset, &iri("b"), &same_as(), &iri("c"));
+        add(&mut dataset, &iri("a"), &same_as(), &iri("c"));
+
+        assert_eq!(dataset.quads_with_p(&iri("p")).count(), 1);
+        assert_eq!(dataset.quads_with_p(&same_as()).count(), 2);
+        assert!(dataset.contains(&iri("b"), &same_as(), &iri("c"), None as Option<&RcTerm>).unwrap());
+
+        assert!(dataset.remove(&iri("c"), &iri("p"), &iri("o"), None as Option<&RcTerm>).unwrap());
+        assert_eq!(dataset.quads_with_s(&iri("a")).count(), 2);
+        assert_eq!(dataset.equivalent_terms(&iri("b")).len(), 3);
+    }
+
+    #[test]
+    fn keeps_the_asserted_same_as_quads() {
+        let mut dataset = TreeDataset::new_with_same_as_canonicalization();
+        add(&mut dataset, &iri("a"), &same_as(), &iri("b"));
+        add(&mut dataset, &iri("b"), &iri("p"), &iri("o"));
+
+        let quads = dataset.quads().map(|quad| RcQuad::new_from_quad(&quad.unwrap())).collect::<Vec<_>>();
+        assert_eq!(quads.len(), 2);
+        assert!(quads.contains(&RcQuad::new(&iri("a"), &same_as(), &iri("b"), None as Option<&RcTerm>)));
+        assert_eq!(dataset.quads_with_o(&iri("a")).count(), 1);
+
+        assert!(!dataset.insert(&iri("a"), &same_as(), &iri("b"), None as Option<&RcTerm>).unwrap());
+        assert!(!dataset.remove(&iri("b"), &same_as(), &iri("a"), None as Option<&RcTerm>).unwrap());
+        assert!(dataset.remove(&iri("a"), &same_as(), &iri("b"), None as Option<&RcTerm>).unwrap());
+        assert!(!dataset.remove(&iri("a"), &same_as(), &iri("b"), None as Option<&RcTerm>).unwrap());
+
+        assert_eq!(dataset.quads().count(), 1);
+        assert_eq!(dataset.quads_with_p(&same_as()).count(), 0);
+        assert_eq!(dataset.equivalent_terms(&iri("a")), vec![iri("a")]);
+        assert_eq!(dataset.equivalent_terms(&iri("b")), vec![iri("b")]);
+    }
+
+    #[test]
+    fn rebuilds_the_class_without_the_removed_link() {
+        let mut dataset = TreeDataset::new_with_same_as_canonicalization();
+        add(&mut dataset, &iri("a"), &same_as(), &iri("b"));
+        add(&mut dataset, &iri("b"), &same_as(), &iri("c"));
+        add(&mut dataset, &iri("a"), &iri("p"), &iri("o"));
+
+        assert!(dataset.remove(&iri("a"), &same_as(), &iri("b"), None as Option<&RcTerm>).unwrap());
+
+        assert_eq!(dataset.equivalent_terms(&iri("a")), vec![iri("a")]);
+        assert_eq!(dataset.equivalent_terms(&iri("c")).len(), 2);
+        assert_eq!(dataset.quads_with_s(&iri("a")).count(), 1);
+        assert_eq!(dataset.quads_with_s(&iri("c")).count(), 1);
+        assert!(!dataset.contains(&iri("b"), &iri("p"), &iri("o"), None as Option<&RcTerm>).unwrap());
+
+        assert_eq!(dataset.drop_graph(None), 2);
+        assert_eq!(dataset.equivalent_terms(&iri("b")), vec![iri("b")]);
+    }
+
+    #[test]
+    fn returns_quads_by_object_value() {
+        let integer = |value: &str| RcTerm::new_literal_dt(value, RcTerm::new_iri("http://www.w3.org/2001/XMLSchema#integer").unwrap()).unwrap();
+
+        let mut dataset = TreeDataset::new_with_literal_index();
+        add(&mut dataset, &iri("a"), &iri("value"), &integer("42"));
+        add(&mut dataset, &iri("b"), &iri("value"), &integer("12"));
+        add(&mut dataset, &iri("c"), &iri("value"), &integer("31"));
+        add(&mut dataset, &iri("c"), &iri("other"), &integer("35"));
+
+        let thirty = Bound::Excluded(LiteralKey::Number(30.0));
+        let subjects = |quads: Vec<RcQuad>| quads.iter().map(|quad| quad.s().clone()).collect::<Vec<_>>();
+        assert_eq!(subjects(dataset.quads_with_object_in_range(Some(&iri("value")), None, thirty, Bound::Unbounded)), vec![iri("c"), iri("a")]);
+        assert_eq!(dataset.quads_with_object_in_range(None, Some(None), thirty, Bound::Unbounded).len(), 3);
+        assert_eq!(dataset.quads_with_object_in_range(Some(&iri("unknown")), None, thirty, Bound::Unbounded).len(), 0);
+
+        dataset.remove(&iri("c"), &iri("value"), &integer("31"), None as Option<&RcTerm>).unwrap();
+        assert_eq!(subjects(dataset.quads_with_object_in_range(Some(&iri("value")), None, thirty, Bound::Unbounded)), vec![iri("a")]);
+        assert_eq!(TreeDataset::new().quads_with_object_in_range(None, None, thirty, Bound::Unbounded).len(), 0);
+    }
+
+    #[test]
+    fn searches_literals_by_words() {
+        let label = iri("label");
+        let mut dataset = TreeDataset::new_with_text_index();
+        add(&mut dataset, &iri("paris"), &label, &RcTerm::new_literal_lang("Paris", "fr").unwrap());
+        add(&mut dataset, &iri("parc"), &label, &RcTerm::new_literal_lang("Parc des Princes", "fr").unwrap());
+        add(&mut dataset, &iri("paris"), &iri("comment"), &RcTerm::new_literal_lang("Paris is big", "en").unwrap());
+
+        let subjects = |quads: Vec<RcQuad>| quads.iter().map(|quad| quad.s().clone()).collect::<Vec<_>>();
+        assert_eq!(subjects(dataset.search_literals("paris", Some(&label), None, None)), vec![iri("paris")]);
+        assert_eq!(dataset.search_literals("PAR", None, None, None).len(), 3);
+        assert_eq!(dataset.search_literals("PAR", None, Some("en"), None).len(), 1);
+        assert_eq!(dataset.search_literals("PAR", None, None, Some(2)).len(), 2);
+
+        dataset.remove(&iri("parc"), &label, &RcTerm::new_literal_lang("Parc des Princes", "fr").unwrap(), None as Option<&RcTerm>).unwrap();
+        assert_eq!(dataset.search_literals("princes", None, None, None).len(), 0);
+    }
+
+    #[test]
+    fn keeps_terms_apart_by_default() {
+        let mut dataset = TreeDataset::new();
+        add(&mut dataset, &iri("a"), &same_as(), &iri("b"));
+        add(&mut dataset, &iri("a"), &iri("p"), &iri("o"));
+
+        assert_eq!(dataset.quads_with_s(&iri("b")).count(), 0);
+        assert_eq!(dataset.equivalent_terms(&iri("a")), vec![iri("a")]);
+    }
+}
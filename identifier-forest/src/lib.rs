@@ -25,12 +25,17 @@
 //! - [`BlockOrder`]: A structure that enables to convert between [`Block`]s
 //! and "canonical" (SPOG) quad of identfiers.
 //!
+//! The [`owl_rl`] module provides a forward-chaining engine that materializes
+//! some OWL 2 RL entailments of the quads of a forest into another forest.
+//!
 //! [RDF]: https://www.w3.org/TR/rdf11-primer/
 //! [dataset]: https://www.w3.org/TR/rdf11-concepts/#dfn-rdf-dataset
 //! [RDF Term]: https://www.w3.org/TR/rdf11-concepts/#dfn-rdf-term
 #![deny(missing_docs)]
 #![allow(clippy::assertions_on_constants)]
 
+pub mod owl_rl;
+
 use once_cell::unsync::OnceCell;
 use std::collections::BTreeSet;
 
@@ -593,6 +598,20 @@ impl IndexingForest4 {
         identifier_quad_pattern: [Option<u32>; NB_OF_TERMS],
         can_build_new_tree: bool,
     ) -> IndexingForest4Filter {
+        let (block_order, tree) = self.best_tree(identifier_quad_pattern, can_build_new_tree);
+        block_order.filter(tree, identifier_quad_pattern)
+    }
+
+    /// Return the tree that is the best suited to answer the given pattern,
+    /// and its block order.
+    ///
+    /// This function can potentially build a new tree in the structure if the
+    /// `can_build_new_tree` parameter is equal to true.
+    fn best_tree(
+        &self,
+        identifier_quad_pattern: [Option<u32>; NB_OF_TERMS],
+        can_build_new_tree: bool,
+    ) -> (&BlockOrder, &BTreeSet<Block<u32>>) {
         // Find best index
         let term_roles = [
             &identifier_quad_pattern[0],
@@ -614,8 +633,7 @@ impl IndexingForest4 {
             }
         }
 
-        // Split research
-        let tree_description = match best_alt_tree_pos {
+        match best_alt_tree_pos {
             Some(x) => {
                 let alternative_tree_description = &self.optional_trees[x];
 
@@ -636,11 +654,7 @@ impl IndexingForest4 {
                 )
             }
             None => (&self.base_tree.0, &self.base_tree.1),
-        };
-
-        tree_description
-            .0
-            .filter(&tree_description.1, identifier_quad_pattern)
+        }
     }
 
     /// Return an iterator on identifier quads from the dataset, matching
@@ -656,6 +670,45 @@ impl IndexingForest4 {
         self.search_all_matching_quads(identifier_quad_pattern, true)
     }
 
+    /// Return, in ascending order, every identifier that is used with the
+    /// given role in the stored quads.
+    ///
+    /// If the forest has a tree that sorts the quads by this role first, the
+    /// tree is built if needed and the search jumps from one identifier to the
+    /// next one instead of visiting every quad.
+    pub fn distinct_identifiers(&self, term_role: TermRole) -> Vec<u32> {
+        let mut pattern = [None; NB_OF_TERMS];
+        pattern[term_role as usize] = Some(0);
+
+        let (block_order, tree) = self.best_tree(pattern, true);
+
+        if block_order.get_term_roles()[0] != term_role {
+            let identifiers: BTreeSet<u32> = block_order
+                .filter(tree, [None; NB_OF_TERMS])
+                .map(|identifier_quad| identifier_quad[term_role as usize])
+                .collect();
+            return identifiers.into_iter().collect();
+        }
+
+        let mut identifiers = vec![];
+        let mut lower_bound = Some(u32::MIN);
+
+        while let Some(min) = lower_bound {
+            let mut first_block = [u32::MIN; NB_OF_TERMS];
+            first_block[0] = min;
+
+            match tree.range(Block::new(first_block)..).next() {
+                None => break,
+                Some(block) => {
+                    identifiers.push(block.data[0]);
+                    lower_bound = block.data[0].checked_add(1);
+                }
+            }
+        }
+
+        identifiers
+    }
+
     /// Insert in the dataset the quad described by the given array of identifiers.
     ///
     /// Returns true if the quad has been inserted in the dataset (it was not
@@ -704,6 +757,12 @@ impl IndexingForest4 {
         true
     }
 
+    /// Return true if the quad described by the given array of identifiers is
+    /// in the dataset
+    pub fn contains(&self, identifier_quad: &[u32; NB_OF_TERMS]) -> bool {
+        self.base_tree.0.contains(&self.base_tree.1, identifier_quad)
+    }
+
     /// Return the number of currently instancied trees
     pub fn get_number_of_living_trees(&self) -> usize {
         1 + self
@@ -727,3 +786,52 @@ impl IndexingForest4 {
         iter.next(); // Ensure the tree is not lazily built
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn filled(mut forest: IndexingForest4) -> IndexingForest4 {
+        forest.insert([3, 1, 7, 0]);
+        forest.insert([1, 1, 8, 0]);
+        forest.insert([3, 2, 7, 5]);
+        forest.insert([u32::MAX, 2, 0, 0]);
+        forest
+    }
+
+    #[test]
+    fn lists_distinct_identifiers() {
+        let forest = filled(IndexingForest4::new());
+
+        assert_eq!(forest.distinct_identifiers(TermRole::Subject), vec![1, 3, u32::MAX]);
+        assert_eq!(forest.distinct_identifiers(TermRole::Predicate), vec![1, 2]);
+        assert_eq!(forest.distinct_identifiers(TermRole::Object), vec![0, 7, 8]);
+        assert_eq!(forest.distinct_identifiers(TermRole::Graph), vec![0, 5]);
+        assert!(IndexingForest4::new().distinct_identifiers(TermRole::Subject).is_empty());
+    }
+
+    #[test]
+    fn lists_distinct_identifiers_without_a_matching_tree() {
+        let forest = filled(IndexingForest4::new_with_indexes(
+            &[[TermRole::Object, TermRole::Graph, TermRole::Predicate, TermRole::Subject]],
+            None,
+        ));
+
+        assert_eq!(forest.distinct_identifiers(TermRole::Subject), vec![1, 3, u32::MAX]);
+        assert_eq!(forest.distinct_identifiers(TermRole::Object), vec![0, 7, 8]);
+        assert_eq!(forest.get_number_of_living_trees(), 2);
+    }
+
+    #[test]
+    fn contains_inserted_quads() {
+        let mut forest = filled(IndexingForest4::new());
+
+        assert!(forest.contains(&[3, 1, 7, 0]));
+        assert!(forest.contains(&[u32::MAX, 2, 0, 0]));
+        assert!(!forest.contains(&[3, 1, 7, 5]));
+
+        assert!(forest.delete([3, 1, 7, 0]));
+        assert!(!forest.contains(&[3, 1, 7, 0]));
+        assert!(!forest.delete([3, 1, 7, 0]));
+    }
+}
@@ -0,0 +1,657 @@
+//! A forward-chaining engine for a subset of the [OWL 2 RL] rules.
+//!
+//! The engine works on identifier quads: it reads the asserted quads from an
+//! [`IndexingForest4`] and writes the quads it infers into another
+//! [`IndexingForest4`], so the asserted quads are never modified. The
+//! identifiers of the OWL vocabulary are given by the user in an
+//! [`OwlVocabulary`].
+//!
+//! The supported rules are:
+//! - [`OwlRule::SameAs`] applies `eq-sym`, `eq-trans`, `eq-rep-s`,
+//!   `eq-rep-p` and `eq-rep-o`
+//! - [`OwlRule::InverseOf`] applies `prp-inv1` and `prp-inv2`
+//! - [`OwlRule::TransitiveProperty`] applies `prp-trp`
+//! - [`OwlRule::SymmetricProperty`] applies `prp-symp`
+//! - [`OwlRule::EquivalentClass`] applies `cax-eqc1` and `cax-eqc2`
+//! - [`OwlRule::EquivalentProperty`] applies `prp-eqp1` and `prp-eqp2`
+//!
+//! A rule only joins quads of the same graph, and its conclusion is put in
+//! this graph.
+//!
+//! The evaluation is semi-naive: each round only looks for the conclusions
+//! that use at least one quad produced by the previous round. The number of
+//! rounds is bounded, so a rule set that keeps producing quads is stopped
+//! with a [`FixpointLimitReached`] error.
+//!
+//! As the engine does not know the terms behind the identifiers, it may infer
+//! quads that are not valid RDF, like a quad whose subject is a literal
+//! (`prp-symp` on a literal object for example). The user is expected to
+//! ignore or tolerate them.
+//!
+//! [OWL 2 RL]: https://www.w3.org/TR/owl2-profiles/#OWL_2_RL
+
+use crate::{IndexingForest4, IndexingForest4Filter, NB_OF_TERMS};
+
+/// The IRI of `rdf:type`
+pub const RDF_TYPE: &str = "http://www.w3.org/1999/02/22-rdf-syntax-ns#type";
+/// The IRI of `owl:sameAs`
+pub const OWL_SAME_AS: &str = "http://www.w3.org/2002/07/owl#sameAs";
+/// The IRI of `owl:inverseOf`
+pub const OWL_INVERSE_OF: &str = "http://www.w3.org/2002/07/owl#inverseOf";
+/// The IRI of `owl:TransitiveProperty`
+pub const OWL_TRANSITIVE_PROPERTY: &str = "http://www.w3.org/2002/07/owl#TransitiveProperty";
+/// The IRI of `owl:SymmetricProperty`
+pub const OWL_SYMMETRIC_PROPERTY: &str = "http://www.w3.org/2002/07/owl#SymmetricProperty";
+/// The IRI of `owl:equivalentClass`
+pub const OWL_EQUIVALENT_CLASS: &str = "http://www.w3.org/2002/07/owl#equivalentClass";
+/// The IRI of `owl:equivalentProperty`
+pub const OWL_EQUIVALENT_PROPERTY: &str = "http://www.w3.org/2002/07/owl#equivalentProperty";
+
+/// The default maximum number of rounds of [`OwlReasoner`]
+pub const DEFAULT_MAX_ITERATIONS: usize = 100;
+
+/// An identifier quad
+type IdentifierQuad = [u32; NB_OF_TERMS];
+
+/// The identifiers of the terms used by the rules
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct OwlVocabulary {
+    /// Identifier of `rdf:type`
+    pub rdf_type: u32,
+    /// Identifier of `owl:sameAs`
+    pub same_as: u32,
+    /// Identifier of `owl:inverseOf`
+    pub inverse_of: u32,
+    /// Identifier of `owl:TransitiveProperty`
+    pub transitive_property: u32,
+    /// Identifier of `owl:SymmetricProperty`
+    pub symmetric_property: u32,
+    /// Identifier of `owl:equivalentClass`
+    pub equivalent_class: u32,
+    /// Identifier of `owl:equivalentProperty`
+    pub equivalent_property: u32,
+}
+
+impl OwlVocabulary {
+    /// Build the vocabulary by asking the identifier of each IRI to
+    /// `identifier_of`
+    pub fn new<F>(mut identifier_of: F) -> Self
+    where
+        F: FnMut(&str) -> u32,
+    {
+        OwlVocabulary {
+            rdf_type: identifier_of(RDF_TYPE),
+            same_as: identifier_of(OWL_SAME_AS),
+            inverse_of: identifier_of(OWL_INVERSE_OF),
+            transitive_property: identifier_of(OWL_TRANSITIVE_PROPERTY),
+            symmetric_property: identifier_of(OWL_SYMMETRIC_PROPERTY),
+            equivalent_class: identifier_of(OWL_EQUIVALENT_CLASS),
+            equivalent_property: identifier_of(OWL_EQUIVALENT_PROPERTY),
+        }
+    }
+}
+
+/// A group of OWL 2 RL rules that can be enabled or disabled
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum OwlRule {
+    /// Symmetry, transitivity and replacement of `owl:sameAs`
+    SameAs = 0,
+    /// Properties declared as the `owl:inverseOf` of another one
+    InverseOf = 1,
+    /// Properties of type `owl:TransitiveProperty`
+    TransitiveProperty = 2,
+    /// Properties of type `owl:SymmetricProperty`
+    SymmetricProperty = 3,
+    /// Instances of classes linked by `owl:equivalentClass`
+    EquivalentClass = 4,
+    /// Properties linked by `owl:equivalentProperty`
+    EquivalentProperty = 5,
+}
+
+impl OwlRule {
+    /// Every rule group
+    pub const ALL: [OwlRule; 6] = [
+        OwlRule::SameAs,
+        OwlRule::InverseOf,
+        OwlRule::TransitiveProperty,
+        OwlRule::SymmetricProperty,
+        OwlRule::EquivalentClass,
+        OwlRule::EquivalentProperty,
+    ];
+}
+
+/// The set of rules applied by an [`OwlReasoner`]
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct RuleSet {
+    mask: u8,
+}
+
+impl RuleSet {
+    /// Return a rule set without any rule
+    pub fn empty() -> Self {
+        RuleSet { mask: 0 }
+    }
+
+    /// Return a rule set with every rule
+    pub fn all() -> Self {
+        OwlRule::ALL
+            .iter()
+            .fold(RuleSet::empty(), |rules, rule| rules.with(*rule))
+    }
+
+    /// Return this rule set with `rule` enabled
+    pub fn with(self, rule: OwlRule) -> Self {
+        RuleSet {
+            mask: self.mask | (1 << rule as u8),
+        }
+    }
+
+    /// Return this rule set with `rule` disabled
+    pub fn without(self, rule: OwlRule) -> Self {
+        RuleSet {
+            mask: self.mask & !(1 << rule as u8),
+        }
+    }
+
+    /// Return true if `rule` is enabled
+    pub fn contains(&self, rule: OwlRule) -> bool {
+        self.mask & (1 << rule as u8) != 0
+    }
+}
+
+impl Default for RuleSet {
+    fn default() -> Self {
+        RuleSet::all()
+    }
+}
+
+/// The error returned when the rules still produce new quads after the
+/// maximum number of rounds.
+///
+/// The quads inferred before the limit was reached are kept in the inferred
+/// forest.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct FixpointLimitReached {
+    /// The number of rounds that were run
+    pub iterations: usize,
+    /// The number of quads that were inferred
+    pub inferred: usize,
+}
+
+impl std::fmt::Display for FixpointLimitReached {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "No fixpoint after {} iterations ({} inferred quads)",
+            self.iterations, self.inferred
+        )
+    }
+}
+
+impl std::error::Error for FixpointLimitReached {}
+
+/// A forward-chaining engine that materializes the conclusions of a
+/// [`RuleSet`] into an inferred forest
+#[derive(Clone, Copy, Debug)]
+pub struct OwlReasoner {
+    vocabulary: OwlVocabulary,
+    rules: RuleSet,
+    max_iterations: usize,
+}
+
+impl OwlReasoner {
+    /// Build a reasoner that applies every rule with at most
+    /// [`DEFAULT_MAX_ITERATIONS`] rounds
+    pub fn new(vocabulary: OwlVocabulary) -> Self {
+        OwlReasoner {
+            vocabulary,
+            rules: RuleSet::all(),
+            max_iterations: DEFAULT_MAX_ITERATIONS,
+        }
+    }
+
+    /// Return this reasoner with only the given rules
+    pub fn with_rules(mut self, rules: RuleSet) -> Self {
+        self.rules = rules;
+        self
+    }
+
+    /// Return this reasoner with at most `max_iterations` rounds
+    pub fn with_max_iterations(mut self, max_iterations: usize) -> Self {
+        self.max_iterations = max_iterations;
+        self
+    }
+
+    /// Insert in `inferred` every quad entailed by the quads of `asserted`
+    /// and `inferred` that is in neither of them.
+    ///
+    /// Return the number of inferred quads.
+    pub fn materialize(
+        &self,
+        asserted: &IndexingForest4,
+        inferred: &mut IndexingForest4,
+    ) -> Result<usize, FixpointLimitReached> {
+        let known_quads = asserted
+            .filter([None; NB_OF_TERMS])
+            .chain(inferred.filter([None; NB_OF_TERMS]))
+            .collect();
+
+        self.extend(asserted, inferred, known_quads)
+    }
+
+    /// Insert in `inferred` the quads entailed by `new_quads`, that must
+    /// already be inserted in `asserted`, assuming that `inferred` already
+    /// contains the entailments of the other quads of `asserted`.
+    ///
+    /// Return the number of inferred quads.
+    pub fn extend(
+        &self,
+        asserted: &IndexingForest4,
+        inferred: &mut IndexingForest4,
+        new_quads: Vec<[u32; NB_OF_TERMS]>,
+    ) -> Result<usize, FixpointLimitReached> {
+        let mut delta = new_quads;
+        let mut number_of_inferred_quads = 0;
+        let mut iterations = 0;
+
+        while !delta.is_empty() {
+            if iterations == self.max_iterations {
+                return Err(FixpointLimitReached {
+                    iterations,
+                    inferred: number_of_inferred_quads,
+                });
+            }
+
+            iterations += 1;
+
+            let known = KnownQuads { asserted, inferred };
+            let mut derived = vec![];
+
+            for quad in &delta {
+                self.apply_rules(known, *quad, &mut derived);
+            }
+
+            delta = derived
+                .into_iter()
+                .filter(|quad| !asserted.contains(quad) && inferred.insert(*quad))
+                .collect();
+
+            number_of_inferred_quads += delta.len();
+        }
+
+        Ok(number_of_inferred_quads)
+    }
+
+    /// Push in `derived` the conclusions of the enabled rules in which
+    /// `quad` is one of the premises
+    fn apply_rules(
+        &self,
+        known: KnownQuads,
+        quad: IdentifierQuad,
+        derived: &mut Vec<IdentifierQuad>,
+    ) {
+        let vocabulary = &self.vocabulary;
+
+        if self.rules.contains(OwlRule::SameAs) {
+            same_as(vocabulary, known, quad, derived);
+        }
+
+        if self.rules.contains(OwlRule::InverseOf) {
+            inverse_of(vocabulary, known, quad, derived);
+        }
+
+        if self.rules.contains(OwlRule::TransitiveProperty) {
+            transitive_property(vocabulary, known, quad, derived);
+        }
+
+        if self.rules.contains(OwlRule::SymmetricProperty) {
+            symmetric_property(vocabulary, known, quad, derived);
+        }
+
+        if self.rules.contains(OwlRule::EquivalentClass) {
+            equivalent_class(vocabulary, known, quad, derived);
+        }
+
+        if self.rules.contains(OwlRule::EquivalentProperty) {
+            equivalent_property(vocabulary, known, quad, derived);
+        }
+    }
+}
+
+/// The quads that the rules can use as premises
+#[derive(Clone, Copy)]
+struct KnownQuads<'a> {
+    asserted: &'a IndexingForest4,
+    inferred: &'a IndexingForest4,
+}
+
+impl<'a> KnownQuads<'a> {
+    /// Return the asserted and inferred quads that match the pattern
+    fn matching(
+        self,
+        pattern: [Option<u32>; NB_OF_TERMS],
+    ) -> std::iter::Chain<IndexingForest4Filter<'a>, IndexingForest4Filter<'a>> {
+        self.asserted
+            .filter(pattern)
+            .chain(self.inferred.filter(pattern))
+    }
+
+    /// Return true if the quad is asserted or inferred
+    fn contains(self, quad: &IdentifierQuad) -> bool {
+        self.asserted.contains(quad) || self.inferred.contains(quad)
+    }
+}
+
+/// `eq-sym`, `eq-trans` and `eq-rep-*`
+fn same_as(
+    vocabulary: &OwlVocabulary,
+    known: KnownQuads,
+    [s, p, o, g]: IdentifierQuad,
+    derived: &mut Vec<IdentifierQuad>,
+) {
+    let same_as = vocabulary.same_as;
+
+    if p == same_as {
+        derived.push([o, same_as, s, g]);
+
+        for [_, _, z, _] in known.matching([Some(o), Some(same_as), None, Some(g)]) {
+            derived.push([s, same_as, z, g]);
+        }
+
+        for [w, _, _, _] in known.matching([None, Some(same_as), Some(s), Some(g)]) {
+            derived.push([w, same_as, o, g]);
+        }
+
+        for [_, p2, o2, _] in known.matching([Some(s), None, None, Some(g)]) {
+            derived.push([o, p2, o2, g]);
+        }
+
+        for [s2, _, o2, _] in known.matching([None, Some(s), None, Some(g)]) {
+            derived.push([s2, o, o2, g]);
+        }
+
+        for [s2, p2, _, _] in known.matching([None, None, Some(s), Some(g)]) {
+            derived.push([s2, p2, o, g]);
+        }
+    }
+
+    for [_, _, s2, _] in known.matching([Some(s), Some(same_as), None, Some(g)]) {
+        derived.push([s2, p, o, g]);
+    }
+
+    for [_, _, p2, _] in known.matching([Some(p), Some(same_as), None, Some(g)]) {
+        derived.push([s, p2, o, g]);
+    }
+
+    for [_, _, o2, _] in known.matching([Some(o), Some(same_as), None, Some(g)]) {
+        derived.push([s, p, o2, g]);
+    }
+}
+
+/// `prp-inv1` and `prp-inv2`
+fn inverse_of(
+    vocabulary: &OwlVocabulary,
+    known: KnownQuads,
+    [s, p, o, g]: IdentifierQuad,
+    derived: &mut Vec<IdentifierQuad>,
+) {
+    let inverse_of = vocabulary.inverse_of;
+
+    if p == inverse_of {
+        for [x, _, y, _] in known.matching([None, Some(s), None, Some(g)]) {
+            derived.push([y, o, x, g]);
+        }
+
+        for [x, _, y, _] in known.matching([None, Some(o), None, Some(g)]) {
+            derived.push([y, s, x, g]);
+        }
+    }
+
+    for [_, _, inverse, _] in known.matching([Some(p), Some(inverse_of), None, Some(g)]) {
+        derived.push([o, inverse, s, g]);
+    }
+
+    for [inverse, _, _, _] in known.matching([None, Some(inverse_of), Some(p), Some(g)]) {
+        derived.push([o, inverse, s, g]);
+    }
+}
+
+/// `prp-trp`
+fn transitive_property(
+    vocabulary: &OwlVocabulary,
+    known: KnownQuads,
+    [s, p, o, g]: IdentifierQuad,
+    derived: &mut Vec<IdentifierQuad>,
+) {
+    if p == vocabulary.rdf_type && o == vocabulary.transitive_property {
+        for [x, _, y, _] in known.matching([None, Some(s), None, Some(g)]) {
+            for [_, _, z, _] in known.matching([Some(y), Some(s), None, Some(g)]) {
+                derived.push([x, s, z, g]);
+            }
+        }
+    }
+
+    if known.contains(&[p, vocabulary.rdf_type, vocabulary.transitive_property, g]) {
+        for [x, _, _, _] in known.matching([None, Some(p), Some(s), Some(g)]) {
+            derived.push([x, p, o, g]);
+        }
+
+        for [_, _, z, _] in known.matching([Some(o), Some(p), None, Some(g)]) {
+            derived.push([s, p, z, g]);
+        }
+    }
+}
+
+/// `prp-symp`
+fn symmetric_property(
+    vocabulary: &OwlVocabulary,
+    known: KnownQuads,
+    [s, p, o, g]: IdentifierQuad,
+    derived: &mut Vec<IdentifierQuad>,
+) {
+    if p == vocabulary.rdf_type && o == vocabulary.symmetric_property {
+        for [x, _, y, _] in known.matching([None, Some(s), None, Some(g)]) {
+            derived.push([y, s, x, g]);
+        }
+    }
+
+    if known.contains(&[p, vocabulary.rdf_type, vocabulary.symmetric_property, g]) {
+        derived.push([o, p, s, g]);
+    }
+}
+
+/// `cax-eqc1` and `cax-eqc2`
+fn equivalent_class(
+    vocabulary: &OwlVocabulary,
+    known: KnownQuads,
+    [s, p, o, g]: IdentifierQuad,
+    derived: &mut Vec<IdentifierQuad>,
+) {
+    let rdf_type = vocabulary.rdf_type;
+    let equivalent_class = vocabulary.equivalent_class;
+
+    if p == equivalent_class {
+        for [x, _, _, _] in known.matching([None, Some(rdf_type), Some(s), Some(g)]) {
+            derived.push([x, rdf_type, o, g]);
+        }
+
+        for [x, _, _, _] in known.matching([None, Some(rdf_type), Some(o), Some(g)]) {
+            derived.push([x, rdf_type, s, g]);
+        }
+    }
+
+    if p == rdf_type {
+        for [_, _, class, _] in known.matching([Some(o), Some(equivalent_class), None, Some(g)]) {
+            derived.push([s, rdf_type, class, g]);
+        }
+
+        for [class, _, _, _] in known.matching([None, Some(equivalent_class), Some(o), Some(g)]) {
+            derived.push([s, rdf_type, class, g]);
+        }
+    }
+}
+
+/// `prp-eqp1` and `prp-eqp2`
+fn equivalent_property(
+    vocabulary: &OwlVocabulary,
+    known: KnownQuads,
+    [s, p, o, g]: IdentifierQuad,
+    derived: &mut Vec<IdentifierQuad>,
+) {
+    let equivalent_property = vocabulary.equivalent_property;
+
+    if p == equivalent_property {
+        for [x, _, y, _] in known.matching([None, Some(s), None, Some(g)]) {
+            derived.push([x, o, y, g]);
+        }
+
+        for [x, _, y, _] in known.matching([None, Some(o), None, Some(g)]) {
+            derived.push([x, s, y, g]);
+        }
+    }
+
+    for [_, _, property, _] in known.matching([Some(p), Some(equivalent_property), None, Some(g)]) {
+        derived.push([s, property, o, g]);
+    }
+
+    for [property, _, _, _] in known.matching([None, Some(equivalent_property), Some(p), Some(g)]) {
+        derived.push([s, property, o, g]);
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use std::collections::BTreeSet;
+
+    const VOCABULARY: OwlVocabulary = OwlVocabulary {
+        rdf_type: 1,
+        same_as: 2,
+        inverse_of: 3,
+        transitive_property: 4,
+        symmetric_property: 5,
+        equivalent_class: 6,
+        equivalent_property: 7,
+    };
+
+    fn forest(quads: &[IdentifierQuad]) -> IndexingForest4 {
+        let mut forest = IndexingForest4::new();
+        for quad in quads {
+            forest.insert(*quad);
+        }
+        forest
+    }
+
+    fn quads_of(forest: &IndexingForest4) -> BTreeSet<IdentifierQuad> {
+        forest.filter([None; NB_OF_TERMS]).collect()
+    }
+
+    fn infer(rule: OwlRule, asserted: &[IdentifierQuad]) -> BTreeSet<IdentifierQuad> {
+        let asserted = forest(asserted);
+        let mut inferred = IndexingForest4::new();
+
+        OwlReasoner::new(VOCABULARY)
+            .with_rules(RuleSet::empty().with(rule))
+            .materialize(&asserted, &mut inferred)
+            .unwrap();
+
+        quads_of(&inferred)
+    }
+
+    /// Runs rounds that use every known quad as a premise until a round
+    /// infers nothing
+    fn infer_naively(asserted: &IndexingForest4) -> IndexingForest4 {
+        let one_round = OwlReasoner::new(VOCABULARY).with_max_iterations(1);
+        let mut inferred = IndexingForest4::new();
+
+        while one_round.materialize(asserted, &mut inferred) != Ok(0) {}
+
+        inferred
+    }
+
+    #[test]
+    fn applies_each_rule() {
+        let same_as = infer(OwlRule::SameAs, &[[10, 2, 11, 0], [10, 20, 30, 0]]);
+        assert!(same_as.contains(&[11, 2, 10, 0]));
+        assert!(same_as.contains(&[11, 20, 30, 0]));
+
+        let inverse_of = infer(OwlRule::InverseOf, &[[20, 3, 21, 0], [10, 20, 11, 0], [12, 21, 13, 0]]);
+        assert_eq!(inverse_of, [[11, 21, 10, 0], [13, 20, 12, 0]].iter().copied().collect());
+
+        let transitive = infer(OwlRule::TransitiveProperty, &[[20, 1, 4, 0], [10, 20, 11, 0], [11, 20, 12, 0]]);
+        assert_eq!(transitive, [[10, 20, 12, 0]].iter().copied().collect());
+
+        let symmetric = infer(OwlRule::SymmetricProperty, &[[20, 1, 5, 0], [10, 20, 11, 0]]);
+        assert_eq!(symmetric, [[11, 20, 10, 0]].iter().copied().collect());
+
+        let equivalent_class = infer(OwlRule::EquivalentClass, &[[30, 6, 31, 0], [10, 1, 30, 0], [11, 1, 31, 0]]);
+        assert_eq!(equivalent_class, [[10, 1, 31, 0], [11, 1, 30, 0]].iter().copied().collect());
+
+        let equivalent_property = infer(OwlRule::EquivalentProperty, &[[20, 7, 21, 0], [10, 20, 11, 0]]);
+        assert_eq!(equivalent_property, [[10, 21, 11, 0]].iter().copied().collect());
+    }
+
+    #[test]
+    fn only_joins_quads_of_the_same_graph() {
+        assert!(infer(OwlRule::SymmetricProperty, &[[20, 1, 5, 0], [10, 20, 11, 40]]).is_empty());
+    }
+
+    #[test]
+    fn reaches_a_fixpoint_on_cycles() {
+        let asserted = forest(&[
+            [10, 2, 11, 0],
+            [11, 2, 10, 0],
+            [20, 1, 4, 0],
+            [20, 1, 5, 0],
+            [10, 20, 11, 0],
+            [11, 20, 12, 0],
+        ]);
+        let mut inferred = IndexingForest4::new();
+        let reasoner = OwlReasoner::new(VOCABULARY);
+
+        let number_of_inferred_quads = reasoner.materialize(&asserted, &mut inferred).unwrap();
+        assert_eq!(quads_of(&inferred).len(), number_of_inferred_quads);
+        assert!(inferred.contains(&[12, 20, 12, 0]));
+        assert_eq!(reasoner.materialize(&asserted, &mut inferred), Ok(0));
+    }
+
+    #[test]
+    fn semi_naive_evaluation_matches_naive_evaluation() {
+        let asserted = forest(&[
+            [20, 1, 4, 0],
+            [21, 3, 20, 0],
+            [22, 7, 20, 0],
+            [30, 6, 31, 0],
+            [10, 22, 11, 0],
+            [11, 20, 12, 0],
+            [12, 2, 13, 0],
+            [13, 1, 30, 0],
+            [14, 1, 5, 0],
+            [10, 14, 13, 0],
+        ]);
+
+        let mut inferred = IndexingForest4::new();
+        OwlReasoner::new(VOCABULARY).materialize(&asserted, &mut inferred).unwrap();
+
+        assert!(!quads_of(&inferred).is_empty());
+        assert_eq!(quads_of(&inferred), quads_of(&infer_naively(&asserted)));
+    }
+
+    #[test]
+    fn stops_at_the_iteration_limit() {
+        let chain: Vec<IdentifierQuad> = (10..20).map(|x| [x, 20, x + 1, 0]).collect();
+        let mut asserted = forest(&chain);
+        asserted.insert([20, 1, 4, 0]);
+        let mut inferred = IndexingForest4::new();
+
+        let error = OwlReasoner::new(VOCABULARY)
+            .with_max_iterations(2)
+            .materialize(&asserted, &mut inferred)
+            .unwrap_err();
+
+        assert_eq!(error.iterations, 2);
+        assert_eq!(quads_of(&inferred).len(), error.inferred);
+        assert!(inferred.contains(&[10, 20, 12, 0]));
+        assert!(!inferred.contains(&[10, 20, 20, 0]));
+    }
+}
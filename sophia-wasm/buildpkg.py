@@ -47,6 +47,8 @@ def main():
     for file in extra_files:
         data["files"].append(get_bundler_name(file))
     
+    print("= Copying the wrapper")
+    shutil.copy("js/wrapper.js", "pkg/wrapper.js")
     data["files"].append("wrapper.js")
 
     data["sideEffects"] = False
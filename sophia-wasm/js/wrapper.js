@@ -0,0 +1,397 @@
+"use strict";
+// This file provides methods to wrap a Sophia Exported Dataset
+// to improve performances and remove memory leaks
+
+// TODO : a.union(b) where both a and b are wrapping the same class
+
+let n3 = require("n3");
+
+// Number of quads that are moved at once through the web assembly boundary
+const CHUNK_SIZE = 256;
+
+// The terms handed out by a quad are not freed with it
+function rebuild_quad(quad) {
+    return n3.DataFactory.quad(
+        rebuildTerm(quad.subject),
+        rebuildTerm(quad.predicate),
+        rebuildTerm(quad.object),
+        rebuildTerm(quad.graph)
+    );
+}
+
+function remakeFilter(filterFunction) {
+    return function (wasm_quad) {
+        let js_quad = rebuild_quad(wasm_quad);
+        wasm_quad.free();
+        return filterFunction(js_quad);
+    }
+}
+
+function copyTerm(wasmTerm) {
+    switch (wasmTerm.termType) {
+        case 'NamedNode': return n3.DataFactory.namedNode(wasmTerm.value);
+        case 'BlankNode': return n3.DataFactory.blankNode(wasmTerm.value);
+        case 'Variable': return n3.DataFactory.variable(wasmTerm.value);
+        case 'DefaultGraph': return n3.DataFactory.defaultGraph();
+        default:
+            if (wasmTerm.language !== '') {
+                return n3.DataFactory.literal(wasmTerm.value, wasmTerm.language);
+            }
+
+            let datatype = wasmTerm.datatype;
+            let term = n3.DataFactory.literal(wasmTerm.value, n3.DataFactory.namedNode(datatype.value));
+            datatype.free();
+            return term;
+    }
+}
+
+function rebuildTerm(wasmTerm) {
+    let term = copyTerm(wasmTerm);
+    wasmTerm.free();
+    return term;
+}
+
+class SophiaDatasetWrapper {
+    constructor(wrapped) {
+        this.base = wrapped;
+    }
+
+    // == Improve
+    addAll(quads, options) {
+        if (quads instanceof SophiaDatasetWrapper) {
+            quads = quads.base;
+        }
+
+        return this.base.addAll(quads, options);
+    }
+
+    addTriG(trigString, options) {
+        return this.base.addTriG(trigString, options);
+    }
+
+    addNQuads(nQuadsString, options) {
+        return this.base.addNQuads(nQuadsString, options);
+    }
+
+    toArray() {
+        let nquads = this.base.toNQuads();
+        let k = new n3.Parser({ format: 'N-Quads' }).parse(nquads);
+        return k;
+    }
+
+    forEach(quadRunIteratee) {
+        for (let quad of this) {
+            quadRunIteratee(quad);
+        }
+    }
+    
+    // == Create
+    *[Symbol.iterator]() {
+        // The quads are produced on demand from a snapshot of the dataset
+        let rustIterator = this.base.getIterator();
+
+        try {
+            while (true) {
+                let chunk = rustIterator.nextChunk(CHUNK_SIZE);
+                if (chunk.length === 0) {
+                    return;
+                }
+
+                for (let wasmQuad of chunk) {
+                    let quad = rebuild_quad(wasmQuad);
+                    wasmQuad.free();
+                    yield quad;
+                }
+            }
+        } finally {
+            rustIterator.free();
+        }
+    }
+
+    // == Fix
+    add(quad) {
+        this.base.add(quad);
+        return this;
+    }
+
+    delete(quad) {
+        this.base.delete(quad);
+        return this;
+    }
+
+    deleteMatches(subject, predicate, object, graph) {
+        this.base.deleteMatches(subject, predicate, object, graph);
+        return this;
+    }
+
+    // == Unleak
+    some(filter_function) {
+        let func = remakeFilter(filter_function);
+        return this.base.some(func);
+    }
+
+    every(filter_function) {
+        let func = remakeFilter(filter_function);
+        return this.base.every(func);
+    }
+
+    filter(filterFunction) {
+        let func = remakeFilter(filterFunction);
+        return new SophiaDatasetWrapper(this.base.filter(func));
+    }
+
+    reduce(reducer, initial_value) {
+        let newReducer = (acc, wasmQuad) => {
+            let jsQuad = rebuild_quad(wasmQuad);
+            wasmQuad.free();
+            return reducer(acc, jsQuad);
+        };
+
+        return this.base.reduce(newReducer, initial_value);
+    }
+
+    // Transmit
+    free() {
+        this.base.free();
+    }
+
+    has(quad) {
+        return this.base.has(quad);
+    }
+
+    get size() {
+        return this.base.size;
+    }
+
+    toString() {
+        return this.base.toString();
+    }
+
+    toTurtle(prefixes) {
+        return this.base.toTurtle(prefixes);
+    }
+
+    toTriG(prefixes) {
+        return this.base.toTriG(prefixes);
+    }
+
+    toNTriples() {
+        return this.base.toNTriples();
+    }
+
+    addTurtle(text, options) {
+        return this.base.addTurtle(text, options);
+    }
+
+    addNTriples(text, options) {
+        return this.base.addNTriples(text, options);
+    }
+
+    addRdfXml(text, options) {
+        return this.base.addRdfXml(text, options);
+    }
+
+    addJsonLd(document, options) {
+        return this.base.addJsonLd(document, options);
+    }
+
+    toJsonLd(context, options) {
+        return this.base.toJsonLd(context, options);
+    }
+
+    exportIds() {
+        return this.base.exportIds();
+    }
+
+    importIds(ids, terms) {
+        return this.base.importIds(ids, terms);
+    }
+
+    // == Streams
+    matchStream(subject, predicate, object, graph) {
+        return this.base.matchStream(subject, predicate, object, graph);
+    }
+
+    import(stream) {
+        return this.base.import(stream);
+    }
+
+    remove(stream) {
+        return this.base.remove(stream);
+    }
+
+    removeMatches(subject, predicate, object, graph) {
+        return this.base.removeMatches(subject, predicate, object, graph);
+    }
+
+    deleteGraph(graph) {
+        return this.base.deleteGraph(graph);
+    }
+
+    // == Change listeners
+    onChange(callback) {
+        return this.base.onChange((added, removed) => {
+            let rebuild = wasmQuads => wasmQuads.map(wasmQuad => {
+                let quad = rebuild_quad(wasmQuad);
+                wasmQuad.free();
+                return quad;
+            });
+
+            callback(rebuild(added), rebuild(removed));
+        });
+    }
+
+    offChange(id) {
+        return this.base.offChange(id);
+    }
+
+    // == History
+    enableHistory(limit) {
+        this.base.enableHistory(limit);
+    }
+
+    disableHistory() {
+        this.base.disableHistory();
+    }
+
+    beginStep(name) {
+        this.base.beginStep(name);
+    }
+
+    endStep() {
+        this.base.endStep();
+    }
+
+    undo() {
+        return this.base.undo();
+    }
+
+    redo() {
+        return this.base.redo();
+    }
+
+    get canUndo() {
+        return this.base.canUndo;
+    }
+
+    get canRedo() {
+        return this.base.canRedo;
+    }
+
+    get undoName() {
+        return this.base.undoName;
+    }
+
+    get redoName() {
+        return this.base.redoName;
+    }
+
+    // Rewrap
+    match(subject, predicate, object, graph, options) {
+        return new SophiaDatasetWrapper(this.base.match(subject, predicate, object, graph, options));
+    }
+
+    graph(graphName) {
+        return new SophiaDatasetWrapper(this.base.graph(graphName));
+    }
+
+    map(mapFunction) {
+        return new SophiaDatasetWrapper(this.base.map(mapFunction));
+    }
+
+    // Functions that should try to unwrap the other
+    difference(other) {
+        return new SophiaDatasetWrapper(this.base.difference(other));
+    }
+
+    intersection(other) {
+        return new SophiaDatasetWrapper(this.base.intersection(other));
+    }
+
+    union(other, options) {
+        return new SophiaDatasetWrapper(this.base.union(other, options));
+    }
+
+    equals(other) {
+        return this.base.equals(other);
+    }
+
+    contains(other) {
+        return this.base.contains(other);
+    }
+
+    isIsomorphic(other) {
+        return this.base.isIsomorphic(other);
+    }
+
+    toCanonical() {
+        return this.base.toCanonical();
+    }
+
+    canonicalHash() {
+        return this.base.canonicalHash();
+    }
+
+    diff(other) {
+        if (other instanceof SophiaDatasetWrapper) {
+            other = other.base;
+        }
+
+        return this.base.diff(other);
+    }
+
+    applyPatch(patch) {
+        return this.base.applyPatch(patch);
+    }
+
+    validate(shapes) {
+        if (shapes instanceof SophiaDatasetWrapper) {
+            shapes = shapes.base;
+        }
+
+        return new SophiaDatasetWrapper(this.base.validate(shapes));
+    }
+
+    validateShex(schema, shapeMap) {
+        return this.base.validateShex(schema, shapeMap).map(result => {
+            result.node = rebuildTerm(result.node);
+            return result;
+        });
+    }
+
+    skolemize(baseIri) {
+        return this.base.skolemize(baseIri);
+    }
+
+    deskolemize(baseIri) {
+        return this.base.deskolemize(baseIri);
+    }
+
+    // Named graphs
+    graphNames() {
+        return this.base.graphNames().map(rebuildTerm);
+    }
+
+    dropGraph(graphName) {
+        return this.base.dropGraph(graphName);
+    }
+
+    copyGraph(from, to) {
+        return this.base.copyGraph(from, to);
+    }
+
+    moveGraph(from, to) {
+        return this.base.moveGraph(from, to);
+    }
+
+    addGraph(graphName, triples) {
+        if (triples instanceof SophiaDatasetWrapper) {
+            triples = triples.base;
+        }
+
+        return this.base.addGraph(graphName, triples);
+    }
+}
+
+
+module.exports.SophiaDatasetWrapper = SophiaDatasetWrapper;
@@ -0,0 +1,119 @@
+//! Renaming of the blank nodes of imported quads.
+//!
+//! When two documents both use `_:b0`, importing them in the same dataset
+//! would fuse unrelated nodes. The importing functions (`union`, `addAll`,
+//! `addNQuads` and `addTriG`) accept an options object: if its
+//! `renameBlankNodes` property is true, every incoming blank node is given a
+//! fresh label, the same one for all its occurrences in the import.
+//!
+//! `addAll`, `addNQuads` and `addTriG` return an object whose `added`
+//! property is the number of added quads and, if the blank nodes were renamed,
+//! whose `blankNodeMapping` property maps the incoming labels to the fresh
+//! ones. `union` does not report the mapping: `addAll` on the union gives it.
+//!
+//! The blank nodes of JSON-LD documents are always renamed, as the labels
+//! that the JSON-LD to RDF algorithm gives them are only unique within a
+//! document.
+
+#![deny(missing_docs)]
+
+use bjdatasets::RcQuad;
+use js_sys::Reflect;
+use sophia::quad::Quad;
+use sophia::term::{ RcTerm, Term };
+use std::collections::HashMap;
+use uuid::Uuid;
+use wasm_bindgen::JsValue;
+
+/// Gives fresh labels to the blank nodes of an import
+#[derive(Default)]
+pub struct BlankNodeRenamer {
+    /// The fresh blank node of every incoming label
+    mapping: HashMap<String, RcTerm>
+}
+
+impl BlankNodeRenamer {
+    /// Returns a renamer if the Javascript options ask to rename the blank
+    /// nodes. The options may be undefined.
+    pub fn from_options(js_options: &JsValue) -> Result<Option<BlankNodeRenamer>, JsValue> {
+        if js_options.is_null() || js_options.is_undefined() {
+            return Ok(None);
+        }
+
+        let rename = Reflect::get(js_options, &JsValue::from_str("renameBlankNodes"))?;
+
+        if rename.is_truthy() {
+            Ok(Some(BlankNodeRenamer::default()))
+        } else {
+            Ok(None)
+        }
+    }
+
+    /// Returns the fresh blank node of `term` if it is a blank node, or
+    /// `term` itself
+    pub fn rename_term(&mut self, term: &RcTerm) -> RcTerm {
+        match term {
+            Term::BNode(_) => {
+                let label = term.value();
+                let label: &str = label.as_ref();
+
+                self.mapping.entry(label.to_string())
+                    .or_insert_with(|| {
+                        // An hyphenated UUID is always a valid blank node label
+                        RcTerm::new_bnode(Uuid::new_v4().to_hyphenated().to_string()).unwrap()
+                    })
+                    .clone()
+            },
+            _ => term.clone()
+        }
+    }
+
+    /// Returns the quad with its blank nodes renamed
+    pub fn rename_quad<Q>(&mut self, quad: &Q) -> RcQuad
+        where Q: Quad {
+        let quad = RcQuad::new_from_quad(quad);
+        let graph = quad.g().map(|graph| self.rename_term(graph));
+
+        RcQuad::new(
+            &self.rename_term(quad.s()),
+            &self.rename_term(quad.p()),
+            &self.rename_term(quad.o()),
+            graph.as_ref()
+        )
+    }
+
+    /// Returns an object that maps the incoming labels to the fresh ones
+    fn mapping(&self) -> Result<js_sys::Object, JsValue> {
+        let mapping = js_sys::Object::new();
+
+        for (incoming, fresh) in &self.mapping {
+            let fresh = fresh.value();
+            let fresh: &str = fresh.as_ref();
+            Reflect::set(&mapping, &JsValue::from_str(incoming), &JsValue::from_str(fresh))?;
+        }
+
+        Ok(mapping)
+    }
+}
+
+/// Returns the quad with its blank nodes renamed if a renamer is given
+pub fn import_quad<Q>(renamer: Option<&mut BlankNodeRenamer>, quad: &Q) -> RcQuad
+    where Q: Quad {
+    match renamer {
+        Some(renamer) => renamer.rename_quad(quad),
+        None => RcQuad::new_from_quad(quad)
+    }
+}
+
+/// Returns the result of an import, `{ added, blankNodeMapping }`. The
+/// mapping is only given if the blank nodes were renamed.
+pub fn import_result(added: usize, renamer: Option<&BlankNodeRenamer>) -> Result<JsValue, JsValue> {
+    let result = js_sys::Object::new();
+    Reflect::set(&result, &JsValue::from_str("added"), &JsValue::from(added as u32))?;
+
+    if let Some(renamer) = renamer {
+        Reflect::set(&result, &JsValue::from_str("blankNodeMapping"), &renamer.mapping()?)?;
+    }
+
+    Ok(result.into())
+}
@@ -0,0 +1,427 @@
+//! Notification of the changes of the exported datasets.
+//!
+//! Every mutating call of an exported dataset (`add`, `delete`,
+//! `deleteMatches`, `addAll`, the parsing functions...) produces one
+//! `ChangeSet` with the quads it actually added and removed. The change set is
+//! given to the `DatasetObserver` implementation of the `ExportableDataset`,
+//! and to the Javascript callbacks registered with `onChange`.
+//!
+//! The changes are recorded while the call mutates the dataset: the call goes
+//! through a `RecordingDataset`, which keeps the insertions and the removals
+//! that returned true. So the cost of the recording is proportional to the
+//! number of changed quads, not to the size of the dataset. For a dataset that
+//! infers quads, like `RdfsDataset`, only the inserted and removed quads are
+//! recorded, not the inferences they caused.
+//!
+//! The Javascript callbacks are called in a microtask, after the mutating
+//! call returned: the dataset is still borrowed by wasm_bindgen during the
+//! call, so a callback that reads it synchronously would throw.
+
+#![deny(missing_docs)]
+
+use bjdatasets::RcQuad;
+use bjdatasets::graphs::{ copy_graph_by_insertion, drop_graph_by_removal, move_graph_by_reinsertion, NamedGraphs };
+use bjdatasets::skolem::{ find_renamed_quads, renamed_quad, Skolemize };
+use crate::datamodel::quad::SophiaExportQuad;
+use sophia::dataset::DQuadSource;
+use sophia::dataset::Dataset;
+use sophia::dataset::MDResult;
+use sophia::dataset::MutableDataset;
+use sophia::quad::Quad;
+use sophia::term::RcTerm;
+use sophia::term::Term;
+use sophia::term::TermData;
+use sophia::term::TermError;
+use std::collections::HashMap;
+use std::collections::HashSet;
+use wasm_bindgen::prelude::*;
+
+#[wasm_bindgen]
+extern "C" {
+    #[wasm_bindgen(js_name = queueMicrotask)]
+    fn queue_microtask(callback: &JsValue);
+}
+
+/// An effective modification of a dataset
+#[derive(Clone)]
+pub enum Change {
+    /// The quad was inserted and was not in the dataset before
+    Inserted(RcQuad),
+    /// The quad was removed and was in the dataset before
+    Removed(RcQuad)
+}
+
+/// The effective changes of a dataset by one mutating call
+#[derive(Default)]
+pub struct ChangeSet {
+    /// The changes in the order they were done
+    changes: Vec<Change>
+}
+
+impl ChangeSet {
+    /// Returns true if the call changed nothing
+    pub fn is_empty(&self) -> bool {
+        self.changes.is_empty()
+    }
+
+    /// Returns the changes in the order they were done
+    pub fn changes(&self) -> &[Change] {
+        &self.changes
+    }
+
+    /// Returns the quads that were not in the dataset before the call and
+    /// are in it after
+    pub fn added(&self) -> Vec<RcQuad> {
+        self.net_changes(true)
+    }
+
+    /// Returns the quads that were in the dataset before the call and are no
+    /// longer in it after
+    pub fn removed(&self) -> Vec<RcQuad> {
+        self.net_changes(false)
+    }
+
+    /// Returns the quads whose first and last changes are both insertions,
+    /// or both removals.
+    ///
+    /// The changes of a quad are effective, so they alternate: a quad that
+    /// was inserted then removed is neither added nor removed by the call.
+    fn net_changes(&self, inserted: bool) -> Vec<RcQuad> {
+        let mut first_and_last: HashMap<&RcQuad, (bool, bool)> = HashMap::new();
+        let mut quads = vec![];
+
+        for change in &self.changes {
+            let (quad, is_insertion) = match change {
+                Change::Inserted(quad) => (quad, true),
+                Change::Removed(quad) => (quad, false)
+            };
+
+            match first_and_last.get_mut(quad) {
+                Some((_, last)) => *last = is_insertion,
+                None => {
+                    first_and_last.insert(quad, (is_insertion, is_insertion));
+                    quads.push(quad);
+                }
+            }
+        }
+
+        quads.into_iter()
+            .filter(|quad| first_and_last[quad] == (inserted, inserted))
+            .cloned()
+            .collect()
+    }
+}
+
+/// A mutable dataset that records in a change set the effective insertions
+/// and removals done through it.
+///
+/// Without a change set, every call is forwarded to the recorded dataset, so
+/// its specialized operations are kept.
+pub struct RecordingDataset<'a, D> {
+    /// The modified dataset
+    dataset: &'a mut D,
+    /// Where the changes are recorded, if they are observed
+    changes: Option<&'a mut ChangeSet>
+}
+
+impl<'a, D> RecordingDataset<'a, D> {
+    /// Records the changes of `dataset` in `changes`, if given
+    pub fn new(dataset: &'a mut D, changes: Option<&'a mut ChangeSet>) -> Self {
+        Self { dataset, changes }
+    }
+
+    /// Records the change if a change set was given
+    fn record(&mut self, change: Change) {
+        if let Some(changes) = self.changes.as_mut() {
+            changes.changes.push(change);
+        }
+    }
+}
+
+impl<'a, D> Dataset for RecordingDataset<'a, D> where D: Dataset {
+    type Quad = D::Quad;
+    type Error = D::Error;
+
+    fn quads(&self) -> DQuadSource<Self> {
+        self.dataset.quads()
+    }
+
+    fn quads_with_s<'s, TS>(&'s self, s: &'s Term<TS>) -> DQuadSource<'s, Self>
+    where TS: TermData {
+        self.dataset.quads_with_s(s)
+    }
+
+    fn quads_with_p<'s, TP>(&'s self, p: &'s Term<TP>) -> DQuadSource<'s, Self>
+    where TP: TermData {
+        self.dataset.quads_with_p(p)
+    }
+
+    fn quads_with_o<'s, TO>(&'s self, o: &'s Term<TO>) -> DQuadSource<'s, Self>
+    where TO: TermData {
+        self.dataset.quads_with_o(o)
+    }
+
+    fn quads_with_g<'s, TG>(&'s self, g: Option<&'s Term<TG>>) -> DQuadSource<'s, Self>
+    where TG: TermData {
+        self.dataset.quads_with_g(g)
+    }
+
+    fn quads_with_sp<'s, TS, TP>(&'s self, s: &'s Term<TS>, p: &'s Term<TP>) -> DQuadSource<'s, Self>
+    where TS: TermData, TP: TermData {
+        self.dataset.quads_with_sp(s, p)
+    }
+
+    fn quads_with_so<'s, TS, TO>(&'s self, s: &'s Term<TS>, o: &'s Term<TO>) -> DQuadSource<'s, Self>
+    where TS: TermData, TO: TermData {
+        self.dataset.quads_with_so(s, o)
+    }
+
+    fn quads_with_sg<'s, TS, TG>(&'s self, s: &'s Term<TS>, g: Option<&'s Term<TG>>) -> DQuadSource<'s, Self>
+    where TS: TermData, TG: TermData {
+        self.dataset.quads_with_sg(s, g)
+    }
+
+    fn quads_with_po<'s, TP, TO>(&'s self, p: &'s Term<TP>, o: &'s Term<TO>) -> DQuadSource<'s, Self>
+    where TP: TermData, TO: TermData {
+        self.dataset.quads_with_po(p, o)
+    }
+
+    fn quads_with_pg<'s, TP, TG>(&'s self, p: &'s Term<TP>, g: Option<&'s Term<TG>>) -> DQuadSource<'s, Self>
+    where TP: TermData, TG: TermData {
+        self.dataset.quads_with_pg(p, g)
+    }
+
+    fn quads_with_og<'s, TO, TG>(&'s self, o: &'s Term<TO>, g: Option<&'s Term<TG>>) -> DQuadSource<'s, Self>
+    where TO: TermData, TG: TermData {
+        self.dataset.quads_with_og(o, g)
+    }
+
+    fn quads_with_spo<'s, TS, TP, TO>(&'s self, s: &'s Term<TS>, p: &'s Term<TP>, o: &'s Term<TO>) -> DQuadSource<'s, Self>
+    where TS: TermData, TP: TermData, TO: TermData {
+        self.dataset.quads_with_spo(s, p, o)
+    }
+
+    fn quads_with_spg<'s, TS, TP, TG>(&'s self, s: &'s Term<TS>, p: &'s Term<TP>, g: Option<&'s Term<TG>>) -> DQuadSource<'s, Self>
+    where TS: TermData, TP: TermData, TG: TermData {
+        self.dataset.quads_with_spg(s, p, g)
+    }
+
+    fn quads_with_sog<'s, TS, TO, TG>(&'s self, s: &'s Term<TS>, o: &'s Term<TO>, g: Option<&'s Term<TG>>) -> DQuadSource<'s, Self>
+    where TS: TermData, TO: TermData, TG: TermData {
+        self.dataset.quads_with_sog(s, o, g)
+    }
+
+    fn quads_with_pog<'s, TP, TO, TG>(&'s self, p: &'s Term<TP>, o: &'s Term<TO>, g: Option<&'s Term<TG>>) -> DQuadSource<'s, Self>
+    where TP: TermData, TO: TermData, TG: TermData {
+        self.dataset.quads_with_pog(p, o, g)
+    }
+
+    fn quads_with_spog<'s, TS, TP, TO, TG>(&'s self, s: &'s Term<TS>, p: &'s Term<TP>, o: &'s Term<TO>, g: Option<&'s Term<TG>>) -> DQuadSource<'s, Self>
+    where TS: TermData, TP: TermData, TO: TermData, TG: TermData {
+        self.dataset.quads_with_spog(s, p, o, g)
+    }
+}
+
+impl<'a, D> MutableDataset for RecordingDataset<'a, D> where D: MutableDataset {
+    type MutationError = D::MutationError;
+
+    fn insert<T, U, V, W>(
+        &mut self,
+        s: &Term<T>,
+        p: &Term<U>,
+        o: &Term<V>,
+        g: Option<&Term<W>>,
+    ) -> MDResult<Self, bool>
+    where
+        T: TermData,
+        U: TermData,
+        V: TermData,
+        W: TermData,
+    {
+        let inserted = self.dataset.insert(s, p, o, g)?;
+
+        if inserted && self.changes.is_some() {
+            let g: Option<RcTerm> = g.map(|g| g.into());
+            self.record(Change::Inserted(RcQuad::new(&s.into(), &p.into(), &o.into(), g.as_ref())));
+        }
+
+        Ok(inserted)
+    }
+
+    fn remove<T, U, V, W>(
+        &mut self,
+        s: &Term<T>,
+        p: &Term<U>,
+        o: &Term<V>,
+        g: Option<&Term<W>>,
+    ) -> MDResult<Self, bool>
+    where
+        T: TermData,
+        U: TermData,
+        V: TermData,
+        W: TermData,
+    {
+        let removed = self.dataset.remove(s, p, o, g)?;
+
+        if removed && self.changes.is_some() {
+            let g: Option<RcTerm> = g.map(|g| g.into());
+            self.record(Change::Removed(RcQuad::new(&s.into(), &p.into(), &o.into(), g.as_ref())));
+        }
+
+        Ok(removed)
+    }
+}
+
+/// When recording, the graphs are handled quad by quad, so every changed quad
+/// is recorded
+impl<'a, D> NamedGraphs for RecordingDataset<'a, D> where D: NamedGraphs {
+    fn graph_names(&self) -> Vec<Option<RcTerm>> {
+        self.dataset.graph_names()
+    }
+
+    fn drop_graph(&mut self, g: Option<&RcTerm>) -> usize {
+        match self.changes {
+            None => self.dataset.drop_graph(g),
+            Some(_) => drop_graph_by_removal(self, g)
+        }
+    }
+
+    fn copy_graph(&mut self, from: Option<&RcTerm>, to: Option<&RcTerm>) -> usize {
+        match self.changes {
+            None => self.dataset.copy_graph(from, to),
+            Some(_) => copy_graph_by_insertion(self, from, to)
+        }
+    }
+
+    fn move_graph(&mut self, from: Option<&RcTerm>, to: Option<&RcTerm>) -> usize {
+        match self.changes {
+            None => self.dataset.move_graph(from, to),
+            Some(_) => move_graph_by_reinsertion(self, from, to)
+        }
+    }
+}
+
+impl<'a, D> Skolemize for RecordingDataset<'a, D> where D: Skolemize + Dataset {
+    /// Renames the terms with the renaming of the recorded dataset. When
+    /// recording, the quads that contain a renamed term are found first with
+    /// `find_renamed_quads`: they are removed, and their renamed versions are
+    /// inserted unless they were already in the dataset. The dataset is not
+    /// renamed by reinsertion, which would for instance assert the inferred
+    /// quads of an `RdfsDataset`.
+    fn rename_terms<F>(&mut self, mut rename: F) -> Result<usize, TermError>
+        where F: FnMut(&RcTerm) -> Result<Option<RcTerm>, TermError> {
+        if self.changes.is_none() {
+            return self.dataset.rename_terms(rename);
+        }
+
+        let (renaming, renamed_quads) = find_renamed_quads(&*self.dataset, &mut rename)?;
+        let new_quads: Vec<RcQuad> = renamed_quads.iter()
+            .map(|quad| renamed_quad(&renaming, quad))
+            .collect();
+
+        // A new quad that was in the dataset is not inserted by the renaming,
+        // unless it is itself renamed
+        let removed: HashSet<&RcQuad> = renamed_quads.iter().collect();
+        let mut inserted = HashSet::new();
+        let inserted_quads: Vec<RcQuad> = new_quads.into_iter()
+            .filter(|quad| removed.contains(quad)
+                || !self.dataset.contains(quad.s(), quad.p(), quad.o(), quad.g()).unwrap())
+            .filter(|quad| inserted.insert(quad.clone()))
+            .collect();
+
+        let number_of_renamed_terms = self.dataset.rename_terms(|term| match renaming.get(term) {
+            Some(new_term) => Ok(new_term.clone()),
+            None => rename(term)
+        })?;
+
+        for quad in &renamed_quads {
+            self.record(Change::Removed(quad.clone()));
+        }
+
+        for quad in inserted_quads {
+            self.record(Change::Inserted(quad));
+        }
+
+        Ok(number_of_renamed_terms)
+    }
+}
+
+/// Converts quads into a Javascript array of exported quads
+fn to_js_array(quads: &[RcQuad]) -> js_sys::Array {
+    quads.iter()
+        .map(|quad| JsValue::from(SophiaExportQuad::new_from_quad(quad)))
+        .collect()
+}
+
+/// Reacts to the changes of an exported dataset.
+///
+/// The changes are only recorded if `observes_changes` returns true.
+pub trait DatasetObserver {
+    /// Returns true if `on_change` has to be called after the mutating calls
+    fn observes_changes(&self) -> bool {
+        false
+    }
+
+    /// Receives the quads changed by a mutating call. Only called with non
+    /// empty change sets.
+    fn on_change(&mut self, _changes: &ChangeSet) {}
+}
+
+/// The Javascript callbacks registered on an exported dataset
+#[derive(Default)]
+pub struct ChangeListeners {
+    /// The identifier of the last registered callback
+    last_id: u32,
+    /// The registered callbacks and their identifiers
+    callbacks: Vec<(u32, js_sys::Function)>
+}
+
+impl ChangeListeners {
+    /// Registers `callback` and returns the identifier to unregister it
+    pub fn add(&mut self, callback: js_sys::Function) -> u32 {
+        self.last_id += 1;
+        self.callbacks.push((self.last_id, callback));
+        self.last_id
+    }
+
+    /// Unregisters the callback with the given identifier. Returns false if
+    /// there was no such callback.
+    pub fn remove(&mut self, id: u32) -> bool {
+        let number_of_callbacks = self.callbacks.len();
+        self.callbacks.retain(|(callback_id, _)| *callback_id != id);
+        self.callbacks.len() != number_of_callbacks
+    }
+}
+
+impl DatasetObserver for ChangeListeners {
+    fn observes_changes(&self) -> bool {
+        !self.callbacks.is_empty()
+    }
+
+    /// Calls every callback with the array of added quads and the array of
+    /// removed quads in a microtask, unless the call removed every quad it
+    /// added and added back every quad it removed
+    fn on_change(&mut self, changes: &ChangeSet) {
+        let (added, removed) = (changes.added(), changes.removed());
+
+        if added.is_empty() && removed.is_empty() {
+            return;
+        }
+
+        let added = to_js_array(&added);
+        let removed = to_js_array(&removed);
+        let callbacks: Vec<js_sys::Function> = self.callbacks.iter()
+            .map(|(_, callback)| callback.clone())
+            .collect();
+
+        let dispatch = Closure::once_into_js(move || {
+            for callback in callbacks {
+                // An exception in a callback must not prevent the next ones
+                // from being called
+                let _ = callback.call2(&JsValue::NULL, &added, &removed);
+            }
+        });
+
+        queue_microtask(&dispatch);
+    }
+}
@@ -7,8 +7,11 @@ extern crate wasm_bindgen;
 
 use crate::datamodel::term::*;
 use crate::datamodel::quad::*;
+use crate::datamodel::interning;
 use crate::dataset::SophiaExportDataset;
 
+use crate::error::{ term_error, type_error };
+
 use sophia::term::*;
 use uuid::Uuid;
 use wasm_bindgen::prelude::*;
@@ -32,20 +35,20 @@ impl SophiaExportDataFactory {
 
     /// Returns a named node that concerns the given URL
     #[wasm_bindgen(js_name="namedNode")]
-    pub fn named_node(value: &str) -> SophiaExportTerm {
-        SophiaExportTerm { term: Some(RcTerm::new_iri(value).unwrap()) }
+    pub fn named_node(value: &str) -> Result<SophiaExportTerm, JsValue> {
+        Ok(SophiaExportTerm { term: Some(RcTerm::new_iri(value).map_err(term_error)?), shared: false })
     }
 
     /// Returns a blank node that either contains the given URL or a randomly
     /// generated ID using Uuid v4
     #[wasm_bindgen(js_name="blankNode")]
-    pub fn blank_node(value: Option<String>) -> SophiaExportTerm {
+    pub fn blank_node(value: Option<String>) -> Result<SophiaExportTerm, JsValue> {
         let blank_node_name = match value {
             Some(determined_name) => determined_name.to_string(),
             None => Uuid::new_v4().to_hyphenated().to_string()
         };
 
-        SophiaExportTerm { term: Some(RcTerm::new_bnode(blank_node_name).unwrap()) }
+        Ok(SophiaExportTerm { term: Some(RcTerm::new_bnode(blank_node_name).map_err(term_error)?), shared: false })
     }
 
     /// Returns a new ltieral. If `language_or_data` is a Named Node, it will
@@ -53,15 +56,15 @@ impl SophiaExportDataFactory {
     /// spefification il will be used as the language. If undefined, it will be
     /// string.
     #[wasm_bindgen(js_name="literal")]
-    pub fn literal(value_string: Option<String>, language_or_datatype: &JsValue) -> SophiaExportTerm {
+    pub fn literal(value_string: Option<String>, language_or_datatype: &JsValue) -> Result<SophiaExportTerm, JsValue> {
         let value = match value_string.as_ref() {
             None => "",
             Some(contained_value) => contained_value.as_str()
         };
 
         if language_or_datatype.is_null() || language_or_datatype.is_undefined() {
-            let string_term = RcTerm::new_iri("http://www.w3.org/2001/XMLSchema#string").unwrap();
-            SophiaExportTerm { term: Some(RcTerm::new_literal_dt(value, string_term).unwrap()) }
+            let string_term = RcTerm::new_iri_unchecked("http://www.w3.org/2001/XMLSchema#string", true);
+            Ok(SophiaExportTerm { term: Some(RcTerm::new_literal_dt(value, string_term).map_err(term_error)?), shared: false })
         } else {
             match language_or_datatype.as_string() {
                 Some(language) => Self::literal_from_string(value, language.as_str()),
@@ -75,80 +78,83 @@ impl SophiaExportDataFactory {
 
     /// Builds a literal using the passed BCP 47 formatted language
     #[wasm_bindgen(js_name="literalFromString")]
-    pub fn literal_from_string(value: &str, language: &str) -> SophiaExportTerm {
-        SophiaExportTerm {
-            term: Some(RcTerm::new_literal_lang(value, language).unwrap())
-        }
+    pub fn literal_from_string(value: &str, language: &str) -> Result<SophiaExportTerm, JsValue> {
+        Ok(SophiaExportTerm {
+            term: Some(RcTerm::new_literal_lang(value, language).map_err(term_error)?),
+            shared: false
+        })
     }
 
     /// Builds a literal of passed the datatype
     #[wasm_bindgen(js_name="literalFromNamedNode")]
-    pub fn literal_from_named_node(value: &str, named_node: &JsImportTerm) -> SophiaExportTerm {
-        let rcterm = build_rcterm_from_js_import_term(named_node);
-        SophiaExportTerm { term: Some(RcTerm::new_literal_dt(value, rcterm.unwrap()).unwrap()) }
+    pub fn literal_from_named_node(value: &str, named_node: &JsImportTerm) -> Result<SophiaExportTerm, JsValue> {
+        let rcterm = build_quad_component_from_js_import_term(named_node)?;
+        Ok(SophiaExportTerm { term: Some(RcTerm::new_literal_dt(value, rcterm).map_err(term_error)?), shared: false })
     }
 
     /// Builds a new variable
     #[wasm_bindgen(js_name="variable")]
-    pub fn variable(optional_value: Option<String>) -> SophiaExportTerm {
+    pub fn variable(optional_value: Option<String>) -> Result<SophiaExportTerm, JsValue> {
         let value = match optional_value.as_ref() {
             None => "__NoVariableName__",
             Some(contained_value) => contained_value.as_str()
         };
 
-        SophiaExportTerm { term: Some(RcTerm::new_variable(value).unwrap()) }
+        Ok(SophiaExportTerm { term: Some(RcTerm::new_variable(value).map_err(term_error)?), shared: false })
     }
 
     /// Returns a term that represents the default graph
     #[wasm_bindgen(js_name="defaultGraph")]
     pub fn default_graph() -> SophiaExportTerm {
-        SophiaExportTerm { term: None }
+        SophiaExportTerm::default_graph()
     }
 
     /// Retuns a quad managed by Sophia's back end with the given subject,
     /// predicate, object and graph. If no graph is passed, the default graph
     /// will be used.
     #[wasm_bindgen(js_name="quad")]
-    pub fn quad(subject: &JsImportTerm, predicate: &JsImportTerm, object: &JsImportTerm, graph: &JsImportTerm) -> SophiaExportQuad {
-        SophiaExportQuad {
-            _subject: build_rcterm_from_js_import_term(subject).unwrap(),
-            _predicate: build_rcterm_from_js_import_term(predicate).unwrap(),
-            _object: build_rcterm_from_js_import_term(object).unwrap(),
-            _graph: if graph.is_null() || graph.is_undefined() {
-                None
-            } else {
-                build_rcterm_from_js_import_term(graph)
-            }
-        }
+    pub fn quad(subject: &JsImportTerm, predicate: &JsImportTerm, object: &JsImportTerm, graph: &JsImportTerm) -> Result<SophiaExportQuad, JsValue> {
+        let graph = if graph.is_null() || graph.is_undefined() {
+            None
+        } else {
+            build_rcterm_from_js_import_term(graph)?
+        };
+
+        Ok(SophiaExportQuad::new(
+            &build_quad_component_from_js_import_term(subject)?,
+            &build_quad_component_from_js_import_term(predicate)?,
+            &build_quad_component_from_js_import_term(object)?,
+            graph.as_ref()
+        ))
     }
 
     /// Returns a quad managed by Sophia's back end with the given subject,
     /// predicate and object. The assigned graph will be the default graph.
     #[wasm_bindgen(js_name="triple")]
-    pub fn triple(subject: &JsImportTerm, predicate: &JsImportTerm, object: &JsImportTerm) -> SophiaExportQuad {
-        SophiaExportQuad {
-            _subject: build_rcterm_from_js_import_term(subject).unwrap(),
-            _predicate: build_rcterm_from_js_import_term(predicate).unwrap(),
-            _object: build_rcterm_from_js_import_term(object).unwrap(),
-            _graph: None
-        }
+    pub fn triple(subject: &JsImportTerm, predicate: &JsImportTerm, object: &JsImportTerm) -> Result<SophiaExportQuad, JsValue> {
+        Ok(SophiaExportQuad::new(
+            &build_quad_component_from_js_import_term(subject)?,
+            &build_quad_component_from_js_import_term(predicate)?,
+            &build_quad_component_from_js_import_term(object)?,
+            None
+        ))
     }
 
     /// Returns a new term managed by Sophia's back end that is identical wrt
     /// RDF.JS specification
     #[wasm_bindgen(js_name="fromTerm")]
-    pub fn from_term(original: &JsImportTerm) -> SophiaExportTerm {
-        if original.term_type().as_str() == "DefaultGraph" {
-            SophiaExportDataFactory::default_graph()
-        } else {
-            SophiaExportTerm { term: build_rcterm_from_js_import_term(original) }
-        }
+    pub fn from_term(original: &JsImportTerm) -> Result<SophiaExportTerm, JsValue> {
+        Ok(SophiaExportTerm { term: build_rcterm_from_js_import_term(original)?, shared: false })
     }
 
     /// Returns a new quad managed by Sophia's back end that is identical wrt
     /// RDF.JS specification
     #[wasm_bindgen(js_name="fromQuad")]
-    pub fn from_quad(original: &JsImportQuad) -> SophiaExportQuad {
+    pub fn from_quad(original: &JsImportQuad) -> Result<SophiaExportQuad, JsValue> {
+        if let Some(quad) = crate::registry::take_back::<SophiaExportQuad>(original) {
+            return Ok(quad);
+        }
+
         Self::quad(
             &original.subject(),
             &original.predicate(),
@@ -157,27 +163,46 @@ impl SophiaExportDataFactory {
         )
     }
 
+    /// Returns the stable handle of a term equal to `original`: interning
+    /// equal terms always returns the same object.
+    ///
+    /// The returned term is owned by the term cache, so it must not be freed,
+    /// and it can not be modified. The cache keeps the 4096 most recently
+    /// interned terms: the older ones are forgotten like by `clearTermCache`.
+    #[wasm_bindgen(js_name="internTerm")]
+    pub fn intern_term(original: &JsImportTerm) -> Result<JsValue, JsValue> {
+        Ok(interning::intern_term(build_rcterm_from_js_import_term(original)?))
+    }
+
+    /// Empties the term cache. The previously interned terms stay usable but
+    /// are not returned anymore by `internTerm`, so they can then be freed.
+    #[wasm_bindgen(js_name="clearTermCache")]
+    pub fn clear_term_cache() {
+        interning::clear_term_cache();
+    }
+
     /// Returns a dataset managed by Sophia's back end. `parameter` can either
     /// be undefined, another dataset or a sequence of quads. If `parameter`
     /// isn't undefined, the returned dataset will contain every quad in the
     /// given argument.
     #[wasm_bindgen(js_name="dataset")]
-    pub fn dataset(parameter: &JsValue) -> SophiaExportDataset {
+    pub fn dataset(parameter: &JsValue) -> Result<SophiaExportDataset, JsValue> {
         let mut ds = SophiaExportDataset::new();
 
         if !parameter.is_null() && !parameter.is_undefined() {
-            // TODO : Error Management - Do we want to crash if the parameter is not an array ?
-            // TODO : Manage every iterable (js_array::Array probably only manager javascript arrays)
-            let parameter_copy: JsValue = parameter.clone();
-            let js_array: js_sys::Array = parameter_copy.into();
-
-            js_array.iter().for_each(|js_value| {
-                // TODO : Error Management - What if the cast from JsValue to JsImportQuad fails ?
-                let js_quad: JsImportQuad = js_value.into();
-                ds.add(&js_quad);
-            });
+            let iterator = js_sys::try_iter(parameter)?
+                .ok_or_else(|| type_error("DataFactory.dataset expects a sequence of quads"))?;
+
+            interning::with_import_memo(|| -> Result<(), JsValue> {
+                for js_value in iterator {
+                    let js_quad: JsImportQuad = js_value?.into();
+                    ds.add(&js_quad)?;
+                }
+
+                Ok(())
+            })?;
         }
 
-        ds
+        Ok(ds)
     }
 }
@@ -0,0 +1,170 @@
+//! Caches of the terms that cross the web assembly boundary.
+//!
+//! - `DataFactory.internTerm` hands out stable term handles: interning equal
+//!   terms always returns the same Javascript object, so hot loops do not
+//!   allocate a new `SophiaExportTerm` for every use of a term. The handles
+//!   are shared, so they can not be modified, and they are owned by the cache
+//!   so they must not be freed. Only the explicitly interned terms are kept,
+//!   and at most the `INTERNED_CAPACITY` most recently interned ones: the
+//!   least recently interned term is forgotten when the cache is full, and
+//!   `DataFactory.clearTermCache` forgets them all. A forgotten handle stays
+//!   usable and is then owned by Javascript, which frees it.
+//! - The Javascript term objects imported by a bulk import (`addAll`,
+//!   `addGraph`, `DataFactory.dataset`...) are memoized by identity, so a term
+//!   object used by many quads is only read once through its `termType`,
+//!   `value`, `language` and `datatype` properties. The memo only lives during
+//!   the import, so the imported objects and their terms are not kept after
+//!   it returns.
+
+#![deny(missing_docs)]
+
+use crate::datamodel::term::{ JsImportTerm, SophiaExportTerm };
+use sophia::term::RcTerm;
+use std::cell::RefCell;
+use std::collections::{ BTreeMap, HashMap };
+use wasm_bindgen::prelude::*;
+use wasm_bindgen::JsCast;
+
+/// Maximum number of terms kept by the term cache
+const INTERNED_CAPACITY: usize = 4096;
+
+/// The handles given by `intern_term`, of which the least recently used one
+/// is forgotten when the cache is full
+#[derive(Default)]
+struct TermCache {
+    /// The handle of every interned term and the time of its last use
+    handles: HashMap<Option<RcTerm>, (JsValue, u64)>,
+    /// The interned terms by time of last use
+    uses: BTreeMap<u64, Option<RcTerm>>,
+    /// The time of the next use
+    clock: u64
+}
+
+impl TermCache {
+    /// Returns the handle of the term, building it if it is not cached or if
+    /// Javascript freed it
+    fn handle(&mut self, term: Option<RcTerm>) -> JsValue {
+        let now = self.clock;
+        self.clock += 1;
+
+        if let Some((handle, last_use)) = self.handles.get_mut(&term) {
+            if is_alive(handle) {
+                self.uses.remove(last_use);
+                self.uses.insert(now, term);
+                *last_use = now;
+                return handle.clone();
+            }
+        }
+
+        if let Some((_, last_use)) = self.handles.remove(&term) {
+            self.uses.remove(&last_use);
+        }
+
+        if self.handles.len() >= INTERNED_CAPACITY {
+            let oldest = self.uses.keys().next().copied();
+
+            if let Some(oldest) = oldest.and_then(|oldest| self.uses.remove(&oldest)) {
+                self.handles.remove(&oldest);
+            }
+        }
+
+        let handle: JsValue = SophiaExportTerm::shared(term.clone()).into();
+        self.uses.insert(now, term.clone());
+        self.handles.insert(term, (handle.clone(), now));
+        handle
+    }
+}
+
+/// The terms imported by the running bulk import
+#[derive(Default)]
+struct ImportMemo {
+    /// Maps the already imported Javascript term objects to the index of
+    /// their term. Only built at the first import.
+    objects: Option<js_sys::Map>,
+    /// The imported terms
+    terms: Vec<Option<RcTerm>>
+}
+
+thread_local! {
+    /// The handles given by `intern_term`
+    static INTERNED: RefCell<TermCache> = RefCell::new(TermCache::default());
+
+    /// The memo of the running bulk import, if any
+    static IMPORT_MEMO: RefCell<Option<ImportMemo>> = RefCell::new(None);
+}
+
+/// Returns the stable handle of the given term
+pub fn intern_term(term: Option<RcTerm>) -> JsValue {
+    INTERNED.with(|interned| interned.borrow_mut().handle(term))
+}
+
+/// Forgets every interned term. The handles that were previously given stay
+/// usable but are not stable anymore.
+pub fn clear_term_cache() {
+    INTERNED.with(|interned| *interned.borrow_mut() = TermCache::default());
+}
+
+/// Returns false if the handed out object has been freed by Javascript
+pub fn is_alive(handle: &JsValue) -> bool {
+    // A freed wasm_bindgen object has a null pointer
+    js_sys::Reflect::get(handle, &JsValue::from_str("ptr"))
+        .ok()
+        .and_then(|ptr| ptr.as_f64())
+        .map_or(false, |ptr| ptr != 0.0)
+}
+
+/// Runs a bulk import, during which the imported Javascript terms are
+/// memoized by identity. The imports nested in another one share its memo.
+pub fn with_import_memo<R>(import: impl FnOnce() -> R) -> R {
+    let is_outermost = IMPORT_MEMO.with(|memo| {
+        let mut memo = memo.borrow_mut();
+
+        match *memo {
+            Some(_) => false,
+            None => {
+                *memo = Some(ImportMemo::default());
+                true
+            }
+        }
+    });
+
+    let result = import();
+
+    if is_outermost {
+        IMPORT_MEMO.with(|memo| *memo.borrow_mut() = None);
+    }
+
+    result
+}
+
+/// Returns the term that was memoized for the given Javascript object, if it
+/// has already been imported by the running bulk import
+pub fn memoized_term(js_term: &JsImportTerm) -> Option<Option<RcTerm>> {
+    if !js_term.is_object() {
+        return None;
+    }
+
+    IMPORT_MEMO.with(|memo| {
+        let memo = memo.borrow();
+        let memo = memo.as_ref()?;
+        let index = memo.objects.as_ref()?.get(js_term.unchecked_ref()).as_f64()?;
+        memo.terms.get(index as usize).cloned()
+    })
+}
+
+/// Remembers that the given Javascript object is the given term, if a bulk
+/// import is running
+pub fn memoize_term(js_term: &JsImportTerm, term: &Option<RcTerm>) {
+    if !js_term.is_object() {
+        return;
+    }
+
+    IMPORT_MEMO.with(|memo| {
+        if let Some(memo) = memo.borrow_mut().as_mut() {
+            let index = memo.terms.len();
+            memo.terms.push(term.clone());
+            memo.objects.get_or_insert_with(js_sys::Map::new)
+                .set(js_term.unchecked_ref(), &JsValue::from(index as u32));
+        }
+    })
+}
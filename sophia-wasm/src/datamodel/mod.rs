@@ -1,4 +1,5 @@
 
 pub mod factory;
+pub mod interning;
 pub mod quad;
 pub mod term;
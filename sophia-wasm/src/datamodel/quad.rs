@@ -5,9 +5,11 @@
 
 extern crate wasm_bindgen;
 
+use crate::datamodel::interning::is_alive;
 use crate::datamodel::term::*;
 
 use sophia::term::*;
+use std::cell::RefCell;
 use std::rc::Rc;
 use wasm_bindgen::prelude::*;
 use sophia::quad::Quad;
@@ -57,16 +59,6 @@ extern "C" {
     /// Returns true if the two quads are equals according to RDF.JS specification
     #[wasm_bindgen(js_name=equals)]
     pub fn quads_equals(this: &JsImportQuad, other_quad: &JsImportQuad);
-    
-    /// Returns a pointer to this quad.
-    /// 
-    /// This is mainly used to be able to detect a Rust managed quad from an
-    /// imported quad (as wasm_bindgen doesn't let us use polymorphism).
-    /// 
-    /// Using this method can be very unsafe because it supposes only this
-    /// object has a method called getRustPtr in the Javascript world. 
-    #[wasm_bindgen(method, getter=getRustPtr)]
-    pub fn quads_get_rust_ptr(this: &JsImportQuad) -> JsValue;
 }
 
 
@@ -74,6 +66,12 @@ extern "C" {
 //   ==== EXPORTATION ==== EXPORTATION ==== EXPORTATION ==== EXPORTATION ====
 
 /// A SophiaExportQuad owns its data in the form of four RcTerms.
+///
+/// The terms handed out by its getters are built once and shared, so reading
+/// a component several times returns the same object. These terms can not be
+/// modified. They are owned by Javascript, so they stay usable after the quad
+/// is freed and must be freed on their own; a freed term is built again by
+/// the next read.
 #[wasm_bindgen(js_name = Quad)]
 pub struct SophiaExportQuad {
     /// Subject of the quad
@@ -87,7 +85,9 @@ pub struct SophiaExportQuad {
     pub _object: RcTerm,
     /// Graph of the quad. The default graph is represented as None
     #[wasm_bindgen(skip)]
-    pub _graph: Option<RcTerm>
+    pub _graph: Option<RcTerm>,
+    /// The terms handed out for the subject, predicate, object and graph
+    handles: RefCell<[Option<JsValue>; 4]>
 }
 
 // A SophiaExportQuad is a trivial to implement as a quad
@@ -107,7 +107,8 @@ impl SophiaExportQuad {
             _subject: s.clone(),
             _predicate: p.clone(),
             _object: o.clone(),
-            _graph: g.cloned()
+            _graph: g.cloned(),
+            handles: Default::default()
         }
     }
 
@@ -118,7 +119,8 @@ impl SophiaExportQuad {
             _subject: quad.s().clone(),
             _predicate: quad.p().clone(),
             _object: quad.o().clone(),
-            _graph: quad.g().cloned()
+            _graph: quad.g().cloned(),
+            handles: Default::default()
         }
     }
 
@@ -129,38 +131,62 @@ impl SophiaExportQuad {
             _subject: quad.s().into(),
             _predicate: quad.p().into(),
             _object: quad.o().into(),
-            _graph: quad.g().clone().map(|t| t.into())
+            _graph: quad.g().clone().map(|t| t.into()),
+            handles: Default::default()
         }
     }
+
+    /// Returns the handed out term of the component at the given position,
+    /// building it at the first access or if Javascript freed it
+    fn handle(&self, position: usize) -> JsValue {
+        let mut handles = self.handles.borrow_mut();
+
+        if let Some(handle) = handles[position].as_ref().filter(|handle| is_alive(handle)) {
+            return handle.clone();
+        }
+
+        let term = match position {
+            0 => Some(self._subject.clone()),
+            1 => Some(self._predicate.clone()),
+            2 => Some(self._object.clone()),
+            _ => self._graph.clone()
+        };
+
+        let handle: JsValue = SophiaExportTerm::shared(term).into();
+        handles[position] = Some(handle.clone());
+        handle
+    }
+
+    /// Forgets the handed out term of the component at the given position
+    fn release_handle(&mut self, position: usize) {
+        self.handles.get_mut()[position] = None;
+    }
 }
 
 #[wasm_bindgen(js_class = Quad)]
 impl SophiaExportQuad {
     /// Returns the subject of the quad
     #[wasm_bindgen(method, getter)]
-    pub fn subject(&self) -> SophiaExportTerm {
-        SophiaExportTerm::new(&self._subject)
+    pub fn subject(&self) -> JsValue {
+        self.handle(0)
     }
 
     /// Returns the predicate of the quad
     #[wasm_bindgen(method, getter)]
-    pub fn predicate(&self) -> SophiaExportTerm {
-        SophiaExportTerm::new(&self._predicate)
+    pub fn predicate(&self) -> JsValue {
+        self.handle(1)
     }
 
     /// Returns the object of the quad
     #[wasm_bindgen(method, getter)]
-    pub fn object(&self) -> SophiaExportTerm {
-        SophiaExportTerm::new(&self._object)
+    pub fn object(&self) -> JsValue {
+        self.handle(2)
     }
 
     /// Returns the graph of the quad
     #[wasm_bindgen(method, getter)]
-    pub fn graph(&self) -> SophiaExportTerm {
-        match &self._graph {
-            None => SophiaExportTerm::default_graph(),
-            Some(term) => SophiaExportTerm::new(term)
-        }
+    pub fn graph(&self) -> JsValue {
+        self.handle(3)
     }
 }
 
@@ -186,22 +212,16 @@ impl SophiaExportQuad {
         if other.is_null() {
             false
         } else {
-            let ptr = other.quads_get_rust_ptr().as_f64();
-
-            let ptr = match ptr {
-                None => None,
-                Some(ptr_val) => {
-                    let ptr_u32 = ptr_val as u32;
-                    let ptr_ptr = ptr_u32 as *const SophiaExportQuad;
-                    unsafe { ptr_ptr.as_ref() }
-                }
-            };
-
-            match ptr {
-                None => self.subject().equals(&other.subject())
-                        && self.predicate().equals(&other.predicate())
-                        && self.object().equals(&other.object())
-                        && self.graph().equals(&other.graph()),
+            let exported_rust_quad = crate::registry::take_back::<SophiaExportQuad>(other);
+
+            match exported_rust_quad {
+                None => SophiaExportTerm::new(&self._subject).equals(&other.subject())
+                        && SophiaExportTerm::new(&self._predicate).equals(&other.predicate())
+                        && SophiaExportTerm::new(&self._object).equals(&other.object())
+                        && match &self._graph {
+                            None => SophiaExportTerm::default_graph(),
+                            Some(graph) => SophiaExportTerm::new(graph)
+                        }.equals(&other.graph()),
                 Some(exported_rust_quad) => {
                         self._subject == exported_rust_quad._subject
                         && self._predicate == exported_rust_quad._predicate
@@ -217,40 +237,46 @@ impl SophiaExportQuad {
 impl SophiaExportQuad {
     /// Modifies the subject of this quad
     #[wasm_bindgen(method, setter)]
-    pub fn set_subject(&mut self, other: &JsImportTerm) {
-        self._subject = build_rcterm_from_js_import_term(other).unwrap();
+    pub fn set_subject(&mut self, other: &JsImportTerm) -> Result<(), JsValue> {
+        self._subject = build_quad_component_from_js_import_term(other)?;
+        self.release_handle(0);
+        Ok(())
     }
     
     /// Modifies the predicate of this quad
     #[wasm_bindgen(method, setter)]
-    pub fn set_predicate(&mut self, other: &JsImportTerm) {
-        self._predicate = build_rcterm_from_js_import_term(other).unwrap();
+    pub fn set_predicate(&mut self, other: &JsImportTerm) -> Result<(), JsValue> {
+        self._predicate = build_quad_component_from_js_import_term(other)?;
+        self.release_handle(1);
+        Ok(())
     }
 
     /// Modifies the object of this quad
     #[wasm_bindgen(method, setter)]
-    pub fn set_object(&mut self, other: &JsImportTerm) {
-        self._object = build_rcterm_from_js_import_term(other).unwrap();
+    pub fn set_object(&mut self, other: &JsImportTerm) -> Result<(), JsValue> {
+        self._object = build_quad_component_from_js_import_term(other)?;
+        self.release_handle(2);
+        Ok(())
     }
     
     /// Modifies the graph of this quad
     #[wasm_bindgen(method, setter)]
-    pub fn set_graph(&mut self, other: &JsImportTerm) {
-        self._graph = build_rcterm_from_js_import_term(other);
+    pub fn set_graph(&mut self, other: &JsImportTerm) -> Result<(), JsValue> {
+        self._graph = build_rcterm_from_js_import_term(other)?;
+        self.release_handle(3);
+        Ok(())
     }
 }
 
 #[wasm_bindgen(js_class = Quad)]
 impl SophiaExportQuad {
-    /// Returns a pointer to this quad.
+    /// Lends a copy of this quad to the registry and returns the ticket to
+    /// take it back.
     /// 
-    /// This is mainly used to be able to detect a Rust managed quad from an
+    /// This is used to be able to detect a Rust managed quad from an
     /// imported quad (as wasm_bindgen doesn't let us use polymorphism).
-    /// 
-    /// Using this method can be very unsafe because it supposes only this
-    /// object has a method called getRustPtr in the Javascript world. 
-    #[wasm_bindgen(method, getter=getRustPtr)]
-    pub fn quads_get_rust_ptr(&self) -> *const SophiaExportQuad {
-        self
+    #[wasm_bindgen(method, getter=rustTicket)]
+    pub fn rust_ticket(&self) -> u32 {
+        crate::registry::hand_out(SophiaExportQuad::new(&self._subject, &self._predicate, &self._object, self._graph.as_ref()))
     }
 }
@@ -11,6 +11,8 @@ use sophia::term::Term::Iri;
 use sophia::term::Term::Literal;
 use sophia::term::Term::Variable;
 use wasm_bindgen::prelude::*;
+use crate::error::{ invalid_term_error, term_error, type_error };
+use crate::datamodel::interning::{ memoize_term, memoized_term };
 
 // ============================================================================
 //   ==== IMPORTATION ==== IMPORTATION ==== IMPORTATION ==== IMPORTATION ====
@@ -49,37 +51,69 @@ pub extern "C" {
 }
 
 /// Builds a new RcTerm that has the representation used by Sophia for the give
-/// JsImportTerm
-pub fn build_rcterm_from_js_import_term(term: &JsImportTerm) -> Option<RcTerm> {
+/// JsImportTerm.
+///
+/// The default graph is represented as `None`. Returns an `InvalidTermError`
+/// if the term type is unknown or if the term is not valid.
+///
+/// Terms exported by Sophia are directly taken back, and the other terms are
+/// memoized by identity during the bulk imports (see
+/// `crate::datamodel::interning`).
+pub fn build_rcterm_from_js_import_term(term: &JsImportTerm) -> Result<Option<RcTerm>, JsValue> {
+    if term.is_null() || term.is_undefined() {
+        return Err(invalid_term_error("Expected a term"));
+    }
+
+    if let Some(exported_rust_term) = crate::registry::take_back::<Option<RcTerm>>(term) {
+        return Ok(exported_rust_term);
+    }
+
+    if let Some(memoized) = memoized_term(term) {
+        return Ok(memoized);
+    }
+
+    let rcterm = read_rcterm_from_js_import_term(term)?;
+    memoize_term(term, &rcterm);
+    Ok(rcterm)
+}
+
+/// Builds a new RcTerm by reading the properties of the JsImportTerm
+fn read_rcterm_from_js_import_term(term: &JsImportTerm) -> Result<Option<RcTerm>, JsValue> {
     match term.term_type().as_str() {
-        "NamedNode" => Some(RcTerm::new_iri(term.value()).unwrap()),
-        "BlankNode" => Some(RcTerm::new_bnode(term.value()).unwrap()),
+        "NamedNode" => Ok(Some(RcTerm::new_iri(term.value()).map_err(term_error)?)),
+        "BlankNode" => Ok(Some(RcTerm::new_bnode(term.value()).map_err(term_error)?)),
         "Literal" => {
             let value = term.value();
             let language = term.language();
 
-            let a = if language != "" { // Lang
+            let literal_result = if language != "" { // Lang
                 RcTerm::new_literal_lang(value, language)
             } else {
-                let datatype = term.datatype();
-                RcTerm::new_literal_dt(value, build_rcterm_from_js_import_term(&datatype).unwrap())
+                let datatype = build_rcterm_from_js_import_term(&term.datatype())?
+                    .ok_or_else(|| invalid_term_error("The datatype of a literal can not be the default graph"))?;
+                RcTerm::new_literal_dt(value, datatype)
             };
             
-            Some(a.unwrap())
+            Ok(Some(literal_result.map_err(term_error)?))
         },
-        "Variable" => Some(RcTerm::new_variable(term.value()).unwrap()),
-        "DefaultGraph" => None,
-        _ => None
+        "Variable" => Ok(Some(RcTerm::new_variable(term.value()).map_err(term_error)?)),
+        "DefaultGraph" => Ok(None),
+        "Quad" => Err(invalid_term_error("Quoted triples (RDF-star) are not supported by Sophia")),
+        unknown_type => Err(invalid_term_error(&format!("Unknown term type {}", unknown_type)))
     }
 }
 
 /// Builds a StringTerm from a JsImportTerm
 /// 
 /// This is the fastest way to build a Term from an object imported from Javascript
-pub fn build_stringterm_from_js_import_term(term: &JsImportTerm) -> Option<Term<String>> {
+pub fn build_stringterm_from_js_import_term(term: &JsImportTerm) -> Result<Option<Term<String>>, JsValue> {
+    if term.is_null() || term.is_undefined() {
+        return Err(invalid_term_error("Expected a term"));
+    }
+
     match term.term_type().as_str() {
-        "NamedNode" => Some(Term::<String>::new_iri(term.value()).unwrap()),
-        "BlankNode" => Some(Term::<String>::new_bnode(term.value()).unwrap()),
+        "NamedNode" => Ok(Some(Term::<String>::new_iri(term.value()).map_err(term_error)?)),
+        "BlankNode" => Ok(Some(Term::<String>::new_bnode(term.value()).map_err(term_error)?)),
         "Literal" => {
             let value = term.value();
             let language = term.language();
@@ -87,18 +121,27 @@ pub fn build_stringterm_from_js_import_term(term: &JsImportTerm) -> Option<Term<
             let literal_result = if language != "" { // Lang
                 Term::<String>::new_literal_lang(value, language)
             } else {
-                let datatype = term.datatype();
-                Term::<String>::new_literal_dt(value, build_stringterm_from_js_import_term(&datatype).unwrap())
+                let datatype = build_stringterm_from_js_import_term(&term.datatype())?
+                    .ok_or_else(|| invalid_term_error("The datatype of a literal can not be the default graph"))?;
+                Term::<String>::new_literal_dt(value, datatype)
             };
             
-            Some(literal_result.unwrap())
+            Ok(Some(literal_result.map_err(term_error)?))
         },
-        "Variable" => Some(Term::<String>::new_variable(term.value()).unwrap()),
-        "DefaultGraph" => None,
-        _ => None
+        "Variable" => Ok(Some(Term::<String>::new_variable(term.value()).map_err(term_error)?)),
+        "DefaultGraph" => Ok(None),
+        "Quad" => Err(invalid_term_error("Quoted triples (RDF-star) are not supported by Sophia")),
+        unknown_type => Err(invalid_term_error(&format!("Unknown term type {}", unknown_type)))
     }
 }
 
+/// Builds the RcTerm of a quad component that can not be the default graph
+/// (the subject, the predicate or the object).
+pub fn build_quad_component_from_js_import_term(term: &JsImportTerm) -> Result<RcTerm, JsValue> {
+    build_rcterm_from_js_import_term(term)?
+        .ok_or_else(|| invalid_term_error("Only the graph of a quad can be the default graph"))
+}
+
 
 // ============================================================================
 //   ==== EXPORTATION ==== EXPORTATION ==== EXPORTATION ==== EXPORTATION ====
@@ -108,18 +151,36 @@ pub fn build_stringterm_from_js_import_term(term: &JsImportTerm) -> Option<Term<
 pub struct SophiaExportTerm {
     /// The encapsulated Sophia Term. If `None`, this term describes the default graph.
     #[wasm_bindgen(skip)]
-    pub term: Option<RcTerm>
+    pub term: Option<RcTerm>,
+    /// True if this object is handed out to several users, like the interned
+    /// terms and the terms of a quad. A shared term can not be modified.
+    #[wasm_bindgen(skip)]
+    pub shared: bool
 }
 
 impl SophiaExportTerm {
     /// Returns a RDF JS compliant term based on Sophia's RcTerm
     pub fn new(term: &RcTerm) -> SophiaExportTerm {
-        SophiaExportTerm { term: Some(term.clone()) }
+        SophiaExportTerm { term: Some(term.clone()), shared: false }
     }
 
     /// Returns a term that represents the default graph
     pub fn default_graph() -> SophiaExportTerm {
-        SophiaExportTerm { term: None }
+        SophiaExportTerm { term: None, shared: false }
+    }
+
+    /// Returns a term that can not be modified. `None` is the default graph.
+    pub fn shared(term: Option<RcTerm>) -> SophiaExportTerm {
+        SophiaExportTerm { term, shared: true }
+    }
+
+    /// Returns an error if this term is shared
+    fn ensure_is_modifiable(&self) -> Result<(), JsValue> {
+        if self.shared {
+            Err(type_error("This term is shared and can not be modified, copy it with DataFactory.fromTerm"))
+        } else {
+            Ok(())
+        }
     }
 }
 
@@ -157,21 +218,25 @@ impl SophiaExportTerm {
 
     /// Modifies the value of this term
     #[wasm_bindgen(setter = value)]
-    pub fn set_value(&mut self, new_value: &str) {
+    pub fn set_value(&mut self, new_value: &str) -> Result<(), JsValue> {
+        self.ensure_is_modifiable()?;
+
         match &self.term {
             None => { /* can't reassign a Default Graph */ },
             Some(real_term) => self.term = Some(match real_term {
-                RcTerm::Iri(_) => RcTerm::new_iri(new_value).unwrap(),
-                RcTerm::BNode(_) => RcTerm::new_bnode(new_value).unwrap(),
-                RcTerm::Variable(_) => RcTerm::new_variable(new_value).unwrap(),
+                RcTerm::Iri(_) => RcTerm::new_iri(new_value),
+                RcTerm::BNode(_) => RcTerm::new_bnode(new_value),
+                RcTerm::Variable(_) => RcTerm::new_variable(new_value),
                 RcTerm::Literal(former_literal) => {
                     match former_literal.lang() {
-                        None => RcTerm::new_literal_dt(new_value, &former_literal.dt()).unwrap(),
-                        Some(lang) => RcTerm::new_literal_lang_unchecked(new_value, lang.as_ref())
+                        None => RcTerm::new_literal_dt(new_value, &former_literal.dt()),
+                        Some(lang) => Ok(RcTerm::new_literal_lang_unchecked(new_value, lang.as_ref()))
                     }
                 }
-            })
+            }.map_err(term_error)?)
         }
+
+        Ok(())
     }
 }
 
@@ -192,13 +257,17 @@ impl SophiaExportTerm {
 
     /// Modifies the language of this term if applicable
     #[wasm_bindgen(method, setter)]
-    pub fn set_language(&mut self, language: &str) {
+    pub fn set_language(&mut self, language: &str) -> Result<(), JsValue> {
+        self.ensure_is_modifiable()?;
+
         // In this implementation, if we set the language of a literal, it will be automatically
         // converted to the datatype langString regardless of its previous datatype.
         // Setting the language of any other term has no effect.
         if let Some(Literal(literal)) = &self.term {
-            self.term = Some(RcTerm::new_literal_lang(literal.value().as_ref(), language).unwrap());
+            self.term = Some(RcTerm::new_literal_lang(literal.value().as_ref(), language).map_err(term_error)?);
         }
+
+        Ok(())
     }
 }
 
@@ -210,21 +279,23 @@ impl SophiaExportTerm {
         match &self.term {
             Some(Literal(literal)) =>
                 // TODO : check if iri always has a type (especially for string)
-                Option::Some(SophiaExportTerm {
-                    term: Some(RcTerm::new_iri_unchecked(literal.dt().value().as_ref(), true))
-                }),
+                Option::Some(SophiaExportTerm::new(&RcTerm::new_iri_unchecked(literal.dt().value().as_ref(), true))),
             _ => Option::None
         }
     }
 
     /// Modifies the dataset of this literal if applicable
     #[wasm_bindgen(method, setter)]
-    pub fn set_datatype(&mut self, named_node: &JsImportTerm) {
+    pub fn set_datatype(&mut self, named_node: &JsImportTerm) -> Result<(), JsValue> {
+        self.ensure_is_modifiable()?;
+
         if let Some(Literal(_)) = &self.term {
             let new_node_value = self.value();
-            let literal_type: RcTerm = RcTerm::new_iri(named_node.value()).unwrap();
-            self.term = Some(RcTerm::new_literal_dt(new_node_value, literal_type).unwrap());
+            let literal_type: RcTerm = RcTerm::new_iri(named_node.value()).map_err(term_error)?;
+            self.term = Some(RcTerm::new_literal_dt(new_node_value, literal_type).map_err(term_error)?);
         }
+
+        Ok(())
     }
 }
 
@@ -260,6 +331,17 @@ impl SophiaExportTerm {
     }
 }
 
+#[wasm_bindgen(js_class="Term")]
+impl SophiaExportTerm {
+    /// Lends a copy of this term to the registry and returns the ticket to
+    /// take it back, so importing a term exported by Sophia does not read its
+    /// properties.
+    #[wasm_bindgen(getter = rustTicket)]
+    pub fn rust_ticket(&self) -> u32 {
+        crate::registry::hand_out(self.term.clone())
+    }
+}
+
 #[wasm_bindgen(js_class="Term")]
 impl SophiaExportTerm {
     /// Returns the n3 representation of this term
@@ -6,7 +6,11 @@
 
 use crate::wasm_bindgen_dataset;
 use crate::wasm_bindgen_wrappeddataset;
-use crate::wrappers_example::{ TreeDatasetAntiWrapper, VecOrDatasetWrapper };
+use crate::wrappers_example::{ RdfsDatasetWrapper, TreeDatasetAntiWrapper, VecOrDatasetWrapper };
+use crate::datamodel::factory::SophiaExportDataFactory;
+use crate::datamodel::quad::{ JsImportQuad, SophiaExportQuad };
+use crate::datamodel::term::{ build_quad_component_from_js_import_term, build_rcterm_from_js_import_term, JsImportTerm };
+use crate::error::{ invalid_term_error, type_error };
 use crate::wrapping::*;
 
 extern crate wasm_bindgen;
@@ -18,6 +22,13 @@ use sophia::term::BoxTerm;
 
 use bjdatasets::treedataset::TreeDataset;
 use bjdatasets::fulldataset::FullIndexDataset;
+use bjdatasets::literalindex::LiteralKey;
+use sophia::dataset::Dataset;
+use sophia::dataset::MutableDataset;
+use sophia::quad::stream::QuadSource;
+use sophia::term::RcTerm;
+
+use js_sys::Reflect;
 
 
 // Dataset structure created by the factory
@@ -27,13 +38,139 @@ wasm_bindgen_dataset!(TreeDataset, "TreeDataset", SophiaExportDataset);
 impl SophiaExportDataset {
     #[wasm_bindgen(js_name = getNumberOfLivingTrees)]
     pub fn get_number_of_living_trees(&self) -> usize {
-        self.base.dataset().get_number_of_living_trees()
+        self.state.borrow().base.dataset().get_number_of_living_trees()
     }
 
     #[wasm_bindgen(js_name = ensureHasIndexFor)]
     pub fn ensure_has_index_for(&mut self, s: bool, p: bool, o: bool, g: bool) {
-        self.base.mutable_dataset().ensure_has_index_for(s, p, o, g);
+        self.state.borrow_mut().base_mut().mutable_dataset().ensure_has_index_for(s, p, o, g);
+    }
+
+    /// Sorts the numeric and temporal literals by value now instead of at
+    /// the first `matchRange` call
+    #[wasm_bindgen(js_name = enableLiteralIndex)]
+    pub fn enable_literal_index(&mut self) {
+        self.state.borrow_mut().base_mut().mutable_dataset().enable_literal_index();
+    }
+
+    /// Returns a dataset with the quads whose object is a numeric or temporal
+    /// literal between `min` and `max`, included.
+    ///
+    /// A null or undefined bound is unbounded, and a null or undefined
+    /// predicate or graph matches every term.
+    #[wasm_bindgen(js_name = matchRange)]
+    pub fn match_range(&self,
+        predicate: &JsImportTerm,
+        min: &JsImportTerm,
+        max: &JsImportTerm,
+        graph: &JsImportTerm) -> Result<SophiaExportDataset, JsValue> {
+        let predicate = if predicate.is_null() || predicate.is_undefined() {
+            None
+        } else {
+            Some(build_quad_component_from_js_import_term(predicate)?)
+        };
+
+        let graph = if graph.is_null() || graph.is_undefined() {
+            None
+        } else {
+            Some(build_rcterm_from_js_import_term(graph)?)
+        };
+
+        let content = self.content();
+        let quads = content.dataset().quads_with_object_in_range(
+            predicate.as_ref(),
+            graph.as_ref().map(Option::as_ref),
+            build_literal_bound(min)?,
+            build_literal_bound(max)?
+        );
+
+        let mut dataset = TreeDataset::new();
+        for quad in quads {
+            dataset.insert(&quad._subject, &quad._predicate, &quad._object, quad._graph.as_ref()).unwrap();
+        }
+
+        Ok(SophiaExportDataset::from_base(DefaultExporter::wrap(dataset)))
+    }
+
+    /// Indexes the words of the string literals now instead of at the first
+    /// `searchLiterals` call
+    #[wasm_bindgen(js_name = enableTextIndex)]
+    pub fn enable_text_index(&mut self) {
+        self.state.borrow_mut().base_mut().mutable_dataset().enable_text_index();
     }
+
+    /// Returns an array with the quads whose object is a string literal with
+    /// a word starting with every word of `text`, best matches first.
+    ///
+    /// `options` may restrict the quads to a `predicate`, the literals to a
+    /// `lang` range, and the number of quads to `limit`.
+    #[wasm_bindgen(js_name = searchLiterals)]
+    pub fn search_literals(&self, text: &str, options: &JsValue) -> Result<js_sys::Array, JsValue> {
+        let options = SearchOptions::new(options)?;
+
+        let quads = self.content().dataset().search_literals(
+            text,
+            options.predicate.as_ref(),
+            options.lang.as_deref(),
+            options.limit
+        );
+
+        Ok(quads.iter()
+            .map(|quad| SophiaExportQuad::new(&quad._subject, &quad._predicate, &quad._object, quad._graph.as_ref()))
+            .map(JsValue::from)
+            .collect())
+    }
+}
+
+/// The options of `searchLiterals`
+struct SearchOptions {
+    predicate: Option<RcTerm>,
+    lang: Option<String>,
+    limit: Option<usize>
+}
+
+impl SearchOptions {
+    /// Reads the `predicate`, `lang` and `limit` properties of a Javascript
+    /// object. The object may be undefined.
+    fn new(js_options: &JsValue) -> Result<SearchOptions, JsValue> {
+        let mut options = SearchOptions { predicate: None, lang: None, limit: None };
+
+        if js_options.is_null() || js_options.is_undefined() {
+            return Ok(options);
+        }
+
+        let predicate = Reflect::get(js_options, &JsValue::from_str("predicate"))?;
+        if !predicate.is_null() && !predicate.is_undefined() {
+            options.predicate = Some(build_quad_component_from_js_import_term(&predicate.into())?);
+        }
+
+        let lang = Reflect::get(js_options, &JsValue::from_str("lang"))?;
+        if !lang.is_null() && !lang.is_undefined() {
+            options.lang = Some(lang.as_string().ok_or_else(|| type_error("The language must be a string"))?);
+        }
+
+        let limit = Reflect::get(js_options, &JsValue::from_str("limit"))?;
+        if !limit.is_null() && !limit.is_undefined() {
+            let limit = limit.as_f64()
+                .filter(|limit| *limit >= 0.0 && limit.fract() == 0.0)
+                .ok_or_else(|| type_error("The limit must be a non-negative integer"))?;
+            options.limit = Some(limit as usize);
+        }
+
+        Ok(options)
+    }
+}
+
+/// Builds the inclusive bound of a `matchRange` from a numeric or temporal
+/// literal, or an unbounded side from null or undefined
+fn build_literal_bound(term: &JsImportTerm) -> Result<std::ops::Bound<LiteralKey>, JsValue> {
+    if term.is_null() || term.is_undefined() {
+        return Ok(std::ops::Bound::Unbounded);
+    }
+
+    LiteralKey::of(&build_quad_component_from_js_import_term(term)?)
+        .map(std::ops::Bound::Included)
+        .ok_or_else(|| invalid_term_error("Expected a numeric, xsd:date or xsd:dateTime literal"))
 }
 
 // Other usable datasets
@@ -58,3 +195,45 @@ wasm_bindgen_wrappeddataset!(TreeDatasetIntoArrayWrapper, "TreeDatasetToA");
 wasm_bindgen_wrappeddataset!(FastDatasetIntoArrayWrapper, "FastDatasetToA");
 wasm_bindgen_wrappeddataset!(LightDatasetIntoArrayWrapper, "LightDatasetToA");
 wasm_bindgen_wrappeddataset!(FullIndexDatasetIntoArrayWrapper, "FullDatasetToA");
+
+// A dataset that keeps the RDFS entailments of its quads
+wasm_bindgen_wrappeddataset!(RdfsDatasetWrapper, "RdfsDataset", SophiaExportRdfsDataset);
+
+#[wasm_bindgen(js_class="RdfsDataset")]
+impl SophiaExportRdfsDataset {
+    /// Returns true if the quad was added, and not only inferred
+    #[wasm_bindgen(js_name = isAsserted)]
+    pub fn is_asserted(&self, quad: &JsImportQuad) -> Result<bool, JsValue> {
+        let quad = SophiaExportDataFactory::from_quad(quad)?;
+        let state = self.state.borrow();
+        let in_view = state.view.as_ref().map_or(true, |pattern| pattern.matches(&quad));
+
+        Ok(in_view && state.base.dataset().is_asserted(&quad._subject, &quad._predicate, &quad._object, quad._graph.as_ref()))
+    }
+
+    /// Returns true if the quad is in the dataset only because it is entailed
+    /// by the others
+    #[wasm_bindgen(js_name = isInferred)]
+    pub fn is_inferred(&self, quad: &JsImportQuad) -> Result<bool, JsValue> {
+        let quad = SophiaExportDataFactory::from_quad(quad)?;
+        let state = self.state.borrow();
+        let in_view = state.view.as_ref().map_or(true, |pattern| pattern.matches(&quad));
+
+        Ok(in_view && state.base.dataset().is_inferred(&quad._subject, &quad._predicate, &quad._object, quad._graph.as_ref()))
+    }
+
+    /// Returns a `TreeDataset` with the asserted quads
+    pub fn asserted(&self) -> SophiaExportDataset {
+        let state = self.state.borrow();
+        let asserted = state.base.dataset().asserted();
+        let mut dataset = TreeDataset::new();
+
+        match &state.view {
+            None => asserted.quads().in_dataset(&mut dataset).unwrap(),
+            Some(pattern) => asserted.quads_matching(&pattern.s, &pattern.p, &pattern.o, &pattern.g)
+                .in_dataset(&mut dataset).unwrap()
+        };
+
+        SophiaExportDataset::from_base(DefaultExporter::wrap(dataset))
+    }
+}
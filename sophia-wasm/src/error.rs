@@ -0,0 +1,133 @@
+//! This module builds the errors that are thrown to the Javascript world.
+//!
+//! Every fallible exported function returns a `Result<_, JsValue>`. The
+//! `JsValue` is a Javascript `Error` whose `name` tells what went wrong, so
+//! callers can catch it and keep using the web assembly instance. The names
+//! are:
+//! - `InvalidIriError` when an IRI does not conform to RFC 3987
+//! - `InvalidTermError` when a term can not be built or has an unknown type
+//! - `ParseError` when a serialized dataset, an RDF Patch, a ShEx schema or a shape map can not be parsed. These errors
+//! also have a `line` and a `column` property when the position is known.
+//! - `JsonLdError` when a JSON-LD document or context is invalid. These errors
+//! have a `code` property with the JSON-LD error code.
+//! - `CanonicalizationError` when a dataset has too many indistinguishable
+//! blank nodes to be canonicalized
+//! - `ShaclError` when a shapes dataset can not be read
+
+#![deny(missing_docs)]
+
+extern crate wasm_bindgen;
+
+use bjdatasets::canonical::CanonicalizationError;
+use bjdatasets::jsonld::JsonLdError;
+use bjdatasets::patch::PatchError;
+use bjdatasets::shacl::ShaclError;
+use bjdatasets::shex::ShexError;
+use sophia::term::TermError;
+use wasm_bindgen::prelude::*;
+use js_sys::Reflect;
+
+/// Builds a Javascript `Error` with the given name and message
+fn named_error(name: &str, message: &str) -> js_sys::Error {
+    let error = js_sys::Error::new(message);
+    error.set_name(name);
+    error
+}
+
+/// Returns an `InvalidIriError` with the given message
+pub fn invalid_iri_error(message: &str) -> JsValue {
+    named_error("InvalidIriError", message).into()
+}
+
+/// Returns an `InvalidTermError` with the given message
+pub fn invalid_term_error(message: &str) -> JsValue {
+    named_error("InvalidTermError", message).into()
+}
+
+/// Returns a `TypeError` with the given message, used when an argument is
+/// not what the RDF.JS specification expects (for example a dataset that is
+/// not iterable)
+pub fn type_error(message: &str) -> JsValue {
+    js_sys::TypeError::new(message).into()
+}
+
+/// Converts an error raised by Sophia while building a term into either an
+/// `InvalidIriError` or an `InvalidTermError`
+pub fn term_error(error: TermError) -> JsValue {
+    match error {
+        TermError::InvalidIri(_) => invalid_iri_error(&error.to_string()),
+        _ => invalid_term_error(&error.to_string())
+    }
+}
+
+/// A syntax error whose position in the parsed text may be known
+pub trait PositionedError: std::fmt::Display {
+    /// Returns the line and the column of the error, both starting at 1
+    fn position(&self) -> Option<(u64, u64)>;
+}
+
+impl PositionedError for rio_turtle::TurtleError {
+    fn position(&self) -> Option<(u64, u64)> {
+        self.textual_position()
+            .map(|position| (position.line_number() + 1, position.byte_number() + 1))
+    }
+}
+
+impl PositionedError for serde_json::Error {
+    fn position(&self) -> Option<(u64, u64)> {
+        Some((self.line() as u64, self.column() as u64))
+    }
+}
+
+impl PositionedError for PatchError {
+    fn position(&self) -> Option<(u64, u64)> {
+        Some((self.line as u64, 1))
+    }
+}
+
+impl PositionedError for ShexError {
+    fn position(&self) -> Option<(u64, u64)> {
+        Some((self.line as u64, 1))
+    }
+}
+
+impl PositionedError for rio_xml::RdfXmlError {
+    fn position(&self) -> Option<(u64, u64)> {
+        // The RDF/XML parser does not track the position of its errors
+        None
+    }
+}
+
+/// Returns a `ParseError` that describes the given syntax error.
+///
+/// If the position of the error is known, the Javascript error has a `line`
+/// and a `column` property.
+pub fn parse_error<E>(error: &E) -> JsValue
+    where E: PositionedError {
+    let js_error = named_error("ParseError", &error.to_string());
+
+    if let Some((line, column)) = error.position() {
+        // Setting a property on a freshly built Error can not fail
+        Reflect::set(&js_error, &JsValue::from_str("line"), &JsValue::from_f64(line as f64)).unwrap();
+        Reflect::set(&js_error, &JsValue::from_str("column"), &JsValue::from_f64(column as f64)).unwrap();
+    }
+
+    js_error.into()
+}
+
+/// Returns a `JsonLdError` that describes the given JSON-LD processing error
+pub fn jsonld_error(error: &JsonLdError) -> JsValue {
+    let js_error = named_error("JsonLdError", &error.to_string());
+    Reflect::set(&js_error, &JsValue::from_str("code"), &JsValue::from_str(error.code)).unwrap();
+    js_error.into()
+}
+
+/// Returns a `CanonicalizationError` that describes the given error
+pub fn canonicalization_error(error: &CanonicalizationError) -> JsValue {
+    named_error("CanonicalizationError", &error.to_string()).into()
+}
+
+/// Returns a `ShaclError` that describes an invalid shapes dataset
+pub fn shacl_error(error: &ShaclError) -> JsValue {
+    named_error("ShaclError", &error.to_string()).into()
+}
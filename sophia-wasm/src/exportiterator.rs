@@ -4,6 +4,11 @@
 //! when we create the iterator : new and deleted elements in the origian
 //! container do not change the state of the iterator.
 //!
+//! The elements are produced on demand: the iterator owns a `Rc` handle to
+//! the iterated container and a resumable Rust iterator on it. The exported
+//! containers are copied on write when they are modified while an iterator
+//! still holds them, which gives the snapshot semantics.
+//!
 //! This feature is implemented as earlier versions of NodeJs do not support
 //! `js_sys::Array::values()`
 
@@ -11,22 +16,42 @@
 
 extern crate wasm_bindgen;
 
+use crate::datamodel::quad::SophiaExportQuad;
+use std::any::Any;
+use std::rc::Rc;
 use wasm_bindgen::prelude::*;
 
 
-/// An exportable iterator on that keeps an array of every elements that needs
-/// to be iterated on
+/// An exportable iterator that lazily produces the quads of a snapshot of an
+/// exported container
 #[wasm_bindgen]
 pub struct RustExportIterator {
-    array: js_sys::Array
+    /// The position in the iterated container. It borrows from `_owner`, so
+    /// it must be declared (and dropped) first.
+    quads: Box<dyn Iterator<Item = SophiaExportQuad>>,
+    /// Keeps the iterated container alive
+    _owner: Rc<dyn Any>
 }
 
 impl RustExportIterator {
-    /// Build an iterator from a `js_sys::Array`
-    pub fn new(array: js_sys::Array) -> RustExportIterator {
-        // We reverse in place so we can think our iterator as a list of quads we have not iterated on yet
-        array.reverse();
-        RustExportIterator { array }
+    /// Build an iterator on the quads that `build_iterator` produces from
+    /// `owner`.
+    ///
+    /// `owner` must not be mutated while it is shared, which is ensured by
+    /// the copy on write of the exported datasets.
+    pub fn new<O, F>(owner: Rc<O>, build_iterator: F) -> RustExportIterator
+        where O: 'static,
+        F: for<'a> FnOnce(&'a O) -> Box<dyn Iterator<Item = SophiaExportQuad> + 'a> {
+        let quads = build_iterator(&owner);
+
+        // The iterator borrows from the content of the `Rc`, which is never
+        // moved, never mutated while shared, and lives as long as the
+        // iterator as both are owned by the returned structure.
+        let quads: Box<dyn Iterator<Item = SophiaExportQuad> + 'static> = unsafe {
+            std::mem::transmute(quads)
+        };
+
+        RustExportIterator { quads, _owner: owner }
     }
 }
 
@@ -35,11 +60,21 @@ impl RustExportIterator {
     /// Returns an `RustExportIteratorNext` that contains to the next element.
     /// This corresponds to the next function in Javascript iterators.
     pub fn next(&mut self) -> RustExportIteratorNext {
-        if self.array.length() != 0 {
-            RustExportIteratorNext{ current_element: Some(self.array.pop()) }
-        } else {
-            RustExportIteratorNext{ current_element: None }
-        }
+        RustExportIteratorNext { current_element: self.quads.next().map(JsValue::from) }
+    }
+
+    /// Returns an array with at most `max_size` elements that have not been
+    /// iterated on yet. An empty array means that the iterator is exhausted.
+    ///
+    /// This enables to move the elements to the Javascript world by chunks
+    /// instead of crossing the boundary for every element.
+    #[wasm_bindgen(js_name = nextChunk)]
+    pub fn next_chunk(&mut self, max_size: usize) -> js_sys::Array {
+        self.quads
+            .by_ref()
+            .take(max_size)
+            .map(JsValue::from)
+            .collect()
     }
 }
 
@@ -0,0 +1,179 @@
+//! Undo and redo of the modifications of the exported datasets.
+//!
+//! Once enabled with `enableHistory(limit)`, the history records the
+//! insertions and removals that every mutating call actually did, in their
+//! order (see `crate::changes`). The recorded operations are grouped into
+//! steps: a step
+//! contains every call between `beginStep(name)` and `endStep()`, or a single
+//! call if no step was begun. `undo()` applies the inverse operations of the
+//! last step in the reverse order, and `redo()` applies them again.
+//!
+//! Only the last `limit` steps are kept.
+
+#![deny(missing_docs)]
+
+use crate::changes::{ Change, ChangeSet, DatasetObserver };
+use sophia::dataset::MutableDataset;
+use sophia::quad::Quad;
+use std::collections::VecDeque;
+
+/// A group of operations that are undone and redone together
+pub struct Step {
+    /// The name given to `begin_step`, if any
+    name: Option<String>,
+    /// The operations in the order they were done
+    operations: Vec<Change>
+}
+
+impl Step {
+    /// Returns the name of the step
+    pub fn name(&self) -> Option<&str> {
+        self.name.as_deref()
+    }
+
+    /// Applies the inverse of the operations of the step in the reverse order
+    /// to `dataset`
+    pub fn undo<D>(&self, dataset: &mut D)
+        where D: MutableDataset {
+        for operation in self.operations.iter().rev() {
+            match operation {
+                Change::Inserted(quad) => dataset.remove(quad.s(), quad.p(), quad.o(), quad.g()).unwrap(),
+                Change::Removed(quad) => dataset.insert(quad.s(), quad.p(), quad.o(), quad.g()).unwrap()
+            };
+        }
+    }
+
+    /// Applies the operations of the step to `dataset` again
+    pub fn redo<D>(&self, dataset: &mut D)
+        where D: MutableDataset {
+        for operation in &self.operations {
+            match operation {
+                Change::Inserted(quad) => dataset.insert(quad.s(), quad.p(), quad.o(), quad.g()).unwrap(),
+                Change::Removed(quad) => dataset.remove(quad.s(), quad.p(), quad.o(), quad.g()).unwrap()
+            };
+        }
+    }
+}
+
+/// The steps that can be undone and redone on an exported dataset
+pub struct History {
+    /// The maximum number of steps that can be undone
+    limit: usize,
+    /// The steps that can be undone, the last one being the most recent
+    done: VecDeque<Step>,
+    /// The steps that can be redone, the last one being the next to redo
+    undone: Vec<Step>,
+    /// The step begun with `begin_step` that is not ended yet
+    current: Option<Step>
+}
+
+impl History {
+    /// Builds an empty history that keeps at most `limit` steps
+    pub fn new(limit: usize) -> Self {
+        Self {
+            limit,
+            done: VecDeque::new(),
+            undone: vec![],
+            current: None
+        }
+    }
+
+    /// Begins a step in which the next calls are recorded. The current step,
+    /// if any, is ended.
+    pub fn begin_step(&mut self, name: Option<String>) {
+        self.end_step();
+        self.current = Some(Step { name, operations: vec![] });
+    }
+
+    /// Ends the current step. Does nothing if no step was begun.
+    pub fn end_step(&mut self) {
+        if let Some(step) = self.current.take() {
+            self.push_done(step);
+        }
+    }
+
+    /// Returns true if a step can be undone
+    pub fn can_undo(&self) -> bool {
+        !self.done.is_empty() || self.current.as_ref().map_or(false, |step| !step.operations.is_empty())
+    }
+
+    /// Returns true if a step can be redone
+    pub fn can_redo(&self) -> bool {
+        !self.undone.is_empty()
+    }
+
+    /// Returns the step to undo. The current step is ended first. The caller
+    /// is expected to give it back with `undone` once applied.
+    pub fn take_undo(&mut self) -> Option<Step> {
+        self.end_step();
+        self.done.pop_back()
+    }
+
+    /// Returns the step to redo. The caller is expected to give it back with
+    /// `redone` once applied.
+    pub fn take_redo(&mut self) -> Option<Step> {
+        self.undone.pop()
+    }
+
+    /// Records that `step` was undone
+    pub fn undone(&mut self, step: Step) {
+        self.undone.push(step);
+    }
+
+    /// Records that `step` was redone
+    pub fn redone(&mut self, step: Step) {
+        self.done.push_back(step);
+        self.truncate();
+    }
+
+    /// Returns the name of the step that `undo` would revert
+    pub fn undo_name(&self) -> Option<&str> {
+        match &self.current {
+            Some(step) if !step.operations.is_empty() => step.name(),
+            _ => self.done.back().and_then(Step::name)
+        }
+    }
+
+    /// Returns the name of the step that `redo` would apply again
+    pub fn redo_name(&self) -> Option<&str> {
+        self.undone.last().and_then(Step::name)
+    }
+
+    /// Adds a step that can be undone. The steps that were undone can no
+    /// longer be redone.
+    fn push_done(&mut self, step: Step) {
+        if step.operations.is_empty() {
+            return;
+        }
+
+        self.undone.clear();
+        self.done.push_back(step);
+        self.truncate();
+    }
+
+    /// Forgets the oldest steps to keep at most `limit` of them
+    fn truncate(&mut self) {
+        while self.done.len() > self.limit {
+            self.done.pop_front();
+        }
+    }
+}
+
+impl DatasetObserver for History {
+    fn observes_changes(&self) -> bool {
+        true
+    }
+
+    /// Records the insertions and removals of the call as they were done
+    fn on_change(&mut self, changes: &ChangeSet) {
+        let operations = changes.changes().iter().cloned();
+
+        match self.current.as_mut() {
+            Some(step) => {
+                step.operations.extend(operations);
+                self.undone.clear();
+            },
+            None => self.push_done(Step { name: None, operations: operations.collect() })
+        }
+    }
+}
@@ -21,5 +21,26 @@ pub mod exportiterator;
 /// Convenience debug and log functions
 pub mod util;
 
+/// Errors thrown to the Javascript world
+pub mod error;
+
+/// Identification of the Rust managed objects passed back by Javascript
+pub mod registry;
+
+/// Bulk interchange of quads as identifiers and a term table
+pub mod termtable;
+
+/// Renaming of the blank nodes of imported quads
+pub mod blanknodes;
+
+/// Notification of the changes of the exported datasets
+pub mod changes;
+
+/// Undo and redo of the modifications of the exported datasets
+pub mod history;
+
+/// RDF.JS Stream interfaces of the exported datasets
+pub mod streams;
+
 /// This modules exposes the details to export a Sophia Dataset to Web Assembly
 pub mod wrapping;
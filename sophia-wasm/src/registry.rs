@@ -0,0 +1,77 @@
+//! Safe identification of the objects managed by Rust that are passed back
+//! as arguments from the Javascript world.
+//!
+//! wasm_bindgen does not let us check whether an imported `JsValue` is one of
+//! our exported structures. Instead of exposing raw pointers, an exported
+//! object hands out a ticket through its `rustTicket` getter: the content
+//! of the object is lent to the registry under a fresh number, and the
+//! receiving Rust function takes it back by reading the ticket.
+//!
+//! - A plain Javascript object that has a `rustTicket` property can not
+//! provide a value, as only the getter of an exported object lends one.
+//! - A freed exported object throws when its getter is called.
+//! - The lent value is downcasted to the expected type, so an object of
+//! another exported class is not mistaken for the expected one.
+//!
+//! In all these cases, the caller is expected to fall back on the generic
+//! RDF.JS path.
+
+#![deny(missing_docs)]
+
+use std::any::Any;
+use std::cell::{ Cell, RefCell };
+use js_sys::Reflect;
+use wasm_bindgen::JsValue;
+
+/// The name of the getter exported objects use to hand out tickets
+pub const TICKET_PROPERTY: &str = "rustTicket";
+
+thread_local! {
+    /// The number of the last handed out ticket
+    static LAST_TICKET: Cell<u32> = Cell::new(0);
+
+    /// The value lent with the last ticket. Tickets are taken back as soon as
+    /// they are read, so only one lent value is kept at a time.
+    static LENT: RefCell<Option<(u32, Box<dyn Any>)>> = RefCell::new(None);
+}
+
+/// Lends `value` to the registry and returns the ticket that will enable to
+/// take it back.
+pub fn hand_out<T: 'static>(value: T) -> u32 {
+    let ticket = LAST_TICKET.with(|last| {
+        let ticket = last.get().wrapping_add(1);
+        last.set(ticket);
+        ticket
+    });
+
+    LENT.with(|lent| *lent.borrow_mut() = Some((ticket, Box::new(value))));
+
+    ticket
+}
+
+/// Reads the ticket of `imported` and returns the value it lent if it is of
+/// type `T`.
+///
+/// Returns `None` if `imported` is not an alive exported object of a class
+/// that lends values of type `T`.
+pub fn take_back<T: 'static>(imported: &JsValue) -> Option<T> {
+    if imported.is_null() || imported.is_undefined() {
+        return None;
+    }
+
+    let ticket = Reflect::get(imported, &JsValue::from_str(TICKET_PROPERTY)).ok()?.as_f64()?;
+
+    LENT.with(|lent| {
+        let mut lent = lent.borrow_mut();
+
+        match lent.take() {
+            Some((lent_ticket, value)) if lent_ticket as f64 == ticket => {
+                value.downcast::<T>().ok().map(|value| *value)
+            },
+            other => {
+                *lent = other;
+                None
+            }
+        }
+    })
+}
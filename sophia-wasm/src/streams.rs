@@ -0,0 +1,325 @@
+//! The [RDF.JS Stream interfaces](https://rdf.js.org/stream-spec/) (Source,
+//! Sink and Store) of the exported datasets.
+//!
+//! The quads cross the web assembly boundary by chunks: a quad stream pulls
+//! at least `CHUNK_SIZE` quads at once from a `RustExportIterator`, and the
+//! quads read from an imported stream are buffered and given to the dataset
+//! in a microtask, with the other quads that arrived in the meantime.
+//!
+//! The callbacks registered on the streams are Rust closures. As a closure
+//! can not be dropped while it runs, they are dropped in a microtask once the
+//! stream has ended or failed.
+
+#![deny(missing_docs)]
+
+use crate::datamodel::factory::SophiaExportDataFactory;
+use crate::datamodel::quad::JsImportQuad;
+use crate::exportiterator::RustExportIterator;
+use bjdatasets::RcQuad;
+use std::cell::RefCell;
+use std::rc::{ Rc, Weak };
+use wasm_bindgen::prelude::*;
+use wasm_bindgen::JsCast;
+
+/// Minimum number of quads that are moved at once to a quad stream
+const CHUNK_SIZE: usize = 256;
+
+#[wasm_bindgen(module = "stream")]
+extern "C" {
+    /// A readable stream
+    #[derive(Clone)]
+    pub type Readable;
+
+    #[wasm_bindgen(constructor)]
+    fn new(options: &JsValue) -> Readable;
+
+    #[wasm_bindgen(method)]
+    fn push(this: &Readable, chunk: &JsValue) -> bool;
+}
+
+#[wasm_bindgen(module = "events")]
+extern "C" {
+    /// The event emitter returned by the functions of the RDF.JS Sink and
+    /// Store interfaces
+    #[derive(Clone)]
+    pub type EventEmitter;
+
+    #[wasm_bindgen(constructor)]
+    fn new() -> EventEmitter;
+
+    #[wasm_bindgen(method, catch)]
+    fn emit(this: &EventEmitter, event: &str, argument: &JsValue) -> Result<bool, JsValue>;
+}
+
+#[wasm_bindgen]
+extern "C" {
+    /// A stream of quads imported from the Javascript world
+    #[derive(Clone)]
+    pub type JsImportStream;
+
+    #[wasm_bindgen(method)]
+    fn on(this: &JsImportStream, event: &str, listener: &JsValue);
+
+    #[wasm_bindgen(method, js_name = removeListener)]
+    fn remove_listener(this: &JsImportStream, event: &str, listener: &JsValue);
+
+    #[wasm_bindgen(js_name = queueMicrotask)]
+    fn queue_microtask(callback: &JsValue);
+}
+
+
+// ============================================================================
+//   ==== SOURCE ==== SOURCE ==== SOURCE ==== SOURCE ==== SOURCE ==== SOURCE
+
+/// The state of a quad stream
+struct QuadStream {
+    /// The quads that have not been pushed yet. `None` once they all were.
+    iterator: Option<RustExportIterator>,
+    /// The stream that the quads are pushed into
+    stream: Option<Readable>,
+    /// The `read` function of `stream`
+    read: Option<Closure<dyn FnMut(f64)>>
+}
+
+/// Returns a readable stream of the quads of the iterator
+pub fn quad_stream(iterator: RustExportIterator) -> Readable {
+    let state = Rc::new(RefCell::new(QuadStream { iterator: Some(iterator), stream: None, read: None }));
+
+    let read_state = state.clone();
+    let read = Closure::wrap(Box::new(move |size: f64| read_chunk(&read_state, size)) as Box<dyn FnMut(f64)>);
+
+    let destroy_state = state.clone();
+    let destroy = Closure::once_into_js(move |error: JsValue, callback: js_sys::Function| {
+        release_quad_stream(&destroy_state);
+        let _ = callback.call1(&JsValue::NULL, &error);
+    });
+
+    let options = js_sys::Object::new();
+    let _ = js_sys::Reflect::set(&options, &JsValue::from_str("objectMode"), &JsValue::TRUE);
+    let _ = js_sys::Reflect::set(&options, &JsValue::from_str("read"), read.as_ref());
+    let _ = js_sys::Reflect::set(&options, &JsValue::from_str("destroy"), &destroy);
+
+    let stream = Readable::new(&options);
+
+    let mut state = state.borrow_mut();
+    state.stream = Some(stream.clone());
+    state.read = Some(read);
+    stream
+}
+
+/// Pushes the next quads of the iterator into the stream, or ends the stream
+fn read_chunk(state: &Rc<RefCell<QuadStream>>, size: f64) {
+    let (stream, chunk) = {
+        let mut state = state.borrow_mut();
+
+        let stream = match &state.stream {
+            None => return,
+            Some(stream) => stream.clone()
+        };
+
+        let chunk = match state.iterator.as_mut() {
+            None => js_sys::Array::new(),
+            Some(iterator) => iterator.next_chunk(CHUNK_SIZE.max(size as usize))
+        };
+
+        (stream, chunk)
+    };
+
+    if chunk.length() == 0 {
+        release_quad_stream(state);
+        stream.push(&JsValue::NULL);
+        return;
+    }
+
+    for quad in chunk.iter() {
+        stream.push(&quad);
+    }
+}
+
+/// Drops the iterator and, in a microtask, the callbacks of a quad stream
+fn release_quad_stream(state: &Rc<RefCell<QuadStream>>) {
+    let (iterator, stream, read) = {
+        let mut state = state.borrow_mut();
+        (state.iterator.take(), state.stream.take(), state.read.take())
+    };
+
+    drop(iterator);
+    drop(stream);
+
+    if let Some(read) = read {
+        queue_microtask(&Closure::once_into_js(move || drop(read)));
+    }
+}
+
+
+// ============================================================================
+//   ==== SINK AND STORE ==== SINK AND STORE ==== SINK AND STORE ==== SINK ====
+
+/// The state of the consumption of an imported stream
+struct StreamConsumption<T> {
+    /// The consumed stream
+    stream: JsImportStream,
+    /// The emitter returned to the caller
+    emitter: EventEmitter,
+    /// The object that consumes the quads. The stream does not keep it alive.
+    target: Weak<RefCell<T>>,
+    /// What is done with the quads
+    consume: Box<dyn FnMut(&mut T, Vec<RcQuad>)>,
+    /// The quads that have not been consumed yet
+    buffer: Vec<RcQuad>,
+    /// The error of the stream, or of the conversion of one of its quads
+    error: Option<JsValue>,
+    /// True if the stream has ended
+    ended: bool,
+    /// True if a microtask to consume the buffer is pending
+    is_scheduled: bool,
+    /// True once `end` or `error` has been emitted
+    finished: bool,
+    /// The callbacks registered on the stream
+    listeners: Vec<(&'static str, Closure<dyn FnMut(JsValue)>)>
+}
+
+/// Gives the quads of `stream` to `consume` with the target object, and
+/// returns an event emitter that emits `end` once they all have been
+/// consumed, or `error`. It emits `error` if the target object is dropped
+/// before.
+pub fn consume_stream<T: 'static>(stream: JsImportStream, target: Weak<RefCell<T>>, consume: Box<dyn FnMut(&mut T, Vec<RcQuad>)>) -> EventEmitter {
+    let emitter = EventEmitter::new();
+
+    let state = Rc::new(RefCell::new(StreamConsumption {
+        stream: stream.clone(),
+        emitter: emitter.clone(),
+        target,
+        consume,
+        buffer: vec![],
+        error: None,
+        ended: false,
+        is_scheduled: false,
+        finished: false,
+        listeners: vec![]
+    }));
+
+    let data_state = state.clone();
+    let on_data = Closure::wrap(Box::new(move |quad: JsValue| {
+        let quad = SophiaExportDataFactory::from_quad(&quad.unchecked_into::<JsImportQuad>());
+
+        {
+            let mut state = data_state.borrow_mut();
+            match quad {
+                Ok(quad) => state.buffer.push(RcQuad::new_from_quad(&quad)),
+                Err(error) => { state.error.get_or_insert(error); }
+            }
+        }
+
+        schedule(&data_state);
+    }) as Box<dyn FnMut(JsValue)>);
+
+    let end_state = state.clone();
+    let on_end = Closure::wrap(Box::new(move |_: JsValue| {
+        end_state.borrow_mut().ended = true;
+        schedule(&end_state);
+    }) as Box<dyn FnMut(JsValue)>);
+
+    let error_state = state.clone();
+    let on_error = Closure::wrap(Box::new(move |error: JsValue| {
+        error_state.borrow_mut().error.get_or_insert(error);
+        schedule(&error_state);
+    }) as Box<dyn FnMut(JsValue)>);
+
+    let listeners = vec![("data", on_data), ("end", on_end), ("error", on_error)];
+
+    for (event, listener) in &listeners {
+        stream.on(event, listener.as_ref());
+    }
+
+    state.borrow_mut().listeners = listeners;
+    emitter
+}
+
+/// Consumes the buffered quads in a microtask
+fn schedule<T: 'static>(state: &Rc<RefCell<StreamConsumption<T>>>) {
+    {
+        let mut state = state.borrow_mut();
+
+        if state.is_scheduled || state.finished {
+            return;
+        }
+
+        state.is_scheduled = true;
+    }
+
+    let state = state.clone();
+    queue_microtask(&Closure::once_into_js(move || consume_buffer(&state)));
+}
+
+/// Consumes the buffered quads, then emits `end` or `error` if the stream has
+/// ended or failed
+fn consume_buffer<T>(state: &Rc<RefCell<StreamConsumption<T>>>) {
+    let result = {
+        let mut state = state.borrow_mut();
+        state.is_scheduled = false;
+
+        if state.finished {
+            return;
+        }
+
+        match state.error.take() {
+            Some(error) => Err(error),
+            None => {
+                let quads = std::mem::take(&mut state.buffer);
+                let state = &mut *state;
+
+                match state.target.upgrade() {
+                    None => Err(js_sys::Error::new("The dataset has been freed").into()),
+                    // This runs in a microtask, so no method of the target runs
+                    Some(target) => {
+                        (state.consume)(&mut target.borrow_mut(), quads);
+                        Ok(())
+                    }
+                }
+            }
+        }
+    };
+
+    match result {
+        Err(error) => finish(state, "error", &error),
+        Ok(()) if state.borrow().ended => finish(state, "end", &JsValue::UNDEFINED),
+        Ok(()) => {}
+    }
+}
+
+/// Unregisters the callbacks from the stream and emits `event`
+fn finish<T>(state: &Rc<RefCell<StreamConsumption<T>>>, event: &str, argument: &JsValue) {
+    let (stream, emitter, listeners) = {
+        let mut state = state.borrow_mut();
+        state.finished = true;
+        state.buffer.clear();
+        (state.stream.clone(), state.emitter.clone(), std::mem::take(&mut state.listeners))
+    };
+
+    for (listener_event, listener) in &listeners {
+        stream.remove_listener(listener_event, listener.as_ref());
+    }
+
+    // This runs in a microtask, so none of the callbacks is running
+    drop(listeners);
+
+    // An `error` without listener is thrown by the emitter
+    let _ = emitter.emit(event, argument);
+}
+
+/// Returns an event emitter that emits, in a microtask, `end` if `result` is
+/// Ok or `error` otherwise
+pub fn emitter_of(result: Result<(), JsValue>) -> EventEmitter {
+    let emitter = EventEmitter::new();
+    let emitted = emitter.clone();
+
+    queue_microtask(&Closure::once_into_js(move || {
+        let _ = match result {
+            Ok(()) => emitted.emit("end", &JsValue::UNDEFINED),
+            Err(error) => emitted.emit("error", &error)
+        };
+    }));
+
+    emitter
+}
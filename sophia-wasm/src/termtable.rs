@@ -0,0 +1,117 @@
+//! Bulk interchange of quads as identifiers.
+//!
+//! Quads are moved through the web assembly boundary as a `Uint32Array` of
+//! identifiers (four per quad, in the subject, predicate, object, graph
+//! order) and a term table, an array of strings in which the identifier of a
+//! term is its index.
+//!
+//! Each term of the table is encoded as a string prefixed by a letter that
+//! tells its kind:
+//! - `I` + iri for a named node
+//! - `B` + identifier for a blank node
+//! - `V` + name for a variable
+//! - `L` + language + `@` + value for a literal with a language
+//! - `D` + datatype iri + `>` + value for other literals
+//!
+//! The identifier 0 is always the default graph, encoded as an empty string.
+
+#![deny(missing_docs)]
+
+use crate::error::{ invalid_term_error, term_error, type_error };
+use sophia::term::*;
+use std::collections::HashMap;
+use wasm_bindgen::JsValue;
+
+/// Returns the encoding in a term table of the given term
+pub fn encode_term(term: &RcTerm) -> String {
+    let value = term.value();
+    let value: &str = value.as_ref();
+
+    match term {
+        Term::Iri(_) => format!("I{}", value),
+        Term::BNode(_) => format!("B{}", value),
+        Term::Variable(_) => format!("V{}", value),
+        Term::Literal(literal) => match literal.lang() {
+            Some(lang) => format!("L{}@{}", lang.as_ref(), value),
+            None => format!("D{}>{}", literal.dt().value().as_ref(), value)
+        }
+    }
+}
+
+/// Builds back the term encoded in a term table. The default graph is
+/// returned as `None`.
+pub fn decode_term(encoded: &str) -> Result<Option<RcTerm>, JsValue> {
+    if encoded.is_empty() {
+        return Ok(None);
+    }
+
+    let (kind, content) = encoded.split_at(1);
+
+    let term = match kind {
+        "I" => RcTerm::new_iri(content),
+        "B" => RcTerm::new_bnode(content),
+        "V" => RcTerm::new_variable(content),
+        "L" => {
+            let separator = content.find('@')
+                .ok_or_else(|| invalid_term_error("A language literal in a term table has no language separator"))?;
+            RcTerm::new_literal_lang(&content[separator + 1..], &content[..separator])
+        },
+        "D" => {
+            let separator = content.find('>')
+                .ok_or_else(|| invalid_term_error("A literal in a term table has no datatype separator"))?;
+            let datatype = RcTerm::new_iri(&content[..separator]).map_err(term_error)?;
+            RcTerm::new_literal_dt(&content[separator + 1..], datatype)
+        },
+        _ => return Err(invalid_term_error(&format!("Unknown term kind in a term table: {}", encoded)))
+    };
+
+    Ok(Some(term.map_err(term_error)?))
+}
+
+/// Builds a term table while assigning identifiers to terms
+pub struct TermTableBuilder {
+    /// The identifier of every term that is already in the table
+    identifiers: HashMap<RcTerm, u32>,
+    /// The encoded terms
+    table: Vec<String>
+}
+
+impl TermTableBuilder {
+    /// Builds a term table that only contains the default graph
+    pub fn new() -> TermTableBuilder {
+        TermTableBuilder { identifiers: HashMap::new(), table: vec!(String::new()) }
+    }
+
+    /// Returns the identifier of the given term, adding it to the table if
+    /// needed
+    pub fn identifier_of(&mut self, term: Option<RcTerm>) -> u32 {
+        match term {
+            None => 0,
+            Some(term) => {
+                if let Some(identifier) = self.identifiers.get(&term) {
+                    return *identifier;
+                }
+
+                let identifier = self.table.len() as u32;
+                self.table.push(encode_term(&term));
+                self.identifiers.insert(term, identifier);
+                identifier
+            }
+        }
+    }
+
+    /// Returns the term table as a Javascript array of strings
+    pub fn to_js_array(&self) -> js_sys::Array {
+        self.table.iter().map(|encoded| JsValue::from_str(encoded)).collect()
+    }
+}
+
+/// Reads a term table coming from the Javascript world
+pub fn read_term_table(term_table: &js_sys::Array) -> Result<Vec<Option<RcTerm>>, JsValue> {
+    term_table.iter()
+        .map(|encoded| match encoded.as_string() {
+            Some(encoded) => decode_term(&encoded),
+            None => Err(type_error("A term table must only contain strings"))
+        })
+        .collect()
+}
@@ -2,9 +2,11 @@
 use bjdatasets::treedataset::TreeDataset;
 use crate::wrapping::MatchRequestOnRcTerm;
 use crate::wrapping::ExportableDataset;
+use crate::changes::DatasetObserver;
 use crate::datamodel::term::JsImportTerm;
 use sophia::dataset::Dataset;
 use sophia::quad::stream::QuadSource;
+use wasm_bindgen::JsValue;
 
 /// An exportable dataset to Web Assembly that priorizes indexes that are are
 /// the opposite of the one used by the source dataset when building a new
@@ -19,6 +21,8 @@ impl Default for TreeDatasetAntiWrapper {
     }
 }
 
+impl DatasetObserver for TreeDatasetAntiWrapper {}
+
 impl ExportableDataset<TreeDataset> for TreeDatasetAntiWrapper {
     fn wrap(dataset: TreeDataset) -> Self {
         Self { base : dataset }
@@ -32,8 +36,8 @@ impl ExportableDataset<TreeDataset> for TreeDatasetAntiWrapper {
         &mut self.base
     }
 
-    fn match_quad(&self, subject: &JsImportTerm, predicate: &JsImportTerm, object: &JsImportTerm, graph: &JsImportTerm) -> Self {
-        let m = MatchRequestOnRcTerm::new(subject, predicate, object, graph);
+    fn match_quad(&self, subject: &JsImportTerm, predicate: &JsImportTerm, object: &JsImportTerm, graph: &JsImportTerm) -> Result<Self, JsValue> {
+        let m = MatchRequestOnRcTerm::new(subject, predicate, object, graph)?;
         let mut quads_iter = self.dataset().quads_matching(&m.s, &m.p, &m.o, &m.g);
 
         let s_is_some = match &m.s { sophia::term::matcher::AnyOrExactly::Any => false, _ => true };
@@ -44,6 +48,6 @@ impl ExportableDataset<TreeDataset> for TreeDatasetAntiWrapper {
         let mut dataset = TreeDataset::new_anti(s_is_some, p_is_some, o_is_some, g_is_some);
         quads_iter.in_dataset(&mut dataset).unwrap();
     
-        Self::wrap(dataset)
+        Ok(Self::wrap(dataset))
     }
 }
@@ -1,18 +1,23 @@
 
 use crate::datamodel::term::JsImportTerm;
 use crate::wrapping::ExportableDataset;
+use crate::changes::DatasetObserver;
 use crate::wrapping::MatchRequestOnRcTerm;
 use bjdatasets::vecordataset::VecOrDataset;
 
 use sophia::dataset::Dataset;
 use sophia::dataset::MutableDataset;
 use sophia::quad::stream::QuadSource;
+use wasm_bindgen::JsValue;
 
 #[derive(Default)]
 pub struct VecOrDatasetWrapper<D> where D: Dataset + MutableDataset + Default {
     base: VecOrDataset<D>
 }
 
+impl<D> DatasetObserver for VecOrDatasetWrapper<D>
+    where D: Dataset + MutableDataset + Default {}
+
 impl<D> ExportableDataset<VecOrDataset<D>> for VecOrDatasetWrapper<D>
     where D: Dataset + MutableDataset + Default,
      <D as MutableDataset>::MutationError: From<<D as Dataset>::Error>,
@@ -30,12 +35,12 @@ impl<D> ExportableDataset<VecOrDataset<D>> for VecOrDatasetWrapper<D>
         &mut self.base
     }
 
-    fn match_quad(&self, subject: &JsImportTerm, predicate: &JsImportTerm, object: &JsImportTerm, graph: &JsImportTerm) -> Self {
-        let m = MatchRequestOnRcTerm::new(subject, predicate, object, graph);
+    fn match_quad(&self, subject: &JsImportTerm, predicate: &JsImportTerm, object: &JsImportTerm, graph: &JsImportTerm) -> Result<Self, JsValue> {
+        let m = MatchRequestOnRcTerm::new(subject, predicate, object, graph)?;
         let mut quads_iter = self.dataset().quads_matching(&m.s, &m.p, &m.o, &m.g);
         let mut dataset = VecOrDataset::<D>::new_vector();
         quads_iter.in_dataset(&mut dataset).unwrap();
     
-        Self::wrap(dataset)
+        Ok(Self::wrap(dataset))
     }
 }
@@ -3,6 +3,8 @@
 
 mod btreeddataset_anti;
 mod dataset_into_vector_wrapper;
+mod rdfs_dataset_wrapper;
 
 pub use btreeddataset_anti::TreeDatasetAntiWrapper;
 pub use dataset_into_vector_wrapper::VecOrDatasetWrapper;
+pub use rdfs_dataset_wrapper::RdfsDatasetWrapper;
@@ -0,0 +1,123 @@
+use bjdatasets::RcQuad;
+use bjdatasets::rdfs::RdfsDataset;
+use crate::blanknodes::{ import_quad, BlankNodeRenamer };
+use crate::changes::DatasetObserver;
+use crate::datamodel::quad::SophiaExportQuad;
+use crate::datamodel::term::{ build_rcterm_from_js_import_term, JsImportTerm };
+use crate::wrapping::MatchRequestOnRcTerm;
+use crate::wrapping::ExportableDataset;
+use sophia::dataset::Dataset;
+use sophia::dataset::MutableDataset;
+use sophia::quad::Quad;
+use std::convert::Infallible;
+use wasm_bindgen::JsValue;
+
+/// An exportable dataset that keeps the RDFS entailments of its quads.
+///
+/// The copies, the views and the datasets built from the quads of this one
+/// keep the asserted and the inferred quads apart instead of asserting every
+/// quad of the original dataset.
+#[derive(Default)]
+pub struct RdfsDatasetWrapper {
+    base: RdfsDataset
+}
+
+impl RdfsDatasetWrapper {
+    /// Returns a dataset with the given quads of this dataset, each one
+    /// asserted or inferred as it is in this dataset
+    fn subset_of<I, Q>(&self, quads: I) -> Self
+        where I: Iterator<Item = Result<Q, Infallible>>, Q: Quad {
+        Self::wrap(self.base.subset(quads.map(|quad| RcQuad::new_from_quad(&quad.unwrap()))))
+    }
+}
+
+impl DatasetObserver for RdfsDatasetWrapper {}
+
+impl ExportableDataset<RdfsDataset> for RdfsDatasetWrapper {
+    fn wrap(dataset: RdfsDataset) -> Self {
+        Self { base: dataset }
+    }
+
+    fn dataset(&self) -> &RdfsDataset {
+        &self.base
+    }
+
+    fn mutable_dataset(&mut self) -> &mut RdfsDataset {
+        &mut self.base
+    }
+
+    fn view_copy(&self, pattern: &MatchRequestOnRcTerm) -> Self {
+        self.subset_of(self.base.quads_matching(&pattern.s, &pattern.p, &pattern.o, &pattern.g))
+    }
+
+    fn difference(&self, imported: &JsValue) -> Result<Self, JsValue> {
+        let other = Self::extract_dataset(imported)?;
+
+        Ok(self.subset_of(self.base.quads().filter(|quad| {
+            let quad = quad.as_ref().unwrap();
+            !other.dataset().contains(quad.s(), quad.p(), quad.o(), quad.g()).unwrap()
+        })))
+    }
+
+    fn intersection(&self, imported: &JsValue) -> Result<Self, JsValue> {
+        let other = Self::extract_dataset(imported)?;
+
+        Ok(self.subset_of(self.base.quads().filter(|quad| {
+            let quad = quad.as_ref().unwrap();
+            other.dataset().contains(quad.s(), quad.p(), quad.o(), quad.g()).unwrap()
+        })))
+    }
+
+    /// Asserts the asserted quads of the other dataset in a copy of this one,
+    /// so the rules also run on the quads of both datasets, then adds its
+    /// other inferred quads as inferred quads
+    fn union(&self, imported: &JsValue, options: &JsValue) -> Result<Self, JsValue> {
+        let other = Self::extract_dataset(imported)?;
+        let mut renamer = BlankNodeRenamer::from_options(options)?;
+        let mut union = self.base.clone();
+        let mut inferred = vec![];
+
+        for quad in other.dataset().quads() {
+            let quad = quad.unwrap();
+            let is_asserted = other.dataset().asserted().contains(quad.s(), quad.p(), quad.o(), quad.g()).unwrap();
+
+            let quad = import_quad(renamer.as_mut(), &quad);
+
+            if is_asserted {
+                union.insert(quad.s(), quad.p(), quad.o(), quad.g()).unwrap();
+            } else {
+                inferred.push(quad);
+            }
+        }
+
+        for quad in inferred {
+            union.insert_inferred(quad.s(), quad.p(), quad.o(), quad.g());
+        }
+
+        Ok(Self::wrap(union))
+    }
+
+    fn graph(&self, graph: &JsImportTerm) -> Result<Self, JsValue> {
+        let graph = build_rcterm_from_js_import_term(graph)?;
+        Ok(self.subset_of(self.base.quads_with_g(graph.as_ref())))
+    }
+
+    fn filter(&self, filter_function: &js_sys::Function) -> Result<Self, JsValue> {
+        let mut kept = vec![];
+
+        for quad in self.base.quads() {
+            let quad = RcQuad::new_from_quad(&quad.unwrap());
+            let js_value = JsValue::from(SophiaExportQuad::new_from_quad(&quad));
+
+            if filter_function.call1(&JsValue::NULL, &js_value)?.is_truthy() {
+                kept.push(quad);
+            }
+        }
+
+        Ok(Self::wrap(self.base.subset(kept)))
+    }
+
+    fn copy(&self) -> Self {
+        Self::wrap(self.base.clone())
+    }
+}
@@ -1,4 +1,5 @@
 
+use crate::changes::DatasetObserver;
 use crate::wrapping::ExportableDataset;
 use sophia::dataset::MutableDataset;
 use sophia::dataset::Dataset;
@@ -22,6 +23,11 @@ impl<D> Default for DefaultExporter<D>
     }
 }
 
+impl<D> DatasetObserver for DefaultExporter<D>
+    where D: MutableDataset + Default,
+    <D as MutableDataset>::MutationError: From<<D as Dataset>::Error>,
+    <D as MutableDataset>::MutationError: From<std::convert::Infallible> {}
+
 impl<D> ExportableDataset<D> for DefaultExporter<D>
     where D: MutableDataset + Default,
     <D as MutableDataset>::MutationError: From<<D as Dataset>::Error>,
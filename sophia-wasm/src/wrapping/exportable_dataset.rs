@@ -6,33 +6,240 @@ use crate::datamodel::term::*;
 use sophia::term::RcTerm;
 use std::default::Default;
 use sophia::quad::stream::QuadSource;
+use sophia::triple::stream::TripleSource;
+use sophia::triple::Triple;
+use sophia::parser::TripleParser;
+use js_sys::Reflect;
 use crate::datamodel::quad::*;
 use crate::datamodel::factory::*;
+use crate::datamodel::interning::with_import_memo;
 use wasm_bindgen::JsValue;
 use sophia::quad::Quad;
-use maybe_owned::MaybeOwned;
 use crate::exportiterator::RustExportIterator;
 use sophia::serializer::QuadSerializer;
 use sophia::serializer::Stringifier;
+use crate::error::{ canonicalization_error, invalid_term_error, jsonld_error, parse_error, shacl_error, term_error, type_error, PositionedError };
+use crate::termtable::{ read_term_table, TermTableBuilder };
+use crate::blanknodes::{ import_quad, import_result, BlankNodeRenamer };
+use crate::changes::{ ChangeSet, DatasetObserver, RecordingDataset };
+use bjdatasets::RcQuad;
+use bjdatasets::serializer::{ DatasetSerializer, PrefixMap };
+use bjdatasets::canonical::DatasetCanonicalizer;
+use bjdatasets::graphs::NamedGraphs;
+use bjdatasets::skolem::Skolemize;
+use bjdatasets::patch::{ Diff, Patch };
+use bjdatasets::shacl;
+use bjdatasets::shex;
+use bjdatasets::jsonld::{ dataset_to_jsonld, jsonld_to_quads, JsonLdError };
+use wasm_bindgen::JsCast;
+use std::rc::Rc;
 
-use js_sys::Reflect;
-
-fn build_anyorexactly_for_term(js_parameter: &JsImportTerm) -> AnyOrExactly<RcTerm> {
+fn build_anyorexactly_for_term(js_parameter: &JsImportTerm) -> Result<AnyOrExactly<RcTerm>, JsValue> {
     if js_parameter.is_null() || js_parameter.is_undefined() {
-        AnyOrExactly::Any
+        Ok(AnyOrExactly::Any)
     } else {
-        AnyOrExactly::Exactly(build_rcterm_from_js_import_term(js_parameter).unwrap())
+        Ok(AnyOrExactly::Exactly(build_quad_component_from_js_import_term(js_parameter)?))
     }
 }
 
-fn build_anyorexactly_for_graph(js_parameter: &JsImportTerm) -> AnyOrExactly<Option<RcTerm>> {
+fn build_anyorexactly_for_graph(js_parameter: &JsImportTerm) -> Result<AnyOrExactly<Option<RcTerm>>, JsValue> {
     if js_parameter.is_null() || js_parameter.is_undefined() {
-        AnyOrExactly::Any
+        Ok(AnyOrExactly::Any)
     } else {
-        AnyOrExactly::Exactly(build_rcterm_from_js_import_term(js_parameter))
+        Ok(AnyOrExactly::Exactly(build_rcterm_from_js_import_term(js_parameter)?))
+    }
+}
+
+/// Returns the matcher of the values matched by both `current` and `other`,
+/// or `None` if no value can match both
+fn narrow_matcher<T>(current: &AnyOrExactly<T>, other: AnyOrExactly<T>) -> Option<AnyOrExactly<T>>
+    where T: Clone + PartialEq {
+    match (current, other) {
+        (AnyOrExactly::Any, other) => Some(other),
+        (AnyOrExactly::Exactly(value), AnyOrExactly::Any) => Some(AnyOrExactly::Exactly(value.clone())),
+        (AnyOrExactly::Exactly(value), AnyOrExactly::Exactly(other)) if *value == other => Some(AnyOrExactly::Exactly(other)),
+        (AnyOrExactly::Exactly(_), AnyOrExactly::Exactly(_)) => None
+    }
+}
+
+fn clone_matcher<T>(matcher: &AnyOrExactly<T>) -> AnyOrExactly<T>
+    where T: Clone {
+    match matcher {
+        AnyOrExactly::Any => AnyOrExactly::Any,
+        AnyOrExactly::Exactly(value) => AnyOrExactly::Exactly(value.clone())
+    }
+}
+
+fn matcher_accepts<T>(matcher: &AnyOrExactly<T>, value: &T) -> bool
+    where T: PartialEq {
+    match matcher {
+        AnyOrExactly::Any => true,
+        AnyOrExactly::Exactly(expected) => expected == value
+    }
+}
+
+/// Returns true if the `view` property of the Javascript options of `match`
+/// is true. The options may be undefined.
+pub fn is_view_requested(js_options: &JsValue) -> Result<bool, JsValue> {
+    if js_options.is_null() || js_options.is_undefined() {
+        return Ok(false);
+    }
+
+    Ok(Reflect::get(js_options, &JsValue::from_str("view"))?.is_truthy())
+}
+
+/// Builds a prefix map from a Javascript object that maps prefixes to
+/// namespaces. An undefined object gives an empty prefix map.
+fn build_prefix_map(js_prefixes: &JsValue) -> Result<PrefixMap, JsValue> {
+    let mut prefixes = PrefixMap::new();
+
+    if js_prefixes.is_null() || js_prefixes.is_undefined() {
+        return Ok(prefixes);
+    }
+
+    if !js_prefixes.is_object() {
+        return Err(type_error("The prefixes must be an object that maps prefixes to namespaces"));
+    }
+
+    for entry in js_sys::Object::entries(js_prefixes.unchecked_ref()).iter() {
+        let entry: js_sys::Array = entry.unchecked_into();
+        let prefix = entry.get(0).as_string().unwrap_or_default();
+        let namespace = entry.get(1).as_string()
+            .ok_or_else(|| type_error(&format!("The namespace of the prefix {} must be a string", prefix)))?;
+
+        prefixes.add(&prefix, &namespace).map_err(|message| type_error(&message))?;
+    }
+
+    Ok(prefixes)
+}
+
+/// Parses every quad of `source` before inserting them into `dataset`, so a
+/// syntax error leaves the dataset unchanged. The blank nodes are renamed if
+/// a renamer is given.
+///
+/// Returns the number of quads that were not already in the dataset.
+fn insert_parsed_quads<QS, D>(mut source: QS, mut renamer: Option<&mut BlankNodeRenamer>, dataset: &mut D) -> Result<usize, JsValue>
+    where QS: QuadSource, QS::Error: PositionedError, D: MutableDataset {
+    let mut quads = vec!();
+    source.for_each_quad(|quad| quads.push(import_quad(renamer.as_deref_mut(), &quad)))
+        .map_err(|error| parse_error(&error))?;
+
+    Ok(insert_quads(quads, dataset))
+}
+
+/// Parses every triple of `source` before inserting them into `graph` of
+/// `dataset`, so a syntax error leaves the dataset unchanged.
+///
+/// Returns the number of quads that were not already in the dataset.
+fn insert_parsed_triples<TS, D>(mut source: TS, graph: Option<&RcTerm>, dataset: &mut D) -> Result<usize, JsValue>
+    where TS: TripleSource, TS::Error: PositionedError, D: MutableDataset {
+    let mut quads = vec!();
+    source.for_each_triple(|triple| {
+        let quad = RcQuad::new(&triple.s().into(), &triple.p().into(), &triple.o().into(), graph);
+        quads.push(quad)
+    })
+        .map_err(|error| parse_error(&error))?;
+
+    Ok(insert_quads(quads, dataset))
+}
+
+/// Inserts the quads into the dataset and returns the number of quads that
+/// were not already in it
+fn insert_quads<D>(quads: Vec<RcQuad>, dataset: &mut D) -> usize
+    where D: MutableDataset {
+    quads.iter()
+        .filter(|quad| dataset.insert(quad.s(), quad.p(), quad.o(), quad.g()).unwrap())
+        .count()
+}
+
+/// The options of the parsing functions
+struct ParsingOptions {
+    /// The IRI against which the relative IRIs are resolved
+    base: Option<String>,
+    /// The graph in which the parsed triples are put. `None` is the default
+    /// graph.
+    graph: Option<RcTerm>
+}
+
+impl ParsingOptions {
+    /// Reads the `base` and `graph` properties of a Javascript object. The
+    /// object may be undefined.
+    fn new(js_options: &JsValue) -> Result<ParsingOptions, JsValue> {
+        let mut options = ParsingOptions { base: None, graph: None };
+
+        if js_options.is_null() || js_options.is_undefined() {
+            return Ok(options);
+        }
+
+        let base = Reflect::get(js_options, &JsValue::from_str("base"))?;
+        if !base.is_null() && !base.is_undefined() {
+            let base = base.as_string().ok_or_else(|| type_error("The base IRI must be a string"))?;
+            RcTerm::new_iri(base.as_str()).map_err(term_error)?;
+            options.base = Some(base);
+        }
+
+        let graph = Reflect::get(js_options, &JsValue::from_str("graph"))?;
+        if !graph.is_null() && !graph.is_undefined() {
+            options.graph = build_rcterm_from_js_import_term(&graph.into())?;
+        }
+
+        Ok(options)
     }
 }
 
+/// Converts a Javascript value into a JSON value. A string is parsed as a
+/// JSON text.
+fn to_json(js_value: &JsValue) -> Result<serde_json::Value, JsValue> {
+    let text = match js_value.as_string() {
+        Some(text) => text,
+        None => js_sys::JSON::stringify(js_value)?.as_string()
+            .ok_or_else(|| type_error("Expected a JSON object"))?
+    };
+
+    serde_json::from_str(&text).map_err(|error| parse_error(&error))
+}
+
+/// Converts a JSON value into a Javascript value
+fn from_json(value: &serde_json::Value) -> JsValue {
+    // A serialized JSON value can always be parsed back
+    js_sys::JSON::parse(&value.to_string()).unwrap()
+}
+
+/// Reads the `documentLoader` function of a Javascript options object. The
+/// object may be undefined.
+fn document_loader(js_options: &JsValue) -> Result<Option<js_sys::Function>, JsValue> {
+    if js_options.is_null() || js_options.is_undefined() {
+        return Ok(None);
+    }
+
+    let loader = Reflect::get(js_options, &JsValue::from_str("documentLoader"))?;
+    if loader.is_null() || loader.is_undefined() {
+        return Ok(None);
+    }
+
+    loader.dyn_into::<js_sys::Function>()
+        .map(Some)
+        .map_err(|_| type_error("The documentLoader must be a function"))
+}
+
+/// Loads a remote context by calling the Javascript `loader` with its IRI.
+/// Remote contexts can not be loaded without a loader.
+fn load_context(loader: &Option<js_sys::Function>, iri: &str) -> Result<serde_json::Value, JsonLdError> {
+    let loader = loader.as_ref()
+        .ok_or_else(|| JsonLdError::new("loading remote context failed", format!("No documentLoader was given to load {}", iri)))?;
+
+    let describe = |error: JsValue| {
+        let message = Reflect::get(&error, &JsValue::from_str("message")).ok()
+            .and_then(|message| message.as_string())
+            .or_else(|| error.as_string())
+            .unwrap_or_default();
+        JsonLdError::new("loading remote context failed", format!("{}: {}", iri, message))
+    };
+
+    let document = loader.call1(&JsValue::NULL, &JsValue::from_str(iri)).map_err(describe)?;
+    to_json(&document).map_err(describe)
+}
+
 
 /// A list of AnyOrExactly MatchTerms to build a match request on a Sophia dataset
 pub struct MatchRequestOnRcTerm {
@@ -48,12 +255,45 @@ pub struct MatchRequestOnRcTerm {
 
 impl MatchRequestOnRcTerm {
     /// Builds a `MatchRequestOnRcTerm` from `JsImportTerms`
-    pub fn new(subject: &JsImportTerm, predicate: &JsImportTerm, object: &JsImportTerm, graph: &JsImportTerm) -> MatchRequestOnRcTerm {       
+    pub fn new(subject: &JsImportTerm, predicate: &JsImportTerm, object: &JsImportTerm, graph: &JsImportTerm) -> Result<MatchRequestOnRcTerm, JsValue> {
+        Ok(MatchRequestOnRcTerm {
+            s: build_anyorexactly_for_term(subject)?,
+            p: build_anyorexactly_for_term(predicate)?,
+            o: build_anyorexactly_for_term(object)?,
+            g: build_anyorexactly_for_graph(graph)?
+        })
+    }
+
+    /// Returns the request that matches the quads matched by both this
+    /// request and `other`, or `None` if no quad can match both
+    pub fn narrow(&self, other: MatchRequestOnRcTerm) -> Option<MatchRequestOnRcTerm> {
+        Some(MatchRequestOnRcTerm {
+            s: narrow_matcher(&self.s, other.s)?,
+            p: narrow_matcher(&self.p, other.p)?,
+            o: narrow_matcher(&self.o, other.o)?,
+            g: narrow_matcher(&self.g, other.g)?
+        })
+    }
+
+    /// Returns true if the quad is matched by this request
+    pub fn matches<Q>(&self, quad: &Q) -> bool
+        where Q: Quad {
+        let quad = RcQuad::new_from_quad(quad);
+
+        matcher_accepts(&self.s, &quad._subject)
+            && matcher_accepts(&self.p, &quad._predicate)
+            && matcher_accepts(&self.o, &quad._object)
+            && matcher_accepts(&self.g, &quad._graph)
+    }
+}
+
+impl Clone for MatchRequestOnRcTerm {
+    fn clone(&self) -> Self {
         MatchRequestOnRcTerm {
-            s: build_anyorexactly_for_term(subject),
-            p: build_anyorexactly_for_term(predicate),
-            o: build_anyorexactly_for_term(object),
-            g: build_anyorexactly_for_graph(graph)
+            s: clone_matcher(&self.s),
+            p: clone_matcher(&self.p),
+            o: clone_matcher(&self.o),
+            g: clone_matcher(&self.g)
         }
     }
 }
@@ -74,7 +314,12 @@ impl MatchRequestOnRcTerm {
 /// If no operation is redefined, the `wasm_bindgen_dataset` macro can be used
 /// instead, which builds both a default implementation for this trait and an export
 /// for wasm_bindgen.
-pub trait ExportableDataset<D>: Default
+///
+/// The `DatasetObserver` implementation is notified of the quads changed by
+/// every mutating call of the exported object. The mutating methods receive
+/// the change set in which they record their changes if they are observed,
+/// and mutate the dataset through `recording_dataset`.
+pub trait ExportableDataset<D>: Default + DatasetObserver + 'static
     where D: MutableDataset + Default,
         <D as MutableDataset>::MutationError: From<<D as Dataset>::Error>,
         <D as MutableDataset>::MutationError: From<std::convert::Infallible> {
@@ -88,15 +333,59 @@ pub trait ExportableDataset<D>: Default
     /// Returns a const reference to the contained dataset
     fn dataset(&self) -> &D;
 
+    /// Returns the contained dataset, that records its effective changes in
+    /// `changes` if given
+    fn recording_dataset<'a>(&'a mut self, changes: Option<&'a mut ChangeSet>) -> RecordingDataset<'a, D>
+        where D: 'a {
+        RecordingDataset::new(self.mutable_dataset(), changes)
+    }
+
+
+    fn match_quad(&self, subject: &JsImportTerm, predicate: &JsImportTerm, object: &JsImportTerm, graph: &JsImportTerm) -> Result<Self, JsValue> {
+        let m = MatchRequestOnRcTerm::new(subject, predicate, object, graph)?;
+        Ok(self.view_copy(&m))
+    }
+
+    // ==== Views
+    // A view is an exported object that shares the dataset of another one and
+    // only contains the quads that match a pattern (see the `view` option of
+    // `match`)
 
-    fn match_quad(&self, subject: &JsImportTerm, predicate: &JsImportTerm, object: &JsImportTerm, graph: &JsImportTerm) -> Self {
-        let m = MatchRequestOnRcTerm::new(subject, predicate, object, graph);
-        let mut quads_iter = self.dataset().quads_matching(&m.s, &m.p, &m.o, &m.g);
+    /// Returns a new instance that contains the quads that match `pattern`
+    fn view_copy(&self, pattern: &MatchRequestOnRcTerm) -> Self {
+        let mut quads_iter = self.dataset().quads_matching(&pattern.s, &pattern.p, &pattern.o, &pattern.g);
         let mut dataset = D::default();
         quads_iter.in_dataset(&mut dataset).unwrap();
         Self::wrap(dataset)
     }
 
+    /// Returns the number of quads that match `pattern`
+    fn view_size(&self, pattern: &MatchRequestOnRcTerm) -> usize {
+        self.dataset().quads_matching(&pattern.s, &pattern.p, &pattern.o, &pattern.g).count()
+    }
+
+    /// Returns true if the quad is in the dataset and matches `pattern`
+    fn view_has_quad(&self, pattern: &MatchRequestOnRcTerm, quad: &JsImportQuad) -> Result<bool, JsValue> {
+        let sophia_quad = SophiaExportDataFactory::from_quad(quad)?;
+        Ok(pattern.matches(&sophia_quad) && self.has_quad(quad)?)
+    }
+
+    /// Returns an iterator that lazily produces the quads of the dataset
+    /// shared by `exported` that match `pattern`
+    fn view_iterator(exported: &Rc<Self>, pattern: MatchRequestOnRcTerm) -> RustExportIterator {
+        // The iterator borrows the pattern, so it is kept alive with the dataset
+        let owner = Rc::new((exported.clone(), pattern));
+
+        RustExportIterator::new(owner, |owner: &(Rc<Self>, MatchRequestOnRcTerm)| {
+            let (exported, m) = owner;
+            Box::new(
+                exported.dataset()
+                    .quads_matching(&m.s, &m.p, &m.o, &m.g)
+                    .map(|quad| SophiaExportQuad::new_from_quad(&quad.unwrap()))
+            )
+        })
+    }
+
     fn size(&self) -> usize {
         match self.dataset().quads().into_iter().size_hint() {
             (v1, Some(v2)) if v1 == v2 => v1,
@@ -104,18 +393,20 @@ pub trait ExportableDataset<D>: Default
         }
     }
 
-    fn add(&mut self, quad: &JsImportQuad) {
-        self.mutable_dataset().insert(
-            &build_stringterm_from_js_import_term(&quad.subject()).unwrap(),
-            &build_stringterm_from_js_import_term(&quad.predicate()).unwrap(),
-            &build_stringterm_from_js_import_term(&quad.object()).unwrap(),
-            build_stringterm_from_js_import_term(&quad.graph()).as_ref(),
+    fn add(&mut self, quad: &JsImportQuad, changes: Option<&mut ChangeSet>) -> Result<(), JsValue> {
+        let sophia_quad = SophiaExportDataFactory::from_quad(quad)?;
+        self.recording_dataset(changes).insert(
+            &sophia_quad._subject,
+            &sophia_quad._predicate,
+            &sophia_quad._object,
+            sophia_quad._graph.as_ref()
         ).unwrap();
+        Ok(())
     }
 
-    fn delete(&mut self, quad: &JsImportQuad) {
-        let sophia_quad = SophiaExportDataFactory::from_quad(quad);
-        self.mutable_dataset().remove(
+    fn delete(&mut self, quad: &JsImportQuad, changes: Option<&mut ChangeSet>) -> Result<(), JsValue> {
+        let sophia_quad = SophiaExportDataFactory::from_quad(quad)?;
+        self.recording_dataset(changes).remove(
             &sophia_quad._subject,
             &sophia_quad._predicate,
             &sophia_quad._object,
@@ -124,11 +415,12 @@ pub trait ExportableDataset<D>: Default
                 Some(x) => Some(x)
             }
         ).unwrap();
+        Ok(())
     }
 
-    fn has_quad(&self, quad: &JsImportQuad) -> bool {
-        let sophia_quad = SophiaExportDataFactory::from_quad(quad);
-        self.dataset().contains(
+    fn has_quad(&self, quad: &JsImportQuad) -> Result<bool, JsValue> {
+        let sophia_quad = SophiaExportDataFactory::from_quad(quad)?;
+        Ok(self.dataset().contains(
             &sophia_quad._subject,
             &sophia_quad._predicate,
             &sophia_quad._object,
@@ -136,54 +428,77 @@ pub trait ExportableDataset<D>: Default
                 None => None,
                 Some(x) => Some(x)
             }
-        ).unwrap()
+        ).unwrap())
     }
 
-    fn add_all(&mut self, quads_as_jsvalue: &JsValue) {
-        if quads_as_jsvalue.is_null() || quads_as_jsvalue.is_undefined() {
-            return;
-        }
+    /// Adds the quads of a dataset or of a sequence of quads. If one of the
+    /// quads is invalid, no quad is added.
+    ///
+    /// If `options.renameBlankNodes` is true, the incoming blank nodes are
+    /// renamed. Returns `{ added, blankNodeMapping }` (see
+    /// `crate::blanknodes`).
+    fn add_all(&mut self, quads_as_jsvalue: &JsValue, options: &JsValue, changes: Option<&mut ChangeSet>) -> Result<JsValue, JsValue> {
+        let mut renamer = BlankNodeRenamer::from_options(options)?;
+        let mut quads = vec!();
 
         // Try to detect a SophiaExportDataset
-        match Self::try_from(&quads_as_jsvalue) {
+        match Self::try_from(quads_as_jsvalue) {
             Some(exported) => {
-                exported.dataset().quads().in_dataset(self.mutable_dataset()).unwrap();
+                for quad in exported.dataset().quads() {
+                    quads.push(import_quad(renamer.as_mut(), &quad.unwrap()));
+                }
             },
+            None if quads_as_jsvalue.is_null() || quads_as_jsvalue.is_undefined() => {},
             None => {
-                // We get back our jsvalue and we use the fact that both a dataset and a sequence<quad> can be iterated on to
-                // receive quads.
-                let iterator = js_sys::try_iter(&quads_as_jsvalue);
-
-                match iterator {
-                    Ok(Some(iter)) => {
-                        for js_value in iter {
-                            match js_value {
-                                Ok(some_value) => self.add(&some_value.into()),
-                                _ => {}
-                            }
-                        }
-                    },
-                    _ => {
-                        // TODO : error management
-                        crate::util::log("ExportableDataset::add_all did not receive an iterable");
+                // Both a dataset and a sequence<quad> can be iterated on to
+                // receive quads
+                let iterator = js_sys::try_iter(quads_as_jsvalue)?
+                    .ok_or_else(|| type_error("addAll expects a dataset or a sequence of quads"))?;
+
+                with_import_memo(|| -> Result<(), JsValue> {
+                    for js_value in iterator {
+                        let quad = SophiaExportDataFactory::from_quad(&js_value?.into())?;
+                        quads.push(import_quad(renamer.as_mut(), &quad));
                     }
-                }
+
+                    Ok(())
+                })?;
             }
         }
+
+        let added = insert_quads(quads, &mut self.recording_dataset(changes));
+        import_result(added, renamer.as_ref())
+    }
+
+    /// Inserts the given quads and returns the number of quads that were not
+    /// already in the dataset. Used by the RDF.JS Sink interface.
+    fn add_quads(&mut self, quads: Vec<RcQuad>, changes: Option<&mut ChangeSet>) -> usize {
+        insert_quads(quads, &mut self.recording_dataset(changes))
+    }
+
+    /// Removes the given quads and returns the number of quads that were in
+    /// the dataset. Used by the RDF.JS Store interface.
+    fn delete_quads(&mut self, quads: Vec<RcQuad>, changes: Option<&mut ChangeSet>) -> usize {
+        let mut dataset = self.recording_dataset(changes);
+
+        quads.iter()
+            .filter(|quad| dataset.remove(quad.s(), quad.p(), quad.o(), quad.g()).unwrap())
+            .count()
     }
 
-    fn contains(&self, imported: &JsValue) -> bool {
-        let maybe_dataset = Self::extract_dataset(imported);
-        self.contains_dataset(maybe_dataset.dataset())
+    fn contains(&self, imported: &JsValue) -> Result<bool, JsValue> {
+        let maybe_dataset = Self::extract_dataset(imported)?;
+        Ok(self.contains_dataset(maybe_dataset.dataset()))
     }
 
-    fn delete_matches(&mut self, subject: &JsImportTerm, predicate: &JsImportTerm, object: &JsImportTerm, graph: &JsImportTerm) {
-        let m = MatchRequestOnRcTerm::new(subject, predicate, object, graph);
-        self.mutable_dataset().remove_matching(&m.s, &m.p, &m.o, &m.g).unwrap();
+    fn delete_matches(&mut self, subject: &JsImportTerm, predicate: &JsImportTerm, object: &JsImportTerm, graph: &JsImportTerm, changes: Option<&mut ChangeSet>) -> Result<(), JsValue> {
+        let m = MatchRequestOnRcTerm::new(subject, predicate, object, graph)?;
+        self.recording_dataset(changes).remove_matching(&m.s, &m.p, &m.o, &m.g).unwrap();
+        Ok(())
     }
 
-    fn difference(&self, imported: &JsValue) -> Self {
-        let other = Self::extract_dataset(imported);
+    fn difference(&self, imported: &JsValue) -> Result<Self, JsValue> {
+        let other = Self::extract_dataset(imported)?;
 
         let mut dest = D::default();
 
@@ -194,11 +509,11 @@ pub trait ExportableDataset<D>: Default
             })
             .in_dataset(&mut dest).unwrap();
 
-        Self::wrap(dest)
+        Ok(Self::wrap(dest))
     }
     
-    fn intersection(&self, imported: &JsValue) -> Self {
-        let other = Self::extract_dataset(imported);
+    fn intersection(&self, imported: &JsValue) -> Result<Self, JsValue> {
+        let other = Self::extract_dataset(imported)?;
 
         let mut dest = D::default();
 
@@ -209,81 +524,284 @@ pub trait ExportableDataset<D>: Default
             })
             .in_dataset(&mut dest).unwrap();
 
-        Self::wrap(dest)
+        Ok(Self::wrap(dest))
     }
 
-    fn union(&self, imported: &JsValue) -> Self {
-        let other = Self::extract_dataset(imported);
+    /// Returns a new dataset with the quads of both datasets.
+    ///
+    /// If `options.renameBlankNodes` is true, the blank nodes of the other
+    /// dataset are renamed (see `crate::blanknodes`). The mapping is not
+    /// reported.
+    fn union(&self, imported: &JsValue, options: &JsValue) -> Result<Self, JsValue> {
+        let other = Self::extract_dataset(imported)?;
 
         let mut ds = D::default();
 
         self.dataset().quads().in_dataset(&mut ds).unwrap();
-        other.dataset().quads().in_dataset(&mut ds).unwrap();
 
-        Self::wrap(ds)
+        match BlankNodeRenamer::from_options(options)? {
+            None => {
+                other.dataset().quads().in_dataset(&mut ds).unwrap();
+            },
+            Some(mut renamer) => {
+                let quads = other.dataset().quads()
+                    .map(|quad| renamer.rename_quad(&quad.unwrap()))
+                    .collect();
+                insert_quads(quads, &mut ds);
+            }
+        }
+
+        Ok(Self::wrap(ds))
     }
 
-    fn equals(&self, imported: &JsValue) -> bool {
-        let other = Self::extract_dataset(imported);
-        self.size() == other.size() && self.contains_dataset(other.dataset())
+    /// Returns true if both datasets contain the same quads. If blank nodes
+    /// are present, their labels are ignored: the datasets are compared with
+    /// `is_isomorphic`.
+    fn equals(&self, imported: &JsValue) -> Result<bool, JsValue> {
+        let other = Self::extract_dataset(imported)?;
+
+        if self.size() != other.size() {
+            return Ok(false);
+        }
+
+        if self.dataset().has_blank_nodes() || other.dataset().has_blank_nodes() {
+            return self.dataset().is_isomorphic(other.dataset()).map_err(|error| canonicalization_error(&error));
+        }
+
+        Ok(self.contains_dataset(other.dataset()))
     }
 
-    fn for_each(&self, quad_run_iteratee: &js_sys::Function) {
-        self.dataset().quads()
-            .into_iter()
-            .for_each(|quad| {
-                let quad = quad.unwrap();
-                let export_quad = SophiaExportQuad::new_from_quad(&quad);
-                let js_value = JsValue::from(export_quad);
-                quad_run_iteratee.call1(&JsValue::NULL, &js_value).unwrap();
-            });
+    /// Returns the canonical N-Quads serialization of the dataset (RDFC-1.0)
+    fn to_canonical(&self) -> Result<String, JsValue> {
+        self.dataset().to_canonical().map_err(|error| canonicalization_error(&error))
     }
 
-    fn some(&self, filter_function: &js_sys::Function) -> bool {
-        self.dataset().quads()
-            .into_iter()
-            .any(|quad| {
-                let quad = quad.unwrap();
-                let export_quad = SophiaExportQuad::new_from_quad(&quad);
-                let js_value = JsValue::from(export_quad);
-                filter_function.call1(&JsValue::NULL, &js_value).unwrap().is_truthy()
+    /// Returns the hexadecimal SHA-256 digest of the canonical serialization
+    fn canonical_hash(&self) -> Result<String, JsValue> {
+        self.dataset().canonical_hash().map_err(|error| canonicalization_error(&error))
+    }
+
+    /// Returns true if the given dataset is equal to this one up to the labels
+    /// of the blank nodes
+    fn is_isomorphic(&self, imported: &JsValue) -> Result<bool, JsValue> {
+        let other = Self::extract_dataset(imported)?;
+        self.dataset().is_isomorphic(other.dataset()).map_err(|error| canonicalization_error(&error))
+    }
+
+    /// Returns the RDF Patch that turns this dataset into `imported`
+    fn diff(&self, imported: &JsValue) -> Result<String, JsValue>
+        where D: Diff {
+        let other = Self::extract_dataset(imported)?;
+        Ok(self.dataset().diff(other.dataset()).to_string())
+    }
+
+    /// Applies an RDF Patch. The patch is entirely read before being applied,
+    /// so an invalid patch changes nothing. Returns the number of quads that
+    /// were actually added or removed.
+    fn apply_patch(&mut self, text: &str, changes: Option<&mut ChangeSet>) -> Result<usize, JsValue> {
+        let patch = Patch::parse(text).map_err(|error| parse_error(&error))?;
+        Ok(patch.apply(&mut self.recording_dataset(changes)))
+    }
+
+    /// Validates the dataset against the SHACL shapes of `imported` and
+    /// returns the validation report
+    fn validate(&self, imported: &JsValue) -> Result<Self, JsValue> {
+        let shapes = Self::extract_dataset(imported)?;
+        let report = shacl::validate(shapes.dataset(), self.dataset()).map_err(|error| shacl_error(&error))?;
+
+        let mut dataset = D::default();
+        report.write_to(&mut dataset);
+        Ok(Self::wrap(dataset))
+    }
+
+    /// Validates the nodes of the shape map `shape_map` against the shapes of
+    /// the ShEx schema `schema`, both written in their compact syntax.
+    /// Returns one `{ node, shape, conforms, reason }` object per validated
+    /// node.
+    fn validate_shex(&self, schema: &str, shape_map: &str) -> Result<js_sys::Array, JsValue> {
+        let results = shex::validate(self.dataset(), schema, shape_map).map_err(|error| parse_error(&error))?;
+
+        Ok(results.into_iter()
+            .map(|result| {
+                let reason = result.reason.map_or(JsValue::NULL, |reason| JsValue::from_str(&reason));
+
+                // Setting a property on a freshly built object can not fail
+                let exported = js_sys::Object::new();
+                js_sys::Reflect::set(&exported, &JsValue::from_str("node"), &SophiaExportTerm::new(&result.node).into()).unwrap();
+                js_sys::Reflect::set(&exported, &JsValue::from_str("shape"), &JsValue::from_str(&result.shape)).unwrap();
+                js_sys::Reflect::set(&exported, &JsValue::from_str("conforms"), &JsValue::from_bool(result.conforms)).unwrap();
+                js_sys::Reflect::set(&exported, &JsValue::from_str("reason"), &reason).unwrap();
+                JsValue::from(exported)
             })
+            .collect())
     }
 
-    fn every(&self, filter_function: &js_sys::Function) -> bool {
-        self.dataset().quads()
-            .into_iter()
-            .all(|quad| {
-                let quad = quad.unwrap();
-                let export_quad = SophiaExportQuad::new_from_quad(&quad);
-                let js_value = JsValue::from(export_quad);
-                filter_function.call1(&JsValue::NULL, &js_value).unwrap().is_truthy()
+    /// Replaces every blank node with an IRI under
+    /// `{base_iri}/.well-known/genid/` and returns the number of replaced
+    /// blank nodes
+    fn skolemize(&mut self, base_iri: &str, changes: Option<&mut ChangeSet>) -> Result<usize, JsValue>
+        where D: Skolemize {
+        self.recording_dataset(changes).skolemize(base_iri).map_err(term_error)
+    }
+
+    /// Replaces every IRI under `{base_iri}/.well-known/genid/` with a blank
+    /// node and returns the number of replaced IRIs
+    fn deskolemize(&mut self, base_iri: &str, changes: Option<&mut ChangeSet>) -> Result<usize, JsValue>
+        where D: Skolemize {
+        self.recording_dataset(changes).deskolemize(base_iri).map_err(term_error)
+    }
+
+    // ==== Named graphs
+
+    /// Returns the name of every graph that contains at least one quad. The
+    /// default graph is returned as a `DefaultGraph` term.
+    fn graph_names(&self) -> js_sys::Array
+        where D: NamedGraphs {
+        self.dataset()
+            .graph_names()
+            .iter()
+            .map(|graph_name| match graph_name {
+                Some(term) => SophiaExportTerm::new(term),
+                None => SophiaExportTerm::default_graph()
             })
+            .map(JsValue::from)
+            .collect()
+    }
+
+    /// Returns a new dataset with the quads of the graph `graph`
+    fn graph(&self, graph: &JsImportTerm) -> Result<Self, JsValue> {
+        let graph = build_rcterm_from_js_import_term(graph)?;
+        let mut dataset = D::default();
+        self.dataset().quads_with_g(graph.as_ref()).in_dataset(&mut dataset).unwrap();
+        Ok(Self::wrap(dataset))
+    }
+
+    /// Removes every quad of the graph `graph` and returns the number of
+    /// removed quads
+    fn drop_graph(&mut self, graph: &JsImportTerm, changes: Option<&mut ChangeSet>) -> Result<usize, JsValue>
+        where D: NamedGraphs {
+        let graph = build_rcterm_from_js_import_term(graph)?;
+        Ok(self.recording_dataset(changes).drop_graph(graph.as_ref()))
+    }
+
+    /// Copies the quads of the graph `from` in the graph `to` and returns the
+    /// number of added quads
+    fn copy_graph(&mut self, from: &JsImportTerm, to: &JsImportTerm, changes: Option<&mut ChangeSet>) -> Result<usize, JsValue>
+        where D: NamedGraphs {
+        let from = build_rcterm_from_js_import_term(from)?;
+        let to = build_rcterm_from_js_import_term(to)?;
+        Ok(self.recording_dataset(changes).copy_graph(from.as_ref(), to.as_ref()))
+    }
+
+    /// Moves the quads of the graph `from` in the graph `to` and returns the
+    /// number of moved quads
+    fn move_graph(&mut self, from: &JsImportTerm, to: &JsImportTerm, changes: Option<&mut ChangeSet>) -> Result<usize, JsValue>
+        where D: NamedGraphs {
+        let from = build_rcterm_from_js_import_term(from)?;
+        let to = build_rcterm_from_js_import_term(to)?;
+        Ok(self.recording_dataset(changes).move_graph(from.as_ref(), to.as_ref()))
+    }
+
+    /// Adds every triple of `triples`, a dataset or a sequence of quads whose
+    /// graphs are ignored, in the graph `graph`. Returns the number of added
+    /// quads.
+    fn add_graph(&mut self, graph: &JsImportTerm, triples: &JsValue, changes: Option<&mut ChangeSet>) -> Result<usize, JsValue> {
+        let graph = build_rcterm_from_js_import_term(graph)?;
+        let mut triples_to_add = vec!();
+
+        match Self::try_from(triples) {
+            Some(exported) => {
+                for quad in exported.dataset().quads() {
+                    triples_to_add.push(RcQuad::new_from_quad(&quad.unwrap()));
+                }
+            },
+            None => {
+                let iterator = js_sys::try_iter(triples)?
+                    .ok_or_else(|| type_error("addGraph expects a dataset or a sequence of quads"))?;
+
+                with_import_memo(|| -> Result<(), JsValue> {
+                    for js_value in iterator {
+                        let quad = SophiaExportDataFactory::from_quad(&js_value?.into())?;
+                        triples_to_add.push(RcQuad::new_from_quad(&quad));
+                    }
+
+                    Ok(())
+                })?;
+            }
+        }
+
+        let mut dataset = self.recording_dataset(changes);
+        let mut number_of_added_quads = 0;
+
+        for triple in triples_to_add {
+            if dataset.insert(triple.s(), triple.p(), triple.o(), graph.as_ref()).unwrap() {
+                number_of_added_quads += 1;
+            }
+        }
+
+        Ok(number_of_added_quads)
     }
 
-    fn filter(&self, filter_function: &js_sys::Function) -> Self {
+    fn for_each(&self, quad_run_iteratee: &js_sys::Function) -> Result<(), JsValue> {
+        for quad in self.dataset().quads() {
+            let quad = quad.unwrap();
+            let export_quad = SophiaExportQuad::new_from_quad(&quad);
+            let js_value = JsValue::from(export_quad);
+            quad_run_iteratee.call1(&JsValue::NULL, &js_value)?;
+        }
+
+        Ok(())
+    }
+
+    fn some(&self, filter_function: &js_sys::Function) -> Result<bool, JsValue> {
+        for quad in self.dataset().quads() {
+            let quad = quad.unwrap();
+            let export_quad = SophiaExportQuad::new_from_quad(&quad);
+            let js_value = JsValue::from(export_quad);
+            if filter_function.call1(&JsValue::NULL, &js_value)?.is_truthy() {
+                return Ok(true);
+            }
+        }
+
+        Ok(false)
+    }
+
+    fn every(&self, filter_function: &js_sys::Function) -> Result<bool, JsValue> {
+        for quad in self.dataset().quads() {
+            let quad = quad.unwrap();
+            let export_quad = SophiaExportQuad::new_from_quad(&quad);
+            let js_value = JsValue::from(export_quad);
+            if !filter_function.call1(&JsValue::NULL, &js_value)?.is_truthy() {
+                return Ok(false);
+            }
+        }
+
+        Ok(true)
+    }
+
+    fn filter(&self, filter_function: &js_sys::Function) -> Result<Self, JsValue> {
         let mut ds = D::default();
 
-        self.dataset().quads()
-            .filter_quads(|quad| {
-                let export_quad = SophiaExportQuad::new_from_quad(&quad);
-                let js_value = JsValue::from(export_quad);
-                filter_function.call1(&JsValue::NULL, &js_value).unwrap().is_truthy()
-            })
-            .in_dataset(&mut ds)
-            .unwrap();
+        for quad in self.dataset().quads() {
+            let quad = quad.unwrap();
+            let export_quad = SophiaExportQuad::new_from_quad(&quad);
+            let js_value = JsValue::from(export_quad);
+            if filter_function.call1(&JsValue::NULL, &js_value)?.is_truthy() {
+                ds.insert(quad.s(), quad.p(), quad.o(), quad.g()).unwrap();
+            }
+        }
 
-        Self::wrap(ds)
+        Ok(Self::wrap(ds))
     }
 
-    fn reduce(&self, reducer: js_sys::Function, initial_value: &JsValue) -> JsValue {
+    fn reduce(&self, reducer: js_sys::Function, initial_value: &JsValue) -> Result<JsValue, JsValue> {
         let mut iterator = self.dataset().quads();
         let mut accumulated_value = initial_value.clone();
 
         if accumulated_value.as_ref().is_undefined() {
             let first_iter = iterator.next();
             match first_iter {
-                None => return accumulated_value,
+                None => return Ok(accumulated_value),
                 Some(quad_result) => {
                     let quad = quad_result.unwrap();
                     let export_quad = SophiaExportQuad::new_from_quad(&quad);
@@ -297,26 +815,25 @@ pub trait ExportableDataset<D>: Default
             let quad = quad_result.unwrap();
             let export_quad = SophiaExportQuad::new_from_quad(&quad);
             let to_accumulate = JsValue::from(export_quad);
-            accumulated_value = reducer.call2(&JsValue::NULL, &accumulated_value, &to_accumulate).unwrap();
+            accumulated_value = reducer.call2(&JsValue::NULL, &accumulated_value, &to_accumulate)?;
         }
 
-        accumulated_value
+        Ok(accumulated_value)
     }
 
-    fn map(&self, map_function: &js_sys::Function) -> Self {
+    fn map(&self, map_function: &js_sys::Function) -> Result<Self, JsValue> {
         let mut ds = Self::wrap(D::default());
 
-        self.dataset().quads()
-            .for_each_quad(|quad| {
-                let export_quad = SophiaExportQuad::new_from_quad(&quad);
-                let js_value = JsValue::from(export_quad);
-                let mapped_js_quad = map_function.call1(&JsValue::NULL, &js_value).unwrap();
-                let mapped_quad = JsImportQuad::from(mapped_js_quad);
-                ds.add(&mapped_quad);
-            })
-            .unwrap();
+        for quad in self.dataset().quads() {
+            let quad = quad.unwrap();
+            let export_quad = SophiaExportQuad::new_from_quad(&quad);
+            let js_value = JsValue::from(export_quad);
+            let mapped_js_quad = map_function.call1(&JsValue::NULL, &js_value)?;
+            let mapped_quad = JsImportQuad::from(mapped_js_quad);
+            ds.add(&mapped_quad, None)?;
+        }
 
-        ds
+        Ok(ds)
     }
 
     fn to_string(&self) -> String {
@@ -337,64 +854,117 @@ pub trait ExportableDataset<D>: Default
             .collect()
     }
 
-    fn get_iterator(&self) -> RustExportIterator {
-        RustExportIterator::new(self.quads())
-    }
+    /// Returns an object with an `ids` `Uint32Array` that contains the
+    /// identifiers of the terms of every quad and a `terms` array that
+    /// contains the term table (see `crate::termtable`).
+    fn export_ids(&self) -> JsValue {
+        let mut term_table = TermTableBuilder::new();
+        let mut ids = Vec::with_capacity(self.size() * 4);
 
-    // ==== Utility functions
+        for quad in self.dataset().quads() {
+            let quad = quad.unwrap();
+            ids.push(term_table.identifier_of(Some(quad.s().into())));
+            ids.push(term_table.identifier_of(Some(quad.p().into())));
+            ids.push(term_table.identifier_of(Some(quad.o().into())));
+            ids.push(term_table.identifier_of(quad.g().map(|g| g.into())));
+        }
 
-    fn try_from<'a>(imported: &'a JsValue) -> Option<&'a Self> {
-        // TODO : in trait, put method get_uniqueid which is exported and compared here
+        let exported = js_sys::Object::new();
+        js_sys::Reflect::set(&exported, &JsValue::from_str("ids"), &js_sys::Uint32Array::from(&ids[..])).unwrap();
+        js_sys::Reflect::set(&exported, &JsValue::from_str("terms"), &term_table.to_js_array()).unwrap();
+        exported.into()
+    }
 
-        let rust_managed = Reflect::get(imported, &JsValue::from_str("rust_managed"));
-        if rust_managed.is_err() {
-            return None;
+    /// Adds the quads described by identifiers in `term_table`, in the format
+    /// produced by `export_ids`. If an identifier or a term is invalid, no
+    /// quad is added.
+    fn import_ids(&mut self, ids: &[u32], term_table: &js_sys::Array, changes: Option<&mut ChangeSet>) -> Result<(), JsValue> {
+        if ids.len() % 4 != 0 {
+            return Err(type_error("The number of identifiers must be a multiple of 4"));
         }
 
-        let rust_managed = rust_managed.unwrap().as_f64();
+        let terms = read_term_table(term_table)?;
 
-        match rust_managed {
-            None => None,
-            Some(rust_managed) => unsafe {
-                let ptr: *const Self = (rust_managed as u32) as *const Self;
-                
-                if !ptr.is_null() {
-                    ptr.as_ref()
-                } else {
-                    None
-                }
-            }
+        let term_at = |id: u32| terms.get(id as usize)
+            .ok_or_else(|| type_error(&format!("The identifier {} is not in the term table", id)));
+        let quad_component_at = |id: u32| term_at(id)?.as_ref()
+            .ok_or_else(|| invalid_term_error("The default graph can only be used as a graph"));
+
+        let mut quads = Vec::with_capacity(ids.len() / 4);
+
+        for spog in ids.chunks(4) {
+            quads.push(RcQuad::new(
+                quad_component_at(spog[0])?,
+                quad_component_at(spog[1])?,
+                quad_component_at(spog[2])?,
+                term_at(spog[3])?.as_ref()
+            ));
         }
+
+        insert_quads(quads, &mut self.recording_dataset(changes));
+        Ok(())
+    }
+
+    /// Returns an iterator that lazily produces the quads of the dataset
+    /// shared by `exported`.
+    fn get_iterator(exported: &Rc<Self>) -> RustExportIterator {
+        RustExportIterator::new(exported.clone(), |exported: &Self| {
+            Box::new(
+                exported.dataset()
+                    .quads()
+                    .map(|quad| SophiaExportQuad::new_from_quad(&quad.unwrap()))
+            )
+        })
     }
 
-    fn extract_dataset<'a>(imported: &'a JsValue) -> MaybeOwned<'a, Self> {
-        let that = Self::try_from(imported);
+    /// Returns an iterator that lazily produces the quads of the dataset
+    /// shared by `exported` that match the given pattern, without building a
+    /// new dataset. Used by the RDF.JS Source interface.
+    fn match_iterator(exported: &Rc<Self>, subject: &JsImportTerm, predicate: &JsImportTerm, object: &JsImportTerm, graph: &JsImportTerm) -> Result<RustExportIterator, JsValue> {
+        let m = MatchRequestOnRcTerm::new(subject, predicate, object, graph)?;
+        Ok(Self::view_iterator(exported, m))
+    }
 
-        match that {
-            Some(value) => MaybeOwned::Borrowed(value),
+    /// Returns a new instance that contains the same quads.
+    ///
+    /// This is used to modify an exported dataset that is still shared with
+    /// iterators: the iterators keep the former version of the dataset.
+    /// Wrappers that hold other data than the dataset should redefine this
+    /// method to copy it too.
+    fn copy(&self) -> Self {
+        let mut dataset = D::default();
+        self.dataset().quads().in_dataset(&mut dataset).unwrap();
+        Self::wrap(dataset)
+    }
+
+    // ==== Utility functions
+
+    /// Returns the dataset exported by `imported` if it is an alive instance
+    /// of the same exported class.
+    fn try_from(imported: &JsValue) -> Option<Rc<Self>> {
+        crate::registry::take_back::<Rc<Self>>(imported)
+    }
+
+    fn extract_dataset(imported: &JsValue) -> Result<Rc<Self>, JsValue> {
+        match Self::try_from(imported) {
+            Some(value) => Ok(value),
             None => {
                 // TODO : there is probably a better dataset structure to just add quads and then iterate on
                 let mut exported_dataset = Self::wrap( D::default() );
                 
                 // We use the fact that we can iterate on the dataset
-                let import_as_js_value = JsValue::from(imported);
-                let iterator = js_sys::try_iter(&import_as_js_value);
-                match iterator {
-                    Ok(Some(iter)) => {
-                        for js_value in iter {
-                            match js_value {
-                                Ok(some_value) => exported_dataset.add(&some_value.into()),
-                                _ => {}
-                            }
-                        }
-                    },
-                    _ => {
-                        // We panic as we should have received a RDF JS compliant graph
-                        panic!("SophiaExportDataset::extract_dataset : Didn't receive an iterable");
+                let iterator = js_sys::try_iter(imported)?
+                    .ok_or_else(|| type_error("Expected a dataset or a sequence of quads"))?;
+
+                with_import_memo(|| -> Result<(), JsValue> {
+                    for js_value in iterator {
+                        exported_dataset.add(&js_value?.into(), None)?;
                     }
-                }
+
+                    Ok(())
+                })?;
             
-                MaybeOwned::Owned(exported_dataset)
+                Ok(Rc::new(exported_dataset))
             }
         }
     }
@@ -422,21 +992,122 @@ pub trait ExportableDataset<D>: Default
     // For the wrapper approach
 
     /// Adds every quads from `nquads` (which is a N-Quad serialization of the quads to add)
-    fn add_nquads(&mut self, nquads: &str) {
-        let mut source = sophia::parser::nq::parse_str(nquads);
-        source.in_dataset(self.mutable_dataset()).unwrap();
+    ///
+    /// If the text is not valid, a `ParseError` is returned and no quad is added.
+    /// If `options.renameBlankNodes` is true, the parsed blank nodes are
+    /// renamed. Returns `{ added, blankNodeMapping }` (see
+    /// `crate::blanknodes`).
+    fn add_nquads(&mut self, nquads: &str, options: &JsValue, changes: Option<&mut ChangeSet>) -> Result<JsValue, JsValue> {
+        let mut renamer = BlankNodeRenamer::from_options(options)?;
+        let added = insert_parsed_quads(sophia::parser::nq::parse_str(nquads), renamer.as_mut(), &mut self.recording_dataset(changes))?;
+        import_result(added, renamer.as_ref())
     }
 
     /// Adds every quads from `text` (which is a TriG serialization of the quads)
-    fn add_trig(&mut self, text: &str) {
-        let mut source = sophia::parser::trig::parse_str(text);
-        source.in_dataset(self.mutable_dataset()).unwrap();
+    ///
+    /// If the text is not valid, a `ParseError` is returned and no quad is added.
+    /// The blank nodes are renamed and the result is returned like in
+    /// `add_nquads`.
+    fn add_trig(&mut self, text: &str, options: &JsValue, changes: Option<&mut ChangeSet>) -> Result<JsValue, JsValue> {
+        let mut renamer = BlankNodeRenamer::from_options(options)?;
+        let added = insert_parsed_quads(sophia::parser::trig::parse_str(text), renamer.as_mut(), &mut self.recording_dataset(changes))?;
+        import_result(added, renamer.as_ref())
     }
     
+    /// Adds every triple from `text`, a Turtle serialization, to the graph
+    /// given in `options`.
+    ///
+    /// `options` may be undefined or have a `base` IRI and a `graph`. If the
+    /// text is not valid, a `ParseError` is returned and no quad is added.
+    /// Returns the number of added quads.
+    fn add_turtle(&mut self, text: &str, options: &JsValue, changes: Option<&mut ChangeSet>) -> Result<usize, JsValue> {
+        let options = ParsingOptions::new(options)?;
+        let parser = sophia::parser::turtle::TurtleParser { base: options.base };
+        insert_parsed_triples(parser.parse(text.as_bytes()), options.graph.as_ref(), &mut self.recording_dataset(changes))
+    }
+
+    /// Adds every triple from `text`, a N-Triples serialization, to the graph
+    /// given in `options`, like `add_turtle`.
+    fn add_ntriples(&mut self, text: &str, options: &JsValue, changes: Option<&mut ChangeSet>) -> Result<usize, JsValue> {
+        let options = ParsingOptions::new(options)?;
+        insert_parsed_triples(sophia::parser::nt::parse_str(text), options.graph.as_ref(), &mut self.recording_dataset(changes))
+    }
+
+    /// Adds every triple from `text`, a RDF/XML serialization, to the graph
+    /// given in `options`, like `add_turtle`.
+    fn add_rdfxml(&mut self, text: &str, options: &JsValue, changes: Option<&mut ChangeSet>) -> Result<usize, JsValue> {
+        let options = ParsingOptions::new(options)?;
+        let parser = sophia::parser::xml::RdfXmlParser { base: options.base };
+        insert_parsed_triples(parser.parse(text.as_bytes()), options.graph.as_ref(), &mut self.recording_dataset(changes))
+    }
+
+    /// Adds the quads of `document`, a JSON-LD document given as an object or
+    /// as a JSON text.
+    ///
+    /// `options` may be undefined or have a `base` IRI, a `graph` in which the
+    /// quads of the default graph are put, and a `documentLoader` function
+    /// that returns the JSON-LD document at an IRI, used for remote contexts.
+    /// If the document is invalid, no quad is added. Returns the number of
+    /// added quads.
+    fn add_jsonld(&mut self, document: &JsValue, options: &JsValue, changes: Option<&mut ChangeSet>) -> Result<usize, JsValue> {
+        let loader = document_loader(options)?;
+        let options = ParsingOptions::new(options)?;
+        let document = to_json(document)?;
+
+        let quads = jsonld_to_quads(&document, options.base.as_deref(), &mut |iri: &str| load_context(&loader, iri))
+            .map_err(|error| jsonld_error(&error))?;
+
+        // The blank node labels are only unique within the document
+        let mut renamer = BlankNodeRenamer::default();
+
+        let quads = quads.iter()
+            .map(|quad| renamer.rename_quad(quad))
+            .map(|quad| match (&options.graph, quad.g()) {
+                (Some(graph), None) => RcQuad::new(quad.s(), quad.p(), quad.o(), Some(graph)),
+                _ => quad
+            })
+            .collect();
+
+        Ok(insert_quads(quads, &mut self.recording_dataset(changes)))
+    }
+
+    /// Returns the dataset as a JSON-LD document compacted with `context`.
+    ///
+    /// `context` may be undefined. Remote contexts are loaded with the
+    /// `documentLoader` of `options`, like in `add_jsonld`.
+    fn to_jsonld(&self, context: &JsValue, options: &JsValue) -> Result<JsValue, JsValue> {
+        let loader = document_loader(options)?;
+        let context = if context.is_undefined() { serde_json::Value::Null } else { to_json(context)? };
+
+        let document = dataset_to_jsonld(self.dataset(), &context, &mut |iri: &str| load_context(&loader, iri))
+            .map_err(|error| jsonld_error(&error))?;
+
+        Ok(from_json(&document))
+    }
+
     /// Returns a N-Quad serialization of the contained dataset
     fn tonquads(&self) -> String {
         let mut serializer = sophia::serializer::nq::NqSerializer::new_stringifier();
         serializer.serialize_dataset(self.dataset()).unwrap();
         serializer.to_string()
     }
+
+    /// Returns a Turtle serialization of the triples of the default graph.
+    ///
+    /// `prefixes` is either undefined or an object that maps prefixes to
+    /// namespaces. The namespaces used in the dataset are given a prefix too.
+    fn to_turtle(&self, prefixes: &JsValue) -> Result<String, JsValue> {
+        Ok(self.dataset().to_turtle(&build_prefix_map(prefixes)?))
+    }
+
+    /// Returns a TriG serialization of the dataset, using the given prefixes
+    /// like `to_turtle`
+    fn to_trig(&self, prefixes: &JsValue) -> Result<String, JsValue> {
+        Ok(self.dataset().to_trig(&build_prefix_map(prefixes)?))
+    }
+
+    /// Returns a N-Triples serialization of the triples of the default graph
+    fn to_ntriples(&self) -> String {
+        self.dataset().to_ntriples()
+    }
 }
@@ -17,9 +17,37 @@ macro_rules! wasm_bindgen_wrappeddataset {
     };
 
     ($base_class: ident, $js_name: expr, $rust_export_name: ident) => {
+        paste::item! {
+            crate::wasm_bindgen_wrappeddataset!($base_class, $js_name, $rust_export_name,
+                [<$rust_export_name State>]
+            );
+        }
+    };
+
+    ($base_class: ident, $js_name: expr, $rust_export_name: ident, $state_name: ident) => {
+        /// The state of an exported dataset. It is shared with the callbacks
+        /// of the streams that modify the dataset after the method that
+        /// registered them has returned.
+        struct $state_name {
+            /// The exported dataset, shared with the iterators on it
+            base: std::rc::Rc<$base_class>,
+            /// If this object is a view, the pattern that the quads of `base`
+            /// must match to be in it
+            view: Option<crate::wrapping::MatchRequestOnRcTerm>,
+            /// The copy of the quads of the view, built the first time a
+            /// method that is not aware of views is called
+            materialized: std::cell::RefCell<Option<std::rc::Rc<$base_class>>>,
+            /// The Javascript callbacks registered with `onChange`
+            listeners: crate::changes::ChangeListeners,
+            /// The steps that can be undone, if `enableHistory` was called
+            history: Option<crate::history::History>
+        }
+
         #[wasm_bindgen(js_name=$js_name)]
         pub struct $rust_export_name {
-            base: $base_class
+            /// The state of the dataset. The imported streams only keep a
+            /// weak reference on it, so they stop once this object is freed.
+            state: std::rc::Rc<std::cell::RefCell<$state_name>>
         }
         
         /// Constructor
@@ -27,38 +55,191 @@ macro_rules! wasm_bindgen_wrappeddataset {
         impl $rust_export_name {
             #[wasm_bindgen(constructor)]
             pub fn new() -> Self {
-                Self { base: $base_class::default() }
+                Self::from_base($base_class::default())
+            }
+        }
+
+        impl $rust_export_name {
+            /// Builds an exported object that owns the given dataset
+            fn from_base(base: $base_class) -> Self {
+                Self::from_state($state_name {
+                    base: std::rc::Rc::new(base),
+                    view: None,
+                    materialized: std::cell::RefCell::new(None),
+                    listeners: crate::changes::ChangeListeners::default(),
+                    history: None
+                })
+            }
+
+            /// Builds a view on the quads of `base` that match `pattern`
+            fn from_view(base: std::rc::Rc<$base_class>, pattern: crate::wrapping::MatchRequestOnRcTerm) -> Self {
+                Self::from_state($state_name {
+                    base,
+                    view: Some(pattern),
+                    materialized: std::cell::RefCell::new(None),
+                    listeners: crate::changes::ChangeListeners::default(),
+                    history: None
+                })
+            }
+
+            /// Builds an exported object with the given state
+            fn from_state(state: $state_name) -> Self {
+                Self { state: std::rc::Rc::new(std::cell::RefCell::new(state)) }
+            }
+
+            /// Returns the dataset that contains the quads of this object
+            fn content(&self) -> std::rc::Rc<$base_class> {
+                self.state.borrow().content()
+            }
+
+            /// Runs a mutating call on the exported dataset and notifies the
+            /// quads it changed (see the `mutate` function of the state)
+            fn mutate<R>(&mut self, mutation: impl FnOnce(&mut $base_class, Option<&mut crate::changes::ChangeSet>) -> R) -> R {
+                self.state.borrow_mut().mutate(mutation)
+            }
+        }
+
+        impl $state_name {
+            /// Returns the dataset that contains the quads of this object.
+            ///
+            /// For a view, the matching quads are copied the first time and
+            /// the copy is reused by the next calls.
+            fn content(&self) -> std::rc::Rc<$base_class> {
+                let pattern = match &self.view {
+                    None => return self.base.clone(),
+                    Some(pattern) => pattern
+                };
+
+                self.materialized.borrow_mut()
+                    .get_or_insert_with(|| std::rc::Rc::new(
+                        crate::wrapping::ExportableDataset::view_copy(&*self.base, pattern)
+                    ))
+                    .clone()
+            }
+
+            /// Returns a mutable reference to the exported dataset.
+            ///
+            /// If iterators are still iterating on the dataset, they keep the
+            /// current version and this object is modified on a copy. A view
+            /// becomes an independent dataset that only contains its quads.
+            fn base_mut(&mut self) -> &mut $base_class {
+                if self.view.is_some() {
+                    self.base = self.content();
+                    self.view = None;
+                    self.materialized = std::cell::RefCell::new(None);
+                }
+
+                if std::rc::Rc::get_mut(&mut self.base).is_none() {
+                    self.base = std::rc::Rc::new(crate::wrapping::ExportableDataset::copy(&*self.base));
+                }
+
+                std::rc::Rc::get_mut(&mut self.base).unwrap()
+            }
+
+            /// Returns true if the changes of this object have to be notified
+            fn is_observed(&self) -> bool {
+                crate::changes::DatasetObserver::observes_changes(&self.listeners)
+                    || crate::changes::DatasetObserver::observes_changes(&*self.base)
+                    || self.history.is_some()
+            }
+
+            /// Gives the quads changed by a mutating call to the observer of
+            /// the exported dataset and to the Javascript callbacks
+            fn notify(&mut self, changes: &crate::changes::ChangeSet) {
+                if changes.is_empty() {
+                    return;
+                }
+
+                if crate::changes::DatasetObserver::observes_changes(&*self.base) {
+                    crate::changes::DatasetObserver::on_change(self.base_mut(), changes);
+                }
+
+                crate::changes::DatasetObserver::on_change(&mut self.listeners, changes);
+
+                if let Some(history) = self.history.as_mut() {
+                    crate::changes::DatasetObserver::on_change(history, changes);
+                }
+            }
+
+            /// Runs a mutating call on the exported dataset and notifies the
+            /// quads it changed.
+            ///
+            /// The call records its effective changes in the given change
+            /// set, which is only given if someone observes this object.
+            fn mutate<R>(&mut self, mutation: impl FnOnce(&mut $base_class, Option<&mut crate::changes::ChangeSet>) -> R) -> R {
+                if !self.is_observed() {
+                    return mutation(self.base_mut(), None);
+                }
+
+                let mut changes = crate::changes::ChangeSet::default();
+                let result = mutation(self.base_mut(), Some(&mut changes));
+                self.notify(&changes);
+                result
             }
         }
 
         /// RDF.JS DatasetCore
         #[wasm_bindgen(js_class=$js_name)]
         impl $rust_export_name {
-            pub fn add(&mut self, quad: &crate::datamodel::quad::JsImportQuad) {
-                crate::wrapping::ExportableDataset::add(&mut self.base, quad);
+            pub fn add(&mut self, quad: &crate::datamodel::quad::JsImportQuad) -> Result<(), JsValue> {
+                self.mutate(|base, changes| crate::wrapping::ExportableDataset::add(base, quad, changes))
             }
 
-            pub fn delete(&mut self, quad: &crate::datamodel::quad::JsImportQuad) {
-                crate::wrapping::ExportableDataset::delete(&mut self.base, quad);
+            pub fn delete(&mut self, quad: &crate::datamodel::quad::JsImportQuad) -> Result<(), JsValue> {
+                self.mutate(|base, changes| crate::wrapping::ExportableDataset::delete(base, quad, changes))
             }
 
-            pub fn has(&self, quad: &crate::datamodel::quad::JsImportQuad) -> bool {
-                crate::wrapping::ExportableDataset::has_quad(&self.base, quad)
+            pub fn has(&self, quad: &crate::datamodel::quad::JsImportQuad) -> Result<bool, JsValue> {
+                let state = self.state.borrow();
+
+                match &state.view {
+                    None => crate::wrapping::ExportableDataset::has_quad(&*state.base, quad),
+                    Some(pattern) => crate::wrapping::ExportableDataset::view_has_quad(&*state.base, pattern, quad)
+                }
             }
 
             #[wasm_bindgen(getter)]
             pub fn size(&self) -> usize {
-                crate::wrapping::ExportableDataset::size(&self.base)
+                let state = self.state.borrow();
+
+                match &state.view {
+                    None => crate::wrapping::ExportableDataset::size(&*state.base),
+                    Some(pattern) => crate::wrapping::ExportableDataset::view_size(&*state.base, pattern)
+                }
             }
 
+            /// Returns the quads that match the pattern. If `options.view` is
+            /// true, the returned object is a view that shares the quads of
+            /// this object instead of a copy of them.
             #[wasm_bindgen(js_name="match")]
             pub fn match_quad(&self,
                 subject: &crate::datamodel::term::JsImportTerm,
                 predicate: &crate::datamodel::term::JsImportTerm,
                 object: &crate::datamodel::term::JsImportTerm,
-                graph: &crate::datamodel::term::JsImportTerm) -> Self {
-                Self { base: 
-                    crate::wrapping::ExportableDataset::match_quad(&self.base, subject, predicate, object, graph)
+                graph: &crate::datamodel::term::JsImportTerm,
+                options: &JsValue) -> Result<$rust_export_name, JsValue> {
+                let view_requested = crate::wrapping::is_view_requested(options)?;
+                let state = self.state.borrow();
+
+                if state.view.is_none() && !view_requested {
+                    return Ok(Self::from_base(
+                        crate::wrapping::ExportableDataset::match_quad(&*state.base, subject, predicate, object, graph)?
+                    ));
+                }
+
+                let pattern = crate::wrapping::MatchRequestOnRcTerm::new(subject, predicate, object, graph)?;
+                let pattern = match &state.view {
+                    None => pattern,
+                    Some(view) => match view.narrow(pattern) {
+                        Some(narrowed) => narrowed,
+                        None => return Ok(Self::new())
+                    }
+                };
+
+                if view_requested {
+                    Ok(Self::from_view(state.base.clone(), pattern))
+                } else {
+                    Ok(Self::from_base(crate::wrapping::ExportableDataset::view_copy(&*state.base, &pattern)))
                 }
             }
         }
@@ -66,44 +247,118 @@ macro_rules! wasm_bindgen_wrappeddataset {
         /// RDF.JS Dataset
         #[wasm_bindgen(js_class=$js_name)]
         impl $rust_export_name {
-            pub fn difference(&self, imported: &JsValue) -> Self {
-                Self { base: crate::wrapping::ExportableDataset::difference(&self.base, imported) }
+            pub fn difference(&self, imported: &JsValue) -> Result<$rust_export_name, JsValue> {
+                Ok(Self::from_base(crate::wrapping::ExportableDataset::difference(&*self.content(), imported)?))
+            }
+
+            pub fn intersection(&self, imported: &JsValue) -> Result<$rust_export_name, JsValue> {
+                Ok(Self::from_base(crate::wrapping::ExportableDataset::intersection(&*self.content(), imported)?))
+            }
+
+            pub fn union(&self, imported: &JsValue, options: &JsValue) -> Result<$rust_export_name, JsValue> {
+                Ok(Self::from_base(crate::wrapping::ExportableDataset::union(&*self.content(), imported, options)?))
+            }
+
+            pub fn equals(&self, imported: &JsValue) -> Result<bool, JsValue> {
+                crate::wrapping::ExportableDataset::equals(&*self.content(), imported)
+            }
+
+            #[wasm_bindgen(js_name="toCanonical")]
+            pub fn to_canonical(&self) -> Result<String, JsValue> {
+                crate::wrapping::ExportableDataset::to_canonical(&*self.content())
+            }
+
+            #[wasm_bindgen(js_name="canonicalHash")]
+            pub fn canonical_hash(&self) -> Result<String, JsValue> {
+                crate::wrapping::ExportableDataset::canonical_hash(&*self.content())
+            }
+
+            #[wasm_bindgen(js_name="isIsomorphic")]
+            pub fn is_isomorphic(&self, imported: &JsValue) -> Result<bool, JsValue> {
+                crate::wrapping::ExportableDataset::is_isomorphic(&*self.content(), imported)
+            }
+
+            pub fn diff(&self, imported: &JsValue) -> Result<String, JsValue> {
+                crate::wrapping::ExportableDataset::diff(&*self.content(), imported)
             }
 
-            pub fn intersection(&self, imported: &JsValue) -> Self {
-                Self { base: crate::wrapping::ExportableDataset::intersection(&self.base, imported) }
+            #[wasm_bindgen(js_name="applyPatch")]
+            pub fn apply_patch(&mut self, text: &str) -> Result<usize, JsValue> {
+                self.mutate(|base, changes| crate::wrapping::ExportableDataset::apply_patch(base, text, changes))
             }
 
-            pub fn union(&self, imported: &JsValue) -> Self {
-                Self { base: crate::wrapping::ExportableDataset::union(&self.base, imported) }
+            pub fn validate(&self, shapes: &JsValue) -> Result<$rust_export_name, JsValue> {
+                Ok(Self::from_base(crate::wrapping::ExportableDataset::validate(&*self.content(), shapes)?))
             }
 
-            pub fn equals(&self, imported: &JsValue) -> bool {
-                crate::wrapping::ExportableDataset::equals(&self.base, imported)
+            #[wasm_bindgen(js_name="validateShex")]
+            pub fn validate_shex(&self, schema: &str, shape_map: &str) -> Result<js_sys::Array, JsValue> {
+                crate::wrapping::ExportableDataset::validate_shex(&*self.content(), schema, shape_map)
             }
 
-            pub fn some(&self, filter_function: &js_sys::Function) -> bool {
-                crate::wrapping::ExportableDataset::some(&self.base, filter_function)
+            pub fn skolemize(&mut self, base_iri: &str) -> Result<usize, JsValue> {
+                self.mutate(|base, changes| crate::wrapping::ExportableDataset::skolemize(base, base_iri, changes))
             }
 
-            pub fn every(&self, filter_function: &js_sys::Function) -> bool {
-                crate::wrapping::ExportableDataset::every(&self.base, filter_function)
+            pub fn deskolemize(&mut self, base_iri: &str) -> Result<usize, JsValue> {
+                self.mutate(|base, changes| crate::wrapping::ExportableDataset::deskolemize(base, base_iri, changes))
             }
 
-            pub fn filter(&self, filter_function: &js_sys::Function) -> Self {
-                Self { base: crate::wrapping::ExportableDataset::filter(&self.base, filter_function) }
+            #[wasm_bindgen(js_name="graphNames")]
+            pub fn graph_names(&self) -> js_sys::Array {
+                crate::wrapping::ExportableDataset::graph_names(&*self.content())
             }
 
-            pub fn map(&self, map_function: &js_sys::Function) -> Self {
-                Self { base: crate::wrapping::ExportableDataset::map(&self.base, map_function) }
+            pub fn graph(&self, graph: &crate::datamodel::term::JsImportTerm) -> Result<$rust_export_name, JsValue> {
+                Ok(Self::from_base(crate::wrapping::ExportableDataset::graph(&*self.content(), graph)?))
             }
 
-            pub fn contains(&self, imported: &JsValue) -> bool {
-                crate::wrapping::ExportableDataset::contains(&self.base, imported)
+            #[wasm_bindgen(js_name="dropGraph")]
+            pub fn drop_graph(&mut self, graph: &crate::datamodel::term::JsImportTerm) -> Result<usize, JsValue> {
+                self.mutate(|base, changes| crate::wrapping::ExportableDataset::drop_graph(base, graph, changes))
             }
 
-            pub fn reduce(&self, reducer: js_sys::Function, initial_value: &JsValue) -> JsValue {
-                crate::wrapping::ExportableDataset::reduce(&self.base, reducer, initial_value)
+            #[wasm_bindgen(js_name="copyGraph")]
+            pub fn copy_graph(&mut self,
+                from: &crate::datamodel::term::JsImportTerm,
+                to: &crate::datamodel::term::JsImportTerm) -> Result<usize, JsValue> {
+                self.mutate(|base, changes| crate::wrapping::ExportableDataset::copy_graph(base, from, to, changes))
+            }
+
+            #[wasm_bindgen(js_name="moveGraph")]
+            pub fn move_graph(&mut self,
+                from: &crate::datamodel::term::JsImportTerm,
+                to: &crate::datamodel::term::JsImportTerm) -> Result<usize, JsValue> {
+                self.mutate(|base, changes| crate::wrapping::ExportableDataset::move_graph(base, from, to, changes))
+            }
+
+            #[wasm_bindgen(js_name="addGraph")]
+            pub fn add_graph(&mut self, graph: &crate::datamodel::term::JsImportTerm, triples: &JsValue) -> Result<usize, JsValue> {
+                self.mutate(|base, changes| crate::wrapping::ExportableDataset::add_graph(base, graph, triples, changes))
+            }
+
+            pub fn some(&self, filter_function: &js_sys::Function) -> Result<bool, JsValue> {
+                crate::wrapping::ExportableDataset::some(&*self.content(), filter_function)
+            }
+
+            pub fn every(&self, filter_function: &js_sys::Function) -> Result<bool, JsValue> {
+                crate::wrapping::ExportableDataset::every(&*self.content(), filter_function)
+            }
+
+            pub fn filter(&self, filter_function: &js_sys::Function) -> Result<$rust_export_name, JsValue> {
+                Ok(Self::from_base(crate::wrapping::ExportableDataset::filter(&*self.content(), filter_function)?))
+            }
+
+            pub fn map(&self, map_function: &js_sys::Function) -> Result<$rust_export_name, JsValue> {
+                Ok(Self::from_base(crate::wrapping::ExportableDataset::map(&*self.content(), map_function)?))
+            }
+
+            pub fn contains(&self, imported: &JsValue) -> Result<bool, JsValue> {
+                crate::wrapping::ExportableDataset::contains(&*self.content(), imported)
+            }
+
+            pub fn reduce(&self, reducer: js_sys::Function, initial_value: &JsValue) -> Result<JsValue, JsValue> {
+                crate::wrapping::ExportableDataset::reduce(&*self.content(), reducer, initial_value)
             }
         }
 
@@ -111,8 +366,8 @@ macro_rules! wasm_bindgen_wrappeddataset {
         #[wasm_bindgen(js_class=$js_name)]
         impl $rust_export_name {
             #[wasm_bindgen(js_name="addAll")]
-            pub fn add_all(&mut self, quads_as_jsvalue: &JsValue) {
-                crate::wrapping::ExportableDataset::add_all(&mut self.base, quads_as_jsvalue);
+            pub fn add_all(&mut self, quads_as_jsvalue: &JsValue, options: &JsValue) -> Result<JsValue, JsValue> {
+                self.mutate(|base, changes| crate::wrapping::ExportableDataset::add_all(base, quads_as_jsvalue, options, changes))
             }
 
             #[wasm_bindgen(js_name="deleteMatches")]
@@ -120,23 +375,23 @@ macro_rules! wasm_bindgen_wrappeddataset {
                 subject: &crate::datamodel::term::JsImportTerm,
                 predicate: &crate::datamodel::term::JsImportTerm,
                 object: &crate::datamodel::term::JsImportTerm,
-                graph: &crate::datamodel::term::JsImportTerm) {
-                crate::wrapping::ExportableDataset::delete_matches(&mut self.base, subject, predicate, object, graph);
+                graph: &crate::datamodel::term::JsImportTerm) -> Result<(), JsValue> {
+                self.mutate(|base, changes| crate::wrapping::ExportableDataset::delete_matches(base, subject, predicate, object, graph, changes))
             }
 
             #[wasm_bindgen(js_name="forEach")]
-            pub fn for_each(&self, quad_run_iteratee: &js_sys::Function) {
-                crate::wrapping::ExportableDataset::for_each(&self.base, quad_run_iteratee)
+            pub fn for_each(&self, quad_run_iteratee: &js_sys::Function) -> Result<(), JsValue> {
+                crate::wrapping::ExportableDataset::for_each(&*self.content(), quad_run_iteratee)
             }
 
             #[wasm_bindgen(js_name="toArray")]
             pub fn to_array(&self) -> js_sys::Array {
-                crate::wrapping::ExportableDataset::quads(&self.base)
+                crate::wrapping::ExportableDataset::quads(&*self.content())
             }
 
             #[wasm_bindgen(js_name="toString")]
             pub fn to_string(&self) -> String {
-                crate::wrapping::ExportableDataset::to_string(&self.base)
+                crate::wrapping::ExportableDataset::to_string(&*self.content())
             }
 
         }
@@ -146,38 +401,292 @@ macro_rules! wasm_bindgen_wrappeddataset {
         impl $rust_export_name {
             #[wasm_bindgen]
             pub fn quads(&self) -> js_sys::Array {
-                crate::wrapping::ExportableDataset::quads(&self.base)
+                crate::wrapping::ExportableDataset::quads(&*self.content())
             }
 
             #[wasm_bindgen(js_name="getIterator")]
             pub fn get_iterator(&self) -> crate::exportiterator::RustExportIterator {
-                crate::wrapping::ExportableDataset::get_iterator(&self.base)
+                let state = self.state.borrow();
+
+                match &state.view {
+                    None => crate::wrapping::ExportableDataset::get_iterator(&state.base),
+                    Some(pattern) => crate::wrapping::ExportableDataset::view_iterator(&state.base, pattern.clone())
+                }
+            }
+        }
+
+        #[wasm_bindgen(js_class=$js_name)]
+        impl $rust_export_name {
+            /// Returns an iterator on the quads that match the given pattern,
+            /// without building a new dataset
+            #[wasm_bindgen(js_name="matchIterator")]
+            pub fn match_iterator(&self,
+                subject: &crate::datamodel::term::JsImportTerm,
+                predicate: &crate::datamodel::term::JsImportTerm,
+                object: &crate::datamodel::term::JsImportTerm,
+                graph: &crate::datamodel::term::JsImportTerm) -> Result<crate::exportiterator::RustExportIterator, JsValue> {
+                let state = self.state.borrow();
+                let view = match &state.view {
+                    None => return crate::wrapping::ExportableDataset::match_iterator(&state.base, subject, predicate, object, graph),
+                    Some(view) => view
+                };
+
+                let pattern = crate::wrapping::MatchRequestOnRcTerm::new(subject, predicate, object, graph)?;
+
+                match view.narrow(pattern) {
+                    Some(narrowed) => Ok(crate::wrapping::ExportableDataset::view_iterator(&state.base, narrowed)),
+                    None => Ok(crate::wrapping::ExportableDataset::get_iterator(&std::rc::Rc::new($base_class::default())))
+                }
+            }
+        }
+
+        /// RDF.JS Stream interfaces: Source, Sink and Store (see
+        /// `crate::streams`)
+        #[wasm_bindgen(js_class=$js_name)]
+        impl $rust_export_name {
+            /// Returns a readable stream of the quads that match the pattern.
+            /// This is the `match` method of the Source interface, as `match`
+            /// implements DatasetCore.
+            #[wasm_bindgen(js_name="matchStream")]
+            pub fn match_stream(&self,
+                subject: &crate::datamodel::term::JsImportTerm,
+                predicate: &crate::datamodel::term::JsImportTerm,
+                object: &crate::datamodel::term::JsImportTerm,
+                graph: &crate::datamodel::term::JsImportTerm) -> Result<crate::streams::Readable, JsValue> {
+                Ok(crate::streams::quad_stream(self.match_iterator(subject, predicate, object, graph)?))
+            }
+
+            pub fn import(&mut self, stream: crate::streams::JsImportStream) -> crate::streams::EventEmitter {
+                crate::streams::consume_stream(stream, std::rc::Rc::downgrade(&self.state), Box::new(|state: &mut $state_name, quads| {
+                    state.mutate(|base, changes| crate::wrapping::ExportableDataset::add_quads(base, quads, changes));
+                }))
+            }
+
+            pub fn remove(&mut self, stream: crate::streams::JsImportStream) -> crate::streams::EventEmitter {
+                crate::streams::consume_stream(stream, std::rc::Rc::downgrade(&self.state), Box::new(|state: &mut $state_name, quads| {
+                    state.mutate(|base, changes| crate::wrapping::ExportableDataset::delete_quads(base, quads, changes));
+                }))
+            }
+
+            #[wasm_bindgen(js_name="removeMatches")]
+            pub fn remove_matches(&mut self,
+                subject: &crate::datamodel::term::JsImportTerm,
+                predicate: &crate::datamodel::term::JsImportTerm,
+                object: &crate::datamodel::term::JsImportTerm,
+                graph: &crate::datamodel::term::JsImportTerm) -> crate::streams::EventEmitter {
+                crate::streams::emitter_of(self.delete_matches(subject, predicate, object, graph))
+            }
+
+            /// Removes the quads of the graph, which is either a term or an
+            /// IRI
+            #[wasm_bindgen(js_name="deleteGraph")]
+            pub fn delete_graph(&mut self, graph: &JsValue) -> crate::streams::EventEmitter {
+                let graph: Result<JsValue, JsValue> = match graph.as_string() {
+                    Some(iri) => crate::datamodel::factory::SophiaExportDataFactory::named_node(&iri).map(JsValue::from),
+                    None => Ok(graph.clone())
+                };
+
+                crate::streams::emitter_of(graph.and_then(|graph| self.drop_graph(&graph.into()).map(|_| ())))
+            }
+        }
+
+        /// Bulk interchange of quads as identifiers and a term table
+        #[wasm_bindgen(js_class=$js_name)]
+        impl $rust_export_name {
+            #[wasm_bindgen(js_name="exportIds")]
+            pub fn export_ids(&self) -> JsValue {
+                crate::wrapping::ExportableDataset::export_ids(&*self.content())
+            }
+
+            #[wasm_bindgen(js_name="importIds")]
+            pub fn import_ids(&mut self, ids: &[u32], term_table: &js_sys::Array) -> Result<(), JsValue> {
+                self.mutate(|base, changes| crate::wrapping::ExportableDataset::import_ids(base, ids, term_table, changes))
+            }
+        }
+
+        /// Change listeners (see `crate::changes`)
+        #[wasm_bindgen(js_class=$js_name)]
+        impl $rust_export_name {
+            /// Registers a callback that receives the array of added quads
+            /// and the array of removed quads after each call that changed
+            /// the dataset. Returns the identifier to give to `offChange`.
+            #[wasm_bindgen(js_name="onChange")]
+            pub fn on_change(&mut self, callback: js_sys::Function) -> u32 {
+                self.state.borrow_mut().listeners.add(callback)
+            }
+
+            /// Unregisters a callback. Returns false if it was not registered.
+            #[wasm_bindgen(js_name="offChange")]
+            pub fn off_change(&mut self, id: u32) -> bool {
+                self.state.borrow_mut().listeners.remove(id)
             }
         }
 
+        /// Undo and redo (see `crate::history`)
         #[wasm_bindgen(js_class=$js_name)]
         impl $rust_export_name {
-            #[wasm_bindgen(method, getter)]
-            pub fn rust_managed(&self) -> *const $rust_export_name {
-                self as *const $rust_export_name
+            /// Starts recording the modifications. At most `limit` steps are
+            /// kept. A previous history is discarded.
+            #[wasm_bindgen(js_name="enableHistory")]
+            pub fn enable_history(&mut self, limit: usize) {
+                self.state.borrow_mut().history = Some(crate::history::History::new(limit));
+            }
+
+            /// Stops recording the modifications and discards the history
+            #[wasm_bindgen(js_name="disableHistory")]
+            pub fn disable_history(&mut self) {
+                self.state.borrow_mut().history = None;
+            }
+
+            /// Groups the next modifications, until `endStep`, into one step
+            #[wasm_bindgen(js_name="beginStep")]
+            pub fn begin_step(&mut self, name: Option<String>) {
+                if let Some(history) = self.state.borrow_mut().history.as_mut() {
+                    history.begin_step(name);
+                }
+            }
+
+            #[wasm_bindgen(js_name="endStep")]
+            pub fn end_step(&mut self) {
+                if let Some(history) = self.state.borrow_mut().history.as_mut() {
+                    history.end_step();
+                }
+            }
+
+            /// Reverts the last step. Returns false if there was nothing to
+            /// undo.
+            pub fn undo(&mut self) -> bool {
+                // The history is taken out so the reverting changes are
+                // notified but not recorded
+                let mut state = self.state.borrow_mut();
+                let mut history = match state.history.take() {
+                    Some(history) => history,
+                    None => return false
+                };
+
+                let is_undone = match history.take_undo() {
+                    Some(step) => {
+                        state.mutate(|base, changes| step.undo(&mut crate::wrapping::ExportableDataset::recording_dataset(base, changes)));
+                        history.undone(step);
+                        true
+                    },
+                    None => false
+                };
+
+                state.history = Some(history);
+                is_undone
+            }
+
+            /// Applies again the last undone step. Returns false if there was
+            /// nothing to redo.
+            pub fn redo(&mut self) -> bool {
+                let mut state = self.state.borrow_mut();
+                let mut history = match state.history.take() {
+                    Some(history) => history,
+                    None => return false
+                };
+
+                let is_redone = match history.take_redo() {
+                    Some(step) => {
+                        state.mutate(|base, changes| step.redo(&mut crate::wrapping::ExportableDataset::recording_dataset(base, changes)));
+                        history.redone(step);
+                        true
+                    },
+                    None => false
+                };
+
+                state.history = Some(history);
+                is_redone
+            }
+
+            #[wasm_bindgen(getter=canUndo)]
+            pub fn can_undo(&self) -> bool {
+                self.state.borrow().history.as_ref().map_or(false, |history| history.can_undo())
+            }
+
+            #[wasm_bindgen(getter=canRedo)]
+            pub fn can_redo(&self) -> bool {
+                self.state.borrow().history.as_ref().map_or(false, |history| history.can_redo())
+            }
+
+            /// The name of the step that `undo` would revert
+            #[wasm_bindgen(getter=undoName)]
+            pub fn undo_name(&self) -> Option<String> {
+                self.state.borrow().history.as_ref().and_then(|history| history.undo_name()).map(String::from)
+            }
+
+            /// The name of the step that `redo` would apply again
+            #[wasm_bindgen(getter=redoName)]
+            pub fn redo_name(&self) -> Option<String> {
+                self.state.borrow().history.as_ref().and_then(|history| history.redo_name()).map(String::from)
+            }
+        }
+
+        #[wasm_bindgen(js_class=$js_name)]
+        impl $rust_export_name {
+            /// Lends the exported dataset to the registry and returns the
+            /// ticket to take it back (see `crate::registry`)
+            #[wasm_bindgen(method, getter=rustTicket)]
+            pub fn rust_ticket(&self) -> u32 {
+                crate::registry::hand_out(self.content())
             }
         }
 
         #[wasm_bindgen(js_class=$js_name)]
         impl $rust_export_name {
             #[wasm_bindgen(js_name="addNQuads")]
-            pub fn add_nquads(&mut self, nquads: &str) {
-                crate::wrapping::ExportableDataset::add_nquads(&mut self.base, nquads)
+            pub fn add_nquads(&mut self, nquads: &str, options: &JsValue) -> Result<JsValue, JsValue> {
+                self.mutate(|base, changes| crate::wrapping::ExportableDataset::add_nquads(base, nquads, options, changes))
             }
 
             #[wasm_bindgen(js_name="addTriG")]
-            pub fn add_trig(&mut self, text: &str) {
-                crate::wrapping::ExportableDataset::add_trig(&mut self.base, text)
+            pub fn add_trig(&mut self, text: &str, options: &JsValue) -> Result<JsValue, JsValue> {
+                self.mutate(|base, changes| crate::wrapping::ExportableDataset::add_trig(base, text, options, changes))
+            }
+            
+            #[wasm_bindgen(js_name="addTurtle")]
+            pub fn add_turtle(&mut self, text: &str, options: &JsValue) -> Result<usize, JsValue> {
+                self.mutate(|base, changes| crate::wrapping::ExportableDataset::add_turtle(base, text, options, changes))
+            }
+
+            #[wasm_bindgen(js_name="addNTriples")]
+            pub fn add_ntriples(&mut self, text: &str, options: &JsValue) -> Result<usize, JsValue> {
+                self.mutate(|base, changes| crate::wrapping::ExportableDataset::add_ntriples(base, text, options, changes))
+            }
+
+            #[wasm_bindgen(js_name="addRdfXml")]
+            pub fn add_rdfxml(&mut self, text: &str, options: &JsValue) -> Result<usize, JsValue> {
+                self.mutate(|base, changes| crate::wrapping::ExportableDataset::add_rdfxml(base, text, options, changes))
+            }
+
+            #[wasm_bindgen(js_name="addJsonLd")]
+            pub fn add_jsonld(&mut self, document: &JsValue, options: &JsValue) -> Result<usize, JsValue> {
+                self.mutate(|base, changes| crate::wrapping::ExportableDataset::add_jsonld(base, document, options, changes))
             }
             
             #[wasm_bindgen(js_name="toNQuads")]
             pub fn tonquads(&self) -> String {
-                crate::wrapping::ExportableDataset::tonquads(&self.base)
+                crate::wrapping::ExportableDataset::tonquads(&*self.content())
+            }
+
+            #[wasm_bindgen(js_name="toTurtle")]
+            pub fn to_turtle(&self, prefixes: &JsValue) -> Result<String, JsValue> {
+                crate::wrapping::ExportableDataset::to_turtle(&*self.content(), prefixes)
+            }
+
+            #[wasm_bindgen(js_name="toTriG")]
+            pub fn to_trig(&self, prefixes: &JsValue) -> Result<String, JsValue> {
+                crate::wrapping::ExportableDataset::to_trig(&*self.content(), prefixes)
+            }
+
+            #[wasm_bindgen(js_name="toNTriples")]
+            pub fn to_ntriples(&self) -> String {
+                crate::wrapping::ExportableDataset::to_ntriples(&*self.content())
+            }
+
+            #[wasm_bindgen(js_name="toJsonLd")]
+            pub fn to_jsonld(&self, context: &JsValue, options: &JsValue) -> Result<JsValue, JsValue> {
+                crate::wrapping::ExportableDataset::to_jsonld(&*self.content(), context, options)
             }
         }
     };
@@ -7,6 +7,7 @@ pub mod macro_to_wasm;
 mod exportable_dataset;
 
 pub use exportable_dataset::MatchRequestOnRcTerm;
+pub use exportable_dataset::is_view_requested;
 pub use exportable_dataset::ExportableDataset;
 
 // A default wrapping struct
@@ -0,0 +1,52 @@
+/* global describe, it */
+"use strict";
+
+const assert = require('assert')
+const sophia_wasm = require('./../pkg/sophia_wasm.js')
+
+const DataFactory = sophia_wasm.DataFactory
+
+const document = '_:b0 <http://example.org/p> _:b1 .\n_:b1 <http://example.org/p> "end" .\n'
+
+describe('Blank node renaming', () => {
+  it('should keep unrelated blank nodes apart when the option is given', () => {
+    const dataset = new sophia_wasm.TreeDataset()
+    dataset.addNQuads(document)
+
+    const options = { renameBlankNodes: true }
+    const result = dataset.addNQuads(document, options)
+
+    assert.strictEqual(dataset.size, 4)
+    assert.strictEqual(result.added, 2)
+    assert.deepStrictEqual(Object.keys(result.blankNodeMapping).sort(), ['b0', 'b1'])
+    assert.deepStrictEqual(options, { renameBlankNodes: true })
+    assert.ok(dataset.has(DataFactory.quad(
+      DataFactory.blankNode(result.blankNodeMapping.b0),
+      DataFactory.namedNode('http://example.org/p'),
+      DataFactory.blankNode(result.blankNodeMapping.b1)
+    )))
+  })
+
+  it('should fuse the blank nodes without the option', () => {
+    const dataset = new sophia_wasm.TreeDataset()
+    assert.deepStrictEqual(dataset.addTriG(document), { added: 2 })
+    assert.deepStrictEqual(dataset.addTriG(document), { added: 0 })
+
+    assert.strictEqual(dataset.size, 2)
+  })
+
+  it('should rename the blank nodes of the other dataset in union and addAll', () => {
+    const first = new sophia_wasm.TreeDataset()
+    first.addNQuads(document)
+    const second = new sophia_wasm.TreeDataset()
+    second.addNQuads(document)
+
+    const union = first.union(second, { renameBlankNodes: true })
+    assert.strictEqual(union.size, 4)
+
+    const result = first.addAll(second.toArray(), { renameBlankNodes: true })
+    assert.strictEqual(first.size, 4)
+    assert.strictEqual(result.added, 2)
+    assert.notStrictEqual(result.blankNodeMapping.b0, 'b0')
+  })
+})
@@ -0,0 +1,46 @@
+/* global describe, it */
+"use strict";
+
+const assert = require('assert')
+const sophia_wasm = require('./../pkg/sophia_wasm.js')
+
+function cycle (labels) {
+  const dataset = new sophia_wasm.TreeDataset()
+  dataset.addNQuads(
+    `_:${labels[0]} <http://example.org/p> _:${labels[1]} .\n` +
+    `_:${labels[1]} <http://example.org/p> _:${labels[2]} .\n` +
+    `_:${labels[2]} <http://example.org/p> _:${labels[0]} .\n`
+  )
+  return dataset
+}
+
+describe('Canonicalization', () => {
+  it('should label the blank nodes canonically', () => {
+    const dataset = new sophia_wasm.TreeDataset()
+    dataset.addNQuads('_:x <http://example.org/p> "a" .\n')
+
+    assert.strictEqual(dataset.toCanonical(), '_:c14n0 <http://example.org/p> "a" .\n')
+  })
+
+  it('should give the same canonical form and hash to isomorphic datasets', () => {
+    const first = cycle(['a', 'b', 'c'])
+    const second = cycle(['z', 'x', 'y'])
+
+    assert.strictEqual(first.toCanonical(), second.toCanonical())
+    assert.strictEqual(first.canonicalHash(), second.canonicalHash())
+    assert.strictEqual(first.canonicalHash().length, 64)
+    assert.ok(first.isIsomorphic(second))
+  })
+
+  it('should ignore blank node labels in equals', () => {
+    const chain = new sophia_wasm.TreeDataset()
+    chain.addNQuads(
+      '_:a <http://example.org/p> _:b .\n' +
+      '_:b <http://example.org/p> _:c .\n' +
+      '_:c <http://example.org/p> _:c .\n'
+    )
+
+    assert.ok(cycle(['a', 'b', 'c']).equals(cycle(['c', 'a', 'b'])))
+    assert.ok(!cycle(['a', 'b', 'c']).equals(chain))
+  })
+})
@@ -0,0 +1,132 @@
+/* global describe, it */
+"use strict";
+
+const assert = require('assert')
+const sophia_wasm = require('./../pkg/sophia_wasm.js')
+
+const DataFactory = sophia_wasm.DataFactory
+
+const ex = name => DataFactory.namedNode('http://example.org/' + name)
+
+// The callbacks are called in a microtask after the mutating call
+const flush = () => Promise.resolve()
+
+function listen(dataset) {
+  const batches = []
+  const id = dataset.onChange((added, removed) => {
+    batches.push({ added: added.map(q => q.subject.value), removed: removed.map(q => q.subject.value) })
+  })
+  return { batches, id }
+}
+
+describe('Change listeners', () => {
+  for (const className of ['TreeDataset', 'FastDataset', 'FullDataset']) {
+    it(`should notify the quads changed by the mutations of a ${className}`, async () => {
+      const dataset = new sophia_wasm[className]()
+      const { batches } = listen(dataset)
+
+      dataset.add(DataFactory.quad(ex('s1'), ex('p'), ex('o')))
+      dataset.add(DataFactory.quad(ex('s1'), ex('p'), ex('o')))
+      await flush()
+      assert.deepStrictEqual(batches, [{ added: ['http://example.org/s1'], removed: [] }])
+
+      dataset.addNQuads(
+        '<http://example.org/s1> <http://example.org/p> <http://example.org/o> .\n' +
+        '<http://example.org/s2> <http://example.org/p> <http://example.org/o> .\n'
+      )
+      await flush()
+      assert.deepStrictEqual(batches[1], { added: ['http://example.org/s2'], removed: [] })
+
+      dataset.deleteMatches(null, ex('p'), null, null)
+      await flush()
+      assert.strictEqual(batches.length, 3)
+      assert.deepStrictEqual(batches[2].added, [])
+      assert.deepStrictEqual(batches[2].removed.sort(), ['http://example.org/s1', 'http://example.org/s2'])
+    })
+  }
+
+  it('should not notify the calls that changed nothing', async () => {
+    const dataset = new sophia_wasm.TreeDataset()
+    const { batches } = listen(dataset)
+
+    dataset.delete(DataFactory.quad(ex('s1'), ex('p'), ex('o')))
+    dataset.deleteMatches(ex('s1'), null, null, null)
+    dataset.addAll([])
+    dataset.applyPatch(
+      'A <http://example.org/s1> <http://example.org/p> <http://example.org/o> .\n' +
+      'D <http://example.org/s1> <http://example.org/p> <http://example.org/o> .\n'
+    )
+    await flush()
+
+    assert.deepStrictEqual(batches, [])
+  })
+
+  it('should notify the net changes of a call', async () => {
+    const dataset = new sophia_wasm.TreeDataset()
+    dataset.add(DataFactory.quad(ex('s1'), ex('p'), ex('o')))
+    const { batches } = listen(dataset)
+
+    assert.strictEqual(dataset.applyPatch(
+      'A <http://example.org/s2> <http://example.org/p> <http://example.org/o> .\n' +
+      'D <http://example.org/s2> <http://example.org/p> <http://example.org/o> .\n' +
+      'D <http://example.org/s1> <http://example.org/p> <http://example.org/o> .\n'
+    ), 3)
+    await flush()
+
+    assert.deepStrictEqual(batches, [{ added: [], removed: ['http://example.org/s1'] }])
+  })
+
+  for (const className of ['TreeDataset', 'FastDataset']) {
+    it(`should notify the quads renamed by the skolemization of a ${className}`, async () => {
+      const dataset = new sophia_wasm[className]()
+      dataset.add(DataFactory.quad(DataFactory.blankNode('b0'), ex('p'), ex('o')))
+      dataset.add(DataFactory.quad(ex('s1'), ex('p'), ex('o')))
+      const { batches } = listen(dataset)
+
+      dataset.skolemize('http://example.org/')
+      await flush()
+
+      assert.deepStrictEqual(batches, [{
+        added: ['http://example.org/.well-known/genid/b0'],
+        removed: ['b0']
+      }])
+    })
+  }
+
+  it('should notify the quads moved between graphs', async () => {
+    const dataset = new sophia_wasm.TreeDataset()
+    dataset.add(DataFactory.quad(ex('s1'), ex('p'), ex('o'), ex('g1')))
+    dataset.add(DataFactory.quad(ex('s2'), ex('p'), ex('o'), ex('g2')))
+    const { batches } = listen(dataset)
+
+    dataset.moveGraph(ex('g1'), ex('g2'))
+    dataset.dropGraph(ex('g3'))
+    await flush()
+
+    assert.deepStrictEqual(batches, [{ added: ['http://example.org/s1'], removed: ['http://example.org/s1'] }])
+  })
+
+  it('should let the callbacks read the dataset', async () => {
+    const dataset = new sophia_wasm.TreeDataset()
+    let sizes = []
+    dataset.onChange(() => sizes.push(dataset.size))
+
+    dataset.add(DataFactory.quad(ex('s1'), ex('p'), ex('o')))
+    await flush()
+
+    assert.deepStrictEqual(sizes, [1])
+  })
+
+  it('should stop notifying an unregistered callback', async () => {
+    const dataset = new sophia_wasm.TreeDataset()
+    const { batches, id } = listen(dataset)
+
+    assert.ok(dataset.offChange(id))
+    assert.ok(!dataset.offChange(id))
+
+    dataset.add(DataFactory.quad(ex('s1'), ex('p'), ex('o')))
+    await flush()
+
+    assert.deepStrictEqual(batches, [])
+  })
+})
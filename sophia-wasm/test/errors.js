@@ -0,0 +1,35 @@
+/* global describe, it */
+"use strict";
+
+const assert = require('assert')
+const sophia_wasm = require('./../pkg/sophia_wasm.js')
+
+const DataFactory = sophia_wasm.DataFactory
+
+describe('Errors', () => {
+  it('should throw an InvalidIriError for an invalid IRI', () => {
+    assert.throws(() => DataFactory.namedNode('not an iri'), { name: 'InvalidIriError' })
+  })
+
+  it('should throw an InvalidTermError for an unknown term type', () => {
+    const subject = DataFactory.namedNode('http://example.org/subject')
+    const strange = { termType: 'Strange', value: 'x' }
+
+    assert.throws(() => DataFactory.quad(subject, subject, strange), { name: 'InvalidTermError' })
+  })
+
+  it('should throw a ParseError with its position and keep the dataset usable', () => {
+    const dataset = new sophia_wasm.TreeDataset()
+
+    assert.throws(
+      () => dataset.addNQuads('<http://example.org/s> <http://example.org/p> <http://example.org/o> .\n<http://example.org/s> oops .'),
+      (error) => error.name === 'ParseError' && error.line === 2
+    )
+
+    // No quad from the invalid document has been added
+    assert.strictEqual(dataset.size, 0)
+
+    dataset.addNQuads('<http://example.org/s> <http://example.org/p> <http://example.org/o> .')
+    assert.strictEqual(dataset.size, 1)
+  })
+})
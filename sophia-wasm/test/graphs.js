@@ -0,0 +1,66 @@
+/* global describe, it */
+"use strict";
+
+const assert = require('assert')
+const sophia_wasm = require('./../pkg/sophia_wasm.js')
+
+const DataFactory = sophia_wasm.DataFactory
+
+const g1 = DataFactory.namedNode('http://example.org/g1')
+const g2 = DataFactory.namedNode('http://example.org/g2')
+const defaultGraph = DataFactory.defaultGraph()
+
+function graphDataset (className) {
+  const dataset = new sophia_wasm[className]()
+  dataset.addNQuads(
+    '<http://example.org/s> <http://example.org/p> "1" <http://example.org/g1> .\n' +
+    '<http://example.org/s> <http://example.org/p> "2" <http://example.org/g1> .\n' +
+    '<http://example.org/s> <http://example.org/p> "1" .\n'
+  )
+  return dataset
+}
+
+describe('Named graphs', () => {
+  for (const className of ['TreeDataset', 'FullDataset', 'FastDataset']) {
+    describe(className, () => {
+      it('should list the graph names', () => {
+        const names = graphDataset(className).graphNames().map(term => term.termType + ' ' + term.value).sort()
+        assert.deepStrictEqual(names, ['DefaultGraph ', 'NamedNode http://example.org/g1'])
+      })
+
+      it('should return the dataset of one graph', () => {
+        const graph = graphDataset(className).graph(g1)
+        assert.strictEqual(graph.size, 2)
+        assert.strictEqual(graph.match(null, null, null, g1).size, 2)
+      })
+
+      it('should drop, copy and move graphs', () => {
+        const dataset = graphDataset(className)
+
+        assert.strictEqual(dataset.copyGraph(g1, defaultGraph), 1)
+        assert.strictEqual(dataset.match(null, null, null, defaultGraph).size, 2)
+
+        assert.strictEqual(dataset.moveGraph(g1, g2), 2)
+        assert.strictEqual(dataset.match(null, null, null, g1).size, 0)
+        assert.strictEqual(dataset.match(null, null, null, g2).size, 2)
+
+        assert.strictEqual(dataset.dropGraph(g2), 2)
+        assert.strictEqual(dataset.dropGraph(g2), 0)
+        assert.strictEqual(dataset.size, 2)
+      })
+
+      it('should add triples in a graph', () => {
+        const dataset = new sophia_wasm[className]()
+        const added = dataset.addGraph(g2, [
+          DataFactory.quad(DataFactory.namedNode('http://example.org/s'), DataFactory.namedNode('http://example.org/p'), DataFactory.literal('a'), g1),
+          DataFactory.quad(DataFactory.namedNode('http://example.org/s'), DataFactory.namedNode('http://example.org/p'), DataFactory.literal('b'))
+        ])
+
+        assert.strictEqual(added, 2)
+        assert.strictEqual(dataset.match(null, null, null, g2).size, 2)
+        assert.strictEqual(dataset.addGraph(g2, graphDataset(className)), 2)
+        assert.strictEqual(dataset.size, 4)
+      })
+    })
+  }
+})
@@ -0,0 +1,119 @@
+/* global describe, it */
+"use strict";
+
+const assert = require('assert')
+const sophia_wasm = require('./../pkg/sophia_wasm.js')
+
+const DataFactory = sophia_wasm.DataFactory
+
+const ex = name => DataFactory.namedNode('http://example.org/' + name)
+const quad = (s, o) => DataFactory.quad(ex(s), ex('p'), ex(o))
+
+describe('History', () => {
+  for (const className of ['TreeDataset', 'FastDataset', 'FullDataset']) {
+    it(`should undo and redo the modifications of a ${className}`, () => {
+      const dataset = new sophia_wasm[className]()
+      dataset.add(quad('s0', 'o'))
+      dataset.enableHistory(10)
+
+      assert.ok(!dataset.canUndo)
+      dataset.add(quad('s1', 'o'))
+      dataset.delete(quad('s0', 'o'))
+      assert.ok(dataset.canUndo)
+
+      assert.ok(dataset.undo())
+      assert.ok(dataset.has(quad('s0', 'o')))
+      assert.ok(dataset.undo())
+      assert.strictEqual(dataset.size, 1)
+      assert.ok(!dataset.undo())
+
+      assert.ok(dataset.canRedo)
+      assert.ok(dataset.redo())
+      assert.ok(dataset.redo())
+      assert.strictEqual(dataset.size, 1)
+      assert.ok(dataset.has(quad('s1', 'o')))
+      assert.ok(!dataset.redo())
+    })
+  }
+
+  it('should only record the effective modifications', () => {
+    const dataset = new sophia_wasm.TreeDataset()
+    dataset.add(quad('s0', 'o'))
+    dataset.enableHistory(10)
+
+    dataset.add(quad('s0', 'o'))
+    dataset.delete(quad('s1', 'o'))
+    assert.ok(!dataset.canUndo)
+
+    dataset.addNQuads('<http://example.org/s0> <http://example.org/p> <http://example.org/o> .\n<http://example.org/s2> <http://example.org/p> <http://example.org/o> .\n')
+    assert.ok(dataset.undo())
+    assert.strictEqual(dataset.size, 1)
+    assert.ok(dataset.has(quad('s0', 'o')))
+  })
+
+  it('should undo the operations of a call in the reverse order', () => {
+    const dataset = new sophia_wasm.TreeDataset()
+    dataset.add(DataFactory.quad(ex('s1'), ex('p'), ex('o'), ex('g1')))
+    dataset.enableHistory(10)
+
+    dataset.applyPatch(
+      'A <http://example.org/s2> <http://example.org/p> <http://example.org/o> .\n' +
+      'D <http://example.org/s2> <http://example.org/p> <http://example.org/o> .\n' +
+      'A <http://example.org/s3> <http://example.org/p> <http://example.org/o> .\n'
+    )
+    dataset.moveGraph(ex('g1'), ex('g2'))
+
+    assert.ok(dataset.undo())
+    assert.ok(dataset.has(DataFactory.quad(ex('s1'), ex('p'), ex('o'), ex('g1'))))
+    assert.ok(dataset.undo())
+    assert.strictEqual(dataset.size, 1)
+    assert.ok(dataset.redo())
+    assert.strictEqual(dataset.size, 2)
+    assert.ok(dataset.has(quad('s3', 'o')))
+    assert.ok(!dataset.has(quad('s2', 'o')))
+  })
+
+  it('should group the modifications of a named step', () => {
+    const dataset = new sophia_wasm.TreeDataset()
+    dataset.enableHistory(10)
+
+    dataset.beginStep('move')
+    dataset.add(quad('s1', 'o'))
+    dataset.deleteMatches(ex('s1'), null, null, null)
+    dataset.add(quad('s2', 'o'))
+    dataset.endStep()
+
+    assert.strictEqual(dataset.undoName, 'move')
+    assert.ok(dataset.undo())
+    assert.strictEqual(dataset.size, 0)
+    assert.strictEqual(dataset.redoName, 'move')
+    assert.ok(dataset.redo())
+    assert.strictEqual(dataset.size, 1)
+    assert.ok(dataset.has(quad('s2', 'o')))
+  })
+
+  it('should forget the oldest steps beyond the limit', () => {
+    const dataset = new sophia_wasm.TreeDataset()
+    dataset.enableHistory(2)
+
+    dataset.add(quad('s1', 'o'))
+    dataset.add(quad('s2', 'o'))
+    dataset.add(quad('s3', 'o'))
+
+    assert.ok(dataset.undo())
+    assert.ok(dataset.undo())
+    assert.ok(!dataset.undo())
+    assert.strictEqual(dataset.size, 1)
+  })
+
+  it('should forget the undone steps after a new modification', () => {
+    const dataset = new sophia_wasm.TreeDataset()
+    dataset.enableHistory(10)
+
+    dataset.add(quad('s1', 'o'))
+    dataset.undo()
+    dataset.add(quad('s2', 'o'))
+
+    assert.ok(!dataset.canRedo)
+  })
+})
@@ -0,0 +1,45 @@
+/* global describe, it */
+"use strict";
+
+const assert = require('assert')
+const sophia_wasm = require('./../pkg/sophia_wasm.js')
+
+const DataFactory = sophia_wasm.DataFactory
+
+describe('Bulk interchange of quads as identifiers', () => {
+  it('should move the quads of a dataset into another one', () => {
+    const source = new sophia_wasm.TreeDataset()
+    source.addNQuads(
+      '<http://example.org/s> <http://example.org/p> "chat"@fr .\n' +
+      '<http://example.org/s> <http://example.org/p> _:b <http://example.org/g> .\n' +
+      '_:b <http://example.org/p> "42"^^<http://www.w3.org/2001/XMLSchema#integer> .\n'
+    )
+
+    const exported = source.exportIds()
+    assert.ok(exported.ids instanceof Uint32Array)
+    assert.strictEqual(exported.ids.length, 12)
+    assert.strictEqual(exported.terms[0], '')
+
+    const destination = new sophia_wasm.TreeDataset()
+    destination.importIds(exported.ids, exported.terms)
+
+    assert.strictEqual(destination.size, 3)
+    assert.ok(destination.equals(source))
+  })
+
+  it('should not import anything if an identifier is invalid', () => {
+    const dataset = new sophia_wasm.TreeDataset()
+    const terms = ['', 'Ihttp://example.org/s', 'Ihttp://example.org/p']
+
+    assert.throws(() => dataset.importIds(new Uint32Array([1, 2, 1, 0, 1, 2, 7, 0]), terms), { name: 'TypeError' })
+    assert.throws(() => dataset.importIds(new Uint32Array([0, 2, 1, 0]), terms), { name: 'InvalidTermError' })
+    assert.strictEqual(dataset.size, 0)
+
+    dataset.importIds(new Uint32Array([1, 2, 1, 0]), terms)
+    assert.ok(dataset.has(DataFactory.quad(
+      DataFactory.namedNode('http://example.org/s'),
+      DataFactory.namedNode('http://example.org/p'),
+      DataFactory.namedNode('http://example.org/s')
+    )))
+  })
+})
@@ -0,0 +1,118 @@
+/* global describe, it */
+"use strict";
+
+const assert = require('assert')
+const sophia_wasm = require('./../pkg/sophia_wasm.js')
+
+const DataFactory = sophia_wasm.DataFactory
+
+describe('Term cache', () => {
+  it('should return the same handle for equal terms', () => {
+    const first = DataFactory.internTerm(DataFactory.namedNode('http://example.org/a'))
+    const second = DataFactory.internTerm({ termType: 'NamedNode', value: 'http://example.org/a' })
+
+    assert.strictEqual(first, second)
+    assert.notStrictEqual(first, DataFactory.internTerm(DataFactory.namedNode('http://example.org/b')))
+  })
+
+  function countedTerm () {
+    const term = {
+      termType: 'NamedNode',
+      reads: 0,
+      get value () { this.reads += 1; return 'http://example.org/counted' }
+    }
+    return term
+  }
+
+  it('should import the foreign terms of a bulk import only once', () => {
+    const term = countedTerm()
+    const quads = []
+    for (let i = 0; i != 10; ++i) {
+      quads.push({ subject: term, predicate: term, object: term, graph: DataFactory.defaultGraph() })
+    }
+
+    const dataset = new sophia_wasm.TreeDataset()
+    dataset.addAll(quads)
+
+    assert.strictEqual(dataset.size, 1)
+    assert.strictEqual(term.reads, 1)
+  })
+
+  it('should forget the imported terms after a bulk import', () => {
+    const term = countedTerm()
+    const quad = { subject: term, predicate: term, object: term, graph: DataFactory.defaultGraph() }
+
+    const dataset = new sophia_wasm.TreeDataset()
+    dataset.addAll([quad])
+    dataset.addAll([quad])
+
+    assert.strictEqual(term.reads, 2)
+  })
+
+  it('should not let the shared terms be modified', () => {
+    const interned = DataFactory.internTerm(DataFactory.namedNode('http://example.org/a'))
+    assert.throws(() => { interned.value = 'http://example.org/b' }, TypeError)
+    assert.strictEqual(interned.value, 'http://example.org/a')
+
+    const quad = DataFactory.quad(DataFactory.namedNode('http://example.org/s'), interned, DataFactory.literal('o'))
+    assert.throws(() => { quad.object.language = 'en' }, TypeError)
+    assert.strictEqual(quad.object.language, '')
+  })
+
+  it('should return the same terms from the getters of a quad', () => {
+    const quad = DataFactory.quad(DataFactory.namedNode('http://example.org/s'), DataFactory.namedNode('http://example.org/p'), DataFactory.literal('o'))
+
+    assert.strictEqual(quad.subject, quad.subject)
+    assert.strictEqual(quad.graph, quad.graph)
+    assert.strictEqual(quad.graph.termType, 'DefaultGraph')
+
+    const subject = quad.subject
+    quad.subject = DataFactory.namedNode('http://example.org/s2')
+    assert.strictEqual(quad.subject.value, 'http://example.org/s2')
+    assert.strictEqual(subject.value, 'http://example.org/s')
+    assert.strictEqual(quad.subject, quad.subject)
+  })
+
+  it('should keep the terms of a quad usable after it is freed', () => {
+    const quad = DataFactory.quad(DataFactory.namedNode('http://example.org/s'), DataFactory.namedNode('http://example.org/p'), DataFactory.literal('o'))
+
+    const subject = quad.subject
+    subject.free()
+    assert.strictEqual(quad.subject.value, 'http://example.org/s')
+
+    const object = quad.object
+    quad.free()
+    assert.strictEqual(object.value, 'o')
+    object.free()
+  })
+
+  it('should import modified exported terms with their new value', () => {
+    const term = DataFactory.namedNode('http://example.org/before')
+    DataFactory.internTerm(term)
+    term.value = 'http://example.org/after'
+
+    assert.strictEqual(DataFactory.internTerm(term).value, 'http://example.org/after')
+  })
+
+  it('should forget the least recently interned terms', () => {
+    const first = DataFactory.internTerm(DataFactory.literal('first'))
+    const second = DataFactory.internTerm(DataFactory.literal('second'))
+
+    for (let i = 0; i != 4095; ++i) {
+      DataFactory.internTerm(DataFactory.literal('filler ' + i))
+      DataFactory.internTerm(DataFactory.literal('second'))
+    }
+
+    assert.notStrictEqual(DataFactory.internTerm(DataFactory.literal('first')), first)
+    assert.strictEqual(DataFactory.internTerm(DataFactory.literal('second')), second)
+  })
+
+  it('should give new handles after the cache is cleared', () => {
+    const before = DataFactory.internTerm(DataFactory.literal('x'))
+    DataFactory.clearTermCache()
+    const after = DataFactory.internTerm(DataFactory.literal('x'))
+
+    assert.notStrictEqual(before, after)
+    assert.ok(before.equals(after))
+  })
+})
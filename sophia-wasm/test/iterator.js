@@ -0,0 +1,51 @@
+/* global describe, it */
+"use strict";
+
+const assert = require('assert')
+const sophia_wasm = require('./../pkg/sophia_wasm.js')
+
+const DataFactory = sophia_wasm.DataFactory
+
+function quadNumber (i) {
+  return DataFactory.quad(
+    DataFactory.namedNode('http://example.org/subject'),
+    DataFactory.namedNode('http://example.org/predicate'),
+    DataFactory.literal('' + i)
+  )
+}
+
+describe('Lazy iterator', () => {
+  it('should iterate on the quads contained when it was created', () => {
+    const dataset = new sophia_wasm.TreeDataset()
+    dataset.add(quadNumber(1))
+    dataset.add(quadNumber(2))
+
+    const iterator = dataset.getIterator()
+    dataset.add(quadNumber(3))
+    dataset.delete(quadNumber(1))
+
+    let count = 0
+    for (let next = iterator.next(); !next.done; next = iterator.next()) {
+      count += 1
+    }
+
+    assert.strictEqual(count, 2)
+    assert.strictEqual(dataset.size, 2)
+    assert.ok(dataset.has(quadNumber(3)))
+    assert.ok(!dataset.has(quadNumber(1)))
+    iterator.free()
+  })
+
+  it('should produce the quads by chunks', () => {
+    const dataset = new sophia_wasm.TreeDataset()
+    for (let i = 0; i != 5; ++i) {
+      dataset.add(quadNumber(i))
+    }
+
+    const iterator = dataset.getIterator()
+    assert.strictEqual(iterator.nextChunk(3).length, 3)
+    assert.strictEqual(iterator.nextChunk(3).length, 2)
+    assert.strictEqual(iterator.nextChunk(3).length, 0)
+    iterator.free()
+  })
+})
@@ -0,0 +1,84 @@
+/* global describe, it */
+"use strict";
+
+const assert = require('assert')
+const sophia_wasm = require('./../pkg/sophia_wasm.js')
+
+const DataFactory = sophia_wasm.DataFactory
+
+const context = {
+  ex: 'http://example.org/',
+  knows: { '@id': 'http://example.org/knows', '@type': '@id' }
+}
+
+describe('JSON-LD', () => {
+  it('should add the quads of a document and count them', () => {
+    const dataset = new sophia_wasm.TreeDataset()
+    const added = dataset.addJsonLd({
+      '@context': context,
+      '@id': 'alice',
+      knows: ['ex:bob', 'ex:carol']
+    }, { base: 'http://example.org/' })
+
+    assert.strictEqual(added, 2)
+    assert.ok(dataset.has(DataFactory.quad(
+      DataFactory.namedNode('http://example.org/alice'),
+      DataFactory.namedNode('http://example.org/knows'),
+      DataFactory.namedNode('http://example.org/bob')
+    )))
+  })
+
+  it('should not fuse the blank nodes of two documents', () => {
+    const dataset = new sophia_wasm.TreeDataset()
+    dataset.addJsonLd({ '@context': context, knows: 'ex:bob' })
+    dataset.addJsonLd({ '@context': context, knows: 'ex:carol' })
+
+    const subjects = dataset.toArray().map(quad => quad.subject)
+
+    assert.strictEqual(subjects.length, 2)
+    assert.ok(subjects.every(subject => subject.termType === 'BlankNode'))
+    assert.notStrictEqual(subjects[0].value, subjects[1].value)
+  })
+
+  it('should load remote contexts with the documentLoader', () => {
+    const dataset = new sophia_wasm.TreeDataset()
+    const loaded = []
+    const documentLoader = iri => {
+      loaded.push(iri)
+      return { '@context': context }
+    }
+
+    dataset.addJsonLd('{ "@context": "http://example.org/context", "@id": "ex:alice", "knows": "ex:bob" }',
+      { documentLoader: documentLoader })
+
+    assert.deepStrictEqual(loaded, ['http://example.org/context'])
+    assert.strictEqual(dataset.size, 1)
+
+    assert.throws(
+      () => dataset.addJsonLd({ '@context': 'http://example.org/context' }),
+      (error) => error.name === 'JsonLdError' && error.code === 'loading remote context failed'
+    )
+  })
+
+  it('should compact the dataset with a context', () => {
+    const dataset = new sophia_wasm.TreeDataset()
+    dataset.addJsonLd({ '@context': context, '@id': 'ex:alice', knows: 'ex:bob' })
+
+    assert.deepStrictEqual(dataset.toJsonLd(context), {
+      '@context': context,
+      '@id': 'ex:alice',
+      knows: 'ex:bob'
+    })
+  })
+
+  it('should leave the dataset unchanged on an invalid document', () => {
+    const dataset = new sophia_wasm.TreeDataset()
+
+    assert.throws(() => dataset.addJsonLd('{ "@id": '), (error) => error.name === 'ParseError')
+    assert.throws(
+      () => dataset.addJsonLd({ '@context': { '@id': 'http://example.org/' } }),
+      (error) => error.name === 'JsonLdError' && error.code === 'keyword redefinition'
+    )
+    assert.strictEqual(dataset.size, 0)
+  })
+})
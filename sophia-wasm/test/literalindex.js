@@ -0,0 +1,75 @@
+/* global describe, it */
+"use strict";
+
+const assert = require('assert')
+const sophia_wasm = require('./../pkg/sophia_wasm.js')
+const DataFactory = sophia_wasm.DataFactory
+
+const XSD = 'http://www.w3.org/2001/XMLSchema#'
+
+const ex = name => DataFactory.namedNode('http://example.org/' + name)
+const typed = (value, datatype) => DataFactory.literal(value, DataFactory.namedNode(XSD + datatype))
+
+const DATA = `
+  @prefix ex: <http://example.org/> .
+  @prefix xsd: <http://www.w3.org/2001/XMLSchema#> .
+  ex:a ex:value 42 ; ex:date "2020-05-01"^^xsd:date .
+  ex:b ex:value 12.5 ; ex:date "2020-01-15T10:00:00+02:00"^^xsd:dateTime .
+  ex:c ex:value "31"^^xsd:integer ; ex:other 35 .
+  ex:d ex:value "not a number" .
+  ex:e ex:value 30 .
+  ex:graph { ex:f ex:value 50 }
+`
+
+const subjects = dataset => dataset.toArray().map(quad => quad.subject.value.replace('http://example.org/', ''))
+
+describe('Literal index', () => {
+  for (const indexed of [false, true]) {
+    describe(indexed ? 'With the literal index' : 'Without the literal index', () => {
+      const build = () => {
+        const dataset = new sophia_wasm.TreeDataset()
+        if (indexed) dataset.enableLiteralIndex()
+        dataset.addTriG(DATA)
+        return dataset
+      }
+
+      it('should return the quads in a numeric range, sorted by value', () => {
+        const dataset = build()
+
+        assert.deepStrictEqual(subjects(dataset.matchRange(ex('value'), typed('30', 'integer'), typed('45', 'double'))), ['e', 'c', 'a'])
+        assert.deepStrictEqual(subjects(dataset.matchRange(ex('value'), null, typed('30', 'decimal'))), ['b', 'e'])
+        assert.deepStrictEqual(subjects(dataset.matchRange(null, typed('31', 'integer'), null)).sort(), ['a', 'c', 'c', 'f'])
+      })
+
+      it('should filter by graph', () => {
+        const dataset = build()
+
+        assert.deepStrictEqual(subjects(dataset.matchRange(null, typed('45', 'integer'), null, ex('graph'))), ['f'])
+        assert.deepStrictEqual(subjects(dataset.matchRange(ex('value'), typed('45', 'integer'), null, DataFactory.defaultGraph())), [])
+      })
+
+      it('should compare dates and times as instants', () => {
+        const dataset = build()
+
+        assert.deepStrictEqual(subjects(dataset.matchRange(ex('date'), typed('2020-01-15T08:00:00Z', 'dateTime'), null)), ['b', 'a'])
+        assert.deepStrictEqual(subjects(dataset.matchRange(ex('date'), typed('2020-01-15T08:00:01Z', 'dateTime'), typed('2020-12-31', 'date'))), ['a'])
+        assert.deepStrictEqual(subjects(dataset.matchRange(ex('date'), typed('0', 'integer'), typed('2020-12-31', 'date'))), [])
+      })
+
+      it('should follow the changes of the dataset', () => {
+        const dataset = build()
+        dataset.delete(DataFactory.quad(ex('a'), ex('value'), typed('42', 'integer')))
+        dataset.add(DataFactory.quad(ex('g'), ex('value'), typed('40', 'integer')))
+
+        assert.deepStrictEqual(subjects(dataset.matchRange(ex('value'), typed('35', 'integer'), null)), ['g', 'f'])
+      })
+    })
+  }
+
+  it('should reject bounds that are not numeric or temporal literals', () => {
+    const dataset = new sophia_wasm.TreeDataset()
+
+    assert.throws(() => dataset.matchRange(null, DataFactory.literal('30'), null), error => error.name === 'InvalidTermError')
+    assert.throws(() => dataset.matchRange(null, ex('thirty'), null), error => error.name === 'InvalidTermError')
+  })
+})
@@ -0,0 +1,53 @@
+/* global describe, it */
+"use strict";
+
+const assert = require('assert')
+const sophia_wasm = require('./../pkg/sophia_wasm.js')
+
+const DataFactory = sophia_wasm.DataFactory
+
+describe('Parsing entry points', () => {
+  it('should resolve relative IRIs against the base IRI and count the added quads', () => {
+    const dataset = new sophia_wasm.TreeDataset()
+    const added = dataset.addTurtle('<s> <p> <o>, <o> .', { base: 'http://example.org/' })
+
+    assert.strictEqual(added, 1)
+    assert.ok(dataset.has(DataFactory.quad(
+      DataFactory.namedNode('http://example.org/s'),
+      DataFactory.namedNode('http://example.org/p'),
+      DataFactory.namedNode('http://example.org/o')
+    )))
+  })
+
+  it('should put the parsed triples in the given graph', () => {
+    const dataset = new sophia_wasm.TreeDataset()
+    const graph = DataFactory.namedNode('http://example.org/g')
+    dataset.addNTriples('<http://example.org/s> <http://example.org/p> "o" .\n', { graph: graph })
+
+    assert.strictEqual(dataset.match(null, null, null, graph).size, 1)
+    assert.strictEqual(dataset.addNTriples('<http://example.org/s> <http://example.org/p> "o" .\n', { graph: graph }), 0)
+  })
+
+  it('should parse RDF/XML', () => {
+    const dataset = new sophia_wasm.TreeDataset()
+    const added = dataset.addRdfXml(
+      '<?xml version="1.0"?>\n' +
+      '<rdf:RDF xmlns:rdf="http://www.w3.org/1999/02/22-rdf-syntax-ns#" xmlns:ex="http://example.org/">\n' +
+      '  <rdf:Description rdf:about="s"><ex:p>o</ex:p></rdf:Description>\n' +
+      '</rdf:RDF>\n',
+      { base: 'http://example.org/' }
+    )
+
+    assert.strictEqual(added, 1)
+  })
+
+  it('should report the position of the first syntax error', () => {
+    const dataset = new sophia_wasm.TreeDataset()
+
+    assert.throws(
+      () => dataset.addTurtle('@prefix ex: <http://example.org/> .\nex:s ex:p .'),
+      (error) => error.name === 'ParseError' && error.line === 2
+    )
+    assert.strictEqual(dataset.size, 0)
+  })
+})
@@ -0,0 +1,64 @@
+/* global describe, it */
+"use strict";
+
+const assert = require('assert')
+const sophia_wasm = require('./../pkg/sophia_wasm.js')
+
+const NQUADS_FROM =
+  '<http://example.org/s> <http://example.org/p> "a" .\n' +
+  '<http://example.org/s> <http://example.org/p> "b" <http://example.org/g> .\n'
+
+const NQUADS_TO =
+  '<http://example.org/s> <http://example.org/p> "b" <http://example.org/g> .\n' +
+  '<http://example.org/s> <http://example.org/p> "c"@en .\n'
+
+describe('RDF Patch', () => {
+  for (const className of ['TreeDataset', 'FastDataset', 'FullDataset']) {
+    it(`should produce and apply the patch between two ${className}s`, () => {
+      const from = new sophia_wasm[className]()
+      from.addNQuads(NQUADS_FROM)
+      const to = new sophia_wasm[className]()
+      to.addNQuads(NQUADS_TO)
+
+      const patch = from.diff(to)
+      assert.ok(patch.startsWith('TX .\n'))
+      assert.ok(patch.endsWith('TC .\n'))
+      assert.ok(patch.includes('D <http://example.org/s> <http://example.org/p> "a" .\n'))
+      assert.ok(patch.includes('A <http://example.org/s> <http://example.org/p> "c"@en .\n'))
+      assert.ok(!patch.includes('"b"'))
+
+      assert.strictEqual(from.applyPatch(patch), 2)
+      assert.ok(from.equals(to))
+      assert.strictEqual(from.diff(to), 'TX .\nTC .\n')
+    })
+  }
+
+  it('should accept a sequence of quads', () => {
+    const dataset = new sophia_wasm.TreeDataset()
+    dataset.addNQuads(NQUADS_FROM)
+
+    assert.strictEqual(dataset.diff([]).split('\n').filter(line => line.startsWith('D ')).length, 2)
+  })
+
+  it('should ignore aborted transactions', () => {
+    const dataset = new sophia_wasm.TreeDataset()
+    const added = dataset.applyPatch(
+      'H id <urn:uuid:0> .\n' +
+      'TX .\nA <http://example.org/s> <http://example.org/p> "kept" .\nTC .\n' +
+      'TX .\nA <http://example.org/s> <http://example.org/p> "aborted" .\nTA .\n'
+    )
+
+    assert.strictEqual(added, 1)
+    assert.strictEqual(dataset.size, 1)
+  })
+
+  it('should leave the dataset unchanged on an invalid patch', () => {
+    const dataset = new sophia_wasm.TreeDataset()
+
+    assert.throws(
+      () => dataset.applyPatch('TX .\nA <http://example.org/s> <http://example.org/p> "a" .\nA invalid .\nTC .\n'),
+      error => error.name === 'ParseError' && error.line === 3
+    )
+    assert.strictEqual(dataset.size, 0)
+  })
+})
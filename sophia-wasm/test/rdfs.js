@@ -0,0 +1,103 @@
+/* global describe, it */
+"use strict";
+
+const assert = require('assert')
+const sophia_wasm = require('./../pkg/sophia_wasm.js')
+const DataFactory = sophia_wasm.DataFactory
+
+const RDF_TYPE = 'http://www.w3.org/1999/02/22-rdf-syntax-ns#type'
+const RDFS = 'http://www.w3.org/2000/01/rdf-schema#'
+
+const ex = name => DataFactory.namedNode('http://example.org/' + name)
+const type = DataFactory.namedNode(RDF_TYPE)
+const subClassOf = DataFactory.namedNode(RDFS + 'subClassOf')
+const domain = DataFactory.namedNode(RDFS + 'domain')
+
+describe('RdfsDataset', () => {
+  it('should infer the types of the instances of subclasses', () => {
+    const dataset = new sophia_wasm.RdfsDataset()
+    dataset.add(DataFactory.quad(ex('Cat'), subClassOf, ex('Animal')))
+    dataset.add(DataFactory.quad(ex('Animal'), subClassOf, ex('Being')))
+    dataset.add(DataFactory.quad(ex('tom'), type, ex('Cat')))
+
+    const inferred = DataFactory.quad(ex('tom'), type, ex('Being'))
+    assert.ok(dataset.has(inferred))
+    assert.ok(dataset.isInferred(inferred))
+    assert.ok(!dataset.isAsserted(inferred))
+    assert.ok(dataset.isInferred(DataFactory.quad(ex('Cat'), subClassOf, ex('Being'))))
+    assert.ok(dataset.isAsserted(DataFactory.quad(ex('tom'), type, ex('Cat'))))
+    assert.strictEqual(dataset.asserted().size, 3)
+  })
+
+  it('should retract the quads that are no longer derivable', () => {
+    const dataset = new sophia_wasm.RdfsDataset()
+    dataset.add(DataFactory.quad(ex('owns'), domain, ex('Person')))
+    dataset.add(DataFactory.quad(ex('alice'), ex('owns'), ex('cat')))
+    dataset.add(DataFactory.quad(ex('alice'), ex('owns'), ex('dog')))
+
+    const person = DataFactory.quad(ex('alice'), type, ex('Person'))
+    assert.ok(dataset.isInferred(person))
+
+    dataset.delete(DataFactory.quad(ex('alice'), ex('owns'), ex('cat')))
+    assert.ok(dataset.has(person))
+
+    dataset.delete(DataFactory.quad(ex('alice'), ex('owns'), ex('dog')))
+    assert.ok(!dataset.has(person))
+    assert.strictEqual(dataset.size, 1)
+  })
+
+  it('should not remove inferred quads', () => {
+    const dataset = new sophia_wasm.RdfsDataset()
+    dataset.add(DataFactory.quad(ex('Cat'), subClassOf, ex('Animal')))
+    dataset.add(DataFactory.quad(ex('tom'), type, ex('Cat')))
+
+    const inferred = DataFactory.quad(ex('tom'), type, ex('Animal'))
+    dataset.delete(inferred)
+    assert.ok(dataset.has(inferred))
+  })
+
+  it('should only answer for the quads of a view', () => {
+    const dataset = new sophia_wasm.RdfsDataset()
+    dataset.add(DataFactory.quad(ex('Cat'), subClassOf, ex('Animal')))
+    dataset.add(DataFactory.quad(ex('tom'), type, ex('Cat')))
+
+    const view = dataset.match(ex('tom'), null, null, null, { view: true })
+    assert.ok(view.isInferred(DataFactory.quad(ex('tom'), type, ex('Animal'))))
+    assert.ok(!view.isAsserted(DataFactory.quad(ex('Cat'), subClassOf, ex('Animal'))))
+    assert.strictEqual(view.asserted().size, 1)
+  })
+
+  it('should keep the inferred quads inferred in the derived datasets', () => {
+    const dataset = new sophia_wasm.RdfsDataset()
+    dataset.add(DataFactory.quad(ex('Cat'), subClassOf, ex('Animal')))
+    dataset.add(DataFactory.quad(ex('tom'), type, ex('Cat')))
+
+    const inferred = DataFactory.quad(ex('tom'), type, ex('Animal'))
+    const asserted = DataFactory.quad(ex('tom'), type, ex('Cat'))
+
+    const matched = dataset.match(ex('tom'), null, null, null)
+    assert.strictEqual(matched.size, 2)
+    assert.ok(matched.isInferred(inferred))
+    assert.ok(matched.isAsserted(asserted))
+
+    const filtered = dataset.filter(quad => quad.subject.value === ex('tom').value)
+    assert.ok(filtered.isInferred(inferred))
+    assert.strictEqual(filtered.asserted().size, 1)
+
+    assert.ok(dataset.intersection(matched).isInferred(inferred))
+    assert.ok(dataset.difference(dataset.match(null, subClassOf, null, null)).isInferred(inferred))
+    assert.ok(dataset.graph(DataFactory.defaultGraph()).isInferred(inferred))
+  })
+
+  it('should run the rules on the quads of both datasets of a union', () => {
+    const schema = new sophia_wasm.RdfsDataset()
+    schema.add(DataFactory.quad(ex('Cat'), subClassOf, ex('Animal')))
+
+    const data = new sophia_wasm.RdfsDataset()
+    data.add(DataFactory.quad(ex('tom'), type, ex('Cat')))
+
+    const union = schema.union(data)
+    assert.ok(union.isInferred(DataFactory.quad(ex('tom'), type, ex('Animal'))))
+    assert.strictEqual(union.asserted().size, 2)
+  })
+})
@@ -0,0 +1,54 @@
+/* global describe, it */
+"use strict";
+
+const assert = require('assert')
+const sophia_wasm = require('./../pkg/sophia_wasm.js')
+
+const DataFactory = sophia_wasm.DataFactory
+
+function makeQuad () {
+  return DataFactory.quad(
+    DataFactory.namedNode('http://example.org/subject'),
+    DataFactory.namedNode('http://example.org/predicate'),
+    DataFactory.literal('object')
+  )
+}
+
+describe('Identification of Rust managed objects', () => {
+  it('should recognize an exported quad', () => {
+    assert.ok(makeQuad().equals(makeQuad()))
+  })
+
+  it('should not trust a forged ticket on a quad', () => {
+    const quad = makeQuad()
+    const forged = {
+      rustTicket: quad.rustTicket,
+      subject: DataFactory.namedNode('http://example.org/other'),
+      predicate: quad.predicate,
+      object: quad.object,
+      graph: quad.graph
+    }
+
+    assert.ok(!quad.equals(forged))
+  })
+
+  it('should fall back to the generic path for another exported class', () => {
+    const dataset = new sophia_wasm.TreeDataset()
+    dataset.add(makeQuad())
+
+    const other = new sophia_wasm.FullDataset()
+    other.add(makeQuad())
+
+    assert.throws(() => dataset.union(other), { name: 'TypeError' })
+    assert.ok(dataset.equals([makeQuad()]))
+  })
+
+  it('should not use a freed dataset', () => {
+    const dataset = new sophia_wasm.TreeDataset()
+    const freed = new sophia_wasm.TreeDataset()
+    freed.add(makeQuad())
+    freed.free()
+
+    assert.throws(() => dataset.union(freed))
+  })
+})
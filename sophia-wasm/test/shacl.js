@@ -0,0 +1,70 @@
+/* global describe, it */
+"use strict";
+
+const assert = require('assert')
+const sophia_wasm = require('./../pkg/sophia_wasm.js')
+const DataFactory = sophia_wasm.DataFactory
+
+const PREFIXES =
+  '@prefix ex: <http://example.org/> .\n' +
+  '@prefix sh: <http://www.w3.org/ns/shacl#> .\n' +
+  '@prefix xsd: <http://www.w3.org/2001/XMLSchema#> .\n'
+
+const SHAPES = PREFIXES + `
+  ex:PersonShape a sh:NodeShape ;
+    sh:targetClass ex:Person ;
+    sh:property [
+      sh:path ex:name ;
+      sh:minCount 1 ;
+      sh:datatype xsd:string
+    ] ;
+    sh:property [
+      sh:path ex:email ;
+      sh:pattern "^[^@]+@[^@]+$"
+    ] .
+`
+
+const sh = name => DataFactory.namedNode('http://www.w3.org/ns/shacl#' + name)
+const ex = name => DataFactory.namedNode('http://example.org/' + name)
+
+describe('SHACL validation', () => {
+  it('should report the failed constraints', () => {
+    const shapes = new sophia_wasm.TreeDataset()
+    shapes.addTurtle(SHAPES)
+
+    const data = new sophia_wasm.TreeDataset()
+    data.addTurtle(PREFIXES + `
+      ex:alice a ex:Person ; ex:name "Alice" ; ex:email "alice@example.org" .
+      ex:bob a ex:Person ; ex:email "bob" .
+    `)
+
+    const report = data.validate(shapes)
+    assert.strictEqual(report.match(null, sh('conforms'), null).size, 1)
+    assert.strictEqual(report.match(null, sh('result'), null).size, 2)
+    assert.strictEqual(report.match(null, sh('focusNode'), ex('bob')).size, 2)
+    assert.strictEqual(report.match(null, sh('sourceConstraintComponent'), sh('MinCountConstraintComponent')).size, 1)
+    assert.strictEqual(report.match(null, sh('sourceConstraintComponent'), sh('PatternConstraintComponent')).size, 1)
+    assert.strictEqual(report.match(null, sh('value'), DataFactory.literal('bob')).size, 1)
+  })
+
+  it('should conform when every constraint holds', () => {
+    const data = new sophia_wasm.FastDataset()
+    data.addTurtle(PREFIXES + 'ex:alice a ex:Person ; ex:name "Alice" .')
+
+    const shapes = new sophia_wasm.FastDataset()
+    shapes.addTurtle(SHAPES)
+
+    const report = data.validate(shapes)
+    const conforms = report.match(null, sh('conforms'), null).toArray()
+    assert.strictEqual(conforms.length, 1)
+    assert.strictEqual(conforms[0].object.value, 'true')
+    assert.strictEqual(report.match(null, sh('result'), null).size, 0)
+  })
+
+  it('should throw a ShaclError on invalid shapes', () => {
+    const shapes = new sophia_wasm.TreeDataset()
+    shapes.addTurtle(PREFIXES + 'ex:Shape sh:targetNode ex:alice ; sh:property [ sh:path ex:name ; sh:maxCount "many" ] .')
+
+    assert.throws(() => new sophia_wasm.TreeDataset().validate(shapes), error => error.name === 'ShaclError')
+  })
+})
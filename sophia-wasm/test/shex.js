@@ -0,0 +1,64 @@
+/* global describe, it */
+"use strict";
+
+const assert = require('assert')
+const sophia_wasm = require('./../pkg/sophia_wasm.js')
+
+const SCHEMA = `
+  PREFIX ex: <http://example.org/>
+  PREFIX xsd: <http://www.w3.org/2001/XMLSchema#>
+
+  start = @ex:Person
+
+  ex:Person {
+    ex:name xsd:string ;
+    ex:age xsd:integer MININCLUSIVE 0 ? ;
+    ex:knows @ex:Person *
+  }
+`
+
+const DATA = `
+  @prefix ex: <http://example.org/> .
+  ex:alice ex:name "Alice" ; ex:age 32 ; ex:knows ex:bob .
+  ex:bob ex:name "Bob" ; ex:knows ex:alice .
+  ex:carol ex:name "Carol", "Caroline" .
+  ex:dave ex:name "Dave" ; ex:age -4 .
+`
+
+describe('ShEx validation', () => {
+  it('should check the nodes of a shape map', () => {
+    const data = new sophia_wasm.TreeDataset()
+    data.addTurtle(DATA)
+
+    const results = data.validateShex(SCHEMA, 'ex:alice@ex:Person, ex:carol@ex:Person, ex:dave@START')
+    assert.strictEqual(results.length, 3)
+
+    assert.strictEqual(results[0].node.value, 'http://example.org/alice')
+    assert.strictEqual(results[0].shape, 'http://example.org/Person')
+    assert.strictEqual(results[0].conforms, true)
+    assert.strictEqual(results[0].reason, null)
+
+    assert.strictEqual(results[1].conforms, false)
+    assert.strictEqual(typeof results[1].reason, 'string')
+
+    assert.strictEqual(results[2].shape, 'START')
+    assert.strictEqual(results[2].conforms, false)
+  })
+
+  it('should select the nodes of query shape map entries', () => {
+    const data = new sophia_wasm.FastDataset()
+    data.addTurtle(DATA)
+
+    const results = data.validateShex(SCHEMA, '{FOCUS <http://example.org/name> _}@<http://example.org/Person>')
+    const conforming = results.filter(result => result.conforms).map(result => result.node.value).sort()
+    assert.strictEqual(results.length, 4)
+    assert.deepStrictEqual(conforming, ['http://example.org/alice', 'http://example.org/bob'])
+  })
+
+  it('should throw a ParseError on invalid schemas and shape maps', () => {
+    const data = new sophia_wasm.TreeDataset()
+
+    assert.throws(() => data.validateShex('<http://example.org/S> {\n  ex:p .\n}', ''), error => error.name === 'ParseError' && error.line === 2)
+    assert.throws(() => data.validateShex(SCHEMA, 'ex:alice@ex:Unknown'), error => error.name === 'ParseError')
+  })
+})
@@ -0,0 +1,45 @@
+/* global describe, it */
+"use strict";
+
+const assert = require('assert')
+const sophia_wasm = require('./../pkg/sophia_wasm.js')
+
+const SKOLEM = 'http://example.org/.well-known/genid/'
+
+describe('Skolemization', () => {
+  for (const className of ['TreeDataset', 'FastDataset', 'FullDataset']) {
+    it(`should replace the blank nodes of a ${className} and restore them`, () => {
+      const dataset = new sophia_wasm[className]()
+      dataset.addNQuads(
+        '_:b0 <http://example.org/p> "a" .\n' +
+        '<http://example.org/s> <http://example.org/p> _:b0 _:b0 .\n'
+      )
+
+      assert.strictEqual(dataset.skolemize('http://example.org/'), 1)
+      assert.strictEqual(dataset.size, 2)
+      assert.ok(!dataset.toNQuads().includes('_:'))
+      assert.ok(dataset.toNQuads().includes(`<${SKOLEM}b0> <http://example.org/p> "a"`))
+
+      assert.strictEqual(dataset.deskolemize('http://example.org'), 1)
+      assert.strictEqual(dataset.size, 2)
+      assert.ok(dataset.toNQuads().includes('_:b0 <http://example.org/p> "a"'))
+      assert.ok(!dataset.toNQuads().includes(SKOLEM))
+    })
+  }
+
+  it('should leave the IRIs of other bases untouched', () => {
+    const dataset = new sophia_wasm.TreeDataset()
+    dataset.addNQuads(`<http://other.org/.well-known/genid/x> <http://example.org/p> "a" .\n`)
+
+    assert.strictEqual(dataset.deskolemize('http://example.org/'), 0)
+    assert.ok(dataset.toNQuads().includes('<http://other.org/.well-known/genid/x>'))
+  })
+
+  it('should reject an invalid base IRI', () => {
+    const dataset = new sophia_wasm.TreeDataset()
+    dataset.addNQuads('_:b0 <http://example.org/p> "a" .\n')
+
+    assert.throws(() => dataset.skolemize('not an iri'))
+    assert.ok(dataset.toNQuads().includes('_:b0'))
+  })
+})
@@ -0,0 +1,187 @@
+/* global describe, it */
+"use strict";
+
+const assert = require('assert')
+const { Readable } = require('stream')
+const sophia_wasm = require('./../pkg/sophia_wasm.js')
+const { SophiaDatasetWrapper } = require('./../js/wrapper')
+
+const DataFactory = sophia_wasm.DataFactory
+
+const ex = name => DataFactory.namedNode('http://example.org/' + name)
+
+function quadStream (quads) {
+  return Readable.from(quads, { objectMode: true })
+}
+
+function finished (emitter) {
+  return new Promise((resolve, reject) => {
+    emitter.on('end', resolve)
+    emitter.on('error', reject)
+  })
+}
+
+function subjects (stream) {
+  return new Promise((resolve, reject) => {
+    const values = []
+    stream.on('data', quad => values.push(quad.subject.value))
+    stream.on('end', () => resolve(values.sort()))
+    stream.on('error', reject)
+  })
+}
+
+describe('RDF.JS Stream interfaces', () => {
+  for (const className of ['TreeDataset', 'FastDataset', 'RdfsDataset']) {
+    it(`should import a stream into a ${className}`, async () => {
+      const dataset = new sophia_wasm[className]()
+      const quads = []
+      for (let i = 0; i != 1000; ++i) {
+        quads.push(DataFactory.quad(ex('s' + i), ex('p'), ex('o')))
+      }
+
+      await finished(dataset.import(quadStream(quads)))
+
+      assert.strictEqual(dataset.size, 1000)
+      assert.ok(dataset.has(DataFactory.quad(ex('s999'), ex('p'), ex('o'))))
+    })
+  }
+
+  it('should import plain RDF.JS quads', async () => {
+    const dataset = new sophia_wasm.TreeDataset()
+    const subject = { termType: 'NamedNode', value: 'http://example.org/s' }
+
+    await finished(dataset.import(quadStream([
+      { subject, predicate: ex('p'), object: DataFactory.literal('o'), graph: DataFactory.defaultGraph() }
+    ])))
+
+    assert.strictEqual(dataset.size, 1)
+  })
+
+  it('should emit the errors of the imported stream', async () => {
+    const dataset = new sophia_wasm.TreeDataset()
+    const stream = new Readable({ objectMode: true, read () {} })
+    const emitter = dataset.import(stream)
+
+    stream.push(DataFactory.quad(ex('s'), ex('p'), ex('o')))
+    stream.destroy(new Error('broken'))
+
+    await assert.rejects(finished(emitter), /broken/)
+  })
+
+  it('should emit an error for an invalid quad', async () => {
+    const dataset = new sophia_wasm.TreeDataset()
+    const invalid = { subject: DataFactory.defaultGraph(), predicate: ex('p'), object: ex('o'), graph: DataFactory.defaultGraph() }
+
+    await assert.rejects(finished(dataset.import(quadStream([invalid]))))
+  })
+
+  it('should stream the quads that match a pattern', async () => {
+    const dataset = new sophia_wasm.TreeDataset()
+    for (let i = 0; i != 600; ++i) {
+      dataset.add(DataFactory.quad(ex('s' + i), i % 2 == 0 ? ex('even') : ex('odd'), ex('o')))
+    }
+
+    const values = await subjects(dataset.matchStream(null, ex('even'), null, null))
+
+    assert.strictEqual(values.length, 300)
+    assert.ok(values.includes('http://example.org/s0'))
+    assert.ok(!values.includes('http://example.org/s1'))
+  })
+
+  it('should stream the quads of the dataset when the stream was created', async () => {
+    const dataset = new sophia_wasm.TreeDataset()
+    dataset.add(DataFactory.quad(ex('s1'), ex('p'), ex('o')))
+
+    const stream = dataset.matchStream(null, null, null, null)
+    dataset.add(DataFactory.quad(ex('s2'), ex('p'), ex('o')))
+
+    assert.deepStrictEqual(await subjects(stream), ['http://example.org/s1'])
+  })
+
+  it('should pipe a dataset into another one', async () => {
+    const source = new sophia_wasm.TreeDataset()
+    source.add(DataFactory.quad(ex('s1'), ex('p'), ex('o'), ex('g')))
+    source.add(DataFactory.quad(ex('s2'), ex('p'), ex('o')))
+
+    const target = new sophia_wasm.FastDataset()
+    await finished(target.import(source.matchStream(null, null, null, ex('g'))))
+
+    assert.strictEqual(target.size, 1)
+    assert.ok(target.has(DataFactory.quad(ex('s1'), ex('p'), ex('o'), ex('g'))))
+  })
+
+  it('should remove the quads of a stream', async () => {
+    const dataset = new sophia_wasm.TreeDataset()
+    dataset.add(DataFactory.quad(ex('s1'), ex('p'), ex('o')))
+    dataset.add(DataFactory.quad(ex('s2'), ex('p'), ex('o')))
+
+    await finished(dataset.remove(quadStream([
+      DataFactory.quad(ex('s1'), ex('p'), ex('o')),
+      DataFactory.quad(ex('s3'), ex('p'), ex('o'))
+    ])))
+
+    assert.strictEqual(dataset.size, 1)
+    assert.ok(dataset.has(DataFactory.quad(ex('s2'), ex('p'), ex('o'))))
+  })
+
+  it('should remove the quads that match a pattern', async () => {
+    const dataset = new sophia_wasm.TreeDataset()
+    dataset.add(DataFactory.quad(ex('s1'), ex('p'), ex('o')))
+    dataset.add(DataFactory.quad(ex('s2'), ex('q'), ex('o')))
+
+    await finished(dataset.removeMatches(null, ex('p'), null, null))
+
+    assert.strictEqual(dataset.size, 1)
+  })
+
+  it('should delete a graph given as a term or as an IRI', async () => {
+    const dataset = new sophia_wasm.TreeDataset()
+    dataset.add(DataFactory.quad(ex('s1'), ex('p'), ex('o'), ex('g1')))
+    dataset.add(DataFactory.quad(ex('s2'), ex('p'), ex('o'), ex('g2')))
+    dataset.add(DataFactory.quad(ex('s3'), ex('p'), ex('o')))
+
+    await finished(dataset.deleteGraph(ex('g1')))
+    await finished(dataset.deleteGraph('http://example.org/g2'))
+
+    assert.strictEqual(dataset.size, 1)
+  })
+
+  it('should notify the quads imported from a stream', async () => {
+    const dataset = new sophia_wasm.TreeDataset()
+    let added = 0
+    dataset.onChange(quads => { added += quads.length })
+
+    await finished(dataset.import(quadStream([
+      DataFactory.quad(ex('s1'), ex('p'), ex('o')),
+      DataFactory.quad(ex('s2'), ex('p'), ex('o'))
+    ])))
+    await Promise.resolve()
+
+    assert.strictEqual(added, 2)
+  })
+
+  it('should fail to import into a freed dataset', async () => {
+    const dataset = new sophia_wasm.TreeDataset()
+    const stream = new Readable({ objectMode: true, read () {} })
+    const emitter = dataset.import(stream)
+    dataset.free()
+
+    stream.push(DataFactory.quad(ex('s'), ex('p'), ex('o')))
+    stream.push(null)
+
+    await assert.rejects(finished(emitter), /freed/)
+  })
+
+  it('should stream through a wrapped dataset', async () => {
+    const source = new SophiaDatasetWrapper(new sophia_wasm.TreeDataset())
+    const target = new SophiaDatasetWrapper(new sophia_wasm.FastDataset())
+    source.addTurtle('<http://example.org/s1> <http://example.org/p> <http://example.org/o> .')
+    source.addNTriples('<http://example.org/s2> <http://example.org/p> <http://example.org/o> .\n')
+
+    await finished(target.import(source.matchStream(null, null, null, null)))
+    assert.strictEqual(target.size, 2)
+
+    await finished(target.remove(quadStream([DataFactory.quad(ex('s1'), ex('p'), ex('o'))])))
+    assert.strictEqual(target.size, 1)
+  })
+})
@@ -1,7 +1,7 @@
 "use strict";
 
 let wasm_example = require('./../pkg/sophia_wasm.js');
-let sophia_wasm_wrapped = require('./../pkg/wrapper');
+let sophia_wasm_wrapped = require('./../js/wrapper');
 
 let DataFactory = wasm_example.DataFactory;
 
@@ -0,0 +1,87 @@
+/* global describe, it */
+"use strict";
+
+const assert = require('assert')
+const sophia_wasm = require('./../pkg/sophia_wasm.js')
+const DataFactory = sophia_wasm.DataFactory
+
+const RDFS_LABEL = 'http://www.w3.org/2000/01/rdf-schema#label'
+const SKOS_PREF_LABEL = 'http://www.w3.org/2004/02/skos/core#prefLabel'
+
+const ex = name => DataFactory.namedNode('http://example.org/' + name)
+
+const DATA = `
+  @prefix ex: <http://example.org/> .
+  @prefix rdfs: <http://www.w3.org/2000/01/rdf-schema#> .
+  @prefix skos: <http://www.w3.org/2004/02/skos/core#> .
+  ex:paris rdfs:label "Paris"@fr, "Paris"@en ; skos:prefLabel "Ville de Paris"@fr .
+  ex:parc rdfs:label "Parc des Princes"@fr .
+  ex:newyork rdfs:label "New York"@en-US ; ex:population 8000000 .
+  ex:istanbul rdfs:label "İstanbul"@tr .
+  ex:ilik rdfs:label "ILIK"@tr .
+  ex:comment rdfs:comment "A comment about Paris" .
+`
+
+const subjects = quads => quads.map(quad => quad.subject.value.replace('http://example.org/', ''))
+
+describe('Text index', () => {
+  for (const indexed of [false, true]) {
+    describe(indexed ? 'With the text index' : 'Without the text index', () => {
+      const build = () => {
+        const dataset = new sophia_wasm.TreeDataset()
+        if (indexed) dataset.enableTextIndex()
+        dataset.addTurtle(DATA)
+        return dataset
+      }
+
+      it('should find the literals by word prefix', () => {
+        const dataset = build()
+
+        assert.deepStrictEqual(subjects(dataset.searchLiterals('new yo')), ['newyork'])
+        assert.deepStrictEqual(subjects(dataset.searchLiterals('PARIS', { predicate: DataFactory.namedNode(RDFS_LABEL) })), ['paris', 'paris'])
+        assert.deepStrictEqual(subjects(dataset.searchLiterals('par', { predicate: DataFactory.namedNode(SKOS_PREF_LABEL) })), ['paris'])
+        assert.deepStrictEqual(subjects(dataset.searchLiterals('par')).sort(), ['comment', 'parc', 'paris', 'paris', 'paris'])
+        assert.deepStrictEqual(dataset.searchLiterals('8000000'), [])
+        assert.deepStrictEqual(dataset.searchLiterals(' ! '), [])
+      })
+
+      it('should filter and lowercase by language', () => {
+        const dataset = build()
+
+        const english = dataset.searchLiterals('par', { lang: 'en' })
+        assert.strictEqual(english.length, 1)
+        assert.strictEqual(english[0].object.language, 'en')
+
+        assert.deepStrictEqual(subjects(dataset.searchLiterals('york', { lang: 'en' })), ['newyork'])
+        assert.deepStrictEqual(subjects(dataset.searchLiterals('istanbul')), ['istanbul'])
+        assert.deepStrictEqual(subjects(dataset.searchLiterals('ılık', { lang: 'tr' })), ['ilik'])
+        assert.deepStrictEqual(subjects(dataset.searchLiterals('ILIK', { lang: 'tr' })), ['ilik'])
+        assert.deepStrictEqual(dataset.searchLiterals('ilik', { lang: 'tr' }), [])
+      })
+
+      it('should return the best matches first, up to the limit', () => {
+        const dataset = build()
+
+        assert.deepStrictEqual(subjects(dataset.searchLiterals('paris', { limit: 1 })), ['paris'])
+        assert.strictEqual(dataset.searchLiterals('par', { limit: 2 }).length, 2)
+        assert.strictEqual(dataset.searchLiterals('par', { limit: 0 }).length, 0)
+      })
+
+      it('should follow the changes of the dataset', () => {
+        const dataset = build()
+        dataset.delete(DataFactory.quad(ex('parc'), DataFactory.namedNode(RDFS_LABEL), DataFactory.literal('Parc des Princes', 'fr')))
+        dataset.add(DataFactory.quad(ex('louvre'), DataFactory.namedNode(RDFS_LABEL), DataFactory.literal('Musée du Louvre', 'fr')))
+
+        assert.deepStrictEqual(dataset.searchLiterals('princes'), [])
+        assert.deepStrictEqual(subjects(dataset.searchLiterals('musée louv')), ['louvre'])
+      })
+    })
+  }
+
+  it('should reject invalid options', () => {
+    const dataset = new sophia_wasm.TreeDataset()
+
+    assert.throws(() => dataset.searchLiterals('paris', { limit: -1 }), TypeError)
+    assert.throws(() => dataset.searchLiterals('paris', { lang: 42 }), TypeError)
+  })
+})
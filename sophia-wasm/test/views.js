@@ -0,0 +1,76 @@
+/* global describe, it */
+"use strict";
+
+const assert = require('assert')
+const sophia_wasm = require('./../pkg/sophia_wasm.js')
+const DataFactory = sophia_wasm.DataFactory
+
+const ex = name => DataFactory.namedNode('http://example.org/' + name)
+
+function countIterated(iterator) {
+  let count = 0
+  for (let item = iterator.next(); !item.done; item = iterator.next()) {
+    ++count
+  }
+  return count
+}
+
+function buildDataset(className) {
+  const dataset = new sophia_wasm[className]()
+  dataset.addNQuads(
+    '<http://example.org/s1> <http://example.org/p> <http://example.org/o1> .\n' +
+    '<http://example.org/s1> <http://example.org/p> <http://example.org/o2> .\n' +
+    '<http://example.org/s1> <http://example.org/q> <http://example.org/o1> .\n' +
+    '<http://example.org/s2> <http://example.org/p> <http://example.org/o1> <http://example.org/g> .\n'
+  )
+  return dataset
+}
+
+describe('Match views', () => {
+  for (const className of ['TreeDataset', 'FastDataset', 'FullDataset']) {
+    it(`should answer size and has from the source for a ${className}`, () => {
+      const dataset = buildDataset(className)
+      const view = dataset.match(ex('s1'), null, null, null, { view: true })
+
+      assert.strictEqual(view.size, 3)
+      assert.ok(view.has(DataFactory.quad(ex('s1'), ex('q'), ex('o1'))))
+      assert.ok(!view.has(DataFactory.quad(ex('s2'), ex('p'), ex('o1'), ex('g'))))
+      assert.strictEqual(view.quads().length, 3)
+      assert.strictEqual(countIterated(view.getIterator()), 3)
+      assert.strictEqual(countIterated(view.matchIterator(null, ex('q'), null, null)), 1)
+    })
+  }
+
+  it('should narrow the pattern on further matches', () => {
+    const view = buildDataset('TreeDataset').match(null, ex('p'), null, null, { view: true })
+
+    const narrowed = view.match(null, null, ex('o1'), null, { view: true })
+    assert.strictEqual(narrowed.size, 2)
+
+    const copy = view.match(ex('s1'), null, null)
+    assert.strictEqual(copy.size, 2)
+
+    assert.strictEqual(view.match(null, ex('q'), null, null, { view: true }).size, 0)
+    assert.strictEqual(view.match(null, ex('q'), null).size, 0)
+  })
+
+  it('should copy the quads when the view is modified', () => {
+    const dataset = buildDataset('TreeDataset')
+    const view = dataset.match(ex('s1'), null, null, null, { view: true })
+
+    view.add(DataFactory.quad(ex('s3'), ex('p'), ex('o3')))
+    assert.strictEqual(view.size, 4)
+    assert.ok(view.has(DataFactory.quad(ex('s3'), ex('p'), ex('o3'))))
+    assert.strictEqual(dataset.size, 4)
+    assert.ok(!dataset.has(DataFactory.quad(ex('s3'), ex('p'), ex('o3'))))
+  })
+
+  it('should keep its quads when the source is modified', () => {
+    const dataset = buildDataset('TreeDataset')
+    const view = dataset.match(ex('s1'), null, null, null, { view: true })
+
+    dataset.delete(DataFactory.quad(ex('s1'), ex('q'), ex('o1')))
+    assert.strictEqual(dataset.size, 3)
+    assert.strictEqual(view.size, 3)
+  })
+})